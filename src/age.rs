@@ -0,0 +1,84 @@
+//! When the files at HEAD were created and last changed.
+//!
+//! A file's creation is the oldest commit touching its current path and its
+//! last modification the newest, so a renamed file counts as created by the
+//! rename. Merge commits are skipped, as their first-parent diff repeats the
+//! merged work.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+
+use crate::{churn::get_changed_paths, history::CommitInfo, tree::TreeFile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileAge {
+    pub path: String,
+    /// Committer time of the oldest commit touching the file.
+    pub created: i64,
+    /// Committer time of the newest commit touching the file.
+    pub modified: i64,
+    pub commits: usize,
+}
+
+/// Dates every file of `files` touched by any of `commits`, least recently
+/// modified first.
+pub fn get_file_ages(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    files: &[TreeFile],
+) -> Result<Vec<FileAge>, git2::Error> {
+    let mut ages: HashMap<&str, FileAge> = HashMap::new();
+    let paths: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        for path in get_changed_paths(repo, commit.oid)? {
+            let Some(&path) = paths.get(path.as_str()) else {
+                continue;
+            };
+            let age = ages.entry(path).or_insert_with(|| FileAge {
+                path: path.to_string(),
+                created: commit.time,
+                modified: commit.time,
+                commits: 0,
+            });
+            age.created = age.created.min(commit.time);
+            age.modified = age.modified.max(commit.time);
+            age.commits += 1;
+        }
+    }
+    let mut ages: Vec<FileAge> = ages.into_values().collect();
+    ages.sort_by(|a, b| a.modified.cmp(&b.modified).then(a.path.cmp(&b.path)));
+    Ok(ages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_file_ages() {
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", 10, "add", &[("a.rs", "1"), ("b.rs", "1")]);
+        t.commit_as("A", "a@x.com", 20, "edit", &[("a.rs", "2")]);
+        t.commit_as("A", "a@x.com", 30, "new", &[("c.rs", "1")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let ages: Vec<_> = get_file_ages(&t.repo, &commits, &files)
+            .unwrap()
+            .into_iter()
+            .map(|a| (a.path, a.created, a.modified, a.commits))
+            .collect();
+        assert_eq!(
+            vec![
+                ("b.rs".to_string(), 10, 10, 1),
+                ("a.rs".to_string(), 10, 20, 2),
+                ("c.rs".to_string(), 30, 30, 1),
+            ],
+            ages
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Heuristics for commits generated or co-authored by AI tools.
+
+use crate::{history::CommitInfo, message::trailers};
+
+/// Known AI tools with the lowercase names and emails of their bot
+/// identities. Identities must match exactly, so people who share a name
+/// with a tool, or work at the company behind one, are not flagged.
+/// GitHub noreply addresses are matched by the login they carry.
+const TOOL_IDENTITIES: &[(&str, &[&str], &[&str])] = &[
+    (
+        "Claude",
+        &["claude", "claude code"],
+        &["noreply@anthropic.com"],
+    ),
+    (
+        "Copilot",
+        &["copilot", "github copilot", "copilot-swe-agent[bot]"],
+        &[],
+    ),
+    (
+        "ChatGPT",
+        &["chatgpt", "codex", "chatgpt-codex-connector[bot]"],
+        &["noreply@openai.com"],
+    ),
+    (
+        "Cursor",
+        &["cursor agent", "cursoragent", "cursor[bot]"],
+        &["cursoragent@cursor.com"],
+    ),
+    ("Devin", &["devin ai", "devin-ai-integration[bot]"], &[]),
+    ("Gemini", &["gemini", "gemini-code-assist[bot]"], &[]),
+    ("Aider", &["aider"], &[]),
+    ("Codeium", &["codeium", "windsurf"], &[]),
+    (
+        "Amazon Q",
+        &["amazon q developer", "amazon-q-developer[bot]"],
+        &[],
+    ),
+];
+
+/// Suffix aider adds to the author name of the commits it makes.
+const AIDER_SUFFIX: &str = " (aider)";
+
+/// Signatures tools leave in the message body, matched case-insensitively.
+const TOOL_SIGNATURES: &[(&str, &str)] = &[
+    ("Claude", "generated with [claude code]"),
+    ("Claude", "generated with claude"),
+    ("Copilot", "generated by copilot"),
+    ("ChatGPT", "generated by chatgpt"),
+    ("Aider", "aider: "),
+    ("Cursor", "generated with cursor"),
+];
+
+/// How an AI tool was detected on a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiEvidence {
+    /// The commit was authored by the tool's own identity.
+    Author,
+    /// A `Co-authored-by` trailer names the tool.
+    CoAuthor,
+    /// The message carries a tool's signature line.
+    Signature,
+}
+
+impl AiEvidence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AiEvidence::Author => "author",
+            AiEvidence::CoAuthor => "trailer",
+            AiEvidence::Signature => "signature",
+        }
+    }
+}
+
+/// Returns the login of a GitHub noreply address such as
+/// `123+copilot@users.noreply.github.com`.
+fn noreply_login(email: &str) -> Option<&str> {
+    let local = email.strip_suffix("@users.noreply.github.com")?;
+    Some(local.split_once('+').map_or(local, |(_, login)| login))
+}
+
+/// Returns the tool whose bot identity is `name` and `email`.
+fn match_identity(name: &str, email: &str) -> Option<&'static str> {
+    let (name, email) = (name.trim().to_lowercase(), email.trim().to_lowercase());
+    if name.ends_with(AIDER_SUFFIX) {
+        return Some("Aider");
+    }
+    let login = noreply_login(&email);
+    TOOL_IDENTITIES
+        .iter()
+        .find(|(_, names, emails)| {
+            names.contains(&name.as_str())
+                || emails.contains(&email.as_str())
+                || login.is_some_and(|l| names.contains(&l))
+        })
+        .map(|(tool, _, _)| *tool)
+}
+
+/// Returns the AI tool a commit is attributed to and the strongest evidence
+/// for it, checking author, trailers and message signatures in that order.
+/// Identities must be the recorded ones, before `--anonymize` rewrites them.
+pub fn detect_ai_tool(commit: &CommitInfo) -> Option<(&'static str, AiEvidence)> {
+    if let Some(tool) = match_identity(&commit.author_name, &commit.author_email) {
+        return Some((tool, AiEvidence::Author));
+    }
+    for (key, value) in trailers(&commit.message) {
+        let (name, email) = value.split_once('<').unwrap_or((value, ""));
+        if key.eq_ignore_ascii_case("co-authored-by")
+            && let Some(tool) = match_identity(name, email.trim_end_matches('>'))
+        {
+            return Some((tool, AiEvidence::CoAuthor));
+        }
+    }
+    let message = commit.message.to_lowercase();
+    TOOL_SIGNATURES
+        .iter()
+        .find(|(_, signature)| message.contains(signature))
+        .map(|(tool, _)| (*tool, AiEvidence::Signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(name: &str, email: &str, message: &str) -> CommitInfo {
+        CommitInfo {
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect_ai_tool() {
+        let trailer = commit(
+            "dev",
+            "dev@x.com",
+            "Fix bug\n\nCo-authored-by: Claude <noreply@anthropic.com>\n",
+        );
+        assert_eq!(
+            Some(("Claude", AiEvidence::CoAuthor)),
+            detect_ai_tool(&trailer)
+        );
+
+        let bot = commit(
+            "Copilot",
+            "198982749+Copilot@users.noreply.github.com",
+            "Update",
+        );
+        assert_eq!(Some(("Copilot", AiEvidence::Author)), detect_ai_tool(&bot));
+
+        let aider = commit("dev", "dev@x.com", "aider: refactor parser");
+        assert_eq!(
+            Some(("Aider", AiEvidence::Signature)),
+            detect_ai_tool(&aider)
+        );
+
+        let human = commit(
+            "dev",
+            "dev@x.com",
+            "Fix bug\n\nCo-authored-by: Jane <jane@x.com>\n",
+        );
+        assert_eq!(None, detect_ai_tool(&human));
+    }
+
+    #[test]
+    fn test_people_named_like_tools() {
+        let claude = commit("Claude Dupont", "claude@example.fr", "Fix bug");
+        assert_eq!(None, detect_ai_tool(&claude));
+        let coauthor = commit(
+            "dev",
+            "dev@x.com",
+            "Fix bug\n\nCo-authored-by: Claude Dupont <claude@example.fr>\n",
+        );
+        assert_eq!(None, detect_ai_tool(&coauthor));
+        for (name, email) in [
+            ("Gemini Rossi", "gemini@x.com"),
+            ("Ana Codex", "ana@openai.com"),
+            ("Sam", "sam@cursor.com"),
+        ] {
+            assert_eq!(None, detect_ai_tool(&commit(name, email, "Update")));
+        }
+        let aider = commit("Paul (aider)", "paul@x.com", "Refactor");
+        assert_eq!(Some(("Aider", AiEvidence::Author)), detect_ai_tool(&aider));
+    }
+}
@@ -0,0 +1,204 @@
+//! Alerts on key metrics whose last week is unusual for the repository.
+//!
+//! The normal range of each metric is learned from the history itself: its
+//! values in the weeks before the last full week give a mean and a standard
+//! deviation, and the last full week raises an alert when it lies more than
+//! a given number of deviations away. A metric that did not vary over those
+//! weeks is not judged, as in [`crate::anomalies`]. Running gno on a schedule
+//! with `alerts --fail` turns the alerts into a failing exit status for the
+//! scheduler to act on.
+
+use std::collections::{BTreeMap, HashSet};
+
+use git2::Repository;
+
+use crate::{
+    bucket::Bucketing, churn::get_commit_churn, history::CommitInfo, identity::contributor_key,
+    lang::PathFilter, stats::mean_deviation,
+};
+
+/// Metrics judged, in the order they are reported.
+pub const METRICS: [&str; 3] = ["commits", "contributors", "lines changed"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeekMetrics {
+    /// First day of the week.
+    pub week: i64,
+    pub commits: usize,
+    pub contributors: usize,
+    /// Lines inserted and deleted by the non-merge commits.
+    pub lines: usize,
+}
+
+impl WeekMetrics {
+    /// Returns the value of the metric named as in [`METRICS`].
+    pub fn value(&self, metric: &str) -> f64 {
+        match metric {
+            "commits" => self.commits as f64,
+            "contributors" => self.contributors as f64,
+            "lines changed" => self.lines as f64,
+            _ => unreachable!("unknown metric {}", metric),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Judgement {
+    pub metric: &'static str,
+    pub value: f64,
+    pub mean: f64,
+    pub deviation: f64,
+    /// Standard deviations from the mean, or none when the metric did not
+    /// vary in the baseline.
+    pub sigmas: Option<f64>,
+}
+
+impl Judgement {
+    pub fn is_alert(&self, sigma: f64) -> bool {
+        self.sigmas.is_some_and(|s| s.abs() > sigma)
+    }
+}
+
+/// Measures the `weeks` weeks up to and including the one starting on
+/// `last`, a Monday, oldest first. Lines changed are summed over files
+/// matching `filter`.
+pub fn get_week_metrics(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    last: i64,
+    weeks: usize,
+    filter: &PathFilter,
+) -> Result<Vec<WeekMetrics>, git2::Error> {
+    let first = last - 7 * (weeks as i64 - 1);
+    let mut metrics: BTreeMap<i64, (WeekMetrics, HashSet<String>)> = (0..weeks as i64)
+        .map(|i| {
+            let week = first + 7 * i;
+            let metrics = WeekMetrics {
+                week,
+                ..Default::default()
+            };
+            (week, (metrics, HashSet::new()))
+        })
+        .collect();
+    for commit in commits {
+        let Some((week, authors)) = metrics.get_mut(&Bucketing::Week.start_of(commit.day())) else {
+            continue;
+        };
+        week.commits += 1;
+        authors.insert(contributor_key(commit));
+        if !commit.is_merge() {
+            week.lines += get_commit_churn(repo, commit.oid, filter)?.lines();
+        }
+    }
+    Ok(metrics
+        .into_values()
+        .map(|(mut week, authors)| {
+            week.contributors = authors.len();
+            week
+        })
+        .collect())
+}
+
+/// Judges the last of `weeks` against the ones before it, per metric.
+pub fn judge(weeks: &[WeekMetrics]) -> Vec<Judgement> {
+    let Some((last, baseline)) = weeks.split_last() else {
+        return Vec::new();
+    };
+    METRICS
+        .iter()
+        .map(|&metric| {
+            let values: Vec<f64> = baseline.iter().map(|w| w.value(metric)).collect();
+            let (mean, deviation) = mean_deviation(&values);
+            let value = last.value(metric);
+            Judgement {
+                metric,
+                value,
+                mean,
+                deviation,
+                sigmas: (deviation > 0.0).then(|| (value - mean) / deviation),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::{SECONDS_PER_DAY, days_from_civil},
+        history::collect_commits,
+        progress::Progress,
+        testutil::TestRepo,
+    };
+
+    #[test]
+    fn test_week_metrics() {
+        // 2024-01-01 was a Monday.
+        let monday = days_from_civil(2024, 1, 1);
+        let at = |day: i64| day * SECONDS_PER_DAY + 3600;
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            at(monday - 7),
+            "before",
+            &[("a.txt", "1\n")],
+        );
+        t.commit_as("A", "a@x.com", at(monday), "one", &[("a.txt", "1\n2\n")]);
+        t.commit_as("B", "b@x.com", at(monday + 2), "two", &[("b.txt", "1\n")]);
+        t.commit_as("A", "a@x.com", at(monday + 7), "three", &[("a.txt", "3\n")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let weeks =
+            get_week_metrics(&t.repo, &commits, monday + 7, 2, &PathFilter::default()).unwrap();
+        assert_eq!(
+            vec![
+                WeekMetrics {
+                    week: monday,
+                    commits: 2,
+                    contributors: 2,
+                    lines: 2,
+                },
+                WeekMetrics {
+                    week: monday + 7,
+                    commits: 1,
+                    contributors: 1,
+                    lines: 3,
+                },
+            ],
+            weeks
+        );
+    }
+
+    #[test]
+    fn test_judge() {
+        // Baseline weeks of 9 and 11 commits: a mean of 10, a deviation of 1.
+        let mut weeks: Vec<WeekMetrics> = (0..4)
+            .map(|i| WeekMetrics {
+                week: i * 7,
+                commits: 9 + 2 * (i as usize % 2),
+                contributors: 2,
+                lines: 100,
+            })
+            .collect();
+        weeks.push(WeekMetrics {
+            week: 28,
+            commits: 13,
+            contributors: 5,
+            lines: 100,
+        });
+        let judgements = judge(&weeks);
+        assert_eq!(
+            vec![
+                ("commits", Some(3.0), true),
+                ("contributors", None, false),
+                ("lines changed", None, false),
+            ],
+            judgements
+                .iter()
+                .map(|j| (j.metric, j.sigmas, j.is_alert(2.0)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!((10.0, 1.0), (judgements[0].mean, judgements[0].deviation));
+    }
+}
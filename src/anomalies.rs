@@ -0,0 +1,117 @@
+//! Weeks whose commit volume stands out from the weeks before them.
+//!
+//! Each week is compared with the average of the trailing weeks: a count more
+//! than the given number of standard deviations above it is a spike, as in a
+//! crunch before a release, and one below it a stall. Weeks without enough
+//! history before them, after a run of identical weeks, or still running are
+//! not judged.
+
+use crate::{
+    bucket::{Bucketing, bucket_counts},
+    stats::mean_deviation,
+};
+
+/// Weeks averaged before each judged week.
+pub const TRAILING_WEEKS: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anomaly {
+    /// First day of the week.
+    pub week: i64,
+    pub commits: usize,
+    pub trailing_mean: f64,
+    /// Standard deviations from the trailing mean, negative for stalls.
+    pub sigmas: f64,
+}
+
+impl Anomaly {
+    pub fn kind(&self) -> &'static str {
+        if self.sigmas > 0.0 { "spike" } else { "stall" }
+    }
+}
+
+/// Returns the weeks among the commit `days` deviating more than `sigma`
+/// standard deviations from the [`TRAILING_WEEKS`] before them, oldest first.
+/// Every week finished by `today` is judged, including silent ones after the
+/// last commit; the week still running is not.
+pub fn get_anomalies(days: impl IntoIterator<Item = i64>, today: i64, sigma: f64) -> Vec<Anomaly> {
+    let current = Bucketing::Week.start_of(today);
+    let mut weeks = bucket_counts(Bucketing::Week, days.into_iter().filter(|d| *d < current));
+    if let Some(&(last, _)) = weeks.last() {
+        let mut week = Bucketing::Week.next(last);
+        while week < current {
+            weeks.push((week, 0));
+            week = Bucketing::Week.next(week);
+        }
+    }
+    let mut anomalies = Vec::new();
+    for (i, &(week, commits)) in weeks.iter().enumerate().skip(TRAILING_WEEKS) {
+        let trailing: Vec<f64> = weeks[i - TRAILING_WEEKS..i]
+            .iter()
+            .map(|(_, n)| *n as f64)
+            .collect();
+        let (mean, deviation) = mean_deviation(&trailing);
+        if deviation == 0.0 {
+            continue;
+        }
+        let sigmas = (commits as f64 - mean) / deviation;
+        if sigmas.abs() > sigma {
+            anomalies.push(Anomaly {
+                week,
+                commits,
+                trailing_mean: mean,
+                sigmas,
+            });
+        }
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{date::days_from_civil, stats::parse_sigma};
+
+    #[test]
+    fn test_anomalies() {
+        // 2024-01-01 was a Monday. Weeks alternate between 9 and 11 commits,
+        // for a trailing mean of 10 and a deviation of 1.
+        let monday = days_from_civil(2024, 1, 1);
+        let mut days = Vec::new();
+        for week in 0..TRAILING_WEEKS as i64 {
+            days.extend(std::iter::repeat_n(
+                monday + week * 7,
+                9 + 2 * (week as usize % 2),
+            ));
+        }
+        let next = monday + TRAILING_WEEKS as i64 * 7;
+
+        let mut spike = days.clone();
+        spike.extend(std::iter::repeat_n(next, 13));
+        let anomalies = get_anomalies(spike, next + 7, 2.0);
+        assert_eq!(1, anomalies.len());
+        assert_eq!(
+            (next, 13, 10.0, 3.0, "spike"),
+            (
+                anomalies[0].week,
+                anomalies[0].commits,
+                anomalies[0].trailing_mean,
+                anomalies[0].sigmas,
+                anomalies[0].kind()
+            )
+        );
+
+        // Silent weeks after the last commit are stalls once they are over,
+        // and commits in the week still running are not judged.
+        let mut stall = days;
+        stall.extend(std::iter::repeat_n(next + 7, 30));
+        let anomalies = get_anomalies(stall.clone(), next + 7, 2.0);
+        assert_eq!(1, anomalies.len());
+        assert_eq!(
+            (next, 0, "stall"),
+            (anomalies[0].week, anomalies[0].commits, anomalies[0].kind())
+        );
+        assert!(get_anomalies(stall, next + 6, 2.0).is_empty());
+        assert!(parse_sigma("0").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Timings of every collector on a corpus of local repositories, for catching
+//! performance regressions.
+//!
+//! Each collector runs several times on each repository with a fresh
+//! [`Context`], so shared walks are timed with every collector that uses
+//! them, and the median run is kept. Timings are saved as a JSON baseline and
+//! later runs are compared against it.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use clap::{Arg, ArgAction, Command, value_parser};
+use git2::Repository;
+
+use crate::{
+    config::Config,
+    context::Context,
+    report::{Report, Table, Value},
+};
+
+/// Change against the baseline, in percent, reported as a regression.
+const REGRESSION_PERCENT: f64 = 20.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub repository: String,
+    pub metric: String,
+    /// Median wall time in milliseconds, or `None` if the collector failed.
+    pub median_ms: Option<f64>,
+}
+
+pub fn command() -> Command {
+    Command::new("bench")
+        .about("time every collector on a set of local repositories")
+        .long_about(
+            "Run every collector several times on each repository with nothing cached \
+             between runs, report the median time per collector, and optionally save the \
+             timings as a JSON baseline or compare them against a saved one.",
+        )
+        .arg(
+            Arg::new("repo")
+                .long("repo")
+                .required(true)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+                .help("Repository to run the collectors on (repeatable)"),
+        )
+        .arg(
+            Arg::new("metric")
+                .long("metric")
+                .action(ArgAction::Append)
+                .help("Only time this collector (repeatable; default: all)"),
+        )
+        .arg(
+            Arg::new("runs")
+                .long("runs")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("5")
+                .help("Runs per collector and repository"),
+        )
+        .arg(
+            Arg::new("save")
+                .long("save")
+                .value_parser(value_parser!(PathBuf))
+                .help("Write the timings to this JSON baseline"),
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .value_parser(value_parser!(PathBuf))
+                .help("Compare the timings against this JSON baseline"),
+        )
+}
+
+/// Returns every collector, leaving out the harness itself.
+fn collectors() -> Vec<String> {
+    crate::cli()
+        .get_subcommands()
+        .map(|c| c.get_name().to_string())
+        .filter(|name| name != "bench")
+        .collect()
+}
+
+/// Times `metric` on the repository at `path`, `runs` times.
+fn time_metric(path: &Path, metric: &str, runs: u32) -> Result<f64, Box<dyn Error>> {
+    let matches = crate::cli().try_get_matches_from(["gno", metric])?;
+    let (name, sub) = matches.subcommand().ok_or("no collector given")?;
+    let mut times = Vec::new();
+    for _ in 0..runs {
+        let mut ctx = Context::new(Repository::open(path)?, Config::default());
+        let mut report = Report::new("");
+        let start = Instant::now();
+        crate::run_subcommand(&mut ctx, name, sub, &mut report)?;
+        times.push(start.elapsed().as_secs_f64() * 1_000.0);
+    }
+    times.sort_by(f64::total_cmp);
+    Ok(times[times.len() / 2])
+}
+
+/// Times each of `metrics`, or every collector if empty, on each repository.
+pub fn run_bench(
+    repositories: &[PathBuf],
+    metrics: &[String],
+    runs: u32,
+) -> Result<Vec<Timing>, Box<dyn Error>> {
+    let metrics = if metrics.is_empty() {
+        collectors()
+    } else {
+        metrics.to_vec()
+    };
+    let mut timings = Vec::new();
+    for path in repositories {
+        let repository = fs::canonicalize(path)
+            .map_err(|e| format!("cannot open {}: {}", path.display(), e))?
+            .display()
+            .to_string();
+        for metric in &metrics {
+            // Collectors that need input the corpus lacks, such as a teams
+            // file, fail on their own without stopping the others.
+            let median_ms = time_metric(path, metric, runs).ok();
+            timings.push(Timing {
+                repository: repository.clone(),
+                metric: metric.clone(),
+                median_ms,
+            });
+        }
+    }
+    Ok(timings)
+}
+
+fn json_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Writes timings as a JSON array with one object per line.
+pub fn write_baseline(timings: &[Timing]) -> String {
+    let lines: Vec<String> = timings
+        .iter()
+        .filter_map(|t| {
+            t.median_ms.map(|ms| {
+                format!(
+                    "{{\"repository\":{},\"metric\":{},\"median_ms\":{:.3}}}",
+                    json_string(&t.repository),
+                    json_string(&t.metric),
+                    ms
+                )
+            })
+        })
+        .collect();
+    format!("[\n{}\n]\n", lines.join(",\n"))
+}
+
+/// Reads the string value of `key` from one line of a baseline.
+fn string_field(line: &str, key: &str) -> Option<String> {
+    let start = line.find(&format!("\"{}\":\"", key))? + key.len() + 4;
+    let mut value = String::new();
+    let mut chars = line[start..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return Some(value),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Reads a baseline written by [`write_baseline`].
+pub fn parse_baseline(text: &str) -> Vec<Timing> {
+    text.lines()
+        .filter_map(|line| {
+            let ms = line.split("\"median_ms\":").nth(1)?;
+            let ms = ms.trim_end_matches([',', '}', ' ']).parse().ok()?;
+            Some(Timing {
+                repository: string_field(line, "repository")?,
+                metric: string_field(line, "metric")?,
+                median_ms: Some(ms),
+            })
+        })
+        .collect()
+}
+
+/// Fills `report` with the timings and, given a baseline, their change.
+pub fn report_bench(report: &mut Report, timings: &[Timing], baseline: Option<&[Timing]>) {
+    let columns: &[&str] = if baseline.is_some() {
+        &[
+            "Repository",
+            "Metric",
+            "Median (ms)",
+            "Baseline (ms)",
+            "Change",
+        ]
+    } else {
+        &["Repository", "Metric", "Median (ms)"]
+    };
+    let mut regressions = 0;
+    let mut table = Table::new("Collector timings", columns);
+    for timing in timings {
+        let mut row: Vec<Value> = vec![
+            timing.repository.as_str().into(),
+            timing.metric.as_str().into(),
+            timing
+                .median_ms
+                .map_or("failed".to_string(), |ms| format!("{:.1}", ms))
+                .into(),
+        ];
+        if let Some(baseline) = baseline {
+            let before = baseline
+                .iter()
+                .find(|b| b.repository == timing.repository && b.metric == timing.metric)
+                .and_then(|b| b.median_ms);
+            row.push(
+                before
+                    .map_or("-".to_string(), |ms| format!("{:.1}", ms))
+                    .into(),
+            );
+            match (before, timing.median_ms) {
+                (Some(before), Some(now)) if before > 0.0 => {
+                    let change = (now - before) * 100.0 / before;
+                    regressions += usize::from(change >= REGRESSION_PERCENT);
+                    row.push(Value::Percent(change));
+                }
+                _ => row.push("-".into()),
+            }
+        }
+        table.push(row);
+    }
+    report.field("Repositories", {
+        let mut repositories: Vec<&str> = timings.iter().map(|t| t.repository.as_str()).collect();
+        repositories.dedup();
+        repositories.len()
+    });
+    report.field("Timings", timings.len());
+    if baseline.is_some() {
+        report.field("Regressions", regressions);
+    }
+    report.table(table);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_bench_round_trip() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "1")]);
+        let path = t.repo.workdir().unwrap().to_path_buf();
+
+        let timings = run_bench(&[path], &["commits".to_string(), "teams".to_string()], 1).unwrap();
+        assert!(timings[0].median_ms.is_some());
+        // No teams file in the repository.
+        assert_eq!(None, timings[1].median_ms);
+
+        let baseline = parse_baseline(&write_baseline(&timings));
+        assert_eq!(1, baseline.len());
+        assert_eq!(timings[0].metric, baseline[0].metric);
+        assert_eq!(timings[0].repository, baseline[0].repository);
+    }
+}
@@ -0,0 +1,146 @@
+//! The largest blobs ever committed.
+//!
+//! Every blob in the object database is considered, not only those at HEAD,
+//! since a file deleted long ago still weighs on every clone until history is
+//! rewritten. A blob is attributed to the earliest commit whose first-parent
+//! diff adds it, together with the path it was added at. Blobs no scanned
+//! commit adds, such as those only reachable from stashes or left dangling,
+//! have neither.
+
+use std::collections::HashMap;
+
+use git2::{Delta, ObjectType, Oid, Repository};
+
+use crate::{churn::diff_commit, history::CommitInfo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigBlob {
+    pub oid: Oid,
+    pub size: u64,
+    pub path: Option<String>,
+    /// Commit that introduced the blob.
+    pub commit: Option<Oid>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigBlobs {
+    /// Blobs in the object database.
+    pub count: usize,
+    /// Their total uncompressed size in bytes.
+    pub total_size: u64,
+    /// The largest blobs, largest first.
+    pub largest: Vec<BigBlob>,
+}
+
+/// Finds the `top` largest blobs in the object database of `repo` and the
+/// commits among `commits` that introduced them.
+pub fn get_largest_blobs(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    top: usize,
+) -> Result<BigBlobs, Box<dyn std::error::Error>> {
+    let odb = repo.odb()?;
+    let mut blobs = BigBlobs::default();
+    let mut sizes: Vec<(u64, Oid)> = Vec::new();
+    let mut error = None;
+    odb.foreach(|oid| match odb.read_header(*oid) {
+        Ok((size, ObjectType::Blob)) => {
+            sizes.push((size as u64, *oid));
+            true
+        }
+        Ok(_) => true,
+        Err(e) => {
+            error = Some(e);
+            false
+        }
+    })
+    .or_else(|e| if error.is_some() { Ok(()) } else { Err(e) })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+    // Packed and loose copies of one object are both listed.
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    blobs.count = sizes.len();
+    blobs.total_size = sizes.iter().map(|(size, _)| size).sum();
+    sizes.truncate(top);
+
+    let mut pending: HashMap<Oid, usize> = sizes
+        .iter()
+        .enumerate()
+        .map(|(i, (_, oid))| (*oid, i))
+        .collect();
+    blobs.largest = sizes
+        .iter()
+        .map(|&(size, oid)| BigBlob {
+            oid,
+            size,
+            path: None,
+            commit: None,
+        })
+        .collect();
+    let mut oldest_first: Vec<&CommitInfo> = commits.iter().collect();
+    oldest_first.sort_by_key(|c| c.time);
+    for commit in oldest_first {
+        if pending.is_empty() {
+            break;
+        }
+        let Some(diff) = diff_commit(repo, commit.oid)? else {
+            continue;
+        };
+        for delta in diff.deltas() {
+            if !matches!(delta.status(), Delta::Added | Delta::Modified) {
+                continue;
+            }
+            let file = delta.new_file();
+            if let Some(i) = pending.remove(&file.id()) {
+                let blob = &mut blobs.largest[i];
+                blob.path = file.path().map(|p| p.to_string_lossy().into_owned());
+                blob.commit = Some(commit.oid);
+            }
+        }
+    }
+    Ok(blobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_largest_blobs() {
+        let t = TestRepo::new();
+        let big = "x".repeat(1000);
+        let first = t.commit_as(
+            "A",
+            "a@x.com",
+            100,
+            "add",
+            &[("big.bin", &big), ("a.txt", "a")],
+        );
+        let mut index = t.repo.index().unwrap();
+        index.remove_path(std::path::Path::new("big.bin")).unwrap();
+        index.write().unwrap();
+        let second = t.commit_as("A", "a@x.com", 200, "remove", &[("a.txt", "abc")]);
+        let dangling = t.repo.blob(&[b'y'; 500]).unwrap();
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let blobs = get_largest_blobs(&t.repo, &commits, 3).unwrap();
+        assert_eq!((4, 1504), (blobs.count, blobs.total_size));
+        let found: Vec<(u64, Option<&str>, Option<Oid>)> = blobs
+            .largest
+            .iter()
+            .map(|b| (b.size, b.path.as_deref(), b.commit))
+            .collect();
+        assert_eq!(
+            vec![
+                (1000, Some("big.bin"), Some(first)),
+                (500, None, None),
+                (3, Some("a.txt"), Some(second)),
+            ],
+            found
+        );
+        assert_eq!(dangling, blobs.largest[1].oid);
+    }
+}
@@ -0,0 +1,134 @@
+//! Binary and text files, at HEAD and as they were introduced.
+//!
+//! A blob is binary as [`git2::Blob::is_binary`] tells, the heuristic git
+//! uses for diffs and the other reports here use for line counts.
+//! Introduced blobs are those a non-merge commit adds or modifies, each
+//! counted once at the first commit that brings it in, so the periods show
+//! where binary assets crept in.
+
+use std::collections::{BTreeMap, HashSet};
+
+use git2::{Delta, Repository};
+
+use crate::{bucket::Bucketing, churn::diff_commit, history::CommitInfo, tree::TreeFile};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileKinds {
+    pub text_files: usize,
+    pub text_bytes: u64,
+    pub binary_files: usize,
+    pub binary_bytes: u64,
+}
+
+impl FileKinds {
+    fn add(&mut self, binary: bool, size: u64) {
+        if binary {
+            self.binary_files += 1;
+            self.binary_bytes += size;
+        } else {
+            self.text_files += 1;
+            self.text_bytes += size;
+        }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.text_bytes + self.binary_bytes
+    }
+}
+
+/// Classifies the blobs in `files`.
+pub fn get_head_kinds(repo: &Repository, files: &[TreeFile]) -> Result<FileKinds, git2::Error> {
+    let mut kinds = FileKinds::default();
+    for file in files {
+        let blob = repo.find_blob(file.oid)?;
+        kinds.add(blob.is_binary(), file.size);
+    }
+    Ok(kinds)
+}
+
+/// Classifies the blobs `commits` introduced, per period of `bucketing`.
+/// Returns `(bucket start, kinds)` pairs for the periods introducing any.
+pub fn get_introduced_kinds(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    bucketing: Bucketing,
+) -> Result<Vec<(i64, FileKinds)>, git2::Error> {
+    let mut oldest_first: Vec<&CommitInfo> = commits.iter().filter(|c| !c.is_merge()).collect();
+    oldest_first.sort_by_key(|c| c.time);
+    let mut seen = HashSet::new();
+    let mut periods: BTreeMap<i64, FileKinds> = BTreeMap::new();
+    for commit in oldest_first {
+        let Some(diff) = diff_commit(repo, commit.oid)? else {
+            continue;
+        };
+        for delta in diff.deltas() {
+            let file = delta.new_file();
+            if !matches!(delta.status(), Delta::Added | Delta::Modified) || !seen.insert(file.id())
+            {
+                continue;
+            }
+            // Submodule entries point at commits, not blobs.
+            let Ok(blob) = repo.find_blob(file.id()) else {
+                continue;
+            };
+            periods
+                .entry(bucketing.start_of(commit.day()))
+                .or_default()
+                .add(blob.is_binary(), blob.size() as u64);
+        }
+    }
+    Ok(periods.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_file_kinds() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        t.commit_as(
+            "A",
+            "a@x.com",
+            day,
+            "add",
+            &[("a.txt", "text"), ("b.png", "\0\x01")],
+        );
+        t.commit_as(
+            "A",
+            "a@x.com",
+            400 * day,
+            "edit",
+            &[("b.png", "\0\x01\x02")],
+        );
+
+        let files = get_head_files(&t.repo, None).unwrap();
+        let head = get_head_kinds(&t.repo, &files).unwrap();
+        assert_eq!(
+            FileKinds {
+                text_files: 1,
+                text_bytes: 4,
+                binary_files: 1,
+                binary_bytes: 3
+            },
+            head
+        );
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let years = get_introduced_kinds(&t.repo, &commits, Bucketing::Year).unwrap();
+        assert_eq!(2, years.len());
+        assert_eq!((1, 2), (years[0].1.binary_files, years[0].1.binary_bytes));
+        assert_eq!(
+            (0, 1, 3),
+            (
+                years[1].1.text_files,
+                years[1].1.binary_files,
+                years[1].1.total_bytes()
+            )
+        );
+    }
+}
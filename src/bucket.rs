@@ -0,0 +1,225 @@
+//! Time bucketing shared by all time-bucketed reports.
+
+use std::{fmt, str::FromStr};
+
+use crate::date::{civil_from_days, days_from_civil, format_day, iso_week, parse_date, weekday};
+
+/// How commit timestamps are grouped into periods.
+///
+/// Every bucket is identified by the local day it starts on, so buckets of any
+/// kind sort chronologically and can be stepped through with [`Bucketing::next`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bucketing {
+    /// ISO 8601 weeks, starting on Monday.
+    Week,
+    /// Calendar months.
+    Month,
+    /// Calendar quarters.
+    Quarter,
+    /// Calendar years.
+    Year,
+    /// Fixed-length sprints of `days` days, counted from `anchor`.
+    Sprint { days: u32, anchor: i64 },
+}
+
+impl Bucketing {
+    /// Returns the first day of the bucket containing `day`.
+    pub fn start_of(&self, day: i64) -> i64 {
+        match *self {
+            Bucketing::Week => day - weekday(day) as i64,
+            Bucketing::Month => {
+                let (year, month, _) = civil_from_days(day);
+                days_from_civil(year, month, 1)
+            }
+            Bucketing::Quarter => {
+                let (year, month, _) = civil_from_days(day);
+                days_from_civil(year, (month - 1) / 3 * 3 + 1, 1)
+            }
+            Bucketing::Year => days_from_civil(civil_from_days(day).0, 1, 1),
+            Bucketing::Sprint { days, anchor } => {
+                let days = days as i64;
+                anchor + (day - anchor).div_euclid(days) * days
+            }
+        }
+    }
+
+    /// Returns the first day of the bucket following the one starting at `start`.
+    pub fn next(&self, start: i64) -> i64 {
+        match *self {
+            Bucketing::Week => start + 7,
+            Bucketing::Month | Bucketing::Quarter | Bucketing::Year => {
+                let months = match *self {
+                    Bucketing::Month => 1,
+                    Bucketing::Quarter => 3,
+                    _ => 12,
+                };
+                let (year, month, _) = civil_from_days(start);
+                let index = year * 12 + (month as i64 - 1) + months;
+                days_from_civil(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)
+            }
+            Bucketing::Sprint { days, .. } => start + days as i64,
+        }
+    }
+
+    /// Returns the display label of the bucket starting at `start`.
+    pub fn label(&self, start: i64) -> String {
+        let (year, month, _) = civil_from_days(start);
+        match *self {
+            Bucketing::Week => {
+                let (year, week) = iso_week(start);
+                format!("{}-W{:02}", year, week)
+            }
+            Bucketing::Month => format!("{}-{:02}", year, month),
+            Bucketing::Quarter => format!("{}-Q{}", year, (month - 1) / 3 + 1),
+            Bucketing::Year => year.to_string(),
+            Bucketing::Sprint { .. } => format_day(start),
+        }
+    }
+}
+
+impl FromStr for Bucketing {
+    type Err = String;
+
+    /// Parses `week`, `month`, `quarter`, `year` or
+    /// `sprint:<days>[:<YYYY-MM-DD>]`.
+    ///
+    /// Sprints without an anchor are counted from Monday 1970-01-05.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" => Ok(Bucketing::Week),
+            "month" => Ok(Bucketing::Month),
+            "quarter" => Ok(Bucketing::Quarter),
+            "year" => Ok(Bucketing::Year),
+            _ => {
+                let spec = s.strip_prefix("sprint:").ok_or_else(|| {
+                    format!(
+                        "invalid bucketing '{}', expected week, month, quarter, year or sprint:<days>[:<date>]",
+                        s
+                    )
+                })?;
+                let (days, anchor) = match spec.split_once(':') {
+                    Some((days, anchor)) => (days, parse_date(anchor)?),
+                    None => (spec, 4),
+                };
+                let days = days
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| *d > 0)
+                    .ok_or_else(|| format!("invalid sprint length '{}'", days))?;
+                Ok(Bucketing::Sprint { days, anchor })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Bucketing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bucketing::Week => write!(f, "week"),
+            Bucketing::Month => write!(f, "month"),
+            Bucketing::Quarter => write!(f, "quarter"),
+            Bucketing::Year => write!(f, "year"),
+            Bucketing::Sprint { days, anchor } => {
+                write!(f, "sprint:{}:{}", days, format_day(*anchor))
+            }
+        }
+    }
+}
+
+/// Counts days into buckets, including empty buckets between the first and
+/// last non-empty one. Returns `(bucket start, count)` pairs in order.
+pub fn bucket_counts(
+    bucketing: Bucketing,
+    days: impl IntoIterator<Item = i64>,
+) -> Vec<(i64, usize)> {
+    let mut starts: Vec<i64> = days.into_iter().map(|d| bucketing.start_of(d)).collect();
+    starts.sort_unstable();
+
+    let mut counts = Vec::new();
+    let (Some(&first), Some(&last)) = (starts.first(), starts.last()) else {
+        return counts;
+    };
+    let mut remaining = starts.as_slice();
+    let mut start = first;
+    while start <= last {
+        let n = remaining.iter().take_while(|s| **s == start).count();
+        remaining = &remaining[n..];
+        counts.push((start, n));
+        start = bucketing.next(start);
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bucketing() {
+        assert_eq!(Ok(Bucketing::Week), "week".parse());
+        assert_eq!(Ok(Bucketing::Quarter), "quarter".parse());
+        assert_eq!(
+            Ok(Bucketing::Sprint {
+                days: 14,
+                anchor: days_from_civil(2024, 1, 3)
+            }),
+            "sprint:14:2024-01-03".parse()
+        );
+        assert!("sprint:0".parse::<Bucketing>().is_err());
+        assert!("fortnight".parse::<Bucketing>().is_err());
+    }
+
+    #[test]
+    fn test_bucket_labels() {
+        let day = days_from_civil(2024, 8, 21);
+        assert_eq!(
+            "2024-W34",
+            Bucketing::Week.label(Bucketing::Week.start_of(day))
+        );
+        assert_eq!(
+            "2024-08",
+            Bucketing::Month.label(Bucketing::Month.start_of(day))
+        );
+        assert_eq!(
+            "2024-Q3",
+            Bucketing::Quarter.label(Bucketing::Quarter.start_of(day))
+        );
+        assert_eq!("2024", Bucketing::Year.label(Bucketing::Year.start_of(day)));
+        assert_eq!(
+            days_from_civil(2025, 1, 1),
+            Bucketing::Year.next(Bucketing::Year.start_of(day))
+        );
+
+        let sprint = Bucketing::Sprint {
+            days: 14,
+            anchor: days_from_civil(2024, 8, 5),
+        };
+        assert_eq!("2024-08-19", sprint.label(sprint.start_of(day)));
+        assert_eq!(
+            "2024-07-22",
+            sprint.label(sprint.start_of(days_from_civil(2024, 8, 1)))
+        );
+    }
+
+    #[test]
+    fn test_bucket_counts_fill_gaps() {
+        let days = [
+            days_from_civil(2024, 11, 3),
+            days_from_civil(2025, 1, 20),
+            days_from_civil(2024, 11, 28),
+        ];
+        let counts = bucket_counts(Bucketing::Month, days);
+        let labels: Vec<_> = counts
+            .iter()
+            .map(|(start, n)| (Bucketing::Month.label(*start), *n))
+            .collect();
+        assert_eq!(
+            vec![
+                ("2024-11".to_string(), 2),
+                ("2024-12".to_string(), 0),
+                ("2025-01".to_string(), 1)
+            ],
+            labels
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Changes applied more than once, as by `git cherry-pick`.
+//!
+//! A cherry-pick copies a commit's changes into a new commit, so the two
+//! share a patch id even though their ids, parents and often their dates
+//! differ. Release-branch workflows produce many of them: every fix picked
+//! into a maintained release is a second copy of work already on the main
+//! line.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository};
+
+use crate::{
+    churn::get_patch_id, graph::Generations, history::CommitInfo, message::subject,
+    progress::Progress,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CherryPick {
+    /// Subject of the oldest copy.
+    pub subject: String,
+    /// Commits with the same changes, oldest first.
+    pub commits: Vec<Oid>,
+    /// Local and remote-tracking branches containing any copy, sorted.
+    pub branches: Vec<String>,
+}
+
+impl CherryPick {
+    /// Copies beyond the first.
+    pub fn duplicates(&self) -> usize {
+        self.commits.len() - 1
+    }
+}
+
+/// Returns the branches whose tip is or descends from one of `commits`.
+fn branches_containing(
+    repo: &Repository,
+    generations: &mut Generations,
+    commits: &[Oid],
+) -> Result<Vec<String>, git2::Error> {
+    let mut branches = Vec::new();
+    for branch in repo.branches(None)? {
+        let (branch, _) = branch?;
+        let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        for &commit in commits {
+            if generations.contains(repo, tip, commit)? {
+                branches.push(name.to_string());
+                break;
+            }
+        }
+    }
+    branches.sort();
+    Ok(branches)
+}
+
+/// Groups the non-merge commits among `commits` by patch id and returns the
+/// groups with more than one commit, most copies first. Commits that change
+/// nothing are left out, as they would all share the empty patch id.
+pub fn get_cherry_picks(
+    repo: &Repository,
+    generations: &mut Generations,
+    commits: &[CommitInfo],
+    progress: &mut Progress,
+) -> Result<Vec<CherryPick>, git2::Error> {
+    let empty = repo.diff_tree_to_tree(None, None, None)?.patchid(None)?;
+    let mut groups: HashMap<Oid, Vec<&CommitInfo>> = HashMap::new();
+    progress.begin("patch ids", Some(commits.len()));
+    for commit in commits {
+        progress.tick();
+        if commit.is_merge() {
+            continue;
+        }
+        if let Some(patch_id) = get_patch_id(repo, commit.oid)?
+            && patch_id != empty
+        {
+            groups.entry(patch_id).or_default().push(commit);
+        }
+    }
+    progress.end();
+
+    let mut picks = Vec::new();
+    for mut group in groups.into_values().filter(|g| g.len() > 1) {
+        group.sort_by_key(|c| (c.time, c.oid));
+        let commits: Vec<Oid> = group.iter().map(|c| c.oid).collect();
+        picks.push(CherryPick {
+            subject: subject(&group[0].message).to_string(),
+            branches: branches_containing(repo, generations, &commits)?,
+            commits,
+        });
+    }
+    picks.sort_by(|a, b| {
+        b.commits
+            .len()
+            .cmp(&a.commits.len())
+            .then(a.commits.cmp(&b.commits))
+    });
+    Ok(picks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, testutil::TestRepo};
+
+    #[test]
+    fn test_cherry_picks() {
+        let t = TestRepo::new();
+        let base = t.commit_as("A", "a@x.com", 1, "init", &[("a.txt", "1\n")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        let fix = t.commit_as("A", "a@x.com", 2, "fix", &[("b.txt", "fix\n")]);
+        t.commit_as("A", "a@x.com", 3, "feature", &[("a.txt", "2\n")]);
+        t.checkout("release", Some(base));
+        let picked = t.commit_as("B", "b@x.com", 4, "fix (picked)", &[("b.txt", "fix\n")]);
+        t.checkout(&main, None);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let picks = get_cherry_picks(
+            &t.repo,
+            &mut Generations::load(&t.repo),
+            &commits,
+            &mut Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![CherryPick {
+                subject: "fix".to_string(),
+                commits: vec![fix, picked],
+                branches: {
+                    let mut branches = vec![main, "release".to_string()];
+                    branches.sort();
+                    branches
+                },
+            }],
+            picks
+        );
+        assert_eq!(1, picks[0].duplicates());
+    }
+}
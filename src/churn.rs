@@ -0,0 +1,481 @@
+//! Line churn: insertions and deletions introduced by commits.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{Commit, Delta, DiffFindOptions, Oid, Patch, Repository};
+
+use crate::{
+    history::{CommitInfo, is_missing, replay_history},
+    identity::contributor_key,
+    lang::PathFilter,
+    progress::Progress,
+};
+
+/// Diff size of a single commit against its first parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitChurn {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl CommitChurn {
+    /// Lines inserted or deleted.
+    pub fn lines(&self) -> usize {
+        self.insertions + self.deletions
+    }
+}
+
+impl std::ops::AddAssign for CommitChurn {
+    fn add_assign(&mut self, other: Self) {
+        self.files_changed += other.files_changed;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+    }
+}
+
+/// Returns the changes a commit introduces to the files matching `filter`
+/// relative to its first parent, or relative to the empty tree for root
+/// commits.
+pub fn get_commit_churn(
+    repo: &Repository,
+    oid: Oid,
+    filter: &PathFilter,
+) -> Result<CommitChurn, git2::Error> {
+    match diff_commit(repo, oid)? {
+        Some(diff) => diff_churn(&diff, filter),
+        None => Ok(CommitChurn::default()),
+    }
+}
+
+/// Returns the changes `diff` makes to the files matching `filter`.
+pub fn diff_churn(diff: &git2::Diff<'_>, filter: &PathFilter) -> Result<CommitChurn, git2::Error> {
+    if filter.is_empty() {
+        let stats = diff.stats()?;
+        return Ok(CommitChurn {
+            files_changed: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        });
+    }
+
+    let mut churn = CommitChurn::default();
+    for (index, delta) in diff.deltas().enumerate() {
+        let file = match delta.status() {
+            git2::Delta::Deleted => delta.old_file(),
+            _ => delta.new_file(),
+        };
+        if !file
+            .path()
+            .is_some_and(|p| filter.matches(&p.to_string_lossy()))
+        {
+            continue;
+        }
+        churn.files_changed += 1;
+        if let Some(patch) = Patch::from_diff(diff, index)? {
+            let (_, insertions, deletions) = patch.line_stats()?;
+            churn.insertions += insertions;
+            churn.deletions += deletions;
+        }
+    }
+    Ok(churn)
+}
+
+/// Returns the paths a commit changes relative to its first parent. Deleted
+/// files are listed under their old path.
+pub fn get_changed_paths(repo: &Repository, oid: Oid) -> Result<Vec<String>, git2::Error> {
+    Ok(diff_commit(repo, oid)?.map_or_else(Vec::new, |diff| diff_paths(&diff)))
+}
+
+/// Returns the paths `diff` changes, deleted files under their old path.
+pub fn diff_paths(diff: &git2::Diff<'_>) -> Vec<String> {
+    diff.deltas()
+        .filter_map(|delta| {
+            let file = match delta.status() {
+                git2::Delta::Deleted => delta.old_file(),
+                _ => delta.new_file(),
+            };
+            file.path().map(|p| p.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// Returns the lines inserted and deleted per path a commit changes relative
+/// to its first parent, as `(path, insertions, deletions)`. Deleted files are
+/// listed under their old path.
+pub fn get_path_churn(
+    repo: &Repository,
+    oid: Oid,
+) -> Result<Vec<(String, usize, usize)>, git2::Error> {
+    let Some(diff) = diff_commit(repo, oid)? else {
+        return Ok(Vec::new());
+    };
+    let mut churn = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let file = match delta.status() {
+            Delta::Deleted => delta.old_file(),
+            _ => delta.new_file(),
+        };
+        let Some(path) = file.path() else {
+            continue;
+        };
+        let (insertions, deletions) = match Patch::from_diff(&diff, index)? {
+            Some(patch) => {
+                let (_, insertions, deletions) = patch.line_stats()?;
+                (insertions, deletions)
+            }
+            None => (0, 0),
+        };
+        churn.push((path.to_string_lossy().into_owned(), insertions, deletions));
+    }
+    Ok(churn)
+}
+
+/// Upper bounds of the lines-changed buckets of the commit size histogram.
+pub const SIZE_BUCKETS: [usize; 6] = [10, 50, 100, 500, 1_000, 5_000];
+
+/// Returns the churn of every non-merge commit, in the order given. Merges
+/// are left out since their diff repeats the merged work.
+pub fn get_commit_sizes(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    filter: &PathFilter,
+) -> Result<Vec<(Oid, CommitChurn)>, git2::Error> {
+    commits
+        .iter()
+        .filter(|c| !c.is_merge())
+        .map(|c| Ok((c.oid, get_commit_churn(repo, c.oid, filter)?)))
+        .collect()
+}
+
+/// Counts commits per [`SIZE_BUCKETS`] bound of lines changed, with a last
+/// count for larger commits.
+pub fn size_histogram(sizes: &[(Oid, CommitChurn)]) -> [usize; SIZE_BUCKETS.len() + 1] {
+    let mut counts = [0; SIZE_BUCKETS.len() + 1];
+    for (_, churn) in sizes {
+        let index = SIZE_BUCKETS
+            .iter()
+            .position(|bound| churn.lines() <= *bound)
+            .unwrap_or(SIZE_BUCKETS.len());
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Returns the patch id of a commit's changes against its first parent,
+/// which is the same for a commit and its cherry-picks, or `None` when the
+/// commit has gone missing.
+pub fn get_patch_id(repo: &Repository, oid: Oid) -> Result<Option<Oid>, git2::Error> {
+    diff_commit(repo, oid)?
+        .map(|diff| diff.patchid(None))
+        .transpose()
+}
+
+/// Returns the lines each contributor inserted or deleted in files matching
+/// `filter`, most first. Merge commits are skipped: against their first
+/// parent they repeat the work of the merged branch.
+pub fn get_lines_changed(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    filter: &PathFilter,
+) -> Result<Vec<(String, usize)>, git2::Error> {
+    let mut lines: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        let churn = get_commit_churn(repo, commit.oid, filter)?;
+        *lines.entry(contributor_key(commit)).or_default() += churn.lines();
+    }
+    let mut lines: Vec<_> = lines.into_iter().collect();
+    lines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(lines)
+}
+
+/// Lines changed in one file over its whole life, under all its names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileChurn {
+    /// The file's name at HEAD, or its latest name elsewhere.
+    pub path: String,
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Other names it had on any branch, oldest first.
+    pub former_names: Vec<String>,
+    /// Whether the file is gone from HEAD after being deleted.
+    pub deleted: bool,
+}
+
+fn add_file(files: &mut Vec<FileChurn>) -> usize {
+    files.push(FileChurn::default());
+    files.len() - 1
+}
+
+/// Names `file` `path`, keeping its previous name.
+fn rename(file: &mut FileChurn, path: &str) {
+    if file.path != path {
+        let previous = std::mem::replace(&mut file.path, path.to_string());
+        if !previous.is_empty() {
+            file.former_names.push(previous);
+        }
+    }
+}
+
+/// Sums the insertions and deletions per file over the non-merge `commits`,
+/// following renames detected between each commit and its first parent.
+/// Names are tracked per line of history, replayed parents first, so a
+/// rename on one branch renames the file there only; merges bring in the
+/// names of the files they merge. Files are named as at `head` and matched
+/// by `filter` under their name at the time of each change. Most lines
+/// changed come first. Each commit is reported to `progress`.
+pub fn get_file_churn(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    head: Option<Oid>,
+    filter: &PathFilter,
+    progress: &mut Progress,
+) -> Result<Vec<FileChurn>, git2::Error> {
+    let mut files: Vec<FileChurn> = Vec::new();
+    progress.begin("churn", Some(commits.len()));
+    // Index into `files` of the file under each path, per commit.
+    let at_head = replay_history(
+        commits,
+        head,
+        |commit, current: &mut HashMap<String, usize>, merged| {
+            progress.tick();
+            let Some(mut diff) = diff_commit(repo, commit.oid)? else {
+                return Ok(());
+            };
+            diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+            // The file a merged branch has under `path`, if any.
+            let merged_file = |path: &str| merged.iter().find_map(|m| m.get(path).copied());
+            for (index, delta) in diff.deltas().enumerate() {
+                let path =
+                    |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().into_owned());
+                let (Some(old), Some(new)) = (path(delta.old_file()), path(delta.new_file()))
+                else {
+                    continue;
+                };
+                let file = match delta.status() {
+                    Delta::Renamed => {
+                        let file = merged_file(&new)
+                            .or_else(|| current.remove(&old))
+                            .unwrap_or_else(|| add_file(&mut files));
+                        current.remove(&old);
+                        file
+                    }
+                    Delta::Deleted => {
+                        let Some(file) = current.remove(&old) else {
+                            continue;
+                        };
+                        files[file].deleted = true;
+                        file
+                    }
+                    _ => match current.get(&new) {
+                        Some(file) => *file,
+                        None => merged_file(&new).unwrap_or_else(|| add_file(&mut files)),
+                    },
+                };
+                if delta.status() != Delta::Deleted {
+                    rename(&mut files[file], &new);
+                    current.insert(new, file);
+                }
+                // A merge's diff repeats the work of the merged branch.
+                if commit.is_merge() || !filter.matches(&files[file].path) {
+                    continue;
+                }
+                files[file].commits += 1;
+                if let Some(patch) = Patch::from_diff(&diff, index)? {
+                    let (_, insertions, deletions) = patch.line_stats()?;
+                    files[file].insertions += insertions;
+                    files[file].deletions += deletions;
+                }
+            }
+            Ok::<_, git2::Error>(())
+        },
+    )?;
+    progress.end();
+    for (path, file) in at_head.unwrap_or_default() {
+        rename(&mut files[file], &path);
+        files[file].deleted = false;
+    }
+    for file in &mut files {
+        let mut seen = HashSet::new();
+        let path = file.path.clone();
+        file.former_names
+            .retain(|name| *name != path && seen.insert(name.clone()));
+    }
+    files.retain(|f| f.commits > 0);
+    files.sort_by(|a, b| {
+        (b.insertions + b.deletions)
+            .cmp(&(a.insertions + a.deletions))
+            .then(a.path.cmp(&b.path))
+    });
+    Ok(files)
+}
+
+/// Diffs the commit `oid` against its first parent as [`diff_first_parent`]
+/// does, or returns `None` when the commit or a tree has gone missing, as
+/// after a concurrent `git gc`.
+pub fn diff_commit(repo: &Repository, oid: Oid) -> Result<Option<git2::Diff<'_>>, git2::Error> {
+    match repo
+        .find_commit(oid)
+        .and_then(|commit| diff_first_parent(repo, &commit))
+    {
+        Ok(diff) => Ok(Some(diff)),
+        Err(e) if is_missing(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Diffs `commit` against its first parent, or the empty tree for a root
+/// commit.
+pub fn diff_first_parent<'r>(
+    repo: &'r Repository,
+    commit: &Commit<'_>,
+) -> Result<git2::Diff<'r>, git2::Error> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_commit_churn() {
+        let t = TestRepo::new();
+        let root = t.commit("add", &[("a.txt", "one\ntwo\n"), ("b.txt", "x\n")]);
+        let second = t.commit("edit", &[("a.txt", "one\nthree\n")]);
+
+        assert_eq!(
+            CommitChurn {
+                files_changed: 2,
+                insertions: 3,
+                deletions: 0
+            },
+            get_commit_churn(&t.repo, root, &PathFilter::default()).unwrap()
+        );
+        assert_eq!(
+            CommitChurn {
+                files_changed: 1,
+                insertions: 1,
+                deletions: 1
+            },
+            get_commit_churn(&t.repo, second, &PathFilter::default()).unwrap()
+        );
+        let markdown = PathFilter::new(vec!["md".to_string()]);
+        assert_eq!(
+            CommitChurn::default(),
+            get_commit_churn(&t.repo, root, &markdown).unwrap()
+        );
+        let text = PathFilter::new(vec!["txt".to_string()]);
+        assert_eq!(
+            get_commit_churn(&t.repo, root, &PathFilter::default()).unwrap(),
+            get_commit_churn(&t.repo, root, &text).unwrap()
+        );
+
+        let commits = crate::history::collect_commits(
+            &t.repo,
+            None,
+            &mut crate::progress::Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![("test <test@example.com>".to_string(), 5)],
+            get_lines_changed(&t.repo, &commits, &PathFilter::default()).unwrap()
+        );
+        let sizes = get_commit_sizes(&t.repo, &commits, &PathFilter::default()).unwrap();
+        assert_eq!(2, sizes.len());
+        assert_eq!([2, 0, 0, 0, 0, 0, 0], size_histogram(&sizes));
+    }
+
+    #[test]
+    fn test_file_churn_follows_renames() {
+        let t = TestRepo::new();
+        let body = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        t.commit_as("A", "a@x.com", 1, "add", &[("src/statistics.rs", body)]);
+        let mut index = t.repo.index().unwrap();
+        index
+            .remove_path(std::path::Path::new("src/statistics.rs"))
+            .unwrap();
+        index.write().unwrap();
+        t.commit_as("A", "a@x.com", 2, "rename", &[("src/stats.rs", body)]);
+        let head = t.commit_as(
+            "A",
+            "a@x.com",
+            3,
+            "edit",
+            &[("src/stats.rs", "one\ntwo\nthree\nfour\nfive\n6\n")],
+        );
+
+        let commits = crate::history::collect_commits(
+            &t.repo,
+            None,
+            &mut crate::progress::Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![FileChurn {
+                path: "src/stats.rs".to_string(),
+                commits: 3,
+                insertions: 7,
+                deletions: 1,
+                former_names: vec!["src/statistics.rs".to_string()],
+                deleted: false,
+            }],
+            get_file_churn(
+                &t.repo,
+                &commits,
+                Some(head),
+                &PathFilter::default(),
+                &mut crate::progress::Progress::default(),
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_churn_renames_per_branch() {
+        let t = TestRepo::new();
+        let body = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        let base = t.commit_as("A", "a@x.com", 10, "add", &[("a.txt", body)]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        // The side branch's clock is behind: its rename predates the add.
+        t.checkout("side", Some(base));
+        let mut index = t.repo.index().unwrap();
+        index.remove_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        t.commit_as("B", "b@x.com", 5, "rename", &[("b.txt", body)]);
+        t.checkout(&main, None);
+        let head = t.commit_as("A", "a@x.com", 20, "edit", &[("a.txt", "one\n")]);
+
+        let commits = crate::history::collect_commits(
+            &t.repo,
+            None,
+            &mut crate::progress::Progress::default(),
+        )
+        .unwrap();
+        let files = get_file_churn(
+            &t.repo,
+            &commits,
+            Some(head),
+            &PathFilter::default(),
+            &mut crate::progress::Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(1, files.len());
+        assert_eq!(
+            ("a.txt", 3, vec!["b.txt".to_string()], false),
+            (
+                files[0].path.as_str(),
+                files[0].commits,
+                files[0].former_names.clone(),
+                files[0].deleted
+            )
+        );
+        assert_eq!((6, 5), (files[0].insertions, files[0].deletions));
+    }
+}
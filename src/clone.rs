@@ -0,0 +1,253 @@
+//! Estimates of clone download sizes and times.
+//!
+//! Download sizes are estimated from the uncompressed sizes of the objects
+//! each clone kind transfers, scaled by the compression ratio of the local
+//! object store. Full clones transfer every object the references reach,
+//! shallow clones (depth 1) only the objects of HEAD's commit, and blobless
+//! partial clones (`--filter=blob:none`) the commits and trees the
+//! references reach plus the blobs at HEAD. Unreachable objects stay behind.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+
+use crate::{health::reachable_from, stats::calculate_directory_size, worktrees::common_dir};
+
+/// Uncompressed object counts and sizes by type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObjectCounts {
+    pub commits: (usize, u64),
+    pub trees: (usize, u64),
+    pub blobs: (usize, u64),
+    pub tags: (usize, u64),
+}
+
+impl ObjectCounts {
+    fn add(&mut self, kind: ObjectType, size: u64) {
+        let slot = match kind {
+            ObjectType::Commit => &mut self.commits,
+            ObjectType::Tree => &mut self.trees,
+            ObjectType::Blob => &mut self.blobs,
+            ObjectType::Tag => &mut self.tags,
+            _ => return,
+        };
+        slot.0 += 1;
+        slot.1 += size;
+    }
+
+    pub fn count(&self) -> usize {
+        self.commits.0 + self.trees.0 + self.blobs.0 + self.tags.0
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.commits.1 + self.trees.1 + self.blobs.1 + self.tags.1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneCost {
+    /// Bytes the object store occupies on disk.
+    pub on_disk: u64,
+    /// Every object in the store.
+    pub all: ObjectCounts,
+    /// Objects reachable from the references.
+    pub reachable: ObjectCounts,
+    /// Objects reachable from HEAD's commit alone.
+    pub head: ObjectCounts,
+}
+
+impl CloneCost {
+    /// On-disk bytes per uncompressed byte.
+    pub fn compression_ratio(&self) -> f64 {
+        match self.all.total_size() {
+            0 => 1.0,
+            total => self.on_disk as f64 / total as f64,
+        }
+    }
+
+    fn estimate(&self, uncompressed: u64) -> u64 {
+        (uncompressed as f64 * self.compression_ratio()) as u64
+    }
+
+    /// Estimated download size of a full clone.
+    pub fn full(&self) -> u64 {
+        self.estimate(self.reachable.total_size())
+    }
+
+    /// Estimated download size of a `--depth 1` clone.
+    pub fn shallow(&self) -> u64 {
+        self.estimate(self.head.total_size())
+    }
+
+    /// Estimated download size of a `--filter=blob:none` clone, including the
+    /// blobs fetched to check out HEAD.
+    pub fn blobless(&self) -> u64 {
+        let reachable = &self.reachable;
+        self.estimate(
+            reachable.commits.1 + reachable.trees.1 + reachable.tags.1 + self.head.blobs.1,
+        )
+    }
+}
+
+/// Collects object store statistics for the clone estimates.
+pub fn get_clone_cost(repo: &Repository) -> Result<CloneCost, Box<dyn std::error::Error>> {
+    let odb = repo.odb()?;
+    let mut all = ObjectCounts::default();
+    let mut headers: HashMap<Oid, (ObjectType, u64)> = HashMap::new();
+    let mut error = None;
+    odb.foreach(|oid| match odb.read_header(*oid) {
+        Ok((size, kind)) => {
+            // Objects both loose and packed are listed twice.
+            if headers.insert(*oid, (kind, size as u64)).is_none() {
+                all.add(kind, size as u64);
+            }
+            true
+        }
+        Err(e) => {
+            error = Some(e);
+            false
+        }
+    })
+    .or_else(|e| if error.is_some() { Ok(()) } else { Err(e) })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    let mut roots = Vec::new();
+    for reference in repo.references()? {
+        roots.extend(reference?.target());
+    }
+    let mut reachable = ObjectCounts::default();
+    for oid in reachable_from(repo, roots)? {
+        if let Some((kind, size)) = headers.get(&oid) {
+            reachable.add(*kind, *size);
+        }
+    }
+
+    let mut head = ObjectCounts::default();
+    if let Ok(commit) = repo.head().and_then(|h| h.peel_to_commit()) {
+        let (size, kind) = odb.read_header(commit.id())?;
+        head.add(kind, size as u64);
+        let tree = commit.tree()?;
+        let (size, kind) = odb.read_header(tree.id())?;
+        head.add(kind, size as u64);
+
+        // Identical subtrees and blobs are only transferred once.
+        let mut seen: HashSet<Oid> = HashSet::new();
+        let mut error = None;
+        tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+            let is_object = matches!(entry.kind(), Some(ObjectType::Tree | ObjectType::Blob));
+            if !is_object || !seen.insert(entry.id()) {
+                return TreeWalkResult::Ok;
+            }
+            match odb.read_header(entry.id()) {
+                Ok((size, kind)) => {
+                    head.add(kind, size as u64);
+                    TreeWalkResult::Ok
+                }
+                Err(e) => {
+                    error = Some(e);
+                    TreeWalkResult::Abort
+                }
+            }
+        })?;
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+    }
+
+    Ok(CloneCost {
+        on_disk: calculate_directory_size(&common_dir(repo).join("objects"))?,
+        all,
+        reachable,
+        head,
+    })
+}
+
+/// Parses a bandwidth such as `50Mbps`, `1Gbps` or `800kbps` into bits per
+/// second. A bare number is taken as Mbit/s.
+pub fn parse_bandwidth(s: &str) -> Result<u64, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    let split = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid bandwidth '{}'", s))?;
+    let multiplier = match unit.trim().trim_end_matches("/s").trim_end_matches("ps") {
+        "" | "m" | "mb" | "mbit" => 1e6,
+        "k" | "kb" | "kbit" => 1e3,
+        "g" | "gb" | "gbit" => 1e9,
+        _ => return Err(format!("invalid bandwidth unit in '{}'", s)),
+    };
+    let bps = (number * multiplier) as u64;
+    if bps == 0 {
+        return Err(format!("bandwidth '{}' must be positive", s));
+    }
+    Ok(bps)
+}
+
+/// Formats a bandwidth in bits per second, e.g. `100 Mbit/s`.
+pub fn format_bandwidth(bps: u64) -> String {
+    if bps >= 1_000_000_000 && bps.is_multiple_of(1_000_000) {
+        format!("{} Gbit/s", bps as f64 / 1e9)
+    } else if bps >= 1_000_000 {
+        format!("{} Mbit/s", bps as f64 / 1e6)
+    } else {
+        format!("{} kbit/s", bps as f64 / 1e3)
+    }
+}
+
+/// Returns the seconds needed to transfer `bytes` at `bps` bits per second.
+pub fn transfer_seconds(bytes: u64, bps: u64) -> i64 {
+    (bytes as f64 * 8.0 / bps as f64).ceil() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_parse_bandwidth() {
+        assert_eq!(Ok(50_000_000), parse_bandwidth("50Mbps"));
+        assert_eq!(Ok(1_000_000_000), parse_bandwidth("1 Gbit/s"));
+        assert_eq!(Ok(800_000), parse_bandwidth("800kbps"));
+        assert_eq!(Ok(10_000_000), parse_bandwidth("10"));
+        assert!(parse_bandwidth("fast").is_err());
+        assert_eq!("100 Mbit/s", format_bandwidth(100_000_000));
+        assert_eq!(8, transfer_seconds(1_000_000, 1_000_000));
+    }
+
+    #[test]
+    fn test_clone_cost() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", &"a".repeat(1000))]);
+        t.commit("two", &[("a.txt", &"b".repeat(1000))]);
+
+        let cost = get_clone_cost(&t.repo).unwrap();
+        assert_eq!(2, cost.all.commits.0);
+        assert_eq!(2, cost.all.blobs.0);
+        assert_eq!(1, cost.head.commits.0);
+        assert_eq!((1, 1000), cost.head.blobs);
+        assert!(cost.shallow() < cost.full());
+        assert!(cost.blobless() < cost.full());
+
+        // A stray blob is in the store but not in any clone.
+        t.repo.blob(&[b'x'; 5000]).unwrap();
+        let cost = get_clone_cost(&t.repo).unwrap();
+        assert_eq!(3, cost.all.blobs.0);
+        assert_eq!(2, cost.reachable.blobs.0);
+        assert!(cost.full() < cost.estimate(cost.all.total_size()));
+
+        // A linked worktree measures the object store it shares.
+        let linked_dir = tempfile::TempDir::new().unwrap();
+        let linked_path = linked_dir.path().join("feature");
+        t.repo.worktree("feature", &linked_path, None).unwrap();
+        let linked = Repository::open(&linked_path).unwrap();
+        let linked_cost = get_clone_cost(&linked).unwrap();
+        assert!(linked_cost.on_disk > 0);
+        assert_eq!(cost.on_disk, linked_cost.on_disk);
+    }
+}
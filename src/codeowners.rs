@@ -0,0 +1,198 @@
+//! Coverage of the files at HEAD by CODEOWNERS rules.
+//!
+//! The CODEOWNERS file is read from HEAD's tree, in the first of the places
+//! GitHub looks for it. Each rule is a path glob followed by owners; the
+//! last matching rule wins, and a rule without owners leaves its files
+//! unowned. Patterns are matched like `.gitignore` globs, except that one
+//! ending in `/*` matches only the files directly in its directory, as on
+//! GitHub: `docs/*` owns `docs/a.md` but not `docs/api/a.md`.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository};
+
+use crate::{churn::get_changed_paths, glob::Glob, history::CommitInfo, tree::TreeFile};
+
+/// Where CODEOWNERS is looked for, in order.
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeOwners {
+    /// Path the rules were read from.
+    pub path: String,
+    /// `(glob, matches only direct children, owners)`.
+    rules: Vec<(Glob, bool, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Parses the rules of a CODEOWNERS file read from `path`.
+    pub fn parse(path: &str, text: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split(" #").next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().unwrap_or("");
+            let glob = Glob::new(pattern).map_err(|e| format!("{}:{}: {}", path, number + 1, e))?;
+            let direct = pattern.ends_with("/*");
+            rules.push((glob, direct, fields.map(str::to_string).collect()));
+        }
+        Ok(CodeOwners {
+            path: path.to_string(),
+            rules,
+        })
+    }
+
+    /// Reads the CODEOWNERS file at `head`, if there is one.
+    pub fn load(
+        repo: &Repository,
+        head: Option<Oid>,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let Some(head) = head else {
+            return Ok(None);
+        };
+        let tree = repo.find_commit(head)?.tree()?;
+        for path in CODEOWNERS_PATHS {
+            if let Ok(entry) = tree.get_path(std::path::Path::new(path))
+                && let Ok(blob) = repo.find_blob(entry.id())
+            {
+                let text = String::from_utf8_lossy(blob.content());
+                return Ok(Some(CodeOwners::parse(path, &text)?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the owners of `path`, if any rule gives it some.
+    pub fn owners_of(&self, path: &str) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(glob, direct, _)| {
+                if *direct {
+                    glob.matches_exactly(path)
+                } else {
+                    glob.matches(path)
+                }
+            })
+            .map(|(_, _, owners)| owners.as_slice())
+            .filter(|owners| !owners.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub files: usize,
+    /// Files with an owner.
+    pub owned: usize,
+    /// Files per owner, most first.
+    pub owners: Vec<(String, usize)>,
+    /// Unowned files changed by `commits`, with their commits, most first.
+    pub unowned_changed: Vec<(String, usize)>,
+}
+
+/// Measures how many of `files` have owners, and which unowned ones the
+/// non-merge `commits` changed.
+pub fn get_coverage(
+    repo: &Repository,
+    owners: &CodeOwners,
+    files: &[TreeFile],
+    commits: &[CommitInfo],
+) -> Result<Coverage, git2::Error> {
+    let mut coverage = Coverage {
+        files: files.len(),
+        ..Default::default()
+    };
+    let mut per_owner: HashMap<&str, usize> = HashMap::new();
+    let mut unowned: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        match owners.owners_of(&file.path) {
+            Some(list) => {
+                coverage.owned += 1;
+                for owner in list {
+                    *per_owner.entry(owner).or_default() += 1;
+                }
+            }
+            None => {
+                unowned.insert(&file.path, 0);
+            }
+        }
+    }
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        for path in get_changed_paths(repo, commit.oid)? {
+            if let Some(count) = unowned.get_mut(path.as_str()) {
+                *count += 1;
+            }
+        }
+    }
+    coverage.owners = per_owner
+        .into_iter()
+        .map(|(owner, n)| (owner.to_string(), n))
+        .collect();
+    coverage
+        .owners
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    coverage.unowned_changed = unowned
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(path, n)| (path.to_string(), n))
+        .collect();
+    coverage
+        .unowned_changed
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_coverage() {
+        let rules = "# owners\n*.rs @rust\n/docs/ @docs @writers\n/docs/* @top\ndocs/draft.md\n";
+        let t = TestRepo::new();
+        t.commit(
+            "add",
+            &[
+                (".github/CODEOWNERS", rules),
+                ("src/main.rs", "1"),
+                ("docs/a.md", "1"),
+                ("docs/draft.md", "1"),
+                ("Makefile", "1"),
+            ],
+        );
+        t.commit("edit", &[("Makefile", "2"), ("src/main.rs", "2")]);
+
+        let owners = CodeOwners::load(&t.repo, t.repo.head().unwrap().target())
+            .unwrap()
+            .unwrap();
+        assert_eq!(".github/CODEOWNERS", owners.path);
+        assert_eq!(
+            Some(&["@top".to_string()][..]),
+            owners.owners_of("docs/a.md")
+        );
+        assert_eq!(
+            Some(&["@docs".to_string(), "@writers".to_string()][..]),
+            owners.owners_of("docs/api/a.md")
+        );
+        assert_eq!(None, owners.owners_of("docs/draft.md"));
+
+        let files = get_head_files(&t.repo, None).unwrap();
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let coverage = get_coverage(&t.repo, &owners, &files, &commits).unwrap();
+        assert_eq!((5, 2), (coverage.files, coverage.owned));
+        assert_eq!(
+            vec![
+                ("Makefile".to_string(), 2),
+                (".github/CODEOWNERS".to_string(), 1),
+                ("docs/draft.md".to_string(), 1),
+            ],
+            coverage.unowned_changed
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Which contributors work on the same files at around the same time.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+
+use crate::{
+    churn::get_changed_paths, date::SECONDS_PER_DAY, history::CommitInfo,
+    identity::contributor_key, progress::Progress,
+};
+
+/// Number of files each pair of contributors touched within the window of
+/// each other, keyed by the pair in alphabetical order.
+pub type PairCounts = HashMap<(String, String), usize>;
+
+/// Counts, for every pair of contributors, the files both changed within
+/// `window_days` of each other. Each commit diffed is reported to
+/// `progress`.
+pub fn get_collaboration(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    window_days: i64,
+    progress: &mut Progress,
+) -> Result<PairCounts, git2::Error> {
+    let mut touches: HashMap<String, Vec<(i64, String)>> = HashMap::new();
+    progress.begin("collaboration", Some(commits.len()));
+    for commit in commits {
+        progress.tick();
+        let author = contributor_key(commit);
+        for path in get_changed_paths(repo, commit.oid)? {
+            touches
+                .entry(path)
+                .or_default()
+                .push((commit.time, author.clone()));
+        }
+    }
+    progress.end();
+
+    let window = window_days * SECONDS_PER_DAY;
+    let mut pairs = PairCounts::new();
+    for file_touches in touches.values_mut() {
+        file_touches.sort();
+        let mut file_pairs: HashSet<(&str, &str)> = HashSet::new();
+        // The last time each author touched the file within the window, so
+        // each touch pairs with the authors rather than with every commit.
+        let mut recent: HashMap<&str, i64> = HashMap::new();
+        for (time, author) in file_touches.iter() {
+            recent.retain(|_, last| time - *last <= window);
+            for other in recent.keys() {
+                if *other != author {
+                    let pair = if author.as_str() < *other {
+                        (author.as_str(), *other)
+                    } else {
+                        (*other, author.as_str())
+                    };
+                    file_pairs.insert(pair);
+                }
+            }
+            recent.insert(author, *time);
+        }
+        for (a, b) in file_pairs {
+            *pairs.entry((a.to_string(), b.to_string())).or_default() += 1;
+        }
+    }
+    Ok(pairs)
+}
+
+/// Returns the count for a pair in either order.
+pub fn pair_count(pairs: &PairCounts, a: &str, b: &str) -> usize {
+    let key = if a < b { (a, b) } else { (b, a) };
+    pairs
+        .get(&(key.0.to_string(), key.1.to_string()))
+        .copied()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_collaboration() {
+        let day = SECONDS_PER_DAY;
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            day,
+            "a",
+            &[("shared.txt", "1"), ("a.txt", "1")],
+        );
+        t.commit_as("B", "b@x.com", 3 * day, "b", &[("shared.txt", "2")]);
+        t.commit_as(
+            "C",
+            "c@x.com",
+            60 * day,
+            "c",
+            &[("shared.txt", "3"), ("a.txt", "2")],
+        );
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let pairs = get_collaboration(&t.repo, &commits, 30, &mut Progress::default()).unwrap();
+
+        assert_eq!(1, pair_count(&pairs, "B <b@x.com>", "A <a@x.com>"));
+        assert_eq!(0, pair_count(&pairs, "A <a@x.com>", "C <c@x.com>"));
+        assert_eq!(1, pairs.len());
+    }
+}
@@ -0,0 +1,3477 @@
+//! The report of each subcommand, one function per subcommand building it
+//! from the [`Context`] of the run.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::parser::ValueSource;
+use git2::Oid;
+
+use crate::age::get_file_ages;
+use crate::ai::AiEvidence;
+use crate::alerts::{get_week_metrics, judge};
+use crate::anomalies::{TRAILING_WEEKS, get_anomalies};
+use crate::bigfiles::get_largest_blobs;
+use crate::binary::{get_head_kinds, get_introduced_kinds};
+use crate::branches::{get_branch_counts, get_divergence, get_remotes};
+use crate::bucket::{Bucketing, bucket_counts};
+use crate::cherrypicks::{CherryPick, get_cherry_picks};
+use crate::churn::{
+    CommitChurn, SIZE_BUCKETS, get_commit_churn, get_commit_sizes, get_file_churn,
+    get_lines_changed, size_histogram,
+};
+use crate::clone::{format_bandwidth, get_clone_cost, transfer_seconds};
+use crate::codeowners::{CODEOWNERS_PATHS, CodeOwners, get_coverage};
+use crate::collaboration::{get_collaboration, pair_count};
+use crate::config::Config;
+use crate::context::Context;
+use crate::contributors::{
+    ACTIVE_WINDOWS, RETENTION_MONTHS, get_active_contributors, get_commit_counts, get_merge_counts,
+    get_retention, get_roles, split_minor,
+};
+use crate::conventional::get_conventional_stats;
+use crate::dashboard::{ACTIVITY_DAYS, dashboard_page, get_repo_card, page_name, repo_name};
+use crate::date::{DateFormat, SECONDS_PER_DAY, civil_from_days, format_duration, now, today};
+use crate::dirs::get_directory_stats;
+use crate::doctor::{build_info, cache_files, git_config, problems};
+use crate::domains::get_domain_stats;
+use crate::duplicates::get_duplicates;
+use crate::export::{DEFAULT_FIELDS, Field};
+use crate::extensions::get_extension_mix;
+use crate::fingerprint::default_branch;
+use crate::generated::{
+    DEFAULT_GENERATED_PATTERNS, PathStats, generated_patterns_from_config, get_generated_stats,
+};
+use crate::glob::parse_globs;
+use crate::graph::{Generations, get_history_shape};
+use crate::handoff::get_handoff;
+use crate::health::get_object_store;
+use crate::heatmap::{get_calendar, get_heatmap, heatmap_start};
+use crate::history::{CommitInfo, count_empty_commits, short_id, time_span};
+use crate::hotspots::{Hotspot, SILO_COMMITS, SILO_SHARE, get_hotspots};
+use crate::hours::{BusinessHours, OffHours, get_off_hours};
+use crate::identity::{
+    anonymize_email, anonymize_identities, anonymize_identity, contributor_key, count_contributors,
+    resolve_identities,
+};
+use crate::insights::get_insights;
+use crate::issues::{DEFAULT_PATTERNS, get_issue_stats, patterns_from_config};
+use crate::landing::get_landing_stats;
+use crate::lang::{LanguageMap, OTHER, PathFilter, get_language_breakdown};
+use crate::license::get_license_files;
+use crate::lifetime::get_file_lifetimes;
+use crate::loc::{LineCounts, get_file_lines};
+use crate::merges::get_merge_times;
+use crate::message::{SUBJECT_BUCKETS, SUBJECT_LIMIT, get_message_stats, subject};
+use crate::milestones::get_milestones;
+use crate::owners::{
+    blame_lines, get_directory_ownership, get_owned_lines, in_prefix, last_touched_lines,
+};
+use crate::platform::get_platform_signals;
+use crate::protection::{ProtectionRules, get_branch_history};
+use crate::punchcard::get_punchcard;
+use crate::push::get_push_latency;
+use crate::quality::get_quality_stats;
+use crate::range::{get_range_changes, resolve_range};
+use crate::reflog::get_reflog_stats;
+use crate::releases::{get_release_stats, get_semver_stats};
+use crate::report::{Format, Report, Table, Value, bar, percent, render};
+use crate::retained::get_retained_data;
+use crate::roots::get_histories;
+use crate::share::get_shares;
+use crate::signoff::get_signoff_stats;
+use crate::size::{SizeMode, get_size_breakdown};
+use crate::sparse::{recommend_cones, sparse_checkout_command};
+use crate::sprints::{Sprints, get_sprint_stats};
+use crate::stats::percentile;
+use crate::streaks::{Streak, get_streaks};
+use crate::support::{RELEASE_PATTERNS, commits_between, get_support_matrix};
+use crate::teams::{Teams, get_team_stats};
+use crate::testratio::{
+    DEFAULT_TEST_PATTERNS, get_test_ratio, get_test_ratio_trend, test_patterns_from_config,
+};
+use crate::timezones::{format_offset, get_timezone_distribution, primary_offset};
+use crate::tree::{get_size_distribution, get_special_entries, get_tree_shape};
+use crate::unreachable::get_unreachable_objects;
+use crate::velocity::{VELOCITY_WEEKS, get_velocity};
+use crate::vendor::{VersionStatus, get_vendored_packages};
+use crate::worktrees::{get_worktrees, open_repository};
+
+/// Fills `report` for the subcommand `name` with its arguments `sub`.
+pub fn run_subcommand(
+    ctx: &mut Context,
+    name: &str,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match name {
+        "activity" => activity(ctx, sub, report),
+        "age" => age(ctx, sub, report),
+        "ai" => ai(ctx, sub, report),
+        "alerts" => alerts(ctx, sub, report),
+        #[cfg(feature = "bench")]
+        "bench" => bench(sub, report),
+        "bigfiles" => bigfiles(ctx, sub, report),
+        "binary" => binary(ctx, sub, report),
+        "branches" => branches(ctx, sub, report),
+        "calendar" => calendar(ctx, sub, report),
+        "cherry-picks" => cherry_picks(ctx, sub, report),
+        "churn" => churn(ctx, sub, report),
+        "clone-cost" => clone_cost(ctx, sub, report),
+        "codeowners" => codeowners(ctx, sub, report),
+        "collaboration" => collaboration(ctx, sub, report),
+        "commits" => commits(ctx, sub, report),
+        "committers" => committers(ctx, sub, report),
+        "contributors" => contributors(ctx, sub, report),
+        "conventional" => conventional(ctx, sub, report),
+        "dashboard" => dashboard(ctx, sub, report),
+        "direct-pushes" => direct_pushes(ctx, sub, report),
+        "dirs" => dirs(ctx, sub, report),
+        "doctor" => doctor(ctx, report),
+        "domains" => domains(ctx, sub, report),
+        "duplicates" => duplicates(ctx, sub, report),
+        "export" => export(ctx, sub, report),
+        "extensions" => extensions(ctx, sub, report),
+        "filesizes" => filesizes(ctx, sub, report),
+        "generated" => generated(ctx, sub, report),
+        "handoff" => handoff(ctx, sub, report),
+        "health" => health(ctx, report),
+        "heatmap" => heatmap(ctx, sub, report),
+        "hotspots" => hotspots(ctx, sub, report),
+        "hours" => hours(ctx, sub, report),
+        "issues" => issues(ctx, sub, report),
+        "languages" => languages(ctx, sub, report),
+        "lifetime" => lifetime(ctx, sub, report),
+        "messages" => messages(ctx, sub, report),
+        "milestones" => milestones(ctx, sub, report),
+        "owners" => owners(ctx, sub, report),
+        "platforms" => platforms(ctx, sub, report),
+        "protection" => protection(ctx, sub, report),
+        "punchcard" => punchcard(ctx, sub, report),
+        "push-latency" => push_latency(ctx, report),
+        "quality" => quality(ctx, sub, report),
+        "range" => range(ctx, sub, report),
+        "reflog" => reflog(ctx, sub, report),
+        "releases" => releases(ctx, sub, report),
+        "retained" => retained(ctx, sub, report),
+        "retention" => retention(ctx, sub, report),
+        "roots" => roots(ctx, report),
+        "share" => share(ctx, sub, report),
+        "signoffs" => signoffs(ctx, sub, report),
+        "sparse" => sparse(ctx, sub, report),
+        "sprints" => sprints(ctx, sub, report),
+        "streaks" => streaks(ctx, sub, report),
+        "summary" => summary(ctx, sub, report),
+        "support" => support(ctx, sub, report),
+        "teams" => teams(ctx, sub, report),
+        "test-ratio" => test_ratio(ctx, sub, report),
+        "time-to-merge" => time_to_merge(ctx, sub, report),
+        "timezones" => timezones(ctx, sub, report),
+        "tree" => tree(ctx, sub, report),
+        "unreachable" => unreachable(ctx, sub, report),
+        "vendor" => vendor(ctx, report),
+        "worktrees" => worktrees(ctx, report),
+        _ => Ok(()),
+    }
+}
+
+fn activity(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bucketing = sub
+        .get_one::<Bucketing>("interval")
+        .or(sub.get_one::<Bucketing>("bucket"))
+        .copied()
+        .unwrap_or(Bucketing::Month);
+    let commits = ctx.commits(sub)?;
+    let counts = bucket_counts(bucketing, commits.iter().map(|c| c.day()));
+    let max = counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    report.field("Commits", commits.len());
+    report.field("Periods", counts.len());
+    report.field("Busiest", max);
+
+    let mut table = Table::new(
+        format!("Commits per {}", bucketing),
+        &["Period", "Commits", ""],
+    );
+    for (start, count) in &counts {
+        table.push(vec![
+            bucketing.label(*start).into(),
+            (*count).into(),
+            bar(*count, max, 40).into(),
+        ]);
+    }
+    report.table(table);
+
+    if sub.get_flag("anomalies") {
+        let sigma = *sub.get_one::<f64>("sigma").expect("sigma has a default");
+        let anomalies = get_anomalies(commits.iter().map(|c| c.day()), today(), sigma);
+        report.field("Anomalous Weeks", anomalies.len());
+        let mut table = Table::new(
+            format!(
+                "Weeks more than {} standard deviations from the trailing {} weeks",
+                sigma, TRAILING_WEEKS
+            ),
+            &["Week", "Commits", "Trailing Mean", "Deviation", "Kind"],
+        );
+        for anomaly in &anomalies {
+            table.push(vec![
+                Bucketing::Week.label(anomaly.week).into(),
+                anomaly.commits.into(),
+                format!("{:.1}", anomaly.trailing_mean).into(),
+                format!("{:+.1}σ", anomaly.sigmas).into(),
+                anomaly.kind().into(),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn age(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let filter = PathFilter::from_matches(sub);
+    let files: Vec<_> = ctx
+        .head_files()?
+        .iter()
+        .filter(|f| filter.matches(&f.path))
+        .cloned()
+        .collect();
+    let ages = get_file_ages(&ctx.repo, &commits, &files)?;
+    let now = now();
+    report.field("Files", ages.len());
+    if let Some(oldest) = ages.iter().min_by_key(|a| (a.created, &a.path)) {
+        report.field("Oldest File", oldest.path.as_str());
+        report.field("Created", Value::Time(oldest.created));
+    }
+    let untouched = |days: i64| {
+        ages.iter()
+            .filter(|a| now - a.modified >= days * SECONDS_PER_DAY)
+            .count()
+    };
+    report.field("Untouched for a Year", untouched(365));
+    report.field("Untouched for 3 Years", untouched(3 * 365));
+
+    let shown = if sub.get_flag("all") { ages.len() } else { top };
+    let mut table = Table::new(
+        "Least recently modified files",
+        &[
+            "File",
+            "Created",
+            "Last Modified",
+            "Days Untouched",
+            "Commits",
+        ],
+    );
+    for age in ages.into_iter().take(shown) {
+        table.push(vec![
+            age.path.into(),
+            Value::Time(age.created),
+            Value::Time(age.modified),
+            (((now - age.modified) / SECONDS_PER_DAY).max(0) as usize).into(),
+            age.commits.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn ai(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tools = ctx.ai_tools()?;
+    let commits = ctx.commits(sub)?;
+    let ai_commits: Vec<_> = commits
+        .iter()
+        .filter_map(|c| tools.get(&c.oid).map(|d| (c, *d)))
+        .collect();
+
+    report.field("Total Commits", commits.len());
+    report.field("AI Commits", ai_commits.len());
+    report.field(
+        "AI Share",
+        Value::Percent(percent(ai_commits.len(), commits.len())),
+    );
+    for evidence in [
+        AiEvidence::Author,
+        AiEvidence::CoAuthor,
+        AiEvidence::Signature,
+    ] {
+        let count = ai_commits
+            .iter()
+            .filter(|(_, (_, e))| *e == evidence)
+            .count();
+        report.field(format!("Via {}", evidence.label()), count);
+    }
+
+    let mut tools: Vec<(&str, usize)> = Vec::new();
+    for (_, (tool, _)) in &ai_commits {
+        match tools.iter_mut().find(|(t, _)| t == tool) {
+            Some((_, n)) => *n += 1,
+            None => tools.push((tool, 1)),
+        }
+    }
+    tools.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut table = Table::new("AI commits by tool", &["Tool", "Commits", "Share"]);
+    for (tool, count) in tools {
+        table.push(vec![
+            tool.into(),
+            count.into(),
+            Value::Percent(percent(count, ai_commits.len())),
+        ]);
+    }
+    report.table(table);
+
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Month);
+    let all = bucket_counts(bucketing, commits.iter().map(|c| c.day()));
+    let ai = bucket_counts(bucketing, ai_commits.iter().map(|(c, _)| c.day()));
+    let mut table = Table::new(
+        format!("AI share per {}", bucketing),
+        &["Period", "Commits", "AI Commits", "Share"],
+    );
+    for (start, count) in all {
+        let ai_count = ai.iter().find(|(s, _)| *s == start).map_or(0, |(_, n)| *n);
+        table.push(vec![
+            bucketing.label(start).into(),
+            count.into(),
+            ai_count.into(),
+            Value::Percent(percent(ai_count, count)),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn alerts(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let baseline = *sub.get_one::<u16>("weeks").expect("weeks has a default") as usize;
+    let sigma = *sub.get_one::<f64>("sigma").expect("sigma has a default");
+    let commits = ctx.commits(sub)?;
+    let last = Bucketing::Week.start_of(today()) - 7;
+    let weeks = get_week_metrics(
+        &ctx.repo,
+        &commits,
+        last,
+        baseline + 1,
+        &PathFilter::from_matches(sub),
+    )?;
+    let judgements = judge(&weeks);
+    let alerts = judgements.iter().filter(|j| j.is_alert(sigma)).count();
+    report.field("Week", Bucketing::Week.label(last));
+    report.field("Baseline Weeks", baseline);
+    report.field("Alerts", alerts);
+
+    let mut table = Table::new(
+        format!(
+            "Last week against the {} weeks before (alert beyond {}σ)",
+            baseline, sigma
+        ),
+        &["Metric", "Value", "Mean", "Deviation", "Sigmas", "Status"],
+    );
+    for judgement in &judgements {
+        let (sigmas, status) = match judgement.sigmas {
+            Some(s) if judgement.is_alert(sigma) => (format!("{:+.1}σ", s), "alert"),
+            Some(s) => (format!("{:+.1}σ", s), "ok"),
+            None => ("-".to_string(), "not judged"),
+        };
+        table.push(vec![
+            judgement.metric.into(),
+            (judgement.value as usize).into(),
+            format!("{:.1}", judgement.mean).into(),
+            format!("{:.1}", judgement.deviation).into(),
+            sigmas.into(),
+            status.into(),
+        ]);
+    }
+    report.table(table);
+    if sub.get_flag("fail") && alerts > 0 {
+        ctx.failure = Some(format!(
+            "{} of {} metrics outside their normal range",
+            alerts,
+            judgements.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "bench")]
+fn bench(sub: &clap::ArgMatches, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let repositories: Vec<PathBuf> = sub
+        .get_many::<PathBuf>("repo")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let metrics: Vec<String> = sub
+        .get_many::<String>("metric")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let runs = *sub.get_one::<u32>("runs").expect("runs has a default");
+    let baseline = match sub.get_one::<PathBuf>("baseline") {
+        Some(path) => Some(crate::bench::parse_baseline(
+            &fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?,
+        )),
+        None => None,
+    };
+    let timings = crate::bench::run_bench(&repositories, &metrics, runs)?;
+    if let Some(path) = sub.get_one::<PathBuf>("save") {
+        fs::write(path, crate::bench::write_baseline(&timings))
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    }
+    crate::bench::report_bench(report, &timings, baseline.as_deref());
+    Ok(())
+}
+
+fn bigfiles(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.raw_commits()?.to_vec();
+    let blobs = get_largest_blobs(&ctx.repo, &commits, top)?;
+    report.field("Blobs", blobs.count);
+    report.field("Total Blob Size", Value::Size(blobs.total_size));
+
+    let mut table = Table::new("Largest blobs", &["Blob", "Size", "Path", "Introduced In"]);
+    for blob in &blobs.largest {
+        table.push(vec![
+            short_id(blob.oid).into(),
+            Value::Size(blob.size),
+            blob.path.as_deref().unwrap_or("(unreachable)").into(),
+            blob.commit.map_or_else(|| "-".to_string(), short_id).into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn binary(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let files = ctx.head_files()?.to_vec();
+    let head = get_head_kinds(&ctx.repo, &files)?;
+    report.field("Text Files", head.text_files);
+    report.field("Text Size", Value::Size(head.text_bytes));
+    report.field("Binary Files", head.binary_files);
+    report.field("Binary Size", Value::Size(head.binary_bytes));
+    report.field(
+        "Binary Share",
+        Value::Percent(percent(
+            head.binary_bytes as usize,
+            head.total_bytes() as usize,
+        )),
+    );
+
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Month);
+    let commits = ctx.commits(sub)?;
+    let mut table = Table::new(
+        format!("Blobs introduced per {}", bucketing),
+        &[
+            "Period",
+            "Text Files",
+            "Text Size",
+            "Binary Files",
+            "Binary Size",
+            "Binary Share",
+        ],
+    );
+    for (start, kinds) in get_introduced_kinds(&ctx.repo, &commits, bucketing)? {
+        table.push(vec![
+            bucketing.label(start).into(),
+            kinds.text_files.into(),
+            Value::Size(kinds.text_bytes),
+            kinds.binary_files.into(),
+            Value::Size(kinds.binary_bytes),
+            Value::Percent(percent(
+                kinds.binary_bytes as usize,
+                kinds.total_bytes() as usize,
+            )),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn branches(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let counts = get_branch_counts(&ctx.repo)?;
+    report.field("Local Branches", counts.local);
+    report.field("Remote Branches", counts.remote);
+    report.field("Tracking Branches", counts.tracking);
+    let mut table = Table::new("Remotes", &["Remote", "Branches", "URL", "Push URL"]);
+    for remote in get_remotes(&ctx.repo)? {
+        table.push(vec![
+            remote.name.into(),
+            remote.branches.into(),
+            remote.url.unwrap_or_default().into(),
+            remote.push_url.unwrap_or_default().into(),
+        ]);
+    }
+    report.table(table);
+    if sub.get_flag("divergence") {
+        let branch = match sub.get_one::<String>("branch") {
+            Some(branch) => branch.clone(),
+            None => default_branch(&ctx.repo).ok_or("no default branch, pass --branch")?,
+        };
+        let tip = ctx
+            .branch_tip(&branch)
+            .map_err(|_| format!("unknown branch '{}'", branch))?;
+        report.field("Compared With", branch.as_str());
+        let mut table = Table::new(
+            format!("Divergence from {}", branch),
+            &["Branch", "Ahead", "Behind", "Last Activity"],
+        );
+        let snapshot = ctx.snapshot()?.clone();
+        let mut generations = Generations::load(&ctx.repo);
+        let divergence = get_divergence(&ctx.repo, &mut generations, &snapshot, &branch, tip)?;
+        ctx.save_generations(&generations);
+        for divergence in divergence {
+            table.push(vec![
+                divergence.branch.into(),
+                divergence.ahead.into(),
+                divergence.behind.into(),
+                Value::Time(divergence.last_activity),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn calendar(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let year = *sub.get_one::<i64>("year").expect("year is required");
+    let commits = ctx.commits(sub)?;
+    let calendar = get_calendar(&commits, year);
+    let days = calendar.iter().flatten();
+    let max = days.clone().copied().max().unwrap_or(0);
+    report.field("Year", year.to_string());
+    report.field("Commits", days.clone().sum::<usize>());
+    report.field("Active Days", days.filter(|n| **n > 0).count());
+    report.field("Busiest Day", max);
+
+    // Label days 1, 10, 20 and 30 above their cells.
+    let mut label = vec![' '; 62];
+    for day in [1, 10, 20, 30] {
+        let at = (day - 1) * 2;
+        label.splice(at..at + 2, format!("{:<2}", day).chars());
+    }
+    let label: String = label.into_iter().collect();
+    let mut table = Table::new(
+        format!("Commits per day, {}", year),
+        &["Month", &label, "Commits"],
+    );
+    for (month, counts) in calendar.into_iter().enumerate() {
+        let total: usize = counts.iter().sum();
+        table.push(vec![
+            MONTHS[month].into(),
+            Value::Intensities { counts, max },
+            total.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn cherry_picks(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.raw_commits()?.to_vec();
+    let mut generations = Generations::load(&ctx.repo);
+    let picks = get_cherry_picks(&ctx.repo, &mut generations, &commits, &mut ctx.progress)?;
+    ctx.save_generations(&generations);
+    let duplicates: usize = picks.iter().map(CherryPick::duplicates).sum();
+    let changes = commits.iter().filter(|c| !c.is_merge()).count();
+    report.field("Cherry-Picked Changes", picks.len());
+    report.field("Duplicate Commits", duplicates);
+    report.field("Duplication", Value::Percent(percent(duplicates, changes)));
+    let mut table = Table::new(
+        "Most copied changes",
+        &["Change", "Copies", "First Commit", "Branches"],
+    );
+    for pick in picks.iter().take(top) {
+        table.push(vec![
+            pick.subject.as_str().into(),
+            pick.commits.len().into(),
+            short_id(pick.commits[0]).into(),
+            pick.branches.join(", ").into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn churn(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefix = sub
+        .get_one::<String>("path")
+        .map_or("", |p| p.trim_start_matches("./").trim_end_matches('/'));
+    let prefix = if prefix == "." { "" } else { prefix };
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let head = ctx.head()?;
+    let mut files = get_file_churn(
+        &ctx.repo,
+        &commits,
+        head,
+        &PathFilter::from_matches(sub),
+        &mut ctx.progress,
+    )?;
+    files.retain(|f| {
+        in_prefix(&f.path, prefix) || f.former_names.iter().any(|n| in_prefix(n, prefix))
+    });
+    report.field("Files", files.len());
+    report.field(
+        "Renamed",
+        files.iter().filter(|f| !f.former_names.is_empty()).count(),
+    );
+    report.field(
+        "Insertions",
+        files.iter().map(|f| f.insertions).sum::<usize>(),
+    );
+    report.field(
+        "Deletions",
+        files.iter().map(|f| f.deletions).sum::<usize>(),
+    );
+    let mut table = Table::new(
+        "Churn per file",
+        &["File", "Commits", "Insertions", "Deletions", "Former Names"],
+    );
+    for file in files.into_iter().take(top) {
+        let path = if file.deleted {
+            format!("{} (deleted)", file.path)
+        } else {
+            file.path
+        };
+        table.push(vec![
+            path.into(),
+            file.commits.into(),
+            file.insertions.into(),
+            file.deletions.into(),
+            file.former_names.join(", ").into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn clone_cost(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bandwidths: Vec<u64> = match sub.get_many::<u64>("bandwidth") {
+        Some(values) => values.copied().collect(),
+        None => vec![10_000_000, 100_000_000, 1_000_000_000],
+    };
+    let cost = get_clone_cost(&ctx.repo)?;
+    report.field("Objects", cost.all.count());
+    report.field("Uncompressed", Value::Size(cost.all.total_size()));
+    report.field("Object Store", Value::Size(cost.on_disk));
+    report.field(
+        "Compression",
+        Value::Percent(cost.compression_ratio() * 100.0),
+    );
+
+    let mut columns = vec!["Clone".to_string(), "Download".to_string()];
+    columns.extend(
+        bandwidths
+            .iter()
+            .map(|b| format!("@ {}", format_bandwidth(*b))),
+    );
+    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+    let mut table = Table::new("Estimated clone cost", &columns);
+    for (kind, size) in [
+        ("full", cost.full()),
+        ("shallow (depth 1)", cost.shallow()),
+        ("blobless (blob:none)", cost.blobless()),
+    ] {
+        let mut row = vec![kind.into(), Value::Size(size)];
+        row.extend(
+            bandwidths
+                .iter()
+                .map(|b| format_duration(transfer_seconds(size, *b)).into()),
+        );
+        table.push(row);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn codeowners(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let days = *sub.get_one::<u32>("days").expect("days has a default");
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let head = ctx.head()?;
+    let owners = CodeOwners::load(&ctx.repo, head)?.ok_or_else(|| {
+        format!(
+            "no CODEOWNERS at HEAD, looked for {}",
+            CODEOWNERS_PATHS.join(", ")
+        )
+    })?;
+    let since = now() - days as i64 * SECONDS_PER_DAY;
+    let commits: Vec<CommitInfo> = ctx
+        .commits(sub)?
+        .iter()
+        .filter(|c| c.time >= since)
+        .cloned()
+        .collect();
+    let files = ctx.head_files()?.to_vec();
+    let coverage = get_coverage(&ctx.repo, &owners, &files, &commits)?;
+    report.field("CODEOWNERS", owners.path.as_str());
+    report.field("Files", coverage.files);
+    report.field("Owned Files", coverage.owned);
+    report.field(
+        "Coverage",
+        Value::Percent(percent(coverage.owned, coverage.files)),
+    );
+    report.field("Unowned Files Changed", coverage.unowned_changed.len());
+
+    let mut table = Table::new("Files per owner", &["Owner", "Files", "Share"]);
+    for (owner, count) in coverage.owners.iter().take(top) {
+        table.push(vec![
+            owner.as_str().into(),
+            (*count).into(),
+            Value::Percent(percent(*count, coverage.files)),
+        ]);
+    }
+    report.table(table);
+    let mut table = Table::new(
+        format!("Unowned files changed in the last {} days", days),
+        &["File", "Commits"],
+    );
+    for (path, count) in coverage.unowned_changed.iter().take(top) {
+        table.push(vec![path.as_str().into(), (*count).into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn collaboration(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let window = *sub.get_one::<u32>("window").expect("window has a default");
+    let top = *sub.get_one::<usize>("top").expect("top has a default");
+    let commits = ctx.commits(sub)?;
+    let pairs = get_collaboration(&ctx.repo, &commits, window as i64, &mut ctx.progress)?;
+    report.field("Contributors", count_contributors(&commits));
+    report.field("Collaborating Pairs", pairs.len());
+    report.field("Window", format!("{} days", window));
+
+    let mut ranked: Vec<_> = pairs.iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let mut table = Table::new(
+        "Top collaborating pairs",
+        &["Contributor", "Collaborator", "Shared Files"],
+    );
+    for ((a, b), count) in ranked.iter().take(20) {
+        table.push(vec![a.as_str().into(), b.as_str().into(), (**count).into()]);
+    }
+    report.table(table);
+
+    let mut authors = get_commit_counts(&commits);
+    authors.truncate(top);
+    let labels: Vec<String> = (1..=authors.len()).map(|i| format!("#{}", i)).collect();
+    let mut columns = vec!["Contributor"];
+    columns.extend(labels.iter().map(String::as_str));
+    let mut table = Table::new("Collaboration matrix", &columns);
+    for (i, (author, _)) in authors.iter().enumerate() {
+        let mut row = vec![format!("#{} {}", i + 1, author).into()];
+        for (j, (other, _)) in authors.iter().enumerate() {
+            row.push(if i == j {
+                "-".into()
+            } else {
+                pair_count(&pairs, author, other).into()
+            });
+        }
+        table.push(row);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn commits(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    report.field("Total Commits", ctx.total_commits(sub)?);
+    let commits = ctx.commits(sub)?;
+    let empty = count_empty_commits(&commits);
+    report.field("Empty Commits", empty.empty);
+    report.field("No-op Merges", empty.noop_merges);
+    if let Some(&bucketing) = sub.get_one::<Bucketing>("bucket") {
+        let mut table = Table::new(format!("Commits per {}", bucketing), &["Period", "Commits"]);
+        for (start, count) in bucket_counts(bucketing, commits.iter().map(|c| c.day())) {
+            table.push(vec![bucketing.label(start).into(), count.into()]);
+        }
+        report.table(table);
+    }
+    if sub.get_flag("sizes") {
+        let sizes = get_commit_sizes(&ctx.repo, &commits, &PathFilter::from_matches(sub))?;
+        let mut table = Table::new(
+            "Commit sizes (merges excluded)",
+            &["Measure", "p50", "p90", "p99", "Max"],
+        );
+        let column = |measure: fn(&CommitChurn) -> usize| {
+            let mut values: Vec<usize> = sizes.iter().map(|(_, c)| measure(c)).collect();
+            values.sort_unstable();
+            values
+        };
+        for (name, values) in [
+            ("Files Changed", column(|c| c.files_changed)),
+            ("Insertions", column(|c| c.insertions)),
+            ("Deletions", column(|c| c.deletions)),
+            ("Lines Changed", column(CommitChurn::lines)),
+        ] {
+            let mut row = vec![name.into()];
+            for p in [50.0, 90.0, 99.0, 100.0] {
+                row.push(percentile(&values, p).unwrap_or(0).into());
+            }
+            table.push(row);
+        }
+        report.table(table);
+
+        let histogram = size_histogram(&sizes);
+        let max = histogram.iter().copied().max().unwrap_or(0);
+        let mut table = Table::new(
+            "Lines changed per commit",
+            &["Lines", "Commits", "Share", ""],
+        );
+        for (index, count) in histogram.iter().enumerate() {
+            table.push(vec![
+                range_label(&SIZE_BUCKETS, index, 0).into(),
+                (*count).into(),
+                Value::Percent(percent(*count, sizes.len())),
+                bar(*count, max, 30).into(),
+            ]);
+        }
+        report.table(table);
+    }
+    if let Some(&largest) = sub.get_one::<u16>("largest") {
+        let mut sizes = get_commit_sizes(&ctx.repo, &commits, &PathFilter::from_matches(sub))?;
+        let by_files = sub.get_one::<String>("by").is_some_and(|by| by == "files");
+        sizes.sort_by_key(|(_, c)| {
+            std::cmp::Reverse(if by_files { c.files_changed } else { c.lines() })
+        });
+        let by_oid: HashMap<Oid, &CommitInfo> = commits.iter().map(|c| (c.oid, c)).collect();
+        let mut table = Table::new(
+            format!(
+                "Largest commits by {}",
+                if by_files {
+                    "files touched"
+                } else {
+                    "lines changed"
+                }
+            ),
+            &["Commit", "Author", "Date", "Files", "Lines", "Subject"],
+        );
+        for (oid, churn) in sizes.iter().take(largest as usize) {
+            let commit = by_oid[oid];
+            table.push(vec![
+                commit.short_id().into(),
+                contributor_key(commit).into(),
+                Value::Time(commit.time),
+                churn.files_changed.into(),
+                churn.lines().into(),
+                subject(&commit.message).into(),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn committers(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let roles = get_roles(&commits);
+    let others = commits.iter().filter(|c| c.committed_by_other()).count();
+    report.field("Authors", roles.values().filter(|r| r.authored > 0).count());
+    report.field(
+        "Committers",
+        roles.values().filter(|r| r.committed > 0).count(),
+    );
+    report.field("Committed by Others", others);
+    report.field("Share", Value::Percent(percent(others, commits.len())));
+
+    let mut roles: Vec<_> = roles.into_iter().collect();
+    roles.sort_by(|a, b| {
+        b.1.committed
+            .cmp(&a.1.committed)
+            .then(b.1.committed_for_others.cmp(&a.1.committed_for_others))
+            .then(a.0.cmp(&b.0))
+    });
+    let mut table = Table::new(
+        "Top committers",
+        &["Committer", "Committed", "For Others", "Share", "Authored"],
+    );
+    for (person, r) in roles.iter().filter(|(_, r)| r.committed > 0) {
+        table.push(vec![
+            person.as_str().into(),
+            r.committed.into(),
+            r.committed_for_others.into(),
+            Value::Percent(percent(r.committed_for_others, r.committed)),
+            r.authored.into(),
+        ]);
+    }
+    report.table(table);
+
+    roles.sort_by(|a, b| b.1.authored.cmp(&a.1.authored).then(a.0.cmp(&b.0)));
+    let mut table = Table::new(
+        "Top authors",
+        &[
+            "Author",
+            "Authored",
+            "Committed by Others",
+            "Share",
+            "Committed",
+        ],
+    );
+    for (person, r) in roles.iter().filter(|(_, r)| r.authored > 0) {
+        table.push(vec![
+            person.as_str().into(),
+            r.authored.into(),
+            r.committed_by_others.into(),
+            Value::Percent(percent(r.committed_by_others, r.authored)),
+            r.committed.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn contributors(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let min_commits = *sub
+        .get_one::<usize>("min-commits")
+        .expect("min-commits has a default");
+    let commits = ctx.commits(sub)?;
+    report.field("Contributors", count_contributors(&commits));
+
+    let merges = get_merge_counts(&commits);
+    let (counts, others) = split_minor(get_commit_counts(&commits), min_commits);
+    let mut table = Table::new(
+        "Commits by contributor",
+        &["Contributor", "Commits", "Regular", "Merges", "Share"],
+    );
+    let mut kept_merges = 0;
+    for (contributor, count) in &counts {
+        let merged = merges.get(contributor).copied().unwrap_or(0);
+        kept_merges += merged;
+        table.push(vec![
+            contributor.as_str().into(),
+            (*count).into(),
+            (count - merged).into(),
+            merged.into(),
+            Value::Percent(percent(*count, commits.len())),
+        ]);
+    }
+    if others.contributors > 0 {
+        let merged = merges.values().sum::<usize>() - kept_merges;
+        table.push(vec![
+            format!("others ({} contributors)", others.contributors).into(),
+            others.commits.into(),
+            (others.commits - merged).into(),
+            merged.into(),
+            Value::Percent(percent(others.commits, commits.len())),
+        ]);
+    }
+    report.table(table);
+
+    // Integrators who only merge others' work would otherwise rank
+    // as if they wrote it.
+    let mut table = Table::new("Merge-only contributors", &["Contributor", "Merges"]);
+    for (contributor, count) in get_commit_counts(&commits) {
+        if merges.get(&contributor) == Some(&count) {
+            table.push(vec![contributor.into(), count.into()]);
+        }
+    }
+    report.table(table);
+    if sub.get_flag("show-identities") {
+        // Group the identities as recorded, not as already merged.
+        let mut commits = ctx.raw_commits()?.to_vec();
+        if sub.get_flag("anonymize") {
+            anonymize_identities(&mut commits);
+        }
+        let mut table = Table::new("Merged identities", &["Contributor", "Identity", "Commits"]);
+        for group in resolve_identities(&commits)
+            .iter()
+            .filter(|g| g.members.len() > 1)
+        {
+            let (name, email) = &group.canonical;
+            for ((member_name, member_email), count) in &group.members {
+                table.push(vec![
+                    format!("{} <{}>", name, email).into(),
+                    format!("{} <{}>", member_name, member_email).into(),
+                    (*count).into(),
+                ]);
+            }
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn conventional(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = get_conventional_stats(&ctx.commits(sub)?);
+    let strict = sub.get_flag("strict");
+    let conforming = if strict {
+        stats.clean
+    } else {
+        stats.conventional
+    };
+    report.field("Commits", stats.commits);
+    report.field("Conforming", conforming);
+    report.field(
+        "Conforming Share",
+        Value::Percent(percent(conforming, stats.commits)),
+    );
+    if strict {
+        report.field("With Violations", stats.conventional - stats.clean);
+    }
+    report.field("Scoped", stats.scoped);
+    report.field("Breaking Changes", stats.breaking);
+
+    let mut table = Table::new("Commits by type", &["Type", "Commits", "Share"]);
+    for (kind, count) in &stats.types {
+        table.push(vec![
+            kind.as_str().into(),
+            (*count).into(),
+            Value::Percent(percent(*count, stats.conventional)),
+        ]);
+    }
+    report.table(table);
+    if strict {
+        let mut table = Table::new("Violations", &["Violation", "Commits"]);
+        for (violation, count) in &stats.violations {
+            table.push(vec![violation.to_string().into(), (*count).into()]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn dashboard(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = sub
+        .get_one::<PathBuf>("out-dir")
+        .expect("out-dir has a default");
+    let title = sub.get_one::<String>("title").expect("title has a default");
+    fs::create_dir_all(out_dir)
+        .map_err(|e| format!("cannot create {}: {}", out_dir.display(), e))?;
+    let now = now();
+    let mut cards = Vec::new();
+    let mut taken = HashSet::from(["index.html".to_string()]);
+    for path in sub.get_many::<PathBuf>("repo").expect("repo is required") {
+        let repo = open_repository(Some(path))?;
+        let mut repo_ctx = Context::new(repo, ctx.config.clone());
+        repo_ctx.no_write = ctx.no_write;
+        let name = repo_name(&repo_ctx.repo);
+        let page = page_name(&name, &taken);
+        taken.insert(page.clone());
+        let commits = repo_ctx.commits(sub)?;
+        let ai_tools = repo_ctx.ai_tools()?;
+        let insights = get_insights(&commits, &ai_tools, repo_ctx.head_files()?, now);
+        let card = get_repo_card(&name, &page, &commits, insights.len(), now);
+
+        let mut details = Report::new(name.as_str());
+        details.command = "dashboard".to_string();
+        details.date_format = report.date_format.clone();
+        details.field("Commits", commits.len());
+        if let Some(first) = commits.iter().map(|c| c.time).min() {
+            details.field("First Commit", Value::Time(first));
+        }
+        if let Some(last) = card.last_commit {
+            details.field("Last Commit", Value::Time(last));
+        }
+        details.field(format!("Commits ({} days)", ACTIVITY_DAYS), card.commits);
+        details.field(
+            format!("Contributors ({} days)", ACTIVITY_DAYS),
+            card.contributors.len(),
+        );
+        details.field("Findings", insights.len());
+        let mut table = Table::new(
+            format!("Contributors in the last {} days", ACTIVITY_DAYS),
+            &["Contributor", "Commits", "Share"],
+        );
+        for (contributor, n) in &card.contributors {
+            table.push(vec![
+                contributor.as_str().into(),
+                (*n).into(),
+                Value::Percent(percent(*n, card.commits)),
+            ]);
+        }
+        details.table(table);
+        let mut table = Table::new("Insights", &["Finding", "Next Step"]);
+        for insight in &insights {
+            table.push(vec![
+                insight.finding.as_str().into(),
+                insight.command.into(),
+            ]);
+        }
+        details.table(table);
+        let file = out_dir.join(&page);
+        fs::write(&file, render(&[details], Format::Html))
+            .map_err(|e| format!("cannot write {}: {}", file.display(), e))?;
+        cards.push(card);
+    }
+    let index = out_dir.join("index.html");
+    fs::write(&index, dashboard_page(title, &cards, &report.date_format))
+        .map_err(|e| format!("cannot write {}: {}", index.display(), e))?;
+
+    report.field("Repositories", cards.len());
+    report.field("Dashboard", index.display().to_string());
+    let mut table = Table::new(
+        "Repositories",
+        &[
+            "Repository",
+            "Commits",
+            "Contributors",
+            "Top Contributor",
+            "Findings",
+            "Page",
+        ],
+    );
+    for card in &cards {
+        let top = card.contributors.first().map_or("-", |(name, _)| name);
+        table.push(vec![
+            card.name.as_str().into(),
+            card.commits.into(),
+            card.contributors.len().into(),
+            top.into(),
+            card.findings.into(),
+            card.page.as_str().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn direct_pushes(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let branches: Vec<String> = match sub.get_many::<String>("branch") {
+        Some(branches) => branches.cloned().collect(),
+        None => vec![default_branch(&ctx.repo).ok_or("no default branch, pass --branch")?],
+    };
+    let mut tips = Vec::new();
+    for branch in &branches {
+        tips.push(
+            ctx.branch_tip(branch)
+                .map_err(|_| format!("unknown branch '{}'", branch))?,
+        );
+    }
+    let commits = ctx.commits(sub)?;
+    let recorded = ctx.raw_commits()?.to_vec();
+    let stats = get_landing_stats(&ctx.repo, &tips, &commits, &recorded)?;
+    report.field("Branches", branches.join(", "));
+    report.field("First-Parent Commits", stats.commits);
+    report.field("Merges", stats.merges);
+    report.field("Squash Merges", stats.squashes);
+    report.field("Direct Pushes", stats.direct);
+    report.field(
+        "Direct Share",
+        Value::Percent(percent(stats.direct, stats.commits)),
+    );
+
+    let mut table = Table::new(
+        "Direct pushes per contributor",
+        &["Contributor", "Direct", "Commits", "Share"],
+    );
+    for (author, direct, total) in &stats.authors {
+        table.push(vec![
+            author.as_str().into(),
+            (*direct).into(),
+            (*total).into(),
+            Value::Percent(percent(*direct, *total)),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn dirs(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let depth = *sub.get_one::<u32>("depth").expect("depth has a default") as usize;
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let files = ctx.head_files()?.to_vec();
+    let filter = PathFilter::from_matches(sub);
+    let stats = get_directory_stats(
+        &ctx.repo,
+        &commits,
+        &files,
+        depth,
+        &filter,
+        &mut ctx.progress,
+    )?;
+    report.field("Directories", stats.len());
+    report.field("Depth", depth);
+    let mut table = Table::new(
+        "Activity per directory",
+        &[
+            "Directory",
+            "Commits",
+            "Insertions",
+            "Deletions",
+            "Contributors",
+            "Files",
+            "Size",
+        ],
+    );
+    for directory in stats.into_iter().take(top) {
+        table.push(vec![
+            directory.directory.into(),
+            directory.commits.into(),
+            directory.insertions.into(),
+            directory.deletions.into(),
+            directory.contributors.into(),
+            directory.files.into(),
+            Value::Size(directory.size),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn doctor(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    for (name, value) in build_info() {
+        report.field(name, value);
+    }
+    report.field("Repository", ctx.repo.path().display().to_string());
+
+    let mut table = Table::new("Git config", &["Key", "Value"]);
+    for (key, value) in git_config(&ctx.repo)? {
+        table.push(vec![key.into(), value.into()]);
+    }
+    report.table(table);
+
+    let mut table = Table::new("Files", &["File", "Path", "Size"]);
+    for (name, path, size) in cache_files(&ctx.repo, &ctx.config) {
+        table.push(vec![
+            name.into(),
+            path.into(),
+            size.map_or(Value::Text("missing".into()), Value::Size),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new("Problems", &["Problem"]);
+    for problem in problems(&ctx.repo, ctx.no_write) {
+        table.push(vec![problem.into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn domains(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let domains = get_domain_stats(&commits);
+    let personal: usize = domains
+        .iter()
+        .filter(|d| d.is_personal())
+        .map(|d| d.commits)
+        .sum();
+    report.field("Domains", domains.len());
+    report.field(
+        "Personal Commits",
+        Value::Percent(percent(personal, commits.len())),
+    );
+
+    let mut table = Table::new(
+        "Commits by email domain",
+        &["Domain", "Contributors", "Commits", "Share", "Type"],
+    );
+    for domain in &domains {
+        table.push(vec![
+            domain.domain.as_str().into(),
+            domain.contributors.into(),
+            domain.commits.into(),
+            Value::Percent(percent(domain.commits, commits.len())),
+            if domain.is_personal() {
+                "personal"
+            } else {
+                "organization"
+            }
+            .into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn duplicates(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let duplicates = get_duplicates(ctx.head_files()?);
+    report.field("Duplicate Sets", duplicates.len());
+    report.field(
+        "Extra Copies",
+        duplicates.iter().map(|d| d.paths.len() - 1).sum::<usize>(),
+    );
+    report.field(
+        "Wasted Size",
+        Value::Size(duplicates.iter().map(|d| d.wasted()).sum()),
+    );
+
+    let mut table = Table::new(
+        "Duplicate files by wasted size",
+        &["Blob", "Copies", "Size", "Wasted", "Paths"],
+    );
+    for duplicate in duplicates.iter().take(top) {
+        table.push(vec![
+            short_id(duplicate.oid).into(),
+            duplicate.paths.len().into(),
+            Value::Size(duplicate.size),
+            Value::Size(duplicate.wasted()),
+            duplicate.paths.join(", ").into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn export(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fields: Vec<Field> = match sub.get_many::<Field>("fields") {
+        Some(fields) => fields.copied().collect(),
+        None => DEFAULT_FIELDS.to_vec(),
+    };
+    let commits = ctx.commits(sub)?;
+    let filter = PathFilter::from_matches(sub);
+    let names: Vec<String> = fields.iter().map(Field::to_string).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let mut table = Table::new("Commits", &names);
+    ctx.progress.begin("export", Some(commits.len()));
+    for commit in commits.iter() {
+        ctx.progress.tick();
+        let churn = if fields.iter().any(Field::needs_churn) {
+            get_commit_churn(&ctx.repo, commit.oid, &filter)?
+        } else {
+            CommitChurn::default()
+        };
+        table.push(fields.iter().map(|f| f.value(commit, &churn)).collect());
+    }
+    ctx.progress.end();
+    report.table(table);
+    Ok(())
+}
+
+fn extensions(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Year);
+    let commits = ctx.commits(sub)?;
+    let lines = sub.get_flag("lines");
+    let mix = get_extension_mix(&ctx.repo, &commits, bucketing, lines)?;
+    let unit = if lines { "Lines" } else { "Files" };
+    report.field("Extensions", mix.totals.len());
+
+    let shown: Vec<&str> = mix
+        .totals
+        .iter()
+        .take(top)
+        .map(|(e, _)| e.as_str())
+        .collect();
+    let mut columns = vec!["Period", unit];
+    columns.extend(&shown);
+    if mix.totals.len() > top {
+        columns.push(OTHER);
+    }
+    let mut table = Table::new(
+        format!("{} changed per extension and {}", unit, bucketing),
+        &columns,
+    );
+    for (start, counts) in &mix.periods {
+        let total: usize = counts.values().sum();
+        let mut row: Vec<Value> = vec![bucketing.label(*start).into(), total.into()];
+        let mut rest = total;
+        for extension in &shown {
+            let count = counts.get(*extension).copied().unwrap_or(0);
+            rest -= count;
+            row.push(Value::Percent(percent(count, total)));
+        }
+        if mix.totals.len() > top {
+            row.push(Value::Percent(percent(rest, total)));
+        }
+        table.push(row);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn filesizes(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let thresholds: Vec<u64> = match sub.get_many::<u64>("threshold") {
+        Some(values) => values.copied().collect(),
+        None => vec![1 << 20, 10 << 20, 50 << 20, 100 << 20],
+    };
+    let files = ctx.head_files()?.to_vec();
+    let distribution = get_size_distribution(&files, &thresholds);
+    report.field("Files", distribution.files);
+    report.field("Total Size", Value::Size(distribution.total));
+    report.field("Median (p50)", Value::Size(distribution.p50));
+    report.field("p95", Value::Size(distribution.p95));
+    report.field("p99", Value::Size(distribution.p99));
+    report.field("Largest", Value::Size(distribution.max));
+
+    let mut table = Table::new("File size histogram", &["Size", "Files", "Share", ""]);
+    let max = distribution
+        .histogram
+        .iter()
+        .map(|(_, n)| *n)
+        .max()
+        .unwrap_or(0);
+    for (label, count) in &distribution.histogram {
+        table.push(vec![
+            label.as_str().into(),
+            (*count).into(),
+            Value::Percent(percent(*count, distribution.files)),
+            bar(*count, max, 30).into(),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new("Files over threshold", &["Threshold", "Files", "Share"]);
+    for (threshold, count) in &distribution.over {
+        table.push(vec![
+            Value::Size(*threshold),
+            (*count).into(),
+            Value::Percent(percent(*count, distribution.files)),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn generated(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let patterns: Vec<String> = match sub.get_many::<String>("pattern") {
+        Some(patterns) => patterns.cloned().collect(),
+        None => generated_patterns_from_config(&ctx.config)?.unwrap_or_else(|| {
+            DEFAULT_GENERATED_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect()
+        }),
+    };
+    let globs = parse_globs(&patterns)?;
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let files = ctx.head_files()?.to_vec();
+    let stats = get_generated_stats(&ctx.repo, &commits, &files, &globs)?;
+    let churn = |s: &PathStats| s.insertions + s.deletions;
+    report.field(
+        "Generated Churn Share",
+        Value::Percent(percent(churn(&stats.generated), churn(&stats.all))),
+    );
+
+    let mut table = Table::new(
+        "With and without generated paths",
+        &[
+            "Paths",
+            "Files",
+            "Size",
+            "Commits",
+            "Insertions",
+            "Deletions",
+        ],
+    );
+    for (label, part) in [
+        ("All", &stats.all),
+        ("Generated", &stats.generated),
+        ("Hand-written", &stats.written),
+    ] {
+        table.push(vec![
+            label.into(),
+            part.files.into(),
+            Value::Size(part.size),
+            part.commits.into(),
+            part.insertions.into(),
+            part.deletions.into(),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new("Generated paths by churn", &["Path", "Lines Changed"]);
+    for (path, lines) in stats.paths.iter().take(top) {
+        table.push(vec![path.as_str().into(), (*lines).into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn handoff(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = sub.get_one::<String>("file").expect("file is required");
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let head = ctx.head()?.ok_or("HEAD does not point at a commit")?;
+    let commits = ctx.commits(sub)?;
+    let timeline = get_handoff(&ctx.repo, head, path, &commits, sub.get_flag("anonymize"))?;
+    if timeline.is_empty() {
+        return Err(format!("no history for '{}' reachable from HEAD", path).into());
+    }
+    report.field("File", path.as_str());
+    report.field("Years", timeline.len());
+
+    // The authors holding the most lines at any year end.
+    let mut peaks: HashMap<&str, usize> = HashMap::new();
+    for year in &timeline {
+        for (owner, lines) in &year.owners {
+            let peak = peaks.entry(owner).or_default();
+            *peak = (*peak).max(*lines);
+        }
+    }
+    let mut authors: Vec<(&str, usize)> = peaks.into_iter().collect();
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let authors: Vec<&str> = authors.into_iter().take(top).map(|(a, _)| a).collect();
+    let mut columns = vec!["Year", "Commit", "Lines"];
+    columns.extend(&authors);
+    columns.push("Others");
+    let mut table = Table::new("Line ownership at year end", &columns);
+    for year in &timeline {
+        let lines: usize = year.owners.iter().map(|(_, n)| n).sum();
+        let mut row = vec![
+            year.year.to_string().into(),
+            short_id(year.commit).into(),
+            lines.into(),
+        ];
+        let mut shown = 0;
+        for author in &authors {
+            let owned = year
+                .owners
+                .iter()
+                .find(|(owner, _)| owner == author)
+                .map_or(0, |(_, n)| *n);
+            shown += owned;
+            row.push(Value::Percent(percent(owned, lines)));
+        }
+        row.push(Value::Percent(percent(lines - shown, lines)));
+        table.push(row);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn health(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let store = get_object_store(&ctx.repo)?;
+    report.field("Loose Objects", store.loose.0);
+    report.field("Loose Size", Value::Size(store.loose.1));
+    report.field("Packs", store.packs);
+    report.field("Pack Size", Value::Size(store.pack_bytes));
+    report.field("Packed Objects", store.packed.0);
+    report.field(
+        "Unreachable Objects",
+        store.loose_unreachable.0 + store.packed_unreachable.0,
+    );
+    report.field("Estimated Savings", Value::Size(store.savings()));
+
+    let mut table = Table::new("Recommendations", &["Recommendation"]);
+    for recommendation in store.recommendations() {
+        table.push(vec![recommendation.into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn heatmap(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let weeks = *sub.get_one::<u16>("weeks").expect("weeks has a default") as usize;
+    let mut author = sub.get_one::<String>("author").cloned();
+    if sub.get_flag("anonymize")
+        && let Some(email) = author.as_mut().filter(|a| a.contains('@'))
+    {
+        *email = anonymize_email(email);
+    }
+    let commits = ctx.commits(sub)?;
+    let selected: Vec<&CommitInfo> = commits
+        .iter()
+        .filter(|c| {
+            author
+                .as_ref()
+                .is_none_or(|a| c.author_email.eq_ignore_ascii_case(a) || c.author_name == *a)
+        })
+        .collect();
+    let today = today();
+    let heatmap = get_heatmap(&selected, today, weeks);
+    let days = heatmap.iter().flatten();
+    let max = days.clone().copied().max().unwrap_or(0);
+    report.field("Commits", days.clone().sum::<usize>());
+    report.field("Active Days", days.filter(|n| **n > 0).count());
+    report.field("Busiest Day", max);
+
+    // Label each month above the first week starting in it.
+    let start = heatmap_start(today, weeks);
+    let mut months = vec![' '; weeks * 2];
+    let mut free = 0;
+    for week in 0..weeks {
+        let (_, month, day) = civil_from_days(start + week as i64 * 7);
+        let at = week * 2;
+        if day <= 7 && at >= free && at + 3 <= months.len() {
+            months.splice(at..at + 3, MONTHS[month as usize - 1].chars());
+            free = at + 4;
+        }
+    }
+    let months: String = months.into_iter().collect();
+    let mut table = Table::new(
+        format!("Commits per day, last {} weeks", weeks),
+        &["Day", &months],
+    );
+    for (weekday, name) in WEEKDAYS.iter().enumerate() {
+        table.push(vec![
+            (*name).into(),
+            Value::Intensities {
+                counts: heatmap.iter().map(|week| week[weekday]).collect(),
+                max,
+            },
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn hotspots(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let days = *sub.get_one::<u32>("days").expect("days has a default");
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let files = ctx.head_files()?.to_vec();
+    let since = now() - days as i64 * SECONDS_PER_DAY;
+    let mut hotspots = get_hotspots(&ctx.repo, &commits, since, &files, &mut ctx.progress)?;
+    report.field("Days", days as usize);
+    report.field("Files Changed", hotspots.len());
+    report.field(
+        "Knowledge Silos",
+        hotspots.iter().filter(|h| h.is_silo()).count(),
+    );
+
+    let columns = [
+        "File",
+        "Commits",
+        "Authors",
+        "Top Author",
+        "Top Share",
+        "Size",
+    ];
+    let row = |h: &Hotspot| -> Vec<Value> {
+        vec![
+            h.path.as_str().into(),
+            h.commits.into(),
+            h.authors.into(),
+            h.top_author.0.as_str().into(),
+            Value::Percent(h.top_share()),
+            Value::Size(h.size),
+        ]
+    };
+    let mut table = Table::new("Most changed files", &columns);
+    for hotspot in hotspots.iter().take(top) {
+        table.push(row(hotspot));
+    }
+    report.table(table);
+    hotspots.sort_by(|a, b| {
+        b.authors
+            .cmp(&a.authors)
+            .then(b.commits.cmp(&a.commits))
+            .then(a.path.cmp(&b.path))
+    });
+    let mut table = Table::new("Files with the most authors", &columns);
+    for hotspot in hotspots.iter().take(top) {
+        table.push(row(hotspot));
+    }
+    report.table(table);
+    hotspots.retain(|h| h.is_silo());
+    hotspots.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.path.cmp(&b.path)));
+    let mut table = Table::new(
+        format!(
+            "Knowledge silos (top author {}% or more of {}+ commits)",
+            SILO_SHARE, SILO_COMMITS
+        ),
+        &columns,
+    );
+    for hotspot in hotspots.iter().take(top) {
+        table.push(row(hotspot));
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn hours(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hours = match sub.get_one::<BusinessHours>("business-hours") {
+        Some(hours) => *hours,
+        None => BusinessHours::from_config(&ctx.config)?.unwrap_or_default(),
+    };
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let contributors = get_off_hours(&commits, hours);
+    let mut total = OffHours::default();
+    for (_, counts) in &contributors {
+        total += *counts;
+    }
+    report.field("Business Hours", hours.to_string());
+    report.field("Commits", total.commits);
+    report.field(
+        "Weekend",
+        Value::Percent(percent(total.weekend, total.commits)),
+    );
+    report.field(
+        "After Hours",
+        Value::Percent(percent(total.after_hours, total.commits)),
+    );
+
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let days: Vec<usize> = get_punchcard(&commits)
+        .iter()
+        .map(|hours| hours.iter().sum())
+        .collect();
+    let max = days.iter().copied().max().unwrap_or(0);
+    let mut table = Table::new("Commits by weekday", &["Weekday", "Commits", "Share", ""]);
+    for (name, count) in WEEKDAYS.iter().zip(&days) {
+        table.push(vec![
+            (*name).into(),
+            (*count).into(),
+            Value::Percent(percent(*count, total.commits)),
+            bar(*count, max, 30).into(),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new(
+        "Off-hours commits by contributor",
+        &["Contributor", "Commits", "Weekend", "After Hours"],
+    );
+    for (contributor, counts) in contributors.into_iter().take(top) {
+        table.push(vec![
+            contributor.into(),
+            counts.commits.into(),
+            Value::Percent(percent(counts.weekend, counts.commits)),
+            Value::Percent(percent(counts.after_hours, counts.commits)),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn issues(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let patterns: Vec<String> = match sub.get_many::<String>("pattern") {
+        Some(patterns) => patterns.filter(|p| !p.is_empty()).cloned().collect(),
+        None => patterns_from_config(&ctx.config)?
+            .unwrap_or_else(|| DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect()),
+    };
+    if patterns.is_empty() {
+        return Err("--pattern must not be empty".into());
+    }
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let stats = get_issue_stats(&ctx.commits(sub)?, &patterns);
+    report.field("Commits", stats.commits);
+    report.field("Referencing Issues", stats.referencing);
+    report.field(
+        "Share",
+        Value::Percent(percent(stats.referencing, stats.commits)),
+    );
+    report.field("Distinct Issues", stats.issues.len());
+
+    let mut table = Table::new("Most-referenced issues", &["Issue", "Commits"]);
+    for (issue, count) in stats.issues.iter().take(top) {
+        table.push(vec![issue.as_str().into(), (*count).into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn languages(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let map = LanguageMap::from_config(&ctx.config)?;
+    let files = ctx.head_files()?;
+    let shares = get_language_breakdown(files, &map);
+    let total: u64 = files.iter().map(|f| f.size).sum();
+    report.field(
+        "Languages",
+        shares.iter().filter(|s| s.language != OTHER).count(),
+    );
+    report.field("Files", files.len());
+    report.field("Total Size", Value::Size(total));
+
+    let max = shares.iter().map(|s| s.bytes).max().unwrap_or(0);
+    let mut table = Table::new(
+        "Languages by size",
+        &["Language", "Files", "Size", "Share", ""],
+    );
+    for share in shares {
+        table.push(vec![
+            share.language.into(),
+            share.files.into(),
+            Value::Size(share.bytes),
+            Value::Percent(percent(share.bytes as usize, total as usize)),
+            bar(share.bytes as usize, max as usize, 30).into(),
+        ]);
+    }
+    report.table(table);
+
+    if sub.get_flag("loc") {
+        let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+        let files = ctx.head_files()?.to_vec();
+        let mut lines = get_file_lines(&ctx.repo, &files, &map)?;
+        let mut languages: HashMap<&str, (usize, LineCounts)> = HashMap::new();
+        let mut total = LineCounts::default();
+        for file in &lines {
+            let entry = languages.entry(&file.language).or_default();
+            entry.0 += 1;
+            entry.1 += file.counts;
+            total += file.counts;
+        }
+        report.field("Lines", total.lines);
+        report.field("Code", total.code());
+        report.field("Comments", total.comment);
+        report.field("Blank", total.blank);
+
+        let mut languages: Vec<_> = languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.1.lines.cmp(&a.1.1.lines).then(a.0.cmp(b.0)));
+        let mut table = Table::new(
+            "Lines of code by language",
+            &["Language", "Files", "Lines", "Code", "Comments", "Blank"],
+        );
+        for (language, (files, counts)) in languages {
+            table.push(vec![
+                language.into(),
+                files.into(),
+                counts.lines.into(),
+                counts.code().into(),
+                counts.comment.into(),
+                counts.blank.into(),
+            ]);
+        }
+        report.table(table);
+
+        lines.sort_by(|a, b| {
+            b.counts
+                .lines
+                .cmp(&a.counts.lines)
+                .then(a.path.cmp(&b.path))
+        });
+        let mut table = Table::new(
+            "Longest files",
+            &["File", "Language", "Lines", "Code", "Comments", "Blank"],
+        );
+        for file in lines.into_iter().take(top) {
+            table.push(vec![
+                file.path.into(),
+                file.language.into(),
+                file.counts.lines.into(),
+                file.counts.code().into(),
+                file.counts.comment.into(),
+                file.counts.blank.into(),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn lifetime(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Year);
+    let commits = ctx.commits(sub)?;
+    let lifetimes = get_file_lifetimes(&ctx.repo, &commits, bucketing, &mut ctx.progress)?;
+    report.field("Files Ever", lifetimes.files);
+    report.field("Files Now", ctx.head_files()?.len());
+    report.field("Files Deleted", lifetimes.deleted());
+    for (label, p) in [("Median Lifetime", 50.0), ("p90 Lifetime", 90.0)] {
+        if let Some(seconds) = lifetimes.percentile(p) {
+            report.field(label, format_duration(seconds));
+        }
+    }
+
+    let mut table = Table::new(
+        format!("Files per {}", bucketing),
+        &["Period", "Created", "Deleted", "Alive", "Deletion Rate"],
+    );
+    for (start, period) in &lifetimes.periods {
+        table.push(vec![
+            bucketing.label(*start).into(),
+            period.created.into(),
+            period.deleted.into(),
+            period.alive.into(),
+            Value::Percent(period.deletion_rate()),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn messages(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = get_message_stats(&ctx.commits(sub)?);
+    let describe = |length: Option<usize>| length.map_or(Value::Text("-".into()), Value::from);
+    report.field("Commits", stats.commits);
+    report.field("Median Subject", describe(stats.percentile(50.0)));
+    report.field("p90 Subject", describe(stats.percentile(90.0)));
+    report.field(
+        "Longest Subject",
+        describe(stats.subject_lengths.last().copied()),
+    );
+    report.field(
+        format!("Subjects Over {}", SUBJECT_LIMIT),
+        Value::Percent(percent(stats.long_subjects, stats.commits)),
+    );
+    report.field(
+        "With Body",
+        Value::Percent(percent(stats.with_body, stats.commits)),
+    );
+    report.field(
+        "One-Word Messages",
+        Value::Percent(percent(stats.one_word, stats.commits)),
+    );
+
+    let buckets = stats.buckets();
+    let max = buckets.iter().copied().max().unwrap_or(0);
+    let mut table = Table::new("Subject lengths", &["Characters", "Commits", "Share", ""]);
+    for (index, count) in buckets.iter().enumerate() {
+        table.push(vec![
+            range_label(&SUBJECT_BUCKETS, index, 1).into(),
+            (*count).into(),
+            Value::Percent(percent(*count, stats.commits)),
+            bar(*count, max, 30).into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn milestones(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let days = *sub.get_one::<u16>("days").expect("days has a default") as usize;
+    let today = today();
+    let milestones = get_milestones(&ctx.commits(sub)?, today - days as i64 + 1, today);
+    report.field("Days", days);
+    report.field("Milestones", milestones.len());
+
+    let mut table = Table::new(
+        format!("Milestones of the last {} days", days),
+        &["Date", "Contributor", "Milestone"],
+    );
+    for milestone in milestones {
+        table.push(vec![
+            Value::Date(milestone.day),
+            milestone.contributor.into(),
+            milestone.milestone.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn owners(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefix = sub
+        .get_one::<String>("path")
+        .map_or("", |p| p.trim_start_matches("./").trim_end_matches('/'));
+    let prefix = if prefix == "." { "" } else { prefix };
+    let commits = ctx.commits(sub)?;
+    let tip = ctx.head()?;
+    let head = ctx.head_files()?.to_vec();
+    let files: Vec<_> = head.iter().filter(|f| in_prefix(&f.path, prefix)).collect();
+    if files.is_empty() {
+        return Err(format!("no files at HEAD below '{}'", prefix).into());
+    }
+    let lines = if sub.get_flag("fast") {
+        last_touched_lines(&ctx.repo, &files, &commits)?
+    } else {
+        blame_lines(
+            &ctx.repo,
+            tip,
+            &files,
+            &commits,
+            sub.get_flag("anonymize"),
+            &mut ctx.progress,
+        )?
+    };
+    let directories = get_directory_ownership(&lines);
+    let overall = get_owned_lines(&lines);
+    let total: usize = overall.iter().map(|(_, n)| n).sum();
+    report.field("Files", lines.len());
+    report.field("Lines", total);
+    report.field("Directories", directories.len());
+    report.field(
+        "Top Owner",
+        overall.first().map_or("-".to_string(), |(o, _)| o.clone()),
+    );
+
+    let mut table = Table::new(
+        "Owners per directory",
+        &["Directory", "Lines", "Owner", "Share", "Contributors"],
+    );
+    for directory in &directories {
+        let (owner, count) = directory
+            .owners
+            .first()
+            .map_or(("-", 0), |(o, n)| (o.as_str(), *n));
+        table.push(vec![
+            directory.directory.as_str().into(),
+            directory.lines.into(),
+            owner.into(),
+            Value::Percent(percent(count, directory.lines)),
+            directory.owners.len().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn platforms(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let signals = get_platform_signals(&ctx.repo, &commits)?;
+    let estimates: Vec<_> = signals.iter().map(|(_, s)| s.estimate()).collect();
+    for label in ["Windows", "macOS", "Linux"] {
+        let count = estimates
+            .iter()
+            .filter(|e| e.is_some_and(|(p, _)| p.label() == label))
+            .count();
+        report.field(label, count);
+    }
+    report.field("Unknown", estimates.iter().filter(|e| e.is_none()).count());
+
+    let mut table = Table::new(
+        "Estimated platform per contributor",
+        &[
+            "Contributor",
+            "Platform",
+            "Confidence",
+            "Windows",
+            "macOS",
+            "Linux",
+        ],
+    );
+    for ((author, signals), estimate) in signals.iter().zip(&estimates) {
+        let (platform, confidence) = match estimate {
+            Some((p, c)) => (p.label(), c.label()),
+            None => ("unknown", "-"),
+        };
+        table.push(vec![
+            author.as_str().into(),
+            platform.into(),
+            confidence.into(),
+            signals.windows.into(),
+            signals.macos.into(),
+            signals.linux.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn protection(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rules = ProtectionRules::from_config(&ctx.config)?
+        .ok_or("no protection rules; add a [protection] section to the config")?;
+    let branches = if rules.branches.is_empty() {
+        vec![default_branch(&ctx.repo).ok_or("no default branch, list branches in [protection]")?]
+    } else {
+        rules.branches.clone()
+    };
+    let commits = ctx.commits(sub)?;
+    let recorded = ctx.raw_commits()?.to_vec();
+    let mut generations = Generations::load(&ctx.repo);
+    let mut histories = Vec::new();
+    for branch in &branches {
+        let tip = ctx
+            .branch_tip(branch)
+            .map_err(|_| format!("unknown branch '{}'", branch))?;
+        histories.push(get_branch_history(
+            &ctx.repo,
+            &mut generations,
+            branch,
+            tip,
+            &commits,
+            &recorded,
+        )?);
+    }
+    ctx.save_generations(&generations);
+
+    let mut drifting = 0;
+    let mut table = Table::new(
+        "Drift from the protection rules",
+        &["Branch", "Rule", "Violations", "Status"],
+    );
+    for history in &histories {
+        for (rule, violations) in rules.drift(history) {
+            let status = if violations > 0 { "drift" } else { "ok" };
+            drifting += usize::from(violations > 0);
+            table.push(vec![
+                history.branch.as_str().into(),
+                rule.into(),
+                violations.into(),
+                status.into(),
+            ]);
+        }
+    }
+    report.field("Branches", branches.join(", "));
+    report.field("Rules Drifting", drifting);
+    report.table(table);
+
+    let mut table = Table::new(
+        "History per branch",
+        &[
+            "Branch",
+            "First-Parent Commits",
+            "Unsigned",
+            "Direct Pushes",
+            "Force Pushes",
+        ],
+    );
+    for history in &histories {
+        table.push(vec![
+            history.branch.as_str().into(),
+            history.commits.into(),
+            history.unsigned.into(),
+            history.direct.into(),
+            history.force_pushes.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn punchcard(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let commits = ctx.commits(sub)?;
+    let punchcard = get_punchcard(&commits);
+    let max = punchcard.iter().flatten().copied().max().unwrap_or(0);
+    report.field("Commits", commits.len());
+    if max > 0 {
+        let (day, hour) = (0..7 * 24)
+            .map(|i| (i / 24, i % 24))
+            .find(|&(day, hour)| punchcard[day][hour] == max)
+            .unwrap_or_default();
+        report.field("Busiest Hour", format!("{} {:02}:00", WEEKDAYS[day], hour));
+    }
+
+    let hours: String = (0..24)
+        .step_by(3)
+        .map(|h| format!("{:02}    ", h))
+        .collect();
+    let mut table = Table::new(
+        "Commits by weekday and hour (local time)",
+        &["Day", &hours, "Commits"],
+    );
+    for (day, counts) in WEEKDAYS.iter().zip(&punchcard) {
+        table.push(vec![
+            (*day).into(),
+            Value::Intensities {
+                counts: counts.to_vec(),
+                max,
+            },
+            counts.iter().sum::<usize>().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn push_latency(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let mut generations = Generations::load(&ctx.repo);
+    let latency = get_push_latency(&ctx.repo, &mut generations)?;
+    ctx.save_generations(&generations);
+    let describe = |seconds: Option<i64>| seconds.map_or("-".to_string(), format_duration);
+    report.field("Pushed Commits", latency.latencies.len());
+    report.field("Median", describe(latency.percentile(50.0)));
+    report.field("p90", describe(latency.percentile(90.0)));
+    report.field("Longest", describe(latency.latencies.last().copied()));
+    report.field("Unpushed Commits", latency.unpushed.len());
+    report.field(
+        "Oldest Unpushed",
+        describe(latency.unpushed.first().map(|t| now() - t)),
+    );
+
+    let bounds = [
+        ("< 1 hour", 3_600),
+        ("1 hour – 1 day", SECONDS_PER_DAY),
+        ("1 day – 1 week", 7 * SECONDS_PER_DAY),
+        (">= 1 week", i64::MAX),
+    ];
+    let mut counts = Vec::new();
+    let mut lower = 0;
+    for (label, upper) in bounds {
+        let count = latency
+            .latencies
+            .iter()
+            .filter(|l| **l >= lower && **l < upper)
+            .count();
+        counts.push((label, count));
+        lower = upper;
+    }
+    let max = counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    let mut table = Table::new("Time to push", &["Latency", "Commits", "Share", ""]);
+    for (label, count) in counts {
+        table.push(vec![
+            label.into(),
+            count.into(),
+            Value::Percent(percent(count, latency.latencies.len())),
+            bar(count, max, 30).into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn quality(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = get_quality_stats(&ctx.commits(sub)?);
+    report.field("Commits", stats.commits);
+    for (name, kind, count) in [
+        ("Reverts", "Revert", stats.reverts),
+        ("Fixups", "Fixup", stats.fixups),
+        ("Squashes", "Squash", stats.squashes),
+        ("Rework", "Rework", stats.rework()),
+    ] {
+        report.field(name, count);
+        report.field(
+            format!("{} Ratio", kind),
+            Value::Percent(percent(count, stats.commits)),
+        );
+    }
+    Ok(())
+}
+
+fn range(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let spec = sub.get_one::<String>("range").expect("range is required");
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let (from, to) = resolve_range(&ctx.repo, spec)?;
+    let range: HashSet<Oid> = commits_between(&ctx.repo, to, Some(from))?
+        .into_iter()
+        .collect();
+    let commits: Vec<CommitInfo> = ctx
+        .commits(sub)?
+        .iter()
+        .filter(|c| range.contains(&c.oid))
+        .cloned()
+        .collect();
+    let filter = PathFilter::from_matches(sub);
+    let changes = get_range_changes(&ctx.repo, &commits, &filter)?;
+    report.field("Range", spec.as_str());
+    report.field("Commits", commits.len());
+    report.field("Merges", commits.iter().filter(|c| c.is_merge()).count());
+    report.field("Contributors", count_contributors(&commits));
+    report.field("Files Changed", changes.files.len());
+    report.field("Insertions", changes.churn.insertions);
+    report.field("Deletions", changes.churn.deletions);
+
+    let lines: HashMap<String, usize> = get_lines_changed(&ctx.repo, &commits, &filter)?
+        .into_iter()
+        .collect();
+    let mut table = Table::new("Top contributors", &["Contributor", "Commits", "Lines"]);
+    for (contributor, count) in get_commit_counts(&commits).into_iter().take(top) {
+        let changed = lines.get(&contributor).copied().unwrap_or(0);
+        table.push(vec![contributor.into(), count.into(), changed.into()]);
+    }
+    report.table(table);
+    let mut table = Table::new("Most changed files", &["File", "Commits"]);
+    for (path, count) in changes.files.into_iter().take(top) {
+        table.push(vec![path.into(), count.into()]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn reflog(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let stats = get_reflog_stats(&ctx.repo)?;
+    report.field("Reflogs", stats.refs.len());
+    report.field(
+        "Reflog Entries",
+        stats.refs.iter().map(|r| r.entries).sum::<usize>(),
+    );
+    if let Some(oldest) = &stats.oldest {
+        report.field("Oldest Entry", Value::Time(oldest.time));
+        report.field(
+            "Oldest State",
+            format!(
+                "{} {} ({})",
+                oldest.name,
+                short_id(oldest.oid),
+                oldest.message
+            ),
+        );
+    }
+
+    let mut table = Table::new(
+        "Reflog entries per ref",
+        &["Ref", "Entries", "Oldest", "Newest"],
+    );
+    for reflog in stats.refs.iter().take(top) {
+        table.push(vec![
+            reflog.name.clone().into(),
+            reflog.entries.into(),
+            Value::Time(reflog.oldest),
+            Value::Time(reflog.newest),
+        ]);
+    }
+    report.table(table);
+
+    let weeks = (stats.span as f64 / (7 * SECONDS_PER_DAY) as f64).max(1.0);
+    let mut table = Table::new("HEAD reflog actions", &["Action", "Entries", "Per Week"]);
+    for (action, count) in &stats.actions {
+        table.push(vec![
+            action.clone().into(),
+            (*count).into(),
+            format!("{:.2}", *count as f64 / weeks).into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn releases(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let snapshot = ctx.snapshot()?.clone();
+    let stats = get_release_stats(&ctx.repo, &snapshot)?;
+    let annotated = stats.annotated();
+    report.field("Tags", stats.releases.len());
+    report.field("Annotated", annotated);
+    report.field("Lightweight", stats.releases.len() - annotated);
+    if let Some(cadence) = stats.cadence_days() {
+        report.field("Release Cadence (days)", format!("{:.1}", cadence));
+    }
+    match &stats.since_latest {
+        Some((tag, since)) => {
+            report.field("Latest Tag", tag.as_str());
+            report.field("Commits Since Tag", *since);
+        }
+        None => report.field("Latest Tag", "-"),
+    }
+
+    let semver = get_semver_stats(&stats.releases);
+    if semver.versioned > 0 {
+        report.field("Semver Tags", semver.versioned);
+        report.field("Major Releases", semver.majors);
+        report.field("Minor Releases", semver.minors);
+        report.field("Patch Releases", semver.patches);
+        report.field("Pre-releases", semver.prereleases);
+        if let Some(days) = semver.days_between_majors() {
+            report.field("Days Between Majors", format!("{:.1}", days));
+        }
+    }
+
+    let mut table = Table::new(
+        "Releases",
+        &["Tag", "Date", "Kind", "Commits", "Days Since Previous"],
+    );
+    let skip = stats.releases.len().saturating_sub(top);
+    for (index, release) in stats.releases.iter().enumerate().skip(skip) {
+        let days = match index.checked_sub(1).map(|i| &stats.releases[i]) {
+            Some(previous) => {
+                (((release.time - previous.time) / SECONDS_PER_DAY).max(0) as usize).into()
+            }
+            None => Value::Text("-".into()),
+        };
+        table.push(vec![
+            release.tag.as_str().into(),
+            Value::Time(release.time),
+            if release.annotated {
+                "annotated"
+            } else {
+                "lightweight"
+            }
+            .into(),
+            release.commits.into(),
+            days,
+        ]);
+    }
+    report.table(table);
+    if !semver.out_of_order.is_empty() {
+        let mut table = Table::new(
+            "Warning: versions going backwards",
+            &["Earlier Tag", "Later Tag"],
+        );
+        for (earlier, later) in semver.out_of_order {
+            table.push(vec![earlier.into(), later.into()]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn retained(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.raw_commits()?.to_vec();
+    let files = ctx.head_files()?.to_vec();
+    let data = get_retained_data(&ctx.repo, &commits, &files)?;
+    report.field("History Blobs", data.history.0);
+    report.field("History Size", Value::Size(data.history.1));
+    report.field("Retained Blobs", data.retained.0);
+    report.field("Retained Size", Value::Size(data.retained.1));
+    report.field(
+        "Retained Share",
+        Value::Percent(percent(data.retained.1 as usize, data.history.1 as usize)),
+    );
+    report.field("Deleted File Blobs", data.deleted.0);
+    report.field("Deleted File Size", Value::Size(data.deleted.1));
+
+    let mut table = Table::new(
+        "Largest deleted files",
+        &["File", "Versions", "Size", "Deleted In"],
+    );
+    for file in data.files.iter().take(top) {
+        table.push(vec![
+            file.path.as_str().into(),
+            file.versions.into(),
+            Value::Size(file.size),
+            file.deleted_in
+                .map_or_else(|| "-".to_string(), short_id)
+                .into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn retention(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Quarter);
+    let commits = ctx.commits(sub)?;
+    let cohorts = get_retention(&commits, bucketing, &RETENTION_MONTHS, today());
+    report.field("Cohorts", cohorts.len());
+    report.field("Contributors", count_contributors(&commits));
+
+    let headers: Vec<String> = RETENTION_MONTHS
+        .iter()
+        .map(|m| format!("{} Months", m))
+        .collect();
+    let mut columns = vec!["Cohort", "New Contributors"];
+    columns.extend(headers.iter().map(String::as_str));
+    let mut table = Table::new(format!("Retention per {} cohort", bucketing), &columns);
+    for cohort in &cohorts {
+        let mut row = vec![
+            bucketing.label(cohort.start).into(),
+            cohort.contributors.into(),
+        ];
+        row.extend(
+            cohort
+                .retained
+                .iter()
+                .map(|r| r.map_or(Value::from("-"), Value::Percent)),
+        );
+        table.push(row);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn roots(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.raw_commits()?.to_vec();
+    let histories = get_histories(ctx.snapshot()?, &commits);
+    let roots: usize = histories.iter().map(|h| h.roots.len()).sum();
+    report.field("Root Commits", roots);
+    report.field("Histories", histories.len());
+    let mut table = Table::new(
+        "Histories",
+        &["Roots", "Commits", "Last Activity", "References"],
+    );
+    for history in &histories {
+        let roots: Vec<String> = history.roots.iter().map(|oid| short_id(*oid)).collect();
+        let mut refs = history
+            .refs
+            .iter()
+            .take(3)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if history.refs.len() > 3 {
+            refs.push_str(&format!(" and {} more", history.refs.len() - 3));
+        }
+        table.push(vec![
+            roots.join(", ").into(),
+            history.commits.into(),
+            Value::Time(history.last_activity),
+            refs.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn share(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let tip = ctx.head()?;
+    let head = ctx.head_files()?.to_vec();
+    let files: Vec<_> = head.iter().collect();
+    let owned = if sub.get_flag("fast") {
+        last_touched_lines(&ctx.repo, &files, &commits)?
+    } else {
+        blame_lines(
+            &ctx.repo,
+            tip,
+            &files,
+            &commits,
+            sub.get_flag("anonymize"),
+            &mut ctx.progress,
+        )?
+    };
+    let owned = get_owned_lines(&owned);
+    let changed = get_lines_changed(&ctx.repo, &commits, &PathFilter::from_matches(sub))?;
+    let counts = get_commit_counts(&commits);
+    let total = |counts: &[(String, usize)]| counts.iter().map(|(_, n)| n).sum::<usize>();
+    report.field("Commits", commits.len());
+    report.field("Lines Changed", total(&changed));
+    report.field("Lines Owned", total(&owned));
+
+    let mut table = Table::new(
+        "Share per contributor",
+        &["Contributor", "Commits", "Lines Changed", "Ownership"],
+    );
+    for share in get_shares(&counts, &changed, &owned) {
+        table.push(vec![
+            share.contributor.into(),
+            Value::Percent(share.commits),
+            Value::Percent(share.lines_changed),
+            Value::Percent(share.lines_owned),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn signoffs(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let recorded = ctx.raw_commits()?;
+    let (overall, authors) = get_signoff_stats(&commits, recorded);
+    report.field("Commits", overall.commits);
+    report.field("Signed Off", overall.signed);
+    report.field(
+        "Share",
+        Value::Percent(percent(overall.signed, overall.commits)),
+    );
+    report.field("By Author", overall.signed_by_author);
+    report.field("Unsigned", overall.commits - overall.signed);
+
+    let mut table = Table::new(
+        "Sign-offs per contributor",
+        &["Contributor", "Commits", "Signed Off", "Share", "By Author"],
+    );
+    for (author, stats) in &authors {
+        table.push(vec![
+            author.as_str().into(),
+            stats.commits.into(),
+            stats.signed.into(),
+            Value::Percent(percent(stats.signed, stats.commits)),
+            stats.signed_by_author.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn sparse(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut author = match sub.get_one::<String>("author") {
+        Some(author) => author.clone(),
+        None => ctx
+            .repo
+            .config()?
+            .get_string("user.email")
+            .map_err(|_| "no --author given and user.email is not set")?,
+    };
+    if sub.get_flag("anonymize") && author.contains('@') {
+        author = anonymize_email(&author);
+    }
+    let days = *sub.get_one::<u32>("days").expect("days has a default");
+    let since = now() - days as i64 * SECONDS_PER_DAY;
+    let commits = ctx.commits(sub)?;
+    let recent: Vec<&CommitInfo> = commits
+        .iter()
+        .filter(|c| c.time >= since)
+        .filter(|c| c.author_email.eq_ignore_ascii_case(&author) || c.author_name == author)
+        .collect();
+    if recent.is_empty() {
+        return Err(format!("no commits by {} in the last {} days", author, days).into());
+    }
+    let files = ctx.head_files()?.to_vec();
+    let recommendation = recommend_cones(
+        &ctx.repo,
+        &recent,
+        &files,
+        *sub.get_one::<u32>("depth").expect("depth has a default") as usize,
+        *sub.get_one::<u8>("coverage")
+            .expect("coverage has a default") as f64,
+    )?;
+
+    report.field("Author", author);
+    report.field("Commits", recent.len());
+    report.field("Files Touched", recommendation.files);
+    report.field("Directories", recommendation.directories.len());
+    report.field("Coverage", Value::Percent(recommendation.coverage));
+    report.field("Checkout Size", Value::Size(recommendation.checkout_size));
+    report.field("Full Size", Value::Size(files.iter().map(|f| f.size).sum()));
+
+    let mut table = Table::new("Recommended cones", &["Directory", "Touches", "Cumulative"]);
+    let mut covered = recommendation.root_touches;
+    table.push(vec![
+        "(root files)".into(),
+        covered.into(),
+        Value::Percent(percent(covered, recommendation.touches)),
+    ]);
+    for (dir, touches) in &recommendation.directories {
+        covered += touches;
+        table.push(vec![
+            dir.as_str().into(),
+            (*touches).into(),
+            Value::Percent(percent(covered, recommendation.touches)),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new("Commands", &["Command"]);
+    table.push(vec![
+        sparse_checkout_command(&recommendation.directories).into(),
+    ]);
+    report.table(table);
+    Ok(())
+}
+
+fn sprints(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sprints = match sub.get_one::<Bucketing>("bucket") {
+        Some(&bucketing) => Sprints::from_bucketing(bucketing)
+            .ok_or("--bucket must be a sprint:<days>[:<date>] bucketing")?,
+        None => Sprints::from_config(&ctx.config)?.ok_or(
+            "no sprints defined; add a [sprints] section to the config or pass --bucket sprint:<days>:<date>",
+        )?,
+    };
+    let commits = ctx.commits(sub)?;
+    let stats = get_sprint_stats(
+        &ctx.repo,
+        &commits,
+        &sprints,
+        &PathFilter::from_matches(sub),
+    )?;
+
+    if sub.get_flag("current") {
+        let today = today();
+        let (start, end) = sprints.sprint_of(today).ok_or("no sprint is in progress")?;
+        let current = stats.iter().find(|s| s.start == start);
+        report.field("Start", Value::Date(start));
+        report.field("End", Value::Date(end - 1));
+        report.field("Day", format!("{} of {}", today - start + 1, end - start));
+        report.field("Commits", current.map_or(0, |s| s.commits));
+        report.field("Contributors", current.map_or(0, |s| s.contributors));
+        report.field("Insertions", current.map_or(0, |s| s.churn.insertions));
+        report.field("Deletions", current.map_or(0, |s| s.churn.deletions));
+    } else {
+        report.field("Sprints", stats.len());
+        let mut table = Table::new(
+            "Activity per sprint",
+            &[
+                "Sprint",
+                "End",
+                "Commits",
+                "Contributors",
+                "Insertions",
+                "Deletions",
+            ],
+        );
+        for sprint in &stats {
+            table.push(vec![
+                Value::Date(sprint.start),
+                Value::Date(sprint.end - 1),
+                sprint.commits.into(),
+                sprint.contributors.into(),
+                sprint.churn.insertions.into(),
+                sprint.churn.deletions.into(),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn streaks(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let (overall, authors) = get_streaks(&commits, today());
+    report.field(
+        "Longest Streak",
+        describe_streak(overall.longest_days, "day", &report.date_format),
+    );
+    report.field(
+        "Current Streak",
+        describe_streak(overall.current_days, "day", &report.date_format),
+    );
+    report.field(
+        "Longest Weekly",
+        describe_streak(overall.longest_weeks, "week", &report.date_format),
+    );
+    report.field(
+        "Current Weekly",
+        describe_streak(overall.current_weeks, "week", &report.date_format),
+    );
+
+    let mut table = Table::new(
+        "Streaks per contributor",
+        &[
+            "Contributor",
+            "Longest Days",
+            "Current Days",
+            "Longest Weeks",
+            "Current Weeks",
+        ],
+    );
+    for (author, streaks) in &authors {
+        table.push(vec![
+            author.as_str().into(),
+            streaks.longest_days.length.into(),
+            streaks.current_days.length.into(),
+            streaks.longest_weeks.length.into(),
+            streaks.current_weeks.length.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn summary(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mode = sub
+        .get_one::<SizeMode>("size-mode")
+        .copied()
+        .unwrap_or_default();
+    report.field(
+        "Repository Size",
+        Value::Size(mode.provider().size(&ctx.repo)?),
+    );
+    report.field("Size Mode", mode.to_string());
+    let files = ctx.head_files()?.to_vec();
+    let breakdown = match mode {
+        SizeMode::Reachable => None,
+        _ => Some(get_size_breakdown(
+            &ctx.repo,
+            &files,
+            mode == SizeMode::Allocated,
+        )?),
+    };
+    if let Some(size) = breakdown.as_ref().and_then(|b| b.working_tree) {
+        report.field("Working Tree Size", Value::Size(size));
+    }
+    let licenses = get_license_files(&ctx.repo, &files)?;
+    let mut spdx: Vec<&str> = licenses.iter().filter_map(|l| l.spdx.as_deref()).collect();
+    spdx.sort_unstable();
+    spdx.dedup();
+    let license = if !spdx.is_empty() {
+        spdx.join(", ")
+    } else if licenses.iter().any(|l| !l.notice) {
+        "unknown".to_string()
+    } else {
+        "none".to_string()
+    };
+    report.field("License", license);
+    report.field(
+        "Notice File",
+        licenses
+            .iter()
+            .find(|l| l.notice)
+            .map_or("-", |l| l.path.as_str()),
+    );
+    let commits = ctx.commits(sub)?;
+    report.field("Contributors", count_contributors(&commits));
+    let empty = count_empty_commits(&commits);
+    report.field("Empty Commits", empty.empty);
+    report.field("No-op Merges", empty.noop_merges);
+    let merges = commits.iter().filter(|c| c.is_merge()).count();
+    report.field("Merge Commits", merges);
+    report.field(
+        "Octopus Merges",
+        commits.iter().filter(|c| c.is_octopus()).count(),
+    );
+    report.field(
+        "Merge Ratio",
+        Value::Percent(percent(merges, commits.len())),
+    );
+    if let Some((first, last)) = time_span(&commits) {
+        report.field("First Commit", Value::Time(first));
+        report.field("Last Commit", Value::Time(last));
+        report.field(
+            "Age (days)",
+            ((now() - first) / SECONDS_PER_DAY).max(0) as usize,
+        );
+    }
+    let active = get_active_contributors(&commits, now(), &ACTIVE_WINDOWS);
+    for (days, count) in ACTIVE_WINDOWS.iter().zip(active) {
+        report.field(format!("Active ({}d)", days), count);
+    }
+    for weeks in VELOCITY_WEEKS {
+        let velocity = get_velocity(&commits, now(), weeks);
+        let change = velocity
+            .change()
+            .map_or(String::new(), |c| format!(" ({:+.0}%)", c));
+        report.field(
+            format!("Commits/Week ({}w)", weeks),
+            format!("{:.1} {}{}", velocity.current, velocity.arrow(), change),
+        );
+    }
+    if sub.get_flag("extended") {
+        let oids: Vec<Oid> = ctx.raw_commits()?.iter().map(|c| c.oid).collect();
+        let head = ctx.head()?;
+        let mut generations = Generations::load(&ctx.repo);
+        let shape = get_history_shape(&ctx.repo, &mut generations, &oids, head)?;
+        ctx.save_generations(&generations);
+        report.field("History Depth", shape.depth as usize);
+        report.field("Longest Path", shape.longest as usize);
+        report.field("DAG Width", shape.width);
+    }
+    if let Some(breakdown) = breakdown {
+        let total = breakdown.total();
+        let mut table = Table::new("Repository size breakdown", &["Component", "Size", "Share"]);
+        for (label, size) in breakdown.components {
+            table.push(vec![
+                label.into(),
+                Value::Size(size),
+                Value::Percent(percent(size as usize, total as usize)),
+            ]);
+        }
+        report.table(table);
+    }
+    Ok(())
+}
+
+fn support(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let patterns: Vec<String> = match sub.get_many::<String>("pattern") {
+        Some(patterns) => patterns.cloned().collect(),
+        None => RELEASE_PATTERNS.iter().map(|p| p.to_string()).collect(),
+    };
+    let branch = match sub.get_one::<String>("branch") {
+        Some(branch) => branch.clone(),
+        None => default_branch(&ctx.repo).ok_or("no default branch, pass --branch")?,
+    };
+    let default = ctx
+        .branch_tip(&branch)
+        .map_err(|_| format!("unknown branch '{}'", branch))?;
+    let snapshot = ctx.snapshot()?.clone();
+    let matrix = get_support_matrix(&ctx.repo, &snapshot, default, &patterns)?;
+    report.field("Default Branch", branch);
+    report.field("Release Branches", matrix.len());
+
+    let mut table = Table::new(
+        "Support matrix",
+        &[
+            "Branch",
+            "Latest Tag",
+            "Since Tag",
+            "Last Activity",
+            "Unbackported Fixes",
+        ],
+    );
+    for release in matrix {
+        let (tag, since) = match release.latest_tag {
+            Some((tag, since)) => (Value::Text(tag), Value::Count(since)),
+            None => (Value::Text("-".into()), Value::Text("-".into())),
+        };
+        table.push(vec![
+            release.name.into(),
+            tag,
+            since,
+            Value::Time(release.last_activity),
+            release.unbackported.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn teams(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = sub
+        .get_one::<String>("teams-file")
+        .expect("teams-file has a default");
+    let mapping = Config::load(
+        Path::new(path),
+        sub.value_source("teams-file") == Some(ValueSource::CommandLine),
+    )?;
+    let mut teams = Teams::from_config(&mapping)?;
+    if sub.get_flag("anonymize") {
+        teams.anonymize();
+    }
+    if teams.is_empty() {
+        return Err(format!("no teams defined; add a [teams] section to {}", path).into());
+    }
+    let commits = ctx.commits(sub)?;
+    let paths: Vec<String> = ctx.head_files()?.iter().map(|f| f.path.clone()).collect();
+    let stats = get_team_stats(
+        &ctx.repo,
+        &commits,
+        &teams,
+        &paths,
+        &PathFilter::from_matches(sub),
+    )?;
+    report.field("Teams", stats.len());
+    report.field("Files", paths.len());
+
+    let mut table = Table::new(
+        "Activity per team",
+        &[
+            "Team",
+            "Contributors",
+            "Commits",
+            "Share",
+            "Insertions",
+            "Deletions",
+            "Files Owned",
+        ],
+    );
+    for team in &stats {
+        table.push(vec![
+            team.team.as_str().into(),
+            team.contributors.into(),
+            team.commits.into(),
+            Value::Percent(percent(team.commits, commits.len())),
+            team.churn.insertions.into(),
+            team.churn.deletions.into(),
+            team.files_owned.into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn test_ratio(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let patterns: Vec<String> = match sub.get_many::<String>("pattern") {
+        Some(patterns) => patterns.cloned().collect(),
+        None => test_patterns_from_config(&ctx.config)?.unwrap_or_else(|| {
+            DEFAULT_TEST_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect()
+        }),
+    };
+    let tests = parse_globs(&patterns)?;
+    let map = LanguageMap::from_config(&ctx.config)?;
+    let ratio_text = |ratio: Option<f64>| -> Value {
+        ratio
+            .map_or_else(|| "-".to_string(), |r| format!("{:.2}", r))
+            .into()
+    };
+    let files = ctx.head_files()?.to_vec();
+    let head = get_test_ratio(&ctx.repo, &files, &tests, &map)?;
+    report.field("Test Files", head.test_files);
+    report.field("Test Lines", head.test_lines);
+    report.field("Source Files", head.source_files);
+    report.field("Source Lines", head.source_lines);
+    report.field("Test/Source Files", ratio_text(head.file_ratio()));
+    report.field("Test/Source Lines", ratio_text(head.line_ratio()));
+
+    let bucketing = sub
+        .get_one::<Bucketing>("bucket")
+        .copied()
+        .unwrap_or(Bucketing::Quarter);
+    let commits = ctx.commits(sub)?;
+    let tip = ctx.head()?;
+    let trend = get_test_ratio_trend(&ctx.repo, tip, &commits, &tests, &map, bucketing)?;
+    let mut table = Table::new(
+        format!("Test ratio per {}", bucketing),
+        &[
+            "Period",
+            "Test Files",
+            "Source Files",
+            "Test Lines",
+            "Source Lines",
+            "Line Ratio",
+        ],
+    );
+    for (start, ratio) in trend {
+        table.push(vec![
+            bucketing.label(start).into(),
+            ratio.test_files.into(),
+            ratio.source_files.into(),
+            ratio.test_lines.into(),
+            ratio.source_lines.into(),
+            ratio_text(ratio.line_ratio()),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn time_to_merge(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let commits = ctx.commits(sub)?;
+    let times = get_merge_times(&ctx.repo, &commits)?;
+    let describe = |seconds: Option<i64>| seconds.map_or("-".to_string(), format_duration);
+    report.field("Merges", times.branches.len());
+    report.field("Median", describe(times.percentile(50.0)));
+    report.field("Mean", describe(times.mean()));
+    report.field("p90", describe(times.percentile(90.0)));
+    report.field(
+        "Longest",
+        describe(times.branches.first().map(|b| b.lifetime)),
+    );
+    let mut table = Table::new(
+        "Slowest merges",
+        &["Merge", "Commit", "Commits", "Lifetime"],
+    );
+    for branch in times.branches.iter().take(top) {
+        table.push(vec![
+            branch.subject.as_str().into(),
+            short_id(branch.merge).into(),
+            branch.commits.into(),
+            format_duration(branch.lifetime).into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn timezones(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.commits(sub)?;
+    let (overall, authors) = get_timezone_distribution(&commits);
+    report.field("Time Zones", overall.len());
+    report.field(
+        "Primary Offset",
+        primary_offset(&overall).map_or("-".to_string(), format_offset),
+    );
+
+    let mut table = Table::new("Commits per UTC offset", &["Offset", "Commits", "Share"]);
+    for (offset, count) in &overall {
+        table.push(vec![
+            format_offset(*offset).into(),
+            (*count).into(),
+            Value::Percent(percent(*count, commits.len())),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new(
+        "Author UTC offsets per contributor",
+        &["Contributor", "Primary", "Zones", "Distribution"],
+    );
+    for (author, counts) in &authors {
+        let total: usize = counts.values().sum();
+        let mut shares: Vec<(&i32, &usize)> = counts.iter().collect();
+        shares.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let distribution: Vec<String> = shares
+            .iter()
+            .take(3)
+            .map(|(offset, count)| {
+                format!(
+                    "{} {:.0}%",
+                    format_offset(**offset),
+                    percent(**count, total)
+                )
+            })
+            .collect();
+        table.push(vec![
+            author.as_str().into(),
+            primary_offset(counts)
+                .map_or("-".to_string(), format_offset)
+                .into(),
+            counts.len().into(),
+            distribution.join(", ").into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn tree(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let mut files = ctx.head_files()?.to_vec();
+    let shape = get_tree_shape(&files);
+    report.field("Files", shape.files);
+    report.field("Directories", shape.directories);
+    report.field("Total Size", Value::Size(shape.total));
+    report.field("Average Size", Value::Size(shape.average_size()));
+    if let Some((path, depth)) = &shape.deepest {
+        report.field("Deepest Path", path.as_str());
+        report.field("Depth", *depth);
+    }
+    let special = get_special_entries(&ctx.repo)?;
+    report.field("Symlinks", special.symlinks.len());
+    report.field("Executables", special.executables.len());
+    report.field("Submodules", special.submodules.len());
+    files.sort_by(|a, b| b.size.cmp(&a.size).then(a.path.cmp(&b.path)));
+    let mut table = Table::new("Largest files", &["File", "Size"]);
+    for file in files.into_iter().take(top) {
+        table.push(vec![file.path.into(), Value::Size(file.size)]);
+    }
+    report.table(table);
+    let mut table = Table::new("Symlinks and submodules", &["Path", "Kind"]);
+    for (paths, kind) in [
+        (&special.symlinks, "symlink"),
+        (&special.submodules, "submodule"),
+    ] {
+        for path in paths {
+            table.push(vec![path.as_str().into(), kind.into()]);
+        }
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn unreachable(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *sub.get_one::<u16>("top").expect("top has a default") as usize;
+    let mut unreachable = get_unreachable_objects(&ctx.repo)?;
+    if sub.get_flag("anonymize") {
+        for dangling in &mut unreachable.dangling {
+            anonymize_identity(&mut dangling.author, &mut dangling.email);
+        }
+    }
+    report.field("Unreachable Objects", unreachable.count());
+    report.field("Unreachable Size", Value::Size(unreachable.size()));
+    report.field("Dangling Commits", unreachable.dangling.len());
+
+    let mut table = Table::new("Unreachable objects by kind", &["Kind", "Objects", "Size"]);
+    for (kind, (count, size)) in &unreachable.kinds {
+        table.push(vec![
+            kind.clone().into(),
+            (*count).into(),
+            Value::Size(*size),
+        ]);
+    }
+    report.table(table);
+
+    let mut table = Table::new(
+        "Largest dangling commits",
+        &["Commit", "Date", "Author", "Commits", "Size", "Subject"],
+    );
+    for dangling in unreachable.dangling.iter().take(top) {
+        table.push(vec![
+            short_id(dangling.oid).into(),
+            Value::Date(dangling.day),
+            dangling.author.clone().into(),
+            dangling.commits.into(),
+            Value::Size(dangling.size),
+            dangling.summary.clone().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn vendor(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let head = ctx.head()?;
+    let packages = get_vendored_packages(&ctx.repo, head)?;
+    let count = |status| packages.iter().filter(|p| p.status() == status).count();
+    report.field("Vendored Packages", packages.len());
+    report.field("Drifted", count(VersionStatus::Drifted));
+    report.field("Unknown Version", count(VersionStatus::Unknown));
+
+    let mut table = Table::new(
+        "Vendored packages by last update",
+        &[
+            "Package",
+            "Last Updated",
+            "Age (days)",
+            "Vendored",
+            "Declared",
+            "Status",
+        ],
+    );
+    let today = today();
+    for package in &packages {
+        let day = package.last_updated.map(|t| t.div_euclid(86_400));
+        table.push(vec![
+            package.path.as_str().into(),
+            day.map_or(Value::from("-"), Value::Date),
+            day.map_or(Value::from("-"), |d| {
+                Value::Count((today - d).max(0) as usize)
+            }),
+            package.vendored_version.as_deref().unwrap_or("-").into(),
+            if package.declared_versions.is_empty() {
+                "-".to_string()
+            } else {
+                package.declared_versions.join(", ")
+            }
+            .into(),
+            package.status().label().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+fn worktrees(ctx: &mut Context, report: &mut Report) -> Result<(), Box<dyn std::error::Error>> {
+    let worktrees = get_worktrees(&ctx.repo)?;
+    report.field("Worktrees", worktrees.len());
+    report.field(
+        "With Changes",
+        worktrees
+            .iter()
+            .filter(|w| w.modified + w.untracked > 0)
+            .count(),
+    );
+    report.field(
+        "Disk Usage",
+        Value::Size(worktrees.iter().map(|w| w.disk_usage).sum()),
+    );
+
+    let mut table = Table::new(
+        "Worktrees",
+        &[
+            "Worktree",
+            "Branch",
+            "Modified",
+            "Untracked",
+            "Last Commit",
+            "Subject",
+            "Disk Usage",
+            "Path",
+        ],
+    );
+    for worktree in &worktrees {
+        let mut name = worktree.name.clone();
+        if worktree.locked {
+            name.push_str(" (locked)");
+        }
+        table.push(vec![
+            name.into(),
+            worktree.branch.as_deref().unwrap_or("(detached)").into(),
+            worktree.modified.into(),
+            worktree.untracked.into(),
+            worktree
+                .last_commit
+                .as_ref()
+                .map_or(Value::from("-"), |(time, _)| Value::Time(*time)),
+            worktree
+                .last_commit
+                .as_ref()
+                .map_or("-", |(_, summary)| summary.as_str())
+                .into(),
+            Value::Size(worktree.disk_usage),
+            worktree.path.as_str().into(),
+        ]);
+    }
+    report.table(table);
+    Ok(())
+}
+
+/// Formats a streak as its length and date range, e.g. `3 days (2024-03-04 – 2024-03-06)`.
+fn describe_streak(streak: Streak, unit: &str, dates: &DateFormat) -> String {
+    let day = |day| dates.format_day(day, now());
+    match streak.length {
+        0 => "none".to_string(),
+        1 => format!("1 {} ({})", unit, day(streak.start)),
+        n => format!(
+            "{} {}s ({} – {})",
+            n,
+            unit,
+            day(streak.start),
+            day(streak.end)
+        ),
+    }
+}
+
+/// Labels histogram bucket `index` of the upper `bounds`, the first bucket
+/// starting at `first` and the last one holding everything above the bounds.
+fn range_label(bounds: &[usize], index: usize, first: usize) -> String {
+    match index {
+        0 => format!("{}-{}", first, bounds[0]),
+        i if i < bounds.len() => format!("{}-{}", bounds[i - 1] + 1, bounds[i]),
+        _ => format!("{}+", bounds[bounds.len() - 1] + 1),
+    }
+}
@@ -0,0 +1,306 @@
+//! Loading of the `gno.toml` configuration file.
+//!
+//! Only the subset of TOML gno needs is understood: `[section]` headers,
+//! `key = value` pairs with string, integer, boolean and (possibly multi-line)
+//! array values, and `#` comments.
+
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Array(Vec<ConfigValue>),
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "config line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Parsed configuration, keyed by section name and then by key. Keys before
+/// the first section header live in the `""` section.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Config {
+    sections: HashMap<String, Vec<(String, ConfigValue)>>,
+    /// File the config was loaded from, or looked for when it is missing.
+    pub path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Loads the config file at `path`. A missing file yields an empty config
+    /// unless `required` is set.
+    pub fn load(path: &Path, required: bool) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut config = match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => Config::default(),
+            Err(e) => {
+                return Err(format!("cannot read config file {}: {}", path.display(), e).into());
+            }
+        };
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        let mut lines = text.lines().enumerate();
+
+        while let Some((index, line)) = lines.next() {
+            let err = |message: String| ConfigError {
+                line: index + 1,
+                message,
+            };
+            let mut line = strip_comment(line).trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                config.sections.entry(section.clone()).or_default();
+                continue;
+            }
+
+            let Some((key, _)) = line.split_once('=') else {
+                return Err(err(format!("expected 'key = value', found '{}'", line)));
+            };
+            let key = unquote(key.trim());
+            // Arrays may continue over several lines until the brackets balance.
+            while bracket_depth(&line) > 0 {
+                match lines.next() {
+                    Some((_, next)) => {
+                        line.push(' ');
+                        line.push_str(strip_comment(next).trim());
+                    }
+                    None => return Err(err("unterminated array".to_string())),
+                }
+            }
+            let (_, raw) = line.split_once('=').unwrap_or_default();
+            let value = parse_value(raw.trim()).map_err(err)?;
+            config
+                .sections
+                .entry(section.clone())
+                .or_default()
+                .push((key, value));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the key/value pairs of a section in file order.
+    pub fn section(&self, section: &str) -> &[(String, ConfigValue)] {
+        self.sections.get(section).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&ConfigValue> {
+        self.sections
+            .get(section)?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, section: &str, key: &str) -> Result<Option<&str>, String> {
+        match self.get(section, key) {
+            None => Ok(None),
+            Some(ConfigValue::String(s)) => Ok(Some(s)),
+            Some(_) => Err(format!("config key {}.{} must be a string", section, key)),
+        }
+    }
+
+    pub fn get_int(&self, section: &str, key: &str) -> Result<Option<i64>, String> {
+        match self.get(section, key) {
+            None => Ok(None),
+            Some(ConfigValue::Integer(n)) => Ok(Some(*n)),
+            Some(_) => Err(format!("config key {}.{} must be an integer", section, key)),
+        }
+    }
+
+    pub fn get_bool(&self, section: &str, key: &str) -> Result<Option<bool>, String> {
+        match self.get(section, key) {
+            None => Ok(None),
+            Some(ConfigValue::Bool(b)) => Ok(Some(*b)),
+            Some(_) => Err(format!("config key {}.{} must be a boolean", section, key)),
+        }
+    }
+
+    pub fn get_str_array(&self, section: &str, key: &str) -> Result<Option<Vec<String>>, String> {
+        match self.get(section, key) {
+            None => Ok(None),
+            Some(value) => value.as_str_array().map(Some).ok_or_else(|| {
+                format!("config key {}.{} must be an array of strings", section, key)
+            }),
+        }
+    }
+}
+
+impl ConfigValue {
+    /// Returns the strings of an array value, or a single string as a
+    /// one-element list.
+    pub fn as_str_array(&self) -> Option<Vec<String>> {
+        match self {
+            ConfigValue::String(s) => Some(vec![s.clone()]),
+            ConfigValue::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    ConfigValue::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn bracket_depth(line: &str) -> i32 {
+    let mut in_string = false;
+    let mut depth = 0;
+    let Some((_, value)) = line.split_once('=') else {
+        return 0;
+    };
+    for ch in value.chars() {
+        match ch {
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn unquote(s: &str) -> String {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+        .to_string()
+}
+
+fn parse_value(raw: &str) -> Result<ConfigValue, String> {
+    if let Some(inner) = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        return split_array(inner)
+            .iter()
+            .map(|item| parse_value(item))
+            .collect::<Result<_, _>>()
+            .map(ConfigValue::Array);
+    }
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+        return Ok(ConfigValue::String(
+            raw[1..raw.len() - 1]
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+        ));
+    }
+    match raw {
+        "true" => Ok(ConfigValue::Bool(true)),
+        "false" => Ok(ConfigValue::Bool(false)),
+        _ => raw
+            .replace('_', "")
+            .parse()
+            .map(ConfigValue::Integer)
+            .map_err(|_| format!("invalid value '{}'", raw)),
+    }
+}
+
+/// Splits the inside of an array on top-level commas.
+fn split_array(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut depth = 0;
+    for ch in inner.chars() {
+        match ch {
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    items.push(current);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            # top-level comment
+            name = "gno"
+
+            [sprints]
+            length = 14 # two weeks
+            dates = [
+                "2024-01-08",
+                "2024-01-22", # trailing comma allowed
+            ]
+            live = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Ok(Some("gno")), config.get_str("", "name"));
+        assert_eq!(Ok(Some(14)), config.get_int("sprints", "length"));
+        assert_eq!(
+            Ok(Some(vec![
+                "2024-01-08".to_string(),
+                "2024-01-22".to_string()
+            ])),
+            config.get_str_array("sprints", "dates")
+        );
+        assert_eq!(
+            Some(&ConfigValue::Bool(true)),
+            config.get("sprints", "live")
+        );
+        assert!(config.get_int("sprints", "dates").is_err());
+        assert_eq!(None, config.get("teams", "platform"));
+        assert_eq!(3, config.section("sprints").len());
+        assert!(config.section("teams").is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Config::parse("[a]\njust words").is_err());
+        assert!(Config::parse("list = [\"a\",").is_err());
+        assert!(Config::parse("n = twelve").is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! Computation shared by all subcommands of one invocation.
+//!
+//! `gno summary then contributors` runs several subcommands in one process.
+//! The expensive walks they have in common are done once, on first use, and
+//! handed out from the context afterwards.
+
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use git2::{Oid, Repository};
+
+use crate::{
+    ai::{AiEvidence, detect_ai_tool},
+    churn::get_changed_paths,
+    config::Config,
+    fingerprint::branch_tip,
+    graph::{Generations, cache_path},
+    history::{CommitInfo, RefSnapshot, collect_commits},
+    identity::{
+        IdentityKey, anonymize_identities, apply_identities, apply_identity_key, resolve_identities,
+    },
+    landing::first_parent_chain,
+    lang::PathFilter,
+    progress::Progress,
+    tree::{TreeFile, get_head_files},
+};
+
+pub struct Context {
+    pub repo: Repository,
+    pub config: Config,
+    /// Reports the walks below, for `--progress-events`.
+    pub progress: Progress,
+    /// Keeps caches in memory instead of writing them to the repository, as
+    /// with `--no-write` or after a write failed.
+    pub no_write: bool,
+    /// Why the run fails once its reports are written, as on `alerts --fail`.
+    pub failure: Option<String>,
+    /// References the history walk started from.
+    snapshot: Option<RefSnapshot>,
+    commits: Option<Vec<CommitInfo>>,
+    head_files: Option<Vec<TreeFile>>,
+    /// Commits touching a file matched by the last filter asked for.
+    touching: Option<(PathFilter, HashSet<Oid>)>,
+    /// Commits on the first-parent chain of HEAD.
+    first_parent: Option<HashSet<Oid>>,
+    /// History as last shaped by [`commits`](Context::commits).
+    shaped: Option<(Shape, Rc<[CommitInfo]>)>,
+}
+
+/// Flags [`Context::commits`] shapes the history by.
+#[derive(Debug, Clone, PartialEq)]
+struct Shape {
+    first_parent: bool,
+    filter: PathFilter,
+    identity_key: Option<IdentityKey>,
+    merge_identities: bool,
+    anonymize: bool,
+}
+
+impl Shape {
+    fn from_matches(matches: &clap::ArgMatches) -> Self {
+        Shape {
+            first_parent: matches.get_flag("first-parent"),
+            filter: PathFilter::from_matches(matches),
+            identity_key: matches.get_one::<IdentityKey>("identity-key").copied(),
+            merge_identities: matches.get_flag("merge-identities"),
+            anonymize: matches.get_flag("anonymize"),
+        }
+    }
+}
+
+impl Context {
+    pub fn new(repo: Repository, config: Config) -> Self {
+        Context {
+            repo,
+            config,
+            progress: Progress::default(),
+            no_write: false,
+            failure: None,
+            snapshot: None,
+            commits: None,
+            head_files: None,
+            touching: None,
+            shaped: None,
+            first_parent: None,
+        }
+    }
+
+    /// Writes `generations` back to their cache unless writes are off. When
+    /// the cache cannot be written, as on a read-only mount, writes are
+    /// turned off with a warning and the rest of the run caches in memory.
+    pub fn save_generations(&mut self, generations: &Generations) {
+        if self.no_write {
+            return;
+        }
+        if let Err(e) = generations.save(&self.repo) {
+            eprintln!(
+                "Warning: cannot write {}: {}; caching in memory only",
+                cache_path(&self.repo).display(),
+                e
+            );
+            self.no_write = true;
+        }
+    }
+
+    /// Returns the references as they were when the history was first
+    /// walked, taking the snapshot now if it was not.
+    pub fn snapshot(&mut self) -> Result<&RefSnapshot, git2::Error> {
+        if self.snapshot.is_none() {
+            self.snapshot = Some(RefSnapshot::take(&self.repo)?);
+        }
+        Ok(self.snapshot.as_ref().unwrap())
+    }
+
+    /// Returns the commit HEAD pointed to in the [`snapshot`](Self::snapshot),
+    /// none when it was unborn.
+    pub fn head(&mut self) -> Result<Option<Oid>, git2::Error> {
+        Ok(self.snapshot()?.head)
+    }
+
+    /// Resolves the branch `name` to its tip in the
+    /// [`snapshot`](Self::snapshot), as [`branch_tip`] does.
+    pub fn branch_tip(&mut self, name: &str) -> Result<Oid, git2::Error> {
+        self.snapshot()?;
+        let snapshot = self.snapshot.as_ref().expect("snapshot was taken");
+        branch_tip(&self.repo, snapshot, name)
+    }
+
+    /// Returns the snapshot the reports so far were computed from, if any
+    /// walked the history.
+    pub fn taken_snapshot(&self) -> Option<&RefSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the history reachable from the [`snapshot`](Self::snapshot)
+    /// as recorded, with only `.mailmap` applied.
+    pub fn raw_commits(&mut self) -> Result<&[CommitInfo], git2::Error> {
+        if self.commits.is_none() {
+            self.snapshot()?;
+            self.commits = Some(collect_commits(
+                &self.repo,
+                self.snapshot.as_ref(),
+                &mut self.progress,
+            )?);
+        }
+        Ok(self.commits.as_deref().unwrap_or_default())
+    }
+
+    /// Returns the AI tool each attributed commit is attributed to. Tools are
+    /// detected on the identities as recorded, so `--anonymize` and
+    /// `--merge-identities` do not hide them.
+    pub fn ai_tools(&mut self) -> Result<HashMap<Oid, (&'static str, AiEvidence)>, git2::Error> {
+        Ok(self
+            .raw_commits()?
+            .iter()
+            .filter_map(|c| detect_ai_tool(c).map(|d| (c.oid, d)))
+            .collect())
+    }
+
+    /// Returns the ids of commits touching a file matched by `filter`.
+    fn touching(&mut self, filter: &PathFilter) -> Result<&HashSet<Oid>, git2::Error> {
+        if self
+            .touching
+            .as_ref()
+            .is_none_or(|(cached, _)| cached != filter)
+        {
+            let commits = self.raw_commits()?.to_vec();
+            let mut oids = HashSet::new();
+            self.progress.begin("filter", Some(commits.len()));
+            for commit in &commits {
+                self.progress.tick();
+                if get_changed_paths(&self.repo, commit.oid)?
+                    .iter()
+                    .any(|path| filter.matches(path))
+                {
+                    oids.insert(commit.oid);
+                }
+            }
+            self.progress.end();
+            self.touching = Some((filter.clone(), oids));
+        }
+        Ok(self.touching.as_ref().map(|(_, oids)| oids).unwrap())
+    }
+
+    /// Returns the ids of the commits on the first-parent chain of HEAD,
+    /// none when HEAD is unborn.
+    fn first_parent(&mut self) -> Result<&HashSet<Oid>, git2::Error> {
+        if self.first_parent.is_none() {
+            let chain = match self.head()? {
+                Some(head) => first_parent_chain(&self.repo, head)?,
+                None => Vec::new(),
+            };
+            self.first_parent = Some(chain.into_iter().collect());
+        }
+        Ok(self.first_parent.as_ref().unwrap())
+    }
+
+    /// Counts the commits of the history, on the mainline only with
+    /// `--first-parent`, whatever the other filters of
+    /// [`commits`](Self::commits).
+    pub fn total_commits(&mut self, matches: &clap::ArgMatches) -> Result<usize, git2::Error> {
+        let total = self.raw_commits()?.len();
+        if !matches.get_flag("first-parent") {
+            return Ok(total);
+        }
+        self.first_parent()?;
+        let mainline = self.first_parent.as_ref().expect("mainline was walked");
+        let commits = self.commits.as_deref().unwrap_or_default();
+        Ok(commits.iter().filter(|c| mainline.contains(&c.oid)).count())
+    }
+
+    /// Returns the history restricted to the mainline with `--first-parent`
+    /// and to commits touching the files selected by `--lang` and `--ext`,
+    /// with identities keyed by `--identity-key`,
+    /// merged when `--merge-identities` is set and replaced by pseudonyms
+    /// when `--anonymize` is set. The history is shaped again only when
+    /// these flags differ from the last call.
+    pub fn commits(&mut self, matches: &clap::ArgMatches) -> Result<Rc<[CommitInfo]>, git2::Error> {
+        let shape = Shape::from_matches(matches);
+        if let Some((cached, commits)) = &self.shaped
+            && *cached == shape
+        {
+            return Ok(commits.clone());
+        }
+        let mut commits = self.raw_commits()?.to_vec();
+        if shape.first_parent {
+            let mainline = self.first_parent()?;
+            commits.retain(|c| mainline.contains(&c.oid));
+        }
+        if !shape.filter.is_empty() {
+            let touching = self.touching(&shape.filter)?;
+            commits.retain(|c| touching.contains(&c.oid));
+        }
+        if let Some(key) = shape.identity_key {
+            apply_identity_key(&mut commits, key);
+        }
+        if shape.merge_identities {
+            let groups = resolve_identities(&commits);
+            apply_identities(&mut commits, &groups);
+        }
+        if shape.anonymize {
+            anonymize_identities(&mut commits);
+        }
+        let commits: Rc<[CommitInfo]> = commits.into();
+        self.shaped = Some((shape, commits.clone()));
+        Ok(commits)
+    }
+
+    /// Returns the blobs tracked at HEAD in the [`snapshot`](Self::snapshot).
+    pub fn head_files(&mut self) -> Result<&[TreeFile], git2::Error> {
+        if self.head_files.is_none() {
+            self.snapshot()?;
+            self.head_files = Some(get_head_files(&self.repo, self.snapshot.as_ref())?);
+        }
+        Ok(self.head_files.as_deref().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_walks_are_shared() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "1")]);
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        assert_eq!(1, ctx.raw_commits().unwrap().len());
+        assert_eq!(1, ctx.head_files().unwrap().len());
+
+        // Later commits are not seen: the first walk is reused.
+        t.commit("two", &[("b.txt", "2")]);
+        assert_eq!(1, ctx.raw_commits().unwrap().len());
+        assert_eq!(1, ctx.head_files().unwrap().len());
+    }
+
+    #[test]
+    fn test_unwritable_cache_falls_back_to_memory() {
+        let t = TestRepo::new();
+        let oid = t.commit("one", &[("a.txt", "1")]);
+        // A file where the cache directory belongs makes every write fail.
+        std::fs::write(t.repo.path().join("gno"), "").unwrap();
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        let mut generations = Generations::load(&ctx.repo);
+        assert_eq!(1, generations.get(&ctx.repo, oid).unwrap());
+        ctx.save_generations(&generations);
+        assert!(ctx.no_write);
+    }
+
+    #[test]
+    fn test_first_parent() {
+        let t = TestRepo::new();
+        let base = t.commit("init", &[("a.txt", "1")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", Some(base));
+        let feature = t.commit("feature", &[("f.txt", "1")]);
+        t.checkout(&main, None);
+        let merge = t.merge(feature, 1_700_000_100, "Merge feature");
+
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        assert_eq!(3, ctx.raw_commits().unwrap().len());
+        let mainline = ctx.first_parent().unwrap();
+        assert_eq!(2, mainline.len());
+        assert!(mainline.contains(&merge) && !mainline.contains(&feature));
+    }
+
+    #[test]
+    fn test_total_commits_ignore_path_filters() {
+        let t = TestRepo::new();
+        let base = t.commit("init", &[("a.txt", "1")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", Some(base));
+        let feature = t.commit("feature", &[("f.rs", "1")]);
+        t.checkout(&main, None);
+        t.merge(feature, 1_700_000_100, "Merge feature");
+
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        for (args, total) in [
+            (&["gno", "commits", "--ext", "rs"][..], 3),
+            (&["gno", "commits", "--ext", "rs", "--first-parent"][..], 2),
+        ] {
+            let matches = crate::cli().get_matches_from(args);
+            let sub = matches.subcommand_matches("commits").unwrap();
+            assert_eq!(total, ctx.total_commits(sub).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_ai_tools_survive_anonymize() {
+        let t = TestRepo::new();
+        let oid = t.commit_as(
+            "Copilot",
+            "198982749+Copilot@users.noreply.github.com",
+            1_700_000_000,
+            "Update",
+            &[("a.txt", "1")],
+        );
+        let matches = crate::cli().get_matches_from(["gno", "ai", "--anonymize"]);
+        let sub = matches.subcommand_matches("ai").unwrap();
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        let tools = ctx.ai_tools().unwrap();
+        let commits = ctx.commits(sub).unwrap();
+        assert_ne!("Copilot", commits[0].author_name);
+        assert_eq!(Some(&"Copilot"), tools.get(&oid).map(|(tool, _)| tool));
+    }
+
+    #[test]
+    fn test_commits_are_shaped_once() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.rs", "1")]);
+        t.commit("two", &[("b.txt", "2")]);
+        let mut ctx = Context::new(Repository::open(t.repo.path()).unwrap(), Config::default());
+        let shaped = |args: &[&str]| {
+            let matches = crate::cli().get_matches_from(args);
+            matches.subcommand_matches("commits").unwrap().clone()
+        };
+        let all = shaped(&["gno", "commits"]);
+        let rust = shaped(&["gno", "commits", "--ext", "rs"]);
+
+        let first = ctx.commits(&all).unwrap();
+        assert!(Rc::ptr_eq(&first, &ctx.commits(&all).unwrap()));
+        assert_eq!(1, ctx.commits(&rust).unwrap().len());
+        assert_eq!(2, ctx.commits(&all).unwrap().len());
+    }
+}
@@ -0,0 +1,292 @@
+//! Per-contributor metrics.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    bucket::Bucketing,
+    date::{SECONDS_PER_DAY, add_months},
+    history::CommitInfo,
+    identity::{committer_key, contributor_key},
+};
+
+/// Windows, in days, for which active contributors are reported.
+pub const ACTIVE_WINDOWS: [i64; 3] = [30, 90, 365];
+
+/// Counts distinct authors with commits in each of the last `windows` days
+/// before `now`, in a single pass over the commits.
+pub fn get_active_contributors(commits: &[CommitInfo], now: i64, windows: &[i64]) -> Vec<usize> {
+    let cutoffs: Vec<i64> = windows.iter().map(|d| now - d * SECONDS_PER_DAY).collect();
+    let mut active: Vec<HashSet<String>> = vec![HashSet::new(); windows.len()];
+    for commit in commits {
+        if commit.time < *cutoffs.iter().min().unwrap_or(&now) {
+            continue;
+        }
+        let key = contributor_key(commit);
+        for (set, cutoff) in active.iter_mut().zip(&cutoffs) {
+            if commit.time >= *cutoff {
+                set.insert(key.clone());
+            }
+        }
+    }
+    active.iter().map(HashSet::len).collect()
+}
+
+/// Months after a first commit at which retention is measured.
+pub const RETENTION_MONTHS: [i64; 3] = [3, 6, 12];
+
+/// First-time contributors of one period and how many kept committing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cohort {
+    pub start: i64,
+    pub contributors: usize,
+    /// Share of contributors with a commit at least N months after their
+    /// first, per entry of the requested months. `None` while no contributor
+    /// of the cohort is N months past their first commit.
+    pub retained: Vec<Option<f64>>,
+}
+
+/// Groups contributors into cohorts by the period of their first commit and
+/// measures what share of each cohort was still committing `months` later.
+pub fn get_retention(
+    commits: &[CommitInfo],
+    bucketing: Bucketing,
+    months: &[i64],
+    today: i64,
+) -> Vec<Cohort> {
+    let mut spans: HashMap<String, (i64, i64)> = HashMap::new();
+    for commit in commits {
+        let day = commit.day();
+        let span = spans.entry(contributor_key(commit)).or_insert((day, day));
+        span.0 = span.0.min(day);
+        span.1 = span.1.max(day);
+    }
+
+    let mut cohorts: HashMap<i64, Vec<(i64, i64)>> = HashMap::new();
+    for span in spans.into_values() {
+        cohorts
+            .entry(bucketing.start_of(span.0))
+            .or_default()
+            .push(span);
+    }
+    let mut cohorts: Vec<Cohort> = cohorts
+        .into_iter()
+        .map(|(start, spans)| Cohort {
+            start,
+            contributors: spans.len(),
+            retained: months
+                .iter()
+                .map(|n| {
+                    let observable: Vec<_> = spans
+                        .iter()
+                        .filter(|(first, _)| add_months(*first, *n) <= today)
+                        .collect();
+                    let retained = observable
+                        .iter()
+                        .filter(|(first, last)| *last >= add_months(*first, *n))
+                        .count();
+                    (!observable.is_empty())
+                        .then(|| retained as f64 * 100.0 / observable.len() as f64)
+                })
+                .collect(),
+        })
+        .collect();
+    cohorts.sort_by_key(|c| c.start);
+    cohorts
+}
+
+/// How a person took part in commits, as author and as committer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Roles {
+    pub authored: usize,
+    /// Authored commits someone else committed.
+    pub committed_by_others: usize,
+    pub committed: usize,
+    /// Commits authored by someone else, e.g. applied patches or merged work.
+    pub committed_for_others: usize,
+}
+
+/// Splits every identity's commits into authored and committed ones.
+pub fn get_roles(commits: &[CommitInfo]) -> HashMap<String, Roles> {
+    let mut roles: HashMap<String, Roles> = HashMap::new();
+    for commit in commits {
+        let other = commit.committed_by_other();
+        let author = roles.entry(contributor_key(commit)).or_default();
+        author.authored += 1;
+        author.committed_by_others += other as usize;
+        let committer = roles.entry(committer_key(commit)).or_default();
+        committer.committed += 1;
+        committer.committed_for_others += other as usize;
+    }
+    roles
+}
+
+/// Counts commits per contributor, most commits first.
+pub fn get_commit_counts(commits: &[CommitInfo]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for commit in commits {
+        *counts.entry(contributor_key(commit)).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+/// Counts merge commits per contributor. Contributors without merges are
+/// left out.
+pub fn get_merge_counts(commits: &[CommitInfo]) -> HashMap<String, usize> {
+    let mut merges: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| c.is_merge()) {
+        *merges.entry(contributor_key(commit)).or_default() += 1;
+    }
+    merges
+}
+
+/// Contributors below a commit threshold, folded into one row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Others {
+    pub contributors: usize,
+    pub commits: usize,
+}
+
+/// Keeps the contributors with at least `min_commits` commits and folds the
+/// rest into [`Others`], so totals are unchanged.
+pub fn split_minor(
+    counts: Vec<(String, usize)>,
+    min_commits: usize,
+) -> (Vec<(String, usize)>, Others) {
+    let (kept, minor): (Vec<_>, Vec<_>) = counts.into_iter().partition(|c| c.1 >= min_commits);
+    let others = Others {
+        contributors: minor.len(),
+        commits: minor.iter().map(|c| c.1).sum(),
+    };
+    (kept, others)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_active_contributors() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let commit = |name: &str, days_ago: i64| CommitInfo {
+            author_name: name.to_string(),
+            time: now - days_ago * SECONDS_PER_DAY,
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("a", 1),
+            commit("a", 40),
+            commit("b", 60),
+            commit("c", 200),
+            commit("d", 900),
+        ];
+        assert_eq!(
+            vec![1, 2, 3],
+            get_active_contributors(&commits, now, &ACTIVE_WINDOWS)
+        );
+    }
+
+    #[test]
+    fn test_retention() {
+        let commit = |name: &str, day: i64| CommitInfo {
+            author_name: name.to_string(),
+            time: day * SECONDS_PER_DAY,
+            ..Default::default()
+        };
+        let jan = crate::date::days_from_civil(2024, 1, 10);
+        let commits = vec![
+            commit("a", jan),
+            commit("a", add_months(jan, 7)),
+            commit("b", jan + 5),
+            commit("c", add_months(jan, 3)),
+        ];
+        let cohorts = get_retention(
+            &commits,
+            Bucketing::Quarter,
+            &RETENTION_MONTHS,
+            add_months(jan, 8),
+        );
+
+        assert_eq!(2, cohorts.len());
+        assert_eq!(2, cohorts[0].contributors);
+        assert_eq!(vec![Some(50.0), Some(50.0), None], cohorts[0].retained);
+        assert_eq!(vec![Some(0.0), None, None], cohorts[1].retained);
+    }
+
+    #[test]
+    fn test_roles() {
+        let commit = |author: &str, committer: &str| CommitInfo {
+            author_name: author.to_string(),
+            author_email: format!("{}@corp.com", author),
+            committer_name: committer.to_string(),
+            committer_email: format!("{}@corp.com", committer),
+            ..Default::default()
+        };
+        let commits = vec![commit("a", "a"), commit("b", "a"), commit("c", "a")];
+        let roles = get_roles(&commits);
+
+        let maintainer = roles["a <a@corp.com>"];
+        assert_eq!(
+            (1, 3, 2),
+            (
+                maintainer.authored,
+                maintainer.committed,
+                maintainer.committed_for_others
+            )
+        );
+        let contributor = roles["b <b@corp.com>"];
+        assert_eq!(
+            (1, 1, 0),
+            (
+                contributor.authored,
+                contributor.committed_by_others,
+                contributor.committed
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_minor() {
+        let commit = |name: &str| CommitInfo {
+            author_name: name.to_string(),
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("a"),
+            commit("a"),
+            commit("a"),
+            commit("b"),
+            commit("c"),
+        ];
+        let (kept, others) = split_minor(get_commit_counts(&commits), 2);
+        assert_eq!(vec![("a <>".to_string(), 3)], kept);
+        assert_eq!(
+            Others {
+                contributors: 2,
+                commits: 2
+            },
+            others
+        );
+    }
+
+    #[test]
+    fn test_merge_counts() {
+        let t = TestRepo::new();
+        let base = t.commit_as("A", "a@x.com", 1_700_000_000, "base", &[("a.txt", "1")]);
+        let branch = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", Some(base));
+        let feature = t.commit_as("A", "a@x.com", 1_700_000_100, "feature", &[("b.txt", "1")]);
+        t.checkout(&branch, None);
+        t.commit_as("B", "b@x.com", 1_700_000_200, "fix", &[("a.txt", "2")]);
+        // TestRepo merges as "test <test@example.com>".
+        t.merge(feature, 1_700_000_300, "merge feature");
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        assert_eq!(
+            HashMap::from([("test <test@example.com>".to_string(), 1)]),
+            get_merge_counts(&commits)
+        );
+    }
+}
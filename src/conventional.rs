@@ -0,0 +1,204 @@
+//! Conventional Commits: subjects of the form `type(scope)!: description`.
+//!
+//! Any word before the colon is accepted as a type, since projects extend the
+//! list. Strict checking additionally flags what the common linters reject,
+//! so that a history written by hand can be told apart from one enforced by a
+//! hook.
+
+use std::collections::HashMap;
+
+use crate::history::CommitInfo;
+
+/// The types of the Angular convention that commitlint accepts by default.
+pub const KNOWN_TYPES: [&str; 11] = [
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Violation {
+    /// The type is not all lowercase, as in `Fix:`.
+    UppercaseType,
+    /// The type is not one of [`KNOWN_TYPES`].
+    UnknownType,
+    /// The parentheses of the scope are empty, as in `fix():`.
+    EmptyScope,
+    /// No space follows the colon, as in `fix:typo`.
+    MissingSpace,
+    /// The description ends with a period.
+    TrailingPeriod,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Violation::UppercaseType => "uppercase type",
+            Violation::UnknownType => "unknown type",
+            Violation::EmptyScope => "empty scope",
+            Violation::MissingSpace => "missing space after colon",
+            Violation::TrailingPeriod => "trailing period",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalSubject<'a> {
+    /// The type, lowercased.
+    pub kind: String,
+    pub scope: Option<&'a str>,
+    /// Whether `!` marks a breaking change.
+    pub breaking: bool,
+    pub violations: Vec<Violation>,
+}
+
+/// Parses the subject of `message` as a Conventional Commit, or returns
+/// `None` if it does not have the `type(scope)!: description` shape at all.
+pub fn parse_conventional(message: &str) -> Option<ConventionalSubject<'_>> {
+    let subject = message.lines().next().unwrap_or("").trim_end();
+    let (prefix, description) = subject.split_once(':')?;
+    let (prefix, breaking) = match prefix.strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+        None => (prefix, None),
+    };
+    if kind.is_empty()
+        || !kind.chars().all(|c| c.is_ascii_alphabetic())
+        || scope.is_some_and(|s| s.contains(['(', ')']))
+        || description.trim().is_empty()
+    {
+        return None;
+    }
+
+    let mut violations = Vec::new();
+    if kind.chars().any(|c| c.is_ascii_uppercase()) {
+        violations.push(Violation::UppercaseType);
+    }
+    let kind = kind.to_ascii_lowercase();
+    if !KNOWN_TYPES.contains(&kind.as_str()) {
+        violations.push(Violation::UnknownType);
+    }
+    if scope.is_some_and(|s| s.trim().is_empty()) {
+        violations.push(Violation::EmptyScope);
+    }
+    if !description.starts_with(' ') {
+        violations.push(Violation::MissingSpace);
+    }
+    if description.ends_with('.') {
+        violations.push(Violation::TrailingPeriod);
+    }
+    Some(ConventionalSubject {
+        kind,
+        scope,
+        breaking,
+        violations,
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConventionalStats {
+    /// Commits considered, which excludes merges.
+    pub commits: usize,
+    /// Commits with a conventional subject, violations or not.
+    pub conventional: usize,
+    /// Conventional commits without any violation.
+    pub clean: usize,
+    pub scoped: usize,
+    pub breaking: usize,
+    /// Commits per type, most first.
+    pub types: Vec<(String, usize)>,
+    /// Commits per violation, most first.
+    pub violations: Vec<(Violation, usize)>,
+}
+
+/// Classifies the subjects of all non-merge commits. Merge subjects are
+/// written by git, not by the committer.
+pub fn get_conventional_stats(commits: &[CommitInfo]) -> ConventionalStats {
+    let mut stats = ConventionalStats::default();
+    let mut types: HashMap<String, usize> = HashMap::new();
+    let mut violations: HashMap<Violation, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        stats.commits += 1;
+        let Some(subject) = parse_conventional(&commit.message) else {
+            continue;
+        };
+        stats.conventional += 1;
+        stats.clean += usize::from(subject.violations.is_empty());
+        stats.scoped += usize::from(subject.scope.is_some());
+        stats.breaking += usize::from(subject.breaking);
+        *types.entry(subject.kind).or_default() += 1;
+        for violation in subject.violations {
+            *violations.entry(violation).or_default() += 1;
+        }
+    }
+    stats.types = types.into_iter().collect();
+    stats
+        .types
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    stats.violations = violations.into_iter().collect();
+    stats
+        .violations
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conventional() {
+        assert_eq!(
+            Some(ConventionalSubject {
+                kind: "feat".to_string(),
+                scope: Some("cli"),
+                breaking: true,
+                violations: vec![],
+            }),
+            parse_conventional("feat(cli)!: drop --old\n\nBREAKING CHANGE: gone\n")
+        );
+        assert_eq!(
+            vec![Violation::UppercaseType, Violation::MissingSpace],
+            parse_conventional("Fix:typo").unwrap().violations
+        );
+        assert_eq!(
+            vec![
+                Violation::UnknownType,
+                Violation::EmptyScope,
+                Violation::TrailingPeriod
+            ],
+            parse_conventional("wip(): stuff.").unwrap().violations
+        );
+        assert_eq!(None, parse_conventional("Add a feature"));
+        assert_eq!(None, parse_conventional("Release 1.0: notes"));
+        assert_eq!(None, parse_conventional("fix: "));
+    }
+
+    #[test]
+    fn test_conventional_stats() {
+        let commit = |message: &str| CommitInfo {
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let mut merge = commit("Merge branch 'x'");
+        merge.parents = vec![git2::Oid::zero(), git2::Oid::zero()];
+        let commits = vec![
+            commit("fix: one"),
+            commit("fix(io): two."),
+            commit("feat: three"),
+            commit("Add four"),
+            merge,
+        ];
+        let stats = get_conventional_stats(&commits);
+        assert_eq!(4, stats.commits);
+        assert_eq!(3, stats.conventional);
+        assert_eq!(2, stats.clean);
+        assert_eq!(1, stats.scoped);
+        assert_eq!(
+            vec![("fix".to_string(), 2), ("feat".to_string(), 1)],
+            stats.types
+        );
+        assert_eq!(vec![(Violation::TrailingPeriod, 1)], stats.violations);
+    }
+}
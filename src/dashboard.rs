@@ -0,0 +1,221 @@
+//! One HTML page summarizing many repositories, as an organization publishes.
+//!
+//! Each repository gets a card with its commits and contributors over the
+//! last [`ACTIVITY_DAYS`], its top contributor in that time, a sparkline of
+//! its weekly commits over the last year and the number of findings
+//! `--insights` would list. Cards link to a page of details per repository,
+//! written next to the index. Pages are standalone, with their styles
+//! inline, so the directory can be published as it is.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+
+use crate::{
+    date::{DateFormat, SECONDS_PER_DAY},
+    history::CommitInfo,
+    identity::contributor_key,
+    report::{Value, html_escape, html_page},
+};
+
+/// Days of activity a card sums up.
+pub const ACTIVITY_DAYS: i64 = 90;
+
+/// Weeks the sparkline of a card spans.
+pub const SPARKLINE_WEEKS: usize = 52;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoCard {
+    pub name: String,
+    /// File name of its page of details, next to the index.
+    pub page: String,
+    /// Commits in the last [`ACTIVITY_DAYS`].
+    pub commits: usize,
+    /// Contributors of those commits and their commits, most first.
+    pub contributors: Vec<(String, usize)>,
+    pub last_commit: Option<i64>,
+    /// Commits per week, oldest first, the last being the week up to now.
+    pub weekly: Vec<usize>,
+    pub findings: usize,
+}
+
+/// Sums up `commits` of the repository `name` as of `now`.
+pub fn get_repo_card(
+    name: &str,
+    page: &str,
+    commits: &[CommitInfo],
+    findings: usize,
+    now: i64,
+) -> RepoCard {
+    let since = now - ACTIVITY_DAYS * SECONDS_PER_DAY;
+    let week = 7 * SECONDS_PER_DAY;
+    let mut weekly = vec![0; SPARKLINE_WEEKS];
+    let mut recent = 0;
+    let mut contributors: HashMap<String, usize> = HashMap::new();
+    for commit in commits {
+        // Commits dated in the future count towards the current week.
+        let age = ((now - commit.time).max(0) / week) as usize;
+        if age < SPARKLINE_WEEKS {
+            weekly[SPARKLINE_WEEKS - 1 - age] += 1;
+        }
+        if commit.time >= since {
+            recent += 1;
+            *contributors.entry(contributor_key(commit)).or_default() += 1;
+        }
+    }
+    let mut contributors: Vec<(String, usize)> = contributors.into_iter().collect();
+    contributors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    RepoCard {
+        name: name.to_string(),
+        page: page.to_string(),
+        commits: recent,
+        contributors,
+        last_commit: commits.iter().map(|c| c.time).max(),
+        weekly,
+        findings,
+    }
+}
+
+/// Names `repo` after its working directory, or after its git directory
+/// without `.git` when bare.
+pub fn repo_name(repo: &Repository) -> String {
+    let path = repo.workdir().unwrap_or(repo.path());
+    let name = path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| path.display().to_string());
+    name.strip_suffix(".git").unwrap_or(&name).to_string()
+}
+
+/// Returns a file name for the page of `name` that is not among `taken`.
+pub fn page_name(name: &str, taken: &HashSet<String>) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = match slug.trim_matches('-') {
+        "" => "repo".to_string(),
+        slug => slug.to_string(),
+    };
+    (1..)
+        .map(|n| match n {
+            1 => format!("{}.html", slug),
+            n => format!("{}-{}.html", slug, n),
+        })
+        .find(|page| !taken.contains(page))
+        .expect("some suffix is free")
+}
+
+/// Draws `counts` as an SVG line `width` by `height` pixels, scaled against
+/// the largest count.
+pub fn sparkline(counts: &[usize], width: usize, height: usize) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    let step = width as f64 / counts.len().saturating_sub(1).max(1) as f64;
+    let points: Vec<String> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let y = height as f64 * (1.0 - *count as f64 / max as f64);
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect();
+    format!(
+        "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\
+         <polyline fill=\"none\" stroke=\"#2a7\" stroke-width=\"1.5\" points=\"{}\"/></svg>",
+        points.join(" "),
+        w = width,
+        h = height
+    )
+}
+
+/// Renders the index page titled `title`, one card per repository.
+pub fn dashboard_page(title: &str, cards: &[RepoCard], dates: &DateFormat) -> String {
+    let mut body = format!("<h1>{}</h1>\n<div class=\"cards\">\n", html_escape(title));
+    for card in cards {
+        let last = card
+            .last_commit
+            .map_or("-".to_string(), |t| Value::Time(t).render(dates));
+        let top = card
+            .contributors
+            .first()
+            .map_or("-".to_string(), |(name, _)| name.clone());
+        body.push_str(&format!(
+            "<div class=\"card\">\n<h2><a href=\"{}\">{}</a></h2>\n{}\n<dl>\
+             <dt>Commits ({} days)</dt><dd>{}</dd>\
+             <dt>Contributors</dt><dd>{}</dd>\
+             <dt>Top contributor</dt><dd>{}</dd>\
+             <dt>Last commit</dt><dd>{}</dd>\
+             <dt>Findings</dt><dd>{}</dd></dl>\n</div>\n",
+            html_escape(&card.page),
+            html_escape(&card.name),
+            sparkline(&card.weekly, 200, 32),
+            ACTIVITY_DAYS,
+            card.commits,
+            card.contributors.len(),
+            html_escape(&top),
+            html_escape(&last),
+            card.findings
+        ));
+    }
+    body.push_str("</div>");
+    html_page(title, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_repo_card() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let t = TestRepo::new();
+        t.commit_as("B", "b@x.com", now - 100 * SECONDS_PER_DAY, "old", &[]);
+        t.commit_as("A", "a@x.com", now - 8 * SECONDS_PER_DAY, "a", &[]);
+        t.commit_as("A", "a@x.com", now - SECONDS_PER_DAY, "b", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let card = get_repo_card("gno", "gno.html", &commits, 2, now);
+        assert_eq!(2, card.commits);
+        assert_eq!(vec![("A <a@x.com>".to_string(), 2)], card.contributors);
+        assert_eq!(Some(now - SECONDS_PER_DAY), card.last_commit);
+        assert_eq!(
+            (1, 1, 1),
+            (card.weekly[51], card.weekly[50], card.weekly[37])
+        );
+        assert_eq!(3, card.weekly.iter().sum::<usize>());
+
+        let page = dashboard_page("Org <x>", &[card], &DateFormat::Iso);
+        assert!(page.contains("<title>Org &lt;x&gt;</title>"));
+        assert!(page.contains("<h2><a href=\"gno.html\">gno</a></h2>"));
+        assert!(page.contains("<dt>Top contributor</dt><dd>A &lt;a@x.com&gt;</dd>"));
+    }
+
+    #[test]
+    fn test_page_names_and_sparkline() {
+        let mut taken = HashSet::from(["index.html".to_string()]);
+        assert_eq!("my-repo.html", page_name("My Repo", &taken));
+        taken.insert("my-repo.html".to_string());
+        assert_eq!("my-repo-2.html", page_name("my.repo", &taken));
+        assert_eq!("index-2.html", page_name("index", &taken));
+        assert_eq!("repo.html", page_name("..", &taken));
+        let t = TestRepo::new();
+        let dir = t.repo.workdir().unwrap().canonicalize().unwrap();
+        assert_eq!(
+            dir.file_name().unwrap().to_str().unwrap(),
+            repo_name(&t.repo)
+        );
+        assert_eq!(
+            "<svg width=\"10\" height=\"4\" viewBox=\"0 0 10 4\"><polyline fill=\"none\" \
+             stroke=\"#2a7\" stroke-width=\"1.5\" points=\"0.0,4.0 5.0,0.0 10.0,2.0\"/></svg>",
+            sparkline(&[0, 2, 1], 10, 4)
+        );
+    }
+}
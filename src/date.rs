@@ -0,0 +1,390 @@
+//! Calendar arithmetic on days since the Unix epoch.
+//!
+//! Commit timestamps are converted to the committer's local wall-clock day
+//! before any bucketing, so a commit made late on a Friday in UTC-8 counts as
+//! Friday even though it is already Saturday in UTC.
+
+use std::{fmt, str::FromStr};
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Returns the number of days since 1970-01-01 for the given civil date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Returns the civil `(year, month, day)` for a number of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Returns the local day (days since the epoch) of a timestamp with a UTC
+/// offset in minutes.
+pub fn local_day(seconds: i64, offset_minutes: i32) -> i64 {
+    (seconds + offset_minutes as i64 * 60).div_euclid(SECONDS_PER_DAY)
+}
+
+/// Returns the current time in seconds since the epoch.
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Returns the current UTC day.
+pub fn today() -> i64 {
+    now().div_euclid(SECONDS_PER_DAY)
+}
+
+/// Returns the weekday of a day, with Monday as 0 and Sunday as 6.
+pub fn weekday(days: i64) -> u32 {
+    // 1970-01-01 was a Thursday.
+    (days + 3).rem_euclid(7) as u32
+}
+
+/// Returns the ISO 8601 `(year, week)` a day belongs to.
+pub fn iso_week(days: i64) -> (i64, u32) {
+    let thursday = days - weekday(days) as i64 + 3;
+    let (year, _, _) = civil_from_days(thursday);
+    let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+    (year, week as u32)
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the epoch.
+pub fn parse_date(s: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid date '{}', expected YYYY-MM-DD", s);
+    let mut parts = s.trim().splitn(3, '-');
+    let year: i64 = parts
+        .next()
+        .and_then(|p| p.parse().ok())
+        .ok_or_else(invalid)?;
+    let month: u32 = parts
+        .next()
+        .and_then(|p| p.parse().ok())
+        .ok_or_else(invalid)?;
+    let day: u32 = parts
+        .next()
+        .and_then(|p| p.parse().ok())
+        .ok_or_else(invalid)?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(year, month, day))
+}
+
+/// Formats a day as `YYYY-MM-DD`.
+pub fn format_day(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats a duration in seconds with its two largest units, e.g. `3m 20s`
+/// or `2d 4h`.
+pub fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let units = [("d", SECONDS_PER_DAY), ("h", 3_600), ("m", 60), ("s", 1)];
+    let Some(first) = units.iter().position(|(_, n)| seconds >= *n) else {
+        return "0s".to_string();
+    };
+    let (unit, size) = units[first];
+    let mut out = format!("{}{}", seconds / size, unit);
+    if let Some((next_unit, next_size)) = units.get(first + 1) {
+        let rest = seconds % size / next_size;
+        if rest > 0 {
+            out.push_str(&format!(" {}{}", rest, next_unit));
+        }
+    }
+    out
+}
+
+/// Adds calendar months to a day, clamping to the end of shorter months.
+pub fn add_months(days: i64, months: i64) -> i64 {
+    let (year, month, day) = civil_from_days(days);
+    let index = year * 12 + (month as i64 - 1) + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    days_from_civil(year, month, day.min(days_in_month(year, month)))
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// How dates and times are shown in reports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `2024-03-15` for days, `2024-03-15T09:30:00Z` for times.
+    #[default]
+    Iso,
+    /// How long ago, e.g. `3 days ago`.
+    Relative,
+    /// Seconds since the epoch; days count from their midnight in UTC.
+    Unix,
+    /// A `strftime` pattern, evaluated in UTC.
+    Custom(String),
+}
+
+impl DateFormat {
+    /// Formats a day, with `now` in seconds for relative dates.
+    pub fn format_day(&self, days: i64, now: i64) -> String {
+        match self {
+            DateFormat::Iso => format_day(days),
+            DateFormat::Relative => match now.div_euclid(SECONDS_PER_DAY) - days {
+                0 => "today".to_string(),
+                1 => "yesterday".to_string(),
+                -1 => "tomorrow".to_string(),
+                _ => format_relative((days - now.div_euclid(SECONDS_PER_DAY)) * SECONDS_PER_DAY),
+            },
+            _ => self.format_time(days * SECONDS_PER_DAY, now),
+        }
+    }
+
+    /// Formats a time in seconds since the epoch, with `now` for relative
+    /// dates.
+    pub fn format_time(&self, seconds: i64, now: i64) -> String {
+        match self {
+            DateFormat::Iso => strftime(seconds, "%Y-%m-%dT%H:%M:%SZ"),
+            DateFormat::Relative => format_relative(seconds - now),
+            DateFormat::Unix => seconds.to_string(),
+            DateFormat::Custom(pattern) => strftime(seconds, pattern),
+        }
+    }
+}
+
+impl FromStr for DateFormat {
+    type Err = String;
+
+    /// Parses `iso`, `relative`, `unix` or `custom:<strftime>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "iso" => Ok(DateFormat::Iso),
+            "relative" => Ok(DateFormat::Relative),
+            "unix" => Ok(DateFormat::Unix),
+            _ => match s.strip_prefix("custom:") {
+                Some(pattern) if !pattern.is_empty() => Ok(DateFormat::Custom(pattern.to_string())),
+                _ => Err(format!(
+                    "invalid date format '{}', expected iso, relative, unix or custom:<strftime>",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateFormat::Iso => write!(f, "iso"),
+            DateFormat::Relative => write!(f, "relative"),
+            DateFormat::Unix => write!(f, "unix"),
+            DateFormat::Custom(pattern) => write!(f, "custom:{}", pattern),
+        }
+    }
+}
+
+/// Formats an offset from now in seconds, negative for the past, as
+/// `3 days ago` or `in 2 hours`.
+fn format_relative(offset: i64) -> String {
+    let seconds = offset.abs();
+    if seconds < 60 {
+        return "just now".to_string();
+    }
+    let units = [
+        ("year", 365 * SECONDS_PER_DAY),
+        ("month", 30 * SECONDS_PER_DAY),
+        ("week", 7 * SECONDS_PER_DAY),
+        ("day", SECONDS_PER_DAY),
+        ("hour", 3_600),
+        ("minute", 60),
+    ];
+    let (unit, size) = units
+        .iter()
+        .find(|(_, size)| seconds >= *size)
+        .copied()
+        .unwrap_or(("minute", 60));
+    let n = seconds / size;
+    let amount = format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    if offset < 0 {
+        format!("{} ago", amount)
+    } else {
+        format!("in {}", amount)
+    }
+}
+
+/// Formats a time in seconds since the epoch in UTC with the common
+/// `strftime` conversions: `%Y %y %m %d %e %j %H %M %S %a %A %b %B %F %T %s
+/// %z %Z %%`. Unknown conversions are kept as written.
+pub fn strftime(seconds: i64, pattern: &str) -> String {
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let second_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let (hour, minute, second) = (
+        second_of_day / 3_600,
+        second_of_day % 3_600 / 60,
+        second_of_day % 60,
+    );
+    let weekday = WEEKDAY_NAMES[weekday(days) as usize];
+    let month_name = MONTH_NAMES[month as usize - 1];
+
+    let mut out = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", year)),
+            Some('y') => out.push_str(&format!("{:02}", year.rem_euclid(100))),
+            Some('m') => out.push_str(&format!("{:02}", month)),
+            Some('d') => out.push_str(&format!("{:02}", day)),
+            Some('e') => out.push_str(&format!("{:>2}", day)),
+            Some('j') => out.push_str(&format!("{:03}", days - days_from_civil(year, 1, 1) + 1)),
+            Some('H') => out.push_str(&format!("{:02}", hour)),
+            Some('M') => out.push_str(&format!("{:02}", minute)),
+            Some('S') => out.push_str(&format!("{:02}", second)),
+            Some('a') => out.push_str(&weekday[..3]),
+            Some('A') => out.push_str(weekday),
+            Some('b') => out.push_str(&month_name[..3]),
+            Some('B') => out.push_str(month_name),
+            Some('F') => out.push_str(&format_day(days)),
+            Some('T') => out.push_str(&format!("{:02}:{:02}:{:02}", hour, minute, second)),
+            Some('s') => out.push_str(&seconds.to_string()),
+            Some('z') => out.push_str("+0000"),
+            Some('Z') => out.push_str("UTC"),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_round_trip() {
+        for days in [-800_000, -1, 0, 1, 11_016, 19_723, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days, days_from_civil(y, m, d));
+        }
+        assert_eq!((2000, 2, 29), civil_from_days(days_from_civil(2000, 2, 29)));
+        assert_eq!("1970-01-01", format_day(0));
+    }
+
+    #[test]
+    fn test_iso_week() {
+        // 2021-01-03 is a Sunday that still belongs to 2020-W53.
+        assert_eq!((2020, 53), iso_week(days_from_civil(2021, 1, 3)));
+        assert_eq!((2021, 1), iso_week(days_from_civil(2021, 1, 4)));
+        // 2024-12-30 is a Monday that starts 2025-W01.
+        assert_eq!((2025, 1), iso_week(days_from_civil(2024, 12, 30)));
+        assert_eq!(0, weekday(days_from_civil(2024, 12, 30)));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(Ok(days_from_civil(2024, 3, 15)), parse_date("2024-03-15"));
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+        assert_eq!(
+            days_from_civil(2024, 2, 29),
+            add_months(days_from_civil(2023, 11, 30), 3)
+        );
+        assert_eq!(
+            days_from_civil(2023, 12, 15),
+            add_months(days_from_civil(2024, 12, 15), -12)
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("0s", format_duration(0));
+        assert_eq!("45s", format_duration(45));
+        assert_eq!("3m 20s", format_duration(200));
+        assert_eq!("1h", format_duration(3_600));
+        assert_eq!(
+            "2d 4h",
+            format_duration(2 * SECONDS_PER_DAY + 4 * 3_600 + 59)
+        );
+    }
+
+    #[test]
+    fn test_date_formats() {
+        let day = days_from_civil(2024, 3, 15);
+        let time = day * SECONDS_PER_DAY + 9 * 3_600 + 30 * 60;
+        let now = time + 3 * SECONDS_PER_DAY;
+        assert_eq!("2024-03-15", DateFormat::Iso.format_day(day, now));
+        assert_eq!(
+            "2024-03-15T09:30:00Z",
+            DateFormat::Iso.format_time(time, now)
+        );
+        assert_eq!("3 days ago", DateFormat::Relative.format_time(time, now));
+        assert_eq!(
+            "in 2 hours",
+            DateFormat::Relative.format_time(now + 7_200, now)
+        );
+        assert_eq!("yesterday", DateFormat::Relative.format_day(day + 2, now));
+        assert_eq!(
+            (day * SECONDS_PER_DAY).to_string(),
+            DateFormat::Unix.format_day(day, now)
+        );
+
+        let custom: DateFormat = "custom:%a %d %b %Y, %H:%M %%".parse().unwrap();
+        assert_eq!("Fri 15 Mar 2024, 09:30 %", custom.format_time(time, now));
+        assert!("custom:".parse::<DateFormat>().is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Activity rolled up per directory.
+//!
+//! Every file counts towards the directory it lies in, truncated to a depth,
+//! so with depth 1 all of `src/a/b.rs` counts towards `src`. Files at the
+//! root count towards `.`. History counts files under the name they had at
+//! the time, and merge commits are skipped, as their first-parent diff
+//! repeats the merged work.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+
+use crate::{
+    churn::get_path_churn, history::CommitInfo, identity::contributor_key, lang::PathFilter,
+    progress::Progress, sparse::cone_of, tree::TreeFile,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectoryStats {
+    pub directory: String,
+    /// Commits changing a file in the directory.
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub contributors: usize,
+    /// Files at HEAD and their total size in bytes.
+    pub files: usize,
+    pub size: u64,
+}
+
+fn directory_of(path: &str, depth: usize) -> String {
+    cone_of(path, depth).unwrap_or_else(|| ".".to_string())
+}
+
+/// Rolls up `commits` and the `files` at HEAD matching `filter` per
+/// directory at `depth`, most commits first. Each commit diffed is reported
+/// to `progress`.
+pub fn get_directory_stats(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    files: &[TreeFile],
+    depth: usize,
+    filter: &PathFilter,
+    progress: &mut Progress,
+) -> Result<Vec<DirectoryStats>, git2::Error> {
+    let mut stats: HashMap<String, DirectoryStats> = HashMap::new();
+    let mut contributors: HashMap<String, HashSet<String>> = HashMap::new();
+    progress.begin(
+        "dirs",
+        Some(commits.iter().filter(|c| !c.is_merge()).count()),
+    );
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        progress.tick();
+        let mut touched = HashSet::new();
+        for (path, insertions, deletions) in get_path_churn(repo, commit.oid)? {
+            if !filter.matches(&path) {
+                continue;
+            }
+            let directory = directory_of(&path, depth);
+            let entry = stats.entry(directory.clone()).or_default();
+            entry.insertions += insertions;
+            entry.deletions += deletions;
+            if touched.insert(directory.clone()) {
+                entry.commits += 1;
+                contributors
+                    .entry(directory)
+                    .or_default()
+                    .insert(contributor_key(commit));
+            }
+        }
+    }
+    progress.end();
+    for file in files.iter().filter(|f| filter.matches(&f.path)) {
+        let entry = stats.entry(directory_of(&file.path, depth)).or_default();
+        entry.files += 1;
+        entry.size += file.size;
+    }
+
+    let mut stats: Vec<DirectoryStats> = stats
+        .into_iter()
+        .map(|(directory, mut entry)| {
+            entry.contributors = contributors.get(&directory).map_or(0, HashSet::len);
+            entry.directory = directory;
+            entry
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then(a.directory.cmp(&b.directory))
+    });
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_directory_stats() {
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            1,
+            "add",
+            &[("README", "r\n"), ("src/a/b.rs", "1\n")],
+        );
+        t.commit_as(
+            "B",
+            "b@x.com",
+            2,
+            "edit",
+            &[("src/a/b.rs", "2\n"), ("src/c.rs", "c\nc\n")],
+        );
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let stats = get_directory_stats(
+            &t.repo,
+            &commits,
+            &files,
+            1,
+            &PathFilter::default(),
+            &mut Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                DirectoryStats {
+                    directory: "src".to_string(),
+                    commits: 2,
+                    insertions: 4,
+                    deletions: 1,
+                    contributors: 2,
+                    files: 2,
+                    size: 6,
+                },
+                DirectoryStats {
+                    directory: ".".to_string(),
+                    commits: 1,
+                    insertions: 1,
+                    deletions: 0,
+                    contributors: 1,
+                    files: 1,
+                    size: 2,
+                },
+            ],
+            stats
+        );
+        assert_eq!("src/a", directory_of("src/a/b.rs", 2));
+    }
+}
@@ -0,0 +1,139 @@
+//! Diagnostics for bug reports: how this gno was built and what in the
+//! environment changes its results.
+
+use std::fs;
+
+use git2::Repository;
+
+use crate::{config::Config, graph, worktrees::common_dir};
+
+/// Git settings that change what gno reads or how it attributes work.
+pub const GIT_CONFIG_KEYS: [&str; 9] = [
+    "user.email",
+    "init.defaultBranch",
+    "mailmap.file",
+    "mailmap.blob",
+    "core.ignoreCase",
+    "core.precomposeUnicode",
+    "core.sparseCheckout",
+    "diff.renames",
+    "extensions.worktreeConfig",
+];
+
+/// Returns gno's version, enabled features and the libgit2 it links.
+pub fn build_info() -> Vec<(&'static str, String)> {
+    let libgit2 = git2::Version::get();
+    let (major, minor, patch) = libgit2.libgit2_version();
+    let yes_no = |on: bool| if on { "yes" } else { "no" }.to_string();
+    vec![
+        ("gno Version", env!("CARGO_PKG_VERSION").to_string()),
+        (
+            "Target",
+            format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        ),
+        ("Bench Feature", yes_no(cfg!(feature = "bench"))),
+        ("libgit2 Version", format!("{}.{}.{}", major, minor, patch)),
+        ("git2 Crate", libgit2.crate_version().to_string()),
+        ("Vendored libgit2", yes_no(libgit2.vendored())),
+        ("Threads", yes_no(libgit2.threads())),
+        ("HTTPS", yes_no(libgit2.https())),
+        ("SSH", yes_no(libgit2.ssh())),
+    ]
+}
+
+/// Returns the [`GIT_CONFIG_KEYS`] set for `repo`, in that order.
+pub fn git_config(repo: &Repository) -> Result<Vec<(&'static str, String)>, git2::Error> {
+    let config = repo.config()?.snapshot()?;
+    Ok(GIT_CONFIG_KEYS
+        .iter()
+        .filter_map(|key| config.get_string(key).ok().map(|value| (*key, value)))
+        .collect())
+}
+
+/// Returns the files gno reads or caches outside the history, with their
+/// sizes when they exist.
+pub fn cache_files(repo: &Repository, config: &Config) -> Vec<(&'static str, String, Option<u64>)> {
+    let mut files = vec![("Generations Cache", graph::cache_path(repo))];
+    if let Some(path) = &config.path {
+        files.insert(0, ("Config", path.clone()));
+    }
+    if let Some(workdir) = repo.workdir() {
+        files.push(("Mailmap", workdir.join(".mailmap")));
+    }
+    files
+        .into_iter()
+        .map(|(name, path)| {
+            let size = fs::metadata(&path).ok().map(|m| m.len());
+            (name, path.display().to_string(), size)
+        })
+        .collect()
+}
+
+/// Returns the permission problems and other conditions that make gno skip
+/// work or report less than is there. With `no_write`, nothing is written,
+/// so the cache directory is not probed for write access.
+pub fn problems(repo: &Repository, no_write: bool) -> Vec<String> {
+    let mut problems = Vec::new();
+    let objects = common_dir(repo).join("objects");
+    if let Err(e) = fs::read_dir(&objects) {
+        problems.push(format!("cannot read {}: {}", objects.display(), e));
+    }
+    // The cache directory may not exist yet; its parent must then be writable.
+    let cache = graph::cache_path(repo);
+    let writable = cache
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .filter(|_| !no_write)
+        .map(|dir| tempfile::tempfile_in(dir).map(|_| ()));
+    if let Some(Err(e)) = writable {
+        problems.push(format!(
+            "cannot write the generations cache {}: {}",
+            cache.display(),
+            e
+        ));
+    }
+    if repo.is_shallow() {
+        problems.push("shallow clone: history before the shallow boundary is missing".to_string());
+    }
+    if let Some(workdir) = repo.workdir()
+        && let Err(e) = fs::read(workdir.join(".mailmap"))
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        problems.push(format!("cannot read .mailmap: {}", e));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_doctor() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "1")]);
+        t.repo
+            .config()
+            .unwrap()
+            .set_str("diff.renames", "copies")
+            .unwrap();
+
+        assert!(
+            build_info()
+                .iter()
+                .any(|(name, _)| *name == "libgit2 Version")
+        );
+        assert!(
+            git_config(&t.repo)
+                .unwrap()
+                .contains(&("diff.renames", "copies".to_string()))
+        );
+        let config = Config::load(std::path::Path::new("missing.toml"), false).unwrap();
+        let files = cache_files(&t.repo, &config);
+        assert_eq!(("Config", "missing.toml".to_string(), None), files[0]);
+        assert!(problems(&t.repo, false).is_empty());
+        assert!(problems(&t.repo, true).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+//! Contributor breakdown by email domain.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{history::CommitInfo, identity::contributor_key};
+
+/// Well-known personal email providers, used to tell community contributors
+/// apart from those committing with a corporate address.
+const PERSONAL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "protonmail.com",
+    "proton.me",
+    "gmx.de",
+    "gmx.net",
+    "web.de",
+    "qq.com",
+    "163.com",
+    "users.noreply.github.com",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainStats {
+    pub domain: String,
+    pub contributors: usize,
+    pub commits: usize,
+}
+
+impl DomainStats {
+    /// Whether the domain belongs to a personal email provider.
+    pub fn is_personal(&self) -> bool {
+        is_personal_domain(&self.domain)
+    }
+}
+
+/// Whether `domain` belongs to a personal email provider, shared by people
+/// with nothing else in common.
+pub fn is_personal_domain(domain: &str) -> bool {
+    PERSONAL_DOMAINS.contains(&domain)
+}
+
+/// Returns the lowercased domain of an email address, if it has one.
+pub fn email_domain(email: &str) -> Option<String> {
+    let (_, domain) = email.rsplit_once('@')?;
+    let domain = domain.trim().trim_end_matches('>').to_lowercase();
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// Aggregates commits and distinct contributors per author email domain,
+/// ordered by commit count. Authors without a domain are grouped as `(none)`.
+pub fn get_domain_stats(commits: &[CommitInfo]) -> Vec<DomainStats> {
+    let mut domains: HashMap<String, (HashSet<String>, usize)> = HashMap::new();
+
+    for commit in commits {
+        let domain = email_domain(&commit.author_email).unwrap_or_else(|| "(none)".to_string());
+        let entry = domains.entry(domain).or_default();
+        entry.0.insert(contributor_key(commit));
+        entry.1 += 1;
+    }
+
+    let mut stats: Vec<DomainStats> = domains
+        .into_iter()
+        .map(|(domain, (contributors, commits))| DomainStats {
+            domain,
+            contributors: contributors.len(),
+            commits,
+        })
+        .collect();
+    stats.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.domain.cmp(&b.domain)));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(name: &str, email: &str) -> CommitInfo {
+        CommitInfo {
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_email_domain() {
+        assert_eq!(
+            Some("example.com".to_string()),
+            email_domain("a@Example.COM")
+        );
+        assert_eq!(None, email_domain("root"));
+        assert_eq!(None, email_domain("root@"));
+    }
+
+    #[test]
+    fn test_domain_stats() {
+        let commits = vec![
+            commit("a", "a@corp.com"),
+            commit("a", "a@corp.com"),
+            commit("b", "b@corp.com"),
+            commit("c", "c@gmail.com"),
+            commit("d", "localuser"),
+        ];
+        let stats = get_domain_stats(&commits);
+
+        assert_eq!(3, stats.len());
+        assert_eq!("corp.com", stats[0].domain);
+        assert_eq!(2, stats[0].contributors);
+        assert_eq!(3, stats[0].commits);
+        assert!(!stats[0].is_personal());
+        assert_eq!("(none)", stats[1].domain);
+        assert!(stats[2].is_personal());
+    }
+}
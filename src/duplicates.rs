@@ -0,0 +1,90 @@
+//! Identical files stored under several paths at HEAD.
+//!
+//! Files with the same blob id have the same content. Git stores such a
+//! blob once, but every checkout writes each copy, and copy-pasted assets
+//! or vendored duplicates drift apart once one copy is edited. Empty files,
+//! such as package markers, are not duplicates.
+
+use std::collections::HashMap;
+
+use git2::Oid;
+
+use crate::tree::TreeFile;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSet {
+    pub oid: Oid,
+    /// Size of one copy in bytes.
+    pub size: u64,
+    /// Paths holding the blob, sorted.
+    pub paths: Vec<String>,
+}
+
+impl DuplicateSet {
+    /// Bytes the copies beyond the first take in a checkout.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Groups the non-empty `files` sharing a blob, most wasted bytes first.
+pub fn get_duplicates(files: &[TreeFile]) -> Vec<DuplicateSet> {
+    let mut blobs: HashMap<Oid, DuplicateSet> = HashMap::new();
+    for file in files.iter().filter(|f| f.size > 0) {
+        blobs
+            .entry(file.oid)
+            .or_insert_with(|| DuplicateSet {
+                oid: file.oid,
+                size: file.size,
+                paths: Vec::new(),
+            })
+            .paths
+            .push(file.path.clone());
+    }
+    let mut duplicates: Vec<DuplicateSet> =
+        blobs.into_values().filter(|d| d.paths.len() > 1).collect();
+    for duplicate in &mut duplicates {
+        duplicate.paths.sort();
+    }
+    duplicates.sort_by(|a, b| {
+        b.wasted()
+            .cmp(&a.wasted())
+            .then(a.paths[0].cmp(&b.paths[0]))
+    });
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testutil::TestRepo, tree::get_head_files};
+
+    #[test]
+    fn test_duplicates() {
+        let t = TestRepo::new();
+        t.commit(
+            "add",
+            &[
+                ("a/logo.png", "logo"),
+                ("b/logo.png", "logo"),
+                ("c/icon.png", "logo"),
+                ("x.txt", "xy"),
+                ("y.txt", "xy"),
+                ("a/__init__.py", ""),
+                ("b/__init__.py", ""),
+                ("unique.txt", "u"),
+            ],
+        );
+        let duplicates = get_duplicates(&get_head_files(&t.repo, None).unwrap());
+        assert_eq!(
+            vec![
+                (vec!["a/logo.png", "b/logo.png", "c/icon.png"], 8),
+                (vec!["x.txt", "y.txt"], 2),
+            ],
+            duplicates
+                .iter()
+                .map(|d| (d.paths.iter().map(|p| p.as_str()).collect(), d.wasted()))
+                .collect::<Vec<(Vec<&str>, u64)>>()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! One row per commit, with the columns picked by `--fields`.
+//!
+//! Diff sizes are only computed when a size field is asked for, so an export
+//! of ids and authors stays a plain history walk.
+
+use std::{fmt, str::FromStr};
+
+use crate::{churn::CommitChurn, history::CommitInfo, message::subject, report::Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Oid,
+    ShortOid,
+    Author,
+    AuthorEmail,
+    Committer,
+    CommitterEmail,
+    /// Committer time, the time the rest of the reports use.
+    Date,
+    Parents,
+    Files,
+    Insertions,
+    Deletions,
+    Subject,
+}
+
+/// Every field, in the order they are listed in help.
+pub const FIELDS: [Field; 12] = [
+    Field::Oid,
+    Field::ShortOid,
+    Field::Author,
+    Field::AuthorEmail,
+    Field::Committer,
+    Field::CommitterEmail,
+    Field::Date,
+    Field::Parents,
+    Field::Files,
+    Field::Insertions,
+    Field::Deletions,
+    Field::Subject,
+];
+
+/// Fields exported when `--fields` is not given.
+pub const DEFAULT_FIELDS: [Field; 7] = [
+    Field::Oid,
+    Field::Author,
+    Field::Date,
+    Field::Files,
+    Field::Insertions,
+    Field::Deletions,
+    Field::Subject,
+];
+
+impl Field {
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Oid => "oid",
+            Field::ShortOid => "short-oid",
+            Field::Author => "author",
+            Field::AuthorEmail => "author-email",
+            Field::Committer => "committer",
+            Field::CommitterEmail => "committer-email",
+            Field::Date => "date",
+            Field::Parents => "parents",
+            Field::Files => "files",
+            Field::Insertions => "insertions",
+            Field::Deletions => "deletions",
+            Field::Subject => "subject",
+        }
+    }
+
+    /// Returns whether the field needs the commit's diff.
+    pub fn needs_churn(&self) -> bool {
+        matches!(self, Field::Files | Field::Insertions | Field::Deletions)
+    }
+
+    /// Returns the field's value for `commit`, with `churn` its diff size
+    /// against the first parent when [`Field::needs_churn`].
+    pub fn value(&self, commit: &CommitInfo, churn: &CommitChurn) -> Value {
+        match self {
+            Field::Oid => commit.oid.to_string().into(),
+            Field::ShortOid => commit.short_id().into(),
+            Field::Author => commit.author_name.as_str().into(),
+            Field::AuthorEmail => commit.author_email.as_str().into(),
+            Field::Committer => commit.committer_name.as_str().into(),
+            Field::CommitterEmail => commit.committer_email.as_str().into(),
+            Field::Date => Value::Time(commit.time),
+            Field::Parents => {
+                let parents: Vec<String> = commit.parents.iter().map(|p| p.to_string()).collect();
+                parents.join(" ").into()
+            }
+            Field::Files => churn.files_changed.into(),
+            Field::Insertions => churn.insertions.into(),
+            Field::Deletions => churn.deletions.into(),
+            Field::Subject => subject(&commit.message).into(),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FIELDS
+            .iter()
+            .find(|field| field.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = FIELDS.iter().map(Field::name).collect();
+                format!(
+                    "invalid field '{}', expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(Ok(Field::ShortOid), "short-oid".parse());
+        assert!("sha".parse::<Field>().is_err());
+
+        let commit = CommitInfo {
+            author_name: "A".to_string(),
+            message: "Add parser\n\nLong body".to_string(),
+            parents: vec![git2::Oid::zero()],
+            ..Default::default()
+        };
+        let churn = CommitChurn {
+            files_changed: 2,
+            insertions: 10,
+            deletions: 3,
+        };
+        let row: Vec<Value> = [
+            Field::Author,
+            Field::Subject,
+            Field::Insertions,
+            Field::Parents,
+        ]
+        .iter()
+        .map(|f| f.value(&commit, &churn))
+        .collect();
+        assert_eq!(
+            vec![
+                Value::from("A"),
+                "Add parser".into(),
+                10usize.into(),
+                "0000000000000000000000000000000000000000".into()
+            ],
+            row
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! How the mix of file extensions committed changes over time.
+//!
+//! Each file a non-merge commit adds or modifies counts once for its
+//! extension in the period of the commit, so a project moving from `.js` to
+//! `.ts` shows the shift where the work happened rather than in the tree at
+//! HEAD. Deleted files are not counted. Weighed by lines changed instead,
+//! the mix shows where the effort went: into code, docs or CI config.
+
+use std::collections::{BTreeMap, HashMap};
+
+use git2::{Delta, Repository};
+
+use crate::{
+    bucket::Bucketing,
+    churn::{diff_commit, get_path_churn},
+    history::CommitInfo,
+    lang::extension,
+};
+
+/// Stands for files without an extension.
+pub const NO_EXTENSION: &str = "(none)";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionMix {
+    /// Files or lines changed per extension over all periods, most first.
+    pub totals: Vec<(String, usize)>,
+    /// `(bucket start, files or lines changed per extension)`, oldest first.
+    pub periods: Vec<(i64, HashMap<String, usize>)>,
+}
+
+/// Counts the files `commits` changed per extension and period of
+/// `bucketing`, or with `lines` the lines they inserted and deleted.
+pub fn get_extension_mix(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    bucketing: Bucketing,
+    lines: bool,
+) -> Result<ExtensionMix, git2::Error> {
+    let mut periods: BTreeMap<i64, HashMap<String, usize>> = BTreeMap::new();
+    let mut totals: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        let changes: Vec<(String, usize)> = if lines {
+            get_path_churn(repo, commit.oid)?
+                .into_iter()
+                .map(|(path, insertions, deletions)| (path, insertions + deletions))
+                .collect()
+        } else {
+            let Some(diff) = diff_commit(repo, commit.oid)? else {
+                continue;
+            };
+            diff.deltas()
+                .filter(|d| matches!(d.status(), Delta::Added | Delta::Modified))
+                .filter_map(|d| {
+                    d.new_file()
+                        .path()
+                        .map(|p| p.to_string_lossy().into_owned())
+                })
+                .map(|path| (path, 1))
+                .collect()
+        };
+        let period = periods.entry(bucketing.start_of(commit.day())).or_default();
+        for (path, count) in changes.into_iter().filter(|(_, n)| *n > 0) {
+            let extension =
+                extension(&path).map_or_else(|| NO_EXTENSION.to_string(), |e| format!(".{}", e));
+            *period.entry(extension.clone()).or_default() += count;
+            *totals.entry(extension).or_default() += count;
+        }
+    }
+    let mut totals: Vec<(String, usize)> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(ExtensionMix {
+        totals,
+        periods: periods.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_extension_mix() {
+        let t = TestRepo::new();
+        let year = 366 * 86_400;
+        t.commit_as(
+            "A",
+            "a@x.com",
+            0,
+            "js",
+            &[("a.js", "1"), ("b.js", "1"), ("Makefile", "")],
+        );
+        t.commit_as("A", "a@x.com", year, "ts", &[("a.ts", "1"), ("b.JS", "2")]);
+        t.commit_as("A", "a@x.com", year + 1, "more", &[("a.ts", "2")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let mix = get_extension_mix(&t.repo, &commits, Bucketing::Year, false).unwrap();
+        assert_eq!(
+            vec![
+                (".js".to_string(), 3),
+                (".ts".to_string(), 2),
+                (NO_EXTENSION.to_string(), 1)
+            ],
+            mix.totals
+        );
+        assert_eq!(2, mix.periods.len());
+        assert_eq!(None, mix.periods[0].1.get(".ts"));
+        assert_eq!(Some(&2), mix.periods[1].1.get(".ts"));
+
+        let lines = get_extension_mix(&t.repo, &commits, Bucketing::Year, true).unwrap();
+        assert_eq!(
+            vec![(".js".to_string(), 3), (".ts".to_string(), 3)],
+            lines.totals
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Vendored and generated code, kept apart from hand-written code.
+//!
+//! Lockfiles, vendored dependencies and codegen output change in large
+//! batches nobody writes by hand, so they dominate churn numbers. Paths
+//! matching the configured globs are measured separately from the rest.
+//! Merge commits are skipped, as their first-parent diff repeats the merged
+//! work.
+
+use std::collections::HashMap;
+
+use git2::Repository;
+
+use crate::{
+    churn::get_path_churn,
+    config::Config,
+    glob::{Glob, matches_any},
+    history::CommitInfo,
+    tree::TreeFile,
+};
+
+/// Globs used when neither `--pattern` nor `generated.patterns` is given.
+pub const DEFAULT_GENERATED_PATTERNS: [&str; 14] = [
+    "vendor/",
+    "third_party/",
+    "node_modules/",
+    "dist/",
+    "*.lock",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "*.min.js",
+    "*.min.css",
+    "*.pb.go",
+    "*.pb.rs",
+    "*_pb2.py",
+    "*.generated.*",
+];
+
+/// Reads the `patterns` list of the `[generated]` section.
+pub fn generated_patterns_from_config(config: &Config) -> Result<Option<Vec<String>>, String> {
+    config.get_str_array("generated", "patterns")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathStats {
+    /// Files at HEAD and their size in bytes.
+    pub files: usize,
+    pub size: u64,
+    /// Commits changing any of the paths.
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratedStats {
+    pub all: PathStats,
+    pub generated: PathStats,
+    /// Everything but the generated paths.
+    pub written: PathStats,
+    /// Lines changed per generated path, most first.
+    pub paths: Vec<(String, usize)>,
+}
+
+/// Measures the `files` at HEAD and the churn of `commits`, split by
+/// whether paths match the `generated` globs.
+pub fn get_generated_stats(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    files: &[TreeFile],
+    generated: &[Glob],
+) -> Result<GeneratedStats, git2::Error> {
+    let mut stats = GeneratedStats::default();
+    for file in files {
+        let is_generated = matches_any(generated, &file.path);
+        for part in split(&mut stats, is_generated) {
+            part.files += 1;
+            part.size += file.size;
+        }
+    }
+
+    let mut paths: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        let mut touched = (false, false);
+        for (path, insertions, deletions) in get_path_churn(repo, commit.oid)? {
+            let is_generated = matches_any(generated, &path);
+            for part in split(&mut stats, is_generated) {
+                part.insertions += insertions;
+                part.deletions += deletions;
+            }
+            if is_generated {
+                touched.0 = true;
+                *paths.entry(path).or_default() += insertions + deletions;
+            } else {
+                touched.1 = true;
+            }
+        }
+        stats.all.commits += usize::from(touched.0 || touched.1);
+        stats.generated.commits += usize::from(touched.0);
+        stats.written.commits += usize::from(touched.1);
+    }
+    stats.paths = paths.into_iter().collect();
+    stats
+        .paths
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(stats)
+}
+
+/// Returns the totals a path adds to: all paths, and either the generated
+/// or the hand-written ones.
+fn split(stats: &mut GeneratedStats, is_generated: bool) -> [&mut PathStats; 2] {
+    let GeneratedStats {
+        all,
+        generated,
+        written,
+        ..
+    } = stats;
+    [all, if is_generated { generated } else { written }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        glob::parse_globs, history::collect_commits, progress::Progress, testutil::TestRepo,
+        tree::get_head_files,
+    };
+
+    #[test]
+    fn test_generated_stats() {
+        let t = TestRepo::new();
+        t.commit("add", &[("src/a.rs", "1\n"), ("Cargo.lock", "a\nb\nc\n")]);
+        t.commit("bump", &[("Cargo.lock", "a\nb\nd\n")]);
+        t.commit("edit", &[("src/a.rs", "2\n")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let globs = parse_globs(&["*.lock".to_string()]).unwrap();
+        let stats = get_generated_stats(&t.repo, &commits, &files, &globs).unwrap();
+        assert_eq!(
+            (3, 6, 2),
+            (stats.all.commits, stats.all.insertions, stats.all.deletions)
+        );
+        assert_eq!(
+            PathStats {
+                files: 1,
+                size: 6,
+                commits: 2,
+                insertions: 4,
+                deletions: 1
+            },
+            stats.generated
+        );
+        assert_eq!(
+            (2, 3),
+            (
+                stats.written.commits,
+                stats.written.insertions + stats.written.deletions
+            )
+        );
+        assert_eq!(vec![("Cargo.lock".to_string(), 5)], stats.paths);
+    }
+}
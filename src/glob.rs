@@ -0,0 +1,99 @@
+//! Path globs in the style of `.gitignore`.
+//!
+//! `*` matches any run of characters within a path component, `?` a single
+//! one, and a `**` component any number of components. A pattern without a
+//! slash matches at any depth, so `*_test.go` matches `pkg/a_test.go`; one
+//! with a slash is relative to the repository root. A pattern matching a
+//! directory matches everything inside it, so `vendor` and `vendor/` match
+//! `vendor/lib.rs`.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    components: Vec<String>,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+        if trimmed.is_empty() {
+            return Err(format!("invalid glob '{}'", pattern));
+        }
+        let mut components: Vec<String> = trimmed.split('/').map(str::to_string).collect();
+        if components.len() == 1 && !pattern.starts_with('/') {
+            components.insert(0, "**".to_string());
+        }
+        Ok(Glob { components })
+    }
+
+    /// Returns whether `path`, or a directory containing it, matches.
+    pub fn matches(&self, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').collect();
+        (1..=path.len()).any(|n| match_components(&self.components, &path[..n]))
+    }
+
+    /// Returns whether `path` itself matches, not counting the directories
+    /// containing it.
+    pub fn matches_exactly(&self, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').collect();
+        match_components(&self.components, &path)
+    }
+}
+
+/// Parses every pattern of `patterns`.
+pub fn parse_globs(patterns: &[String]) -> Result<Vec<Glob>, String> {
+    patterns.iter().map(|p| Glob::new(p)).collect()
+}
+
+/// Returns whether any of `globs` matches `path`.
+pub fn matches_any(globs: &[Glob], path: &str) -> bool {
+    globs.iter().any(|g| g.matches(path))
+}
+
+fn match_components(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_components(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((component, path)) => {
+                match_component(first.as_bytes(), component.as_bytes())
+                    && match_components(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_component(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_component(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_component(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let glob = |p: &str| Glob::new(p).unwrap();
+        assert!(glob("*_test.go").matches("pkg/store/db_test.go"));
+        assert!(!glob("*_test.go").matches("pkg/store/db.go"));
+        assert!(glob("tests/**").matches("tests/unit/a.rs"));
+        assert!(glob("tests").matches("tests/a.rs"));
+        assert!(!glob("/tests").matches("crates/x/tests/a.rs"));
+        assert!(glob("tests").matches("crates/x/tests/a.rs"));
+        assert!(glob("**/__tests__/**").matches("src/__tests__/a.js"));
+        assert!(glob("src/*.rs").matches("src/main.rs"));
+        assert!(!glob("src/*.rs").matches("src/bin/main.rs"));
+        assert!(glob("docs/*").matches("docs/api/a.md"));
+        assert!(!glob("docs/*").matches_exactly("docs/api/a.md"));
+        assert!(glob("docs/*").matches_exactly("docs/a.md"));
+        assert!(glob("docs/?.md").matches("docs/a.md"));
+        assert!(Glob::new("/").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Generation numbers for fast ancestry queries.
+//!
+//! A commit's generation is one more than the highest generation among its
+//! parents, with root commits at 1. A commit can only reach commits of a
+//! lower generation, so a walk looking for an ancestor stops descending as
+//! soon as it drops below the ancestor's generation instead of running down
+//! to the roots.
+//!
+//! Generations never change once computed, since a commit's parents are part
+//! of its id. They are cached in `gno/generations` under the common git
+//! directory and only new commits are computed on later runs.
+
+use std::{
+    collections::{BinaryHeap, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use git2::{Oid, Repository};
+
+use crate::worktrees::common_dir;
+
+const CACHE_HEADER: &str = "gno generations v1";
+
+#[derive(Debug, Default)]
+pub struct Generations {
+    numbers: HashMap<Oid, u32>,
+    /// Whether generations were computed since the cache was loaded.
+    dirty: bool,
+}
+
+/// Returns where the generations of `repo` are cached.
+pub fn cache_path(repo: &Repository) -> PathBuf {
+    common_dir(repo).join("gno").join("generations")
+}
+
+impl Generations {
+    /// Loads the cached generations of `repo`. A missing or unreadable cache
+    /// starts empty.
+    pub fn load(repo: &Repository) -> Self {
+        let mut generations = Generations::default();
+        // History is incomplete in shallow clones, so nothing is trusted or
+        // kept across runs.
+        if repo.is_shallow() {
+            return generations;
+        }
+        let Ok(cache) = fs::read_to_string(cache_path(repo)) else {
+            return generations;
+        };
+        let mut lines = cache.lines();
+        if lines.next() != Some(CACHE_HEADER) {
+            return generations;
+        }
+        for line in lines {
+            let Some((oid, number)) = line.split_once(' ') else {
+                continue;
+            };
+            if let (Ok(oid), Ok(number)) = (Oid::from_str(oid), number.parse()) {
+                generations.numbers.insert(oid, number);
+            }
+        }
+        generations
+    }
+
+    /// Writes the cache back if new generations were computed.
+    pub fn save(&self, repo: &Repository) -> std::io::Result<()> {
+        if !self.dirty || repo.is_shallow() {
+            return Ok(());
+        }
+        let path = cache_path(repo);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut cache = format!("{}\n", CACHE_HEADER);
+        for (oid, number) in &self.numbers {
+            cache.push_str(&format!("{} {}\n", oid, number));
+        }
+        // Written aside and renamed so a concurrent run never reads half a
+        // cache.
+        let partial = path.with_extension("tmp");
+        fs::write(&partial, cache)?;
+        fs::rename(partial, path)
+    }
+
+    /// Returns the generation of `oid`, computing it and those of its
+    /// ancestors as needed. Parents missing from the object database, as in
+    /// shallow clones, count as absent.
+    pub fn get(&mut self, repo: &Repository, oid: Oid) -> Result<u32, git2::Error> {
+        if let Some(number) = self.numbers.get(&oid) {
+            return Ok(*number);
+        }
+        // Iterative so that long histories do not overflow the stack.
+        let mut stack = vec![oid];
+        while let Some(&top) = stack.last() {
+            if self.numbers.contains_key(&top) {
+                stack.pop();
+                continue;
+            }
+            let parents: Vec<Oid> = match repo.find_commit(top) {
+                Ok(commit) => commit
+                    .parent_ids()
+                    .filter(|p| repo.find_commit(*p).is_ok())
+                    .collect(),
+                Err(e) if top == oid => return Err(e),
+                Err(_) => Vec::new(),
+            };
+            let pending: Vec<Oid> = parents
+                .iter()
+                .filter(|p| !self.numbers.contains_key(p))
+                .copied()
+                .collect();
+            if pending.is_empty() {
+                let number = parents.iter().map(|p| self.numbers[p]).max().unwrap_or(0) + 1;
+                self.numbers.insert(top, number);
+                self.dirty = true;
+                stack.pop();
+            } else {
+                stack.extend(pending);
+            }
+        }
+        Ok(self.numbers[&oid])
+    }
+
+    /// Returns whether `commit` is `tip` or one of its ancestors.
+    pub fn contains(
+        &mut self,
+        repo: &Repository,
+        tip: Oid,
+        commit: Oid,
+    ) -> Result<bool, git2::Error> {
+        if tip == commit {
+            return Ok(true);
+        }
+        let target = self.get(repo, commit)?;
+        let mut seen = HashSet::new();
+        let mut queue = vec![tip];
+        while let Some(oid) = queue.pop() {
+            if oid == commit {
+                return Ok(true);
+            }
+            if !seen.insert(oid) || self.get(repo, oid)? <= target {
+                continue;
+            }
+            for parent in repo.find_commit(oid)?.parent_ids() {
+                if repo.find_commit(parent).is_ok() {
+                    queue.push(parent);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Counts the commits reachable from `a` but not from `b`, and from `b`
+    /// but not from `a`. Commits are visited highest generation first, so
+    /// the walk stops once only commits reachable from both are left.
+    pub fn ahead_behind(
+        &mut self,
+        repo: &Repository,
+        a: Oid,
+        b: Oid,
+    ) -> Result<(usize, usize), git2::Error> {
+        const BOTH: u8 = 3;
+        // Which of the tips, 1 for `a` and 2 for `b`, reach each commit.
+        let mut reached: HashMap<Oid, u8> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        for (tip, side) in [(a, 1), (b, 2)] {
+            let flags = reached.entry(tip).or_default();
+            if *flags == 0 {
+                queue.push((self.get(repo, tip)?, tip));
+            }
+            *flags |= side;
+        }
+        // Queued commits not yet known to be reachable from both tips.
+        let mut unique = queue.iter().filter(|(_, oid)| reached[oid] != BOTH).count();
+        let (mut ahead, mut behind) = (0, 0);
+        while unique > 0 {
+            let Some((_, oid)) = queue.pop() else {
+                break;
+            };
+            let flags = reached[&oid];
+            match flags {
+                1 => ahead += 1,
+                2 => behind += 1,
+                _ => {}
+            }
+            unique -= usize::from(flags != BOTH);
+            for parent in repo.find_commit(oid)?.parent_ids() {
+                if repo.find_commit(parent).is_err() {
+                    continue;
+                }
+                // Parents have lower generations, so a parent seen before is
+                // still queued.
+                let parent_flags = reached.entry(parent).or_default();
+                let before = *parent_flags;
+                *parent_flags |= flags;
+                if before == 0 {
+                    queue.push((self.get(repo, parent)?, parent));
+                    unique += usize::from(flags != BOTH);
+                } else if before != BOTH && *parent_flags == BOTH {
+                    unique -= 1;
+                }
+            }
+        }
+        Ok((ahead, behind))
+    }
+}
+
+/// Depth and parallelism of a history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryShape {
+    /// Commits on the longest path from HEAD down to a root, HEAD included.
+    pub depth: u32,
+    /// Commits on the longest path in the whole history.
+    pub longest: u32,
+    /// Most commits sharing one generation: how many lines of work ran
+    /// side by side at the widest point.
+    pub width: usize,
+}
+
+/// Measures the shape of the history made of `commits`, with `head` the
+/// commit HEAD points at.
+pub fn get_history_shape(
+    repo: &Repository,
+    generations: &mut Generations,
+    commits: &[Oid],
+    head: Option<Oid>,
+) -> Result<HistoryShape, git2::Error> {
+    let mut widths: HashMap<u32, usize> = HashMap::new();
+    for oid in commits {
+        *widths.entry(generations.get(repo, *oid)?).or_default() += 1;
+    }
+    Ok(HistoryShape {
+        depth: match head {
+            Some(head) => generations.get(repo, head)?,
+            None => 0,
+        },
+        longest: widths.keys().copied().max().unwrap_or(0),
+        width: widths.values().copied().max().unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_generations() {
+        let t = TestRepo::new();
+        let first = t.commit("one", &[("a.txt", "1")]);
+        let second = t.commit("two", &[("a.txt", "2")]);
+        let third = t.commit("three", &[("a.txt", "3")]);
+
+        let mut generations = Generations::load(&t.repo);
+        assert_eq!(3, generations.get(&t.repo, third).unwrap());
+        assert!(generations.contains(&t.repo, third, first).unwrap());
+        assert!(!generations.contains(&t.repo, first, second).unwrap());
+
+        generations.save(&t.repo).unwrap();
+        assert_eq!(
+            HistoryShape {
+                depth: 3,
+                longest: 3,
+                width: 1
+            },
+            get_history_shape(
+                &t.repo,
+                &mut generations,
+                &[first, second, third],
+                Some(third)
+            )
+            .unwrap()
+        );
+        let cached = Generations::load(&t.repo);
+        assert_eq!(Some(&2), cached.numbers.get(&second));
+        assert!(!cached.dirty);
+    }
+
+    #[test]
+    fn test_ahead_behind() {
+        let t = TestRepo::new();
+        let base = t.commit("init", &[("a.txt", "1")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", Some(base));
+        t.commit("f1", &[("f.txt", "1")]);
+        let feature = t.commit("f2", &[("f.txt", "2")]);
+        t.checkout(&main, None);
+        t.commit("m1", &[("a.txt", "2")]);
+        let merge = t.merge(feature, 1_700_000_100, "Merge feature");
+        let tip = t.commit("m2", &[("a.txt", "3")]);
+
+        let mut generations = Generations::load(&t.repo);
+        for (a, b) in [(feature, tip), (tip, feature), (merge, base), (tip, tip)] {
+            assert_eq!(
+                t.repo.graph_ahead_behind(a, b).unwrap(),
+                generations.ahead_behind(&t.repo, a, b).unwrap()
+            );
+        }
+        assert_eq!(
+            (0, 3),
+            generations.ahead_behind(&t.repo, feature, tip).unwrap()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! How ownership of one file passed between authors over the years.
+//!
+//! The file is blamed as it was after the last commit of each year that
+//! changed it, so each year shows who the lines standing at the end of that
+//! year came from.
+
+use std::collections::HashMap;
+
+use git2::{BlameOptions, Oid, Repository};
+
+use crate::{
+    date::{civil_from_days, local_day},
+    history::{CommitInfo, is_missing},
+    identity::{contributor_key, signature_key},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearOwnership {
+    pub year: i64,
+    /// Last commit of the year that changed the file.
+    pub commit: Oid,
+    /// Lines per contributor, most lines first.
+    pub owners: Vec<(String, usize)>,
+}
+
+/// Returns the blob at `path` in `oid`'s tree, if the file exists there.
+fn blob_at(repo: &Repository, oid: Oid, path: &str) -> Result<Option<Oid>, git2::Error> {
+    let tree = repo.find_commit(oid)?.tree()?;
+    match tree.get_path(std::path::Path::new(path)) {
+        Ok(entry) => Ok(Some(entry.id())),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Blames `path` at the end of every year in which a commit reachable from
+/// `head` changed it, oldest year first. Years ending with the file deleted
+/// are left out. Lines are credited to the contributors of `commits`, or to
+/// the recorded author, pseudonymized when `anonymize` is set, for commits
+/// missing from them. Commits that have
+/// gone missing are skipped.
+pub fn get_handoff(
+    repo: &Repository,
+    head: Oid,
+    path: &str,
+    commits: &[CommitInfo],
+    anonymize: bool,
+) -> Result<Vec<YearOwnership>, git2::Error> {
+    let by_oid: HashMap<Oid, &CommitInfo> = commits.iter().map(|c| (c.oid, c)).collect();
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head)?;
+
+    // The last commit per year, by time, that changed the file.
+    let mut last: HashMap<i64, (i64, Oid)> = HashMap::new();
+    for oid in revwalk {
+        let commit = match oid.and_then(|oid| repo.find_commit(oid)) {
+            Ok(commit) => commit,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let oid = commit.id();
+        let blob = blob_at(repo, oid, path)?;
+        let parent_blob = match commit.parent_ids().next() {
+            Some(parent) => blob_at(repo, parent, path)?,
+            None => None,
+        };
+        if blob == parent_blob {
+            continue;
+        }
+        let time = commit.time();
+        let year = civil_from_days(local_day(time.seconds(), time.offset_minutes())).0;
+        let entry = last.entry(year).or_insert((time.seconds(), oid));
+        if time.seconds() > entry.0 {
+            *entry = (time.seconds(), oid);
+        }
+    }
+
+    let mut years: Vec<(i64, Oid)> = last.into_iter().map(|(y, (_, oid))| (y, oid)).collect();
+    years.sort();
+    let mut timeline = Vec::new();
+    for (year, oid) in years {
+        if blob_at(repo, oid, path)?.is_none() {
+            continue;
+        }
+        let mut options = BlameOptions::new();
+        options.use_mailmap(true).newest_commit(oid);
+        let blame = match repo.blame_file(std::path::Path::new(path), Some(&mut options)) {
+            Ok(blame) => blame,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for hunk in blame.iter() {
+            let owner = match by_oid.get(&hunk.final_commit_id()) {
+                Some(commit) => contributor_key(commit),
+                None => signature_key(&hunk.final_signature(), anonymize),
+            };
+            *counts.entry(owner).or_default() += hunk.lines_in_hunk();
+        }
+        let mut owners: Vec<(String, usize)> = counts.into_iter().collect();
+        owners.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        timeline.push(YearOwnership {
+            year,
+            commit: oid,
+            owners,
+        });
+    }
+    Ok(timeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_handoff() {
+        let t = TestRepo::new();
+        // 2023-01-01 and 2024-01-01.
+        let (y2023, y2024) = (1_672_531_200, 1_704_067_200);
+        t.commit_as("A", "a@x.com", y2023, "add", &[("core.rs", "1\n2\n3\n4\n")]);
+        let end_2023 = t.commit_as(
+            "B",
+            "b@x.com",
+            y2023 + 10,
+            "edit",
+            &[("core.rs", "1\n2\n3\nb\n")],
+        );
+        t.commit_as("B", "b@x.com", y2024, "other", &[("other.rs", "x\n")]);
+        let end_2024 = t.commit_as(
+            "C",
+            "c@x.com",
+            y2024 + 10,
+            "rewrite",
+            &[("core.rs", "c\nc\nc\nb\n")],
+        );
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let timeline = get_handoff(&t.repo, end_2024, "core.rs", &commits, false).unwrap();
+        assert_eq!(
+            vec![
+                YearOwnership {
+                    year: 2023,
+                    commit: end_2023,
+                    owners: vec![
+                        ("A <a@x.com>".to_string(), 3),
+                        ("B <b@x.com>".to_string(), 1)
+                    ],
+                },
+                YearOwnership {
+                    year: 2024,
+                    commit: end_2024,
+                    owners: vec![
+                        ("C <c@x.com>".to_string(), 3),
+                        ("B <b@x.com>".to_string(), 1)
+                    ],
+                },
+            ],
+            timeline
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Housekeeping state of the object store, and whether `git gc` would help.
+//!
+//! Loose objects and packs are compared with git's auto-gc thresholds,
+//! `gc.auto` and `gc.autoPackLimit`, where 0 turns a check off. Every loose
+//! object is counted, where git estimates their number from `objects/17`.
+//! Objects are reachable from any reference, from the HEAD, HEAD reflog or
+//! index of any worktree, or from a reflog entry, as `git gc` keeps those.
+//! Savings are estimates: repacked objects are assumed to compress as well
+//! as the existing packs do, and gc keeps unreachable objects younger than
+//! `gc.pruneExpire`, two weeks by default.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use git2::{Index, ObjectType, Oid, Repository};
+
+use crate::worktrees::common_dir;
+
+/// Loose objects above which `git gc --auto` packs them.
+pub const GC_AUTO: usize = 6700;
+
+/// Packs above which `git gc --auto` consolidates them.
+pub const GC_AUTO_PACK_LIMIT: usize = 50;
+
+/// Mode of index entries for submodules, whose commits live elsewhere.
+const GITLINK_MODE: u32 = 0o160000;
+
+/// Returns the git directories of the main worktree and of every linked
+/// one. Each has its own HEAD, HEAD reflog and index.
+fn worktree_dirs(repo: &Repository) -> Vec<PathBuf> {
+    let common = common_dir(repo);
+    let mut dirs = vec![common.clone()];
+    if let Ok(entries) = fs::read_dir(common.join("worktrees")) {
+        dirs.extend(entries.flatten().map(|e| e.path()));
+    }
+    dirs
+}
+
+/// Returns the objects the HEAD, HEAD reflog and index in the git directory
+/// `dir` point to. HEADs naming a branch are left to the references.
+fn worktree_roots(dir: &Path) -> Vec<Oid> {
+    let mut roots = Vec::new();
+    if let Ok(head) = fs::read_to_string(dir.join("HEAD"))
+        && let Ok(oid) = Oid::from_str(head.trim())
+    {
+        roots.push(oid);
+    }
+    if let Ok(log) = fs::read_to_string(dir.join("logs/HEAD")) {
+        for line in log.lines() {
+            roots.extend(
+                line.split_whitespace()
+                    .take(2)
+                    .filter_map(|id| Oid::from_str(id).ok())
+                    .filter(|o| !o.is_zero()),
+            );
+        }
+    }
+    if let Ok(index) = Index::open(&dir.join("index")) {
+        roots.extend(
+            index
+                .iter()
+                .filter(|e| e.mode != GITLINK_MODE)
+                .map(|e| e.id),
+        );
+    }
+    roots
+}
+
+/// Returns every object reachable from the references and their reflogs,
+/// and from the HEAD, HEAD reflog and index of every worktree.
+pub fn get_reachable(repo: &Repository) -> Result<HashSet<Oid>, git2::Error> {
+    let mut pending: Vec<Oid> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if let Some(oid) = reference.target() {
+            pending.push(oid);
+        }
+        if let Some(name) = reference.name() {
+            names.push(name.to_string());
+        }
+    }
+    for dir in worktree_dirs(repo) {
+        pending.extend(worktree_roots(&dir));
+    }
+    for name in names {
+        let Ok(reflog) = repo.reflog(&name) else {
+            continue;
+        };
+        for entry in reflog.iter() {
+            pending.extend(
+                [entry.id_old(), entry.id_new()]
+                    .into_iter()
+                    .filter(|o| !o.is_zero()),
+            );
+        }
+    }
+
+    reachable_from(repo, pending)
+}
+
+/// Returns every object reachable from `roots`. Roots naming objects that
+/// are gone are skipped.
+pub fn reachable_from(repo: &Repository, roots: Vec<Oid>) -> Result<HashSet<Oid>, git2::Error> {
+    let mut pending = roots;
+    let mut reachable = HashSet::new();
+    while let Some(oid) = pending.pop() {
+        if !reachable.insert(oid) {
+            continue;
+        }
+        // Reflogs may name objects pruned since.
+        let Ok(object) = repo.find_object(oid, None) else {
+            continue;
+        };
+        match object.kind() {
+            Some(ObjectType::Commit) => {
+                let commit = object.peel_to_commit()?;
+                pending.push(commit.tree_id());
+                pending.extend(commit.parent_ids());
+            }
+            Some(ObjectType::Tree) => {
+                let tree = object.peel_to_tree()?;
+                pending.extend(
+                    tree.iter()
+                        .filter(|e| matches!(e.kind(), Some(ObjectType::Tree | ObjectType::Blob)))
+                        .map(|e| e.id()),
+                );
+            }
+            Some(ObjectType::Tag) => {
+                if let Some(tag) = object.as_tag() {
+                    pending.push(tag.target_id());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(reachable)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectStore {
+    /// Loose objects and the bytes their files take.
+    pub loose: (usize, u64),
+    /// Those among them unreachable.
+    pub loose_unreachable: (usize, u64),
+    /// Uncompressed size of the reachable loose objects.
+    loose_reachable_size: u64,
+    pub packs: usize,
+    /// Bytes of the pack directory, indexes included.
+    pub pack_bytes: u64,
+    /// Packed objects and their uncompressed size.
+    pub packed: (usize, u64),
+    /// Those among them unreachable.
+    pub packed_unreachable: (usize, u64),
+    /// The `gc.auto` and `gc.autoPackLimit` in effect.
+    pub thresholds: (usize, usize),
+}
+
+impl ObjectStore {
+    /// On-disk bytes per uncompressed byte in the packs.
+    fn pack_ratio(&self) -> Option<f64> {
+        (self.packed.1 > 0).then(|| self.pack_bytes as f64 / self.packed.1 as f64)
+    }
+
+    /// Estimated bytes `git gc --prune=now` would free.
+    pub fn savings(&self) -> u64 {
+        let mut savings = self.loose_unreachable.1;
+        if let Some(ratio) = self.pack_ratio() {
+            let repacked = (self.loose_reachable_size as f64 * ratio) as u64;
+            let reachable_loose = self.loose.1 - self.loose_unreachable.1;
+            savings += reachable_loose.saturating_sub(repacked);
+            savings += (self.packed_unreachable.1 as f64 * ratio) as u64;
+        }
+        savings
+    }
+
+    /// Returns the housekeeping worth doing, or nothing if none is. A
+    /// threshold of 0 turns its check off, as it does auto-gc's.
+    pub fn recommendations(&self) -> Vec<String> {
+        let (gc_auto, pack_limit) = self.thresholds;
+        let mut recommendations = Vec::new();
+        if gc_auto > 0 && self.loose.0 > gc_auto {
+            recommendations.push(format!(
+                "run git gc: {} loose objects exceed gc.auto ({})",
+                self.loose.0, gc_auto
+            ));
+        }
+        if pack_limit > 0 && self.packs > pack_limit {
+            recommendations.push(format!(
+                "run git repack -ad: {} packs exceed gc.autoPackLimit ({})",
+                self.packs, pack_limit
+            ));
+        }
+        let unreachable = self.loose_unreachable.0 + self.packed_unreachable.0;
+        if unreachable > 0 {
+            recommendations.push(format!(
+                "run git gc --prune=now to drop {} unreachable objects, after checking \
+                 git fsck --lost-found for work worth keeping",
+                unreachable
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Returns the loose objects in `objects` with the bytes of their files.
+fn loose_objects(objects: &Path) -> Result<Vec<(Oid, u64)>, std::io::Error> {
+    let mut loose = Vec::new();
+    let Ok(entries) = fs::read_dir(objects) else {
+        return Ok(loose);
+    };
+    for entry in entries {
+        let entry = entry?;
+        let prefix = entry.file_name().to_string_lossy().into_owned();
+        if prefix.len() != 2 || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        for file in fs::read_dir(entry.path())? {
+            let file = file?;
+            let name = format!("{}{}", prefix, file.file_name().to_string_lossy());
+            if let Ok(oid) = Oid::from_str(&name)
+                && name.len() == 40
+            {
+                loose.push((oid, file.metadata()?.len()));
+            }
+        }
+    }
+    Ok(loose)
+}
+
+/// Counts the loose and packed objects of `repo` and which of them are
+/// unreachable.
+pub fn get_object_store(repo: &Repository) -> Result<ObjectStore, Box<dyn std::error::Error>> {
+    let config = repo.config()?.snapshot()?;
+    let threshold = |key: &str, default: usize| {
+        config
+            .get_i64(key)
+            .ok()
+            .and_then(|v| usize::try_from(v).ok())
+            .unwrap_or(default)
+    };
+    let mut store = ObjectStore {
+        thresholds: (
+            threshold("gc.auto", GC_AUTO),
+            threshold("gc.autoPackLimit", GC_AUTO_PACK_LIMIT),
+        ),
+        ..Default::default()
+    };
+    let reachable = get_reachable(repo)?;
+    let odb = repo.odb()?;
+    let objects = common_dir(repo).join("objects");
+
+    let loose = loose_objects(&objects)?;
+    let loose_ids: HashSet<Oid> = loose.iter().map(|(oid, _)| *oid).collect();
+    for (oid, bytes) in &loose {
+        store.loose.0 += 1;
+        store.loose.1 += bytes;
+        if reachable.contains(oid) {
+            store.loose_reachable_size += odb.read_header(*oid)?.0 as u64;
+        } else {
+            store.loose_unreachable.0 += 1;
+            store.loose_unreachable.1 += bytes;
+        }
+    }
+    if let Ok(entries) = fs::read_dir(objects.join("pack")) {
+        for entry in entries {
+            let entry = entry?;
+            store.pack_bytes += entry.metadata()?.len();
+            store.packs += usize::from(entry.path().extension().is_some_and(|e| e == "pack"));
+        }
+    }
+
+    // Objects are listed once per pack or loose file holding them.
+    let mut seen: HashSet<Oid> = HashSet::new();
+    let mut error = None;
+    odb.foreach(|oid| {
+        if loose_ids.contains(oid) || !seen.insert(*oid) {
+            return true;
+        }
+        match odb.read_header(*oid) {
+            Ok((size, _)) => {
+                store.packed.0 += 1;
+                store.packed.1 += size as u64;
+                if !reachable.contains(oid) {
+                    store.packed_unreachable.0 += 1;
+                    store.packed_unreachable.1 += size as u64;
+                }
+                true
+            }
+            Err(e) => {
+                error = Some(e);
+                false
+            }
+        }
+    })
+    .or_else(|e| if error.is_some() { Ok(()) } else { Err(e) })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_object_store() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "hello")]);
+        t.commit("two", &[("a.txt", "hello, world")]);
+        t.repo.blob(&[0; 4096]).unwrap();
+
+        // Two commits, two trees and two blobs are reachable.
+        assert_eq!(6, get_reachable(&t.repo).unwrap().len());
+        let store = get_object_store(&t.repo).unwrap();
+        assert_eq!(7, store.loose.0);
+        assert_eq!(1, store.loose_unreachable.0);
+        assert_eq!((0, 0), (store.packs, store.packed.0));
+        assert_eq!(store.loose_unreachable.1, store.savings());
+        assert_eq!(1, store.recommendations().len());
+
+        t.repo.config().unwrap().set_i64("gc.auto", 5).unwrap();
+        let store = get_object_store(&t.repo).unwrap();
+        assert!(store.recommendations()[0].starts_with("run git gc: 7 loose objects"));
+    }
+
+    #[test]
+    fn test_index_and_worktrees_are_reachable() {
+        let t = TestRepo::new();
+        let base = t.commit("one", &[("a.txt", "hello")]);
+        std::fs::write(t.repo.workdir().unwrap().join("staged.txt"), "staged").unwrap();
+        let mut index = t.repo.index().unwrap();
+        index.add_path(Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        let staged = index.get_path(Path::new("staged.txt"), 0).unwrap().id;
+
+        // A commit only a linked worktree's detached HEAD points to.
+        let sig = git2::Signature::now("t", "t@x.com").unwrap();
+        let tree = t.repo.find_commit(base).unwrap().tree().unwrap();
+        let detached = t.repo.commit(None, &sig, &sig, "wip", &tree, &[]).unwrap();
+        let admin = t.repo.path().join("worktrees/wt");
+        std::fs::create_dir_all(&admin).unwrap();
+        std::fs::write(admin.join("HEAD"), format!("{}\n", detached)).unwrap();
+
+        let reachable = get_reachable(&t.repo).unwrap();
+        assert!(reachable.contains(&staged) && reachable.contains(&detached));
+        let store = get_object_store(&t.repo).unwrap();
+        assert_eq!((0, 0), store.loose_unreachable);
+
+        // gc.auto=0 turns auto-gc off.
+        t.repo.config().unwrap().set_i64("gc.auto", 0).unwrap();
+        let store = get_object_store(&t.repo).unwrap();
+        assert!(store.recommendations().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+//! Daily commit activity over the last weeks, laid out like a contribution
+//! calendar: one column per week, one row per weekday. A whole year can also
+//! be laid out month by month.
+
+use crate::{
+    date::{days_from_civil, days_in_month, weekday},
+    history::CommitInfo,
+};
+
+/// Commits per weekday, Monday first, for each week, oldest first.
+pub type Heatmap = Vec<[usize; 7]>;
+
+/// Returns the first day of the `weeks`-week window ending in the week of
+/// `today`.
+pub fn heatmap_start(today: i64, weeks: usize) -> i64 {
+    today - weekday(today) as i64 - (weeks as i64 - 1) * 7
+}
+
+/// Counts commits per local day over the `weeks` weeks ending with the week
+/// of `today`.
+pub fn get_heatmap(commits: &[&CommitInfo], today: i64, weeks: usize) -> Heatmap {
+    let start = heatmap_start(today, weeks);
+    let mut heatmap = vec![[0; 7]; weeks];
+    for commit in commits {
+        let day = commit.day();
+        if day < start || day > today {
+            continue;
+        }
+        let offset = (day - start) as usize;
+        heatmap[offset / 7][offset % 7] += 1;
+    }
+    heatmap
+}
+
+/// Counts commits per local day of `year`: one row per month, January
+/// first, with one count per day of the month.
+pub fn get_calendar(commits: &[CommitInfo], year: i64) -> Vec<Vec<usize>> {
+    let mut calendar: Vec<Vec<usize>> = (1..=12)
+        .map(|month| vec![0; days_in_month(year, month) as usize])
+        .collect();
+    let mut starts: Vec<i64> = (1..=12).map(|m| days_from_civil(year, m, 1)).collect();
+    starts.push(days_from_civil(year + 1, 1, 1));
+    for commit in commits {
+        let day = commit.day();
+        if let Some(month) = starts.windows(2).position(|w| (w[0]..w[1]).contains(&day)) {
+            calendar[month][(day - starts[month]) as usize] += 1;
+        }
+    }
+    calendar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{SECONDS_PER_DAY, days_from_civil};
+
+    #[test]
+    fn test_heatmap() {
+        // 2024-03-13 was a Wednesday.
+        let today = days_from_civil(2024, 3, 13);
+        let commit = |day: i64| CommitInfo {
+            time: day * SECONDS_PER_DAY,
+            ..Default::default()
+        };
+        let commits = [
+            commit(today),
+            commit(today),
+            commit(today - 7),
+            commit(today - 800),
+        ];
+        let commits: Vec<&CommitInfo> = commits.iter().collect();
+
+        let heatmap = get_heatmap(&commits, today, 52);
+        assert_eq!(52, heatmap.len());
+        assert_eq!(2, heatmap[51][2]);
+        assert_eq!(1, heatmap[50][2]);
+        assert_eq!(3, heatmap.iter().flatten().sum::<usize>());
+        assert_eq!(days_from_civil(2023, 3, 20), heatmap_start(today, 52));
+
+        let commits: Vec<CommitInfo> = commits.into_iter().cloned().collect();
+        let calendar = get_calendar(&commits, 2024);
+        assert_eq!(29, calendar[1].len());
+        assert_eq!(2, calendar[2][12]);
+        assert_eq!(1, calendar[2][5]);
+        assert_eq!(3, calendar.iter().flatten().sum::<usize>());
+    }
+}
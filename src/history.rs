@@ -0,0 +1,638 @@
+//! Walking the commit history reachable from all references.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use git2::{Oid, Repository, Revwalk};
+
+use crate::{date::local_day, identity::fnv1a, progress::Progress};
+
+/// Per-commit data collected during the history walk.
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub oid: Oid,
+    pub author_name: String,
+    pub author_email: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    /// Full commit message.
+    pub message: String,
+    /// Committer timestamp in seconds since the epoch.
+    pub time: i64,
+    /// Committer UTC offset in minutes.
+    pub offset_minutes: i32,
+    /// Author timestamp in seconds since the epoch.
+    pub author_time: i64,
+    /// Author UTC offset in minutes.
+    pub author_offset_minutes: i32,
+    /// Parent commits, first parent first; empty for root commits.
+    pub parents: Vec<Oid>,
+    pub tree: Oid,
+}
+
+impl Default for CommitInfo {
+    fn default() -> Self {
+        CommitInfo {
+            oid: Oid::zero(),
+            author_name: String::new(),
+            author_email: String::new(),
+            committer_name: String::new(),
+            committer_email: String::new(),
+            message: String::new(),
+            time: 0,
+            offset_minutes: 0,
+            author_time: 0,
+            author_offset_minutes: 0,
+            parents: Vec::new(),
+            tree: Oid::zero(),
+        }
+    }
+}
+
+impl CommitInfo {
+    /// Returns the committer's local day of this commit.
+    pub fn day(&self) -> i64 {
+        local_day(self.time, self.offset_minutes)
+    }
+
+    /// Returns the commit id abbreviated as by [`short_id`].
+    pub fn short_id(&self) -> String {
+        short_id(self.oid)
+    }
+
+    /// Returns whether this commit merges two or more parents.
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    /// Returns whether this commit merges three or more parents at once.
+    pub fn is_octopus(&self) -> bool {
+        self.parents.len() > 2
+    }
+
+    /// Returns whether someone other than the author committed this commit,
+    /// e.g. by applying a patch or rebasing it.
+    pub fn committed_by_other(&self) -> bool {
+        !self
+            .author_email
+            .eq_ignore_ascii_case(&self.committer_email)
+            && self.author_name != self.committer_name
+    }
+}
+
+/// Returns `oid` abbreviated to seven hex digits, as git does by default.
+pub fn short_id(oid: Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
+
+/// Returns the times of the oldest and newest commits, if there are any.
+pub fn time_span(commits: &[CommitInfo]) -> Option<(i64, i64)> {
+    commits.iter().fold(None, |span, c| match span {
+        None => Some((c.time, c.time)),
+        Some((first, last)) => Some((first.min(c.time), last.max(c.time))),
+    })
+}
+
+/// The empty tree, as git hashes it.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Commits that change nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmptyCommits {
+    /// Non-merge commits with the tree of their parent, or root commits with
+    /// the empty tree.
+    pub empty: usize,
+    /// Merges with the tree of their first parent, such as `-s ours` merges.
+    pub noop_merges: usize,
+}
+
+/// Counts the commits whose tree equals their first parent's tree. Commits
+/// whose first parent is not among `commits` are not counted.
+pub fn count_empty_commits(commits: &[CommitInfo]) -> EmptyCommits {
+    let trees: HashMap<Oid, Oid> = commits.iter().map(|c| (c.oid, c.tree)).collect();
+    let empty_tree = Oid::from_str(EMPTY_TREE).ok();
+    let mut counts = EmptyCommits::default();
+    for commit in commits {
+        let parent_tree = match commit.parents.first() {
+            Some(parent) => trees.get(parent).copied(),
+            None => empty_tree,
+        };
+        if parent_tree == Some(commit.tree) {
+            if commit.is_merge() {
+                counts.noop_merges += 1;
+            } else {
+                counts.empty += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Returns whether `e` reports an object or reference that is gone, as
+/// when a concurrent `git gc` or ref update removed it during a walk.
+pub fn is_missing(e: &git2::Error) -> bool {
+    e.code() == git2::ErrorCode::NotFound
+}
+
+/// Namespaces of the references reports name. Others, such as pull request
+/// or review refs that can number in the hundreds of thousands, are only
+/// recorded by target.
+const NAMED_NAMESPACES: [&str; 3] = ["refs/heads/", "refs/remotes/", "refs/tags/"];
+
+/// The references and their targets at one point in time.
+///
+/// Walks started from a snapshot see the same history even when a fetch,
+/// push or commit updates the references while they run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefSnapshot {
+    /// Branches, remote-tracking branches and tags with their targets,
+    /// sorted by name.
+    pub refs: Vec<(String, Oid)>,
+    /// Distinct targets of every direct reference, named or not, sorted.
+    pub targets: Vec<Oid>,
+    /// Number of direct references recorded.
+    pub count: usize,
+    /// Commit HEAD pointed to, none when it was unborn.
+    pub head: Option<Oid>,
+}
+
+impl RefSnapshot {
+    /// Records the current references, streamed from the reference database
+    /// as [`revwalk_refs`] does. References deleted while they are listed are
+    /// left out.
+    pub fn take(repo: &Repository) -> Result<Self, git2::Error> {
+        let mut refs = Vec::new();
+        let mut targets: HashSet<Oid> = HashSet::new();
+        let mut count = 0;
+        for reference in repo.references_glob("refs/*")? {
+            let reference = match reference {
+                Ok(reference) => reference,
+                Err(e) if is_missing(&e) => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(oid) = reference.target() else {
+                continue;
+            };
+            count += 1;
+            targets.insert(oid);
+            if let Some(name) = reference.name()
+                && NAMED_NAMESPACES.iter().any(|ns| name.starts_with(ns))
+            {
+                refs.push((name.to_string(), oid));
+            }
+        }
+        refs.sort();
+        let mut targets: Vec<Oid> = targets.into_iter().collect();
+        targets.sort();
+        let head = repo.head().ok().and_then(|h| h.target());
+        Ok(RefSnapshot {
+            refs,
+            targets,
+            count,
+            head,
+        })
+    }
+
+    /// Returns the target the branch, remote-tracking branch or tag `name`
+    /// had, if it existed.
+    pub fn find(&self, name: &str) -> Option<Oid> {
+        self.refs
+            .binary_search_by(|(n, _)| n.as_str().cmp(name))
+            .ok()
+            .map(|i| self.refs[i].1)
+    }
+
+    /// Returns the references whose full name starts with `prefix`, with the
+    /// prefix stripped.
+    pub fn under<'s>(&'s self, prefix: &'s str) -> impl Iterator<Item = (&'s str, Oid)> + 's {
+        self.refs
+            .iter()
+            .filter_map(move |(name, oid)| Some((name.strip_prefix(prefix)?, *oid)))
+    }
+
+    /// Returns a short id of the snapshot, the same for the same references
+    /// and targets, to tell which state of the repository a report shows.
+    pub fn id(&self) -> String {
+        let lines: Vec<String> = self
+            .head
+            .iter()
+            .map(|oid| format!("{} HEAD", oid))
+            .chain(
+                self.refs
+                    .iter()
+                    .map(|(name, oid)| format!("{} {}", oid, name)),
+            )
+            .chain(self.targets.iter().map(|oid| oid.to_string()))
+            .collect();
+        format!("{:016x}", fnv1a(&lines.join("\n")))
+    }
+}
+
+/// Returns the short name of the reference `name`, as `main` for
+/// `refs/heads/main` and `origin/main` for `refs/remotes/origin/main`.
+pub fn shorthand(name: &str) -> &str {
+    ["refs/heads/", "refs/tags/", "refs/remotes/", "refs/"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(name)
+}
+
+/// Returns a revwalk seeded with the targets of every reference.
+pub fn revwalk_all_refs(repo: &Repository) -> Result<Revwalk<'_>, git2::Error> {
+    revwalk_refs(repo, "refs/*")
+}
+
+/// Returns a revwalk seeded with the targets of the references matching
+/// `glob`.
+///
+/// References are streamed from the reference database rather than collected
+/// first, and each target is pushed once, since thousands of references often
+/// share a handful of tips in repositories with many tags or pull request refs.
+/// References and targets that vanish while the walk is set up are skipped.
+pub fn revwalk_refs<'r>(repo: &'r Repository, glob: &str) -> Result<Revwalk<'r>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    let mut pushed: HashSet<Oid> = HashSet::new();
+    for reference in repo.references_glob(glob)? {
+        let reference = match reference {
+            Ok(reference) => reference,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        // Symbolic references have no direct target; what they point to is
+        // matched on its own.
+        if let Some(oid) = reference.target() {
+            push_target(&mut revwalk, &mut pushed, oid)?;
+        }
+    }
+    revwalk.set_sorting(git2::Sort::NONE)?;
+
+    Ok(revwalk)
+}
+
+/// Returns a revwalk seeded with the references of `snapshot`.
+pub fn revwalk_snapshot<'r>(
+    repo: &'r Repository,
+    snapshot: &RefSnapshot,
+) -> Result<Revwalk<'r>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    let mut pushed: HashSet<Oid> = HashSet::new();
+    for oid in &snapshot.targets {
+        push_target(&mut revwalk, &mut pushed, *oid)?;
+    }
+    revwalk.set_sorting(git2::Sort::NONE)?;
+
+    Ok(revwalk)
+}
+
+/// Pushes `oid` unless it was pushed before or no longer exists.
+fn push_target(
+    revwalk: &mut Revwalk<'_>,
+    pushed: &mut HashSet<Oid>,
+    oid: Oid,
+) -> Result<(), git2::Error> {
+    if pushed.insert(oid)
+        && let Err(e) = revwalk.push(oid)
+        && !is_missing(&e)
+    {
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Collects every commit reachable from the references in `snapshot`, or
+/// from the current references without one, each exactly once, with author
+/// and committer identities resolved through `.mailmap`. Commits whose
+/// objects have gone missing are skipped. The walk runs first, so that each
+/// commit read is reported to `progress` against the number found.
+pub fn collect_commits(
+    repo: &Repository,
+    snapshot: Option<&RefSnapshot>,
+    progress: &mut Progress,
+) -> Result<Vec<CommitInfo>, git2::Error> {
+    let mut visited: HashSet<Oid> = HashSet::new();
+    let mut oids = Vec::new();
+    let revwalk = match snapshot {
+        Some(snapshot) => revwalk_snapshot(repo, snapshot)?,
+        None => revwalk_all_refs(repo)?,
+    };
+    for oid in revwalk {
+        match oid {
+            Ok(oid) if visited.insert(oid) => oids.push(oid),
+            Ok(_) => {}
+            Err(e) if is_missing(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    progress.begin("history", Some(oids.len()));
+    let mut commits = Vec::with_capacity(oids.len());
+    // A missing or unreadable .mailmap simply leaves identities as recorded.
+    let mailmap = repo.mailmap().ok();
+    for oid in oids {
+        progress.tick();
+        let commit = match repo.find_commit(oid) {
+            Ok(commit) => commit,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let resolve = |signature: git2::Signature<'_>| match &mailmap {
+            Some(mailmap) => mailmap.resolve_signature(&signature),
+            None => Ok(signature.to_owned()),
+        };
+        let author = resolve(commit.author())?;
+        let committer = resolve(commit.committer())?;
+        let time = committer.when();
+        let author_time = author.when();
+        commits.push(CommitInfo {
+            oid,
+            author_name: author.name().unwrap_or("").to_string(),
+            author_email: author.email().unwrap_or("").to_string(),
+            committer_name: committer.name().unwrap_or("").to_string(),
+            committer_email: committer.email().unwrap_or("").to_string(),
+            message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+            time: time.seconds(),
+            offset_minutes: time.offset_minutes(),
+            author_time: author_time.seconds(),
+            author_offset_minutes: author_time.offset_minutes(),
+            parents: commit.parent_ids().collect(),
+            tree: commit.tree_id(),
+        });
+    }
+    progress.end();
+
+    Ok(commits)
+}
+
+/// Replays `commits` parents first, whatever their timestamps say, so that
+/// state such as the name each file has is tracked per line of history.
+/// Each commit starts from the state `visit` left on its first parent, or
+/// from the default when that parent is not among `commits`, and also sees
+/// the states of its other parents. States are dropped once their last
+/// child is visited; the state of `keep` is returned.
+pub fn replay_history<S, E>(
+    commits: &[CommitInfo],
+    keep: Option<Oid>,
+    mut visit: impl FnMut(&CommitInfo, &mut S, &[&S]) -> Result<(), E>,
+) -> Result<Option<S>, E>
+where
+    S: Clone + Default,
+{
+    let index: HashMap<Oid, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.oid, i))
+        .collect();
+    let mut pending = vec![0usize; commits.len()];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); commits.len()];
+    for (i, commit) in commits.iter().enumerate() {
+        let mut parents: Vec<usize> = commit
+            .parents
+            .iter()
+            .filter_map(|p| index.get(p).copied())
+            .collect();
+        parents.dedup();
+        pending[i] = parents.len();
+        for parent in parents {
+            children[parent].push(i);
+        }
+    }
+    // Among the commits whose parents are done, the oldest goes first.
+    let mut ready: BinaryHeap<Reverse<(i64, usize)>> = (0..commits.len())
+        .filter(|i| pending[*i] == 0)
+        .map(|i| Reverse((commits[i].time, i)))
+        .collect();
+    let mut unvisited_children: Vec<usize> = children.iter().map(Vec::len).collect();
+    let mut states: HashMap<usize, S> = HashMap::new();
+    let mut kept = None;
+    while let Some(Reverse((_, i))) = ready.pop() {
+        let commit = &commits[i];
+        let mut parents: Vec<usize> = commit
+            .parents
+            .iter()
+            .filter_map(|p| index.get(p).copied())
+            .collect();
+        parents.dedup();
+        let first = commit.parents.first().and_then(|p| index.get(p).copied());
+        let mut state = match first {
+            Some(p) if unvisited_children[p] == 1 && Some(commits[p].oid) != keep => {
+                states.remove(&p).unwrap_or_default()
+            }
+            Some(p) => states.get(&p).cloned().unwrap_or_default(),
+            None => S::default(),
+        };
+        {
+            let others: Vec<&S> = parents
+                .iter()
+                .filter(|p| Some(**p) != first)
+                .filter_map(|p| states.get(p))
+                .collect();
+            visit(commit, &mut state, &others)?;
+        }
+        for p in parents {
+            unvisited_children[p] -= 1;
+            if unvisited_children[p] == 0 && Some(commits[p].oid) != keep {
+                states.remove(&p);
+            }
+        }
+        if Some(commit.oid) == keep {
+            kept = Some(state.clone());
+        }
+        if !children[i].is_empty() {
+            states.insert(i, state);
+        }
+        for &child in &children[i] {
+            pending[child] -= 1;
+            if pending[child] == 0 {
+                ready.push(Reverse((commits[child].time, child)));
+            }
+        }
+    }
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_revwalk_refs() {
+        let t = TestRepo::new();
+        let first = t.commit("one", &[("a.txt", "1")]);
+        let second = t.commit("two", &[("a.txt", "2")]);
+        for i in 0..3 {
+            t.repo
+                .reference(&format!("refs/tags/v{}", i), first, false, "tag")
+                .unwrap();
+        }
+        t.repo
+            .reference("refs/pull/1/head", second, false, "pr")
+            .unwrap();
+
+        let walked: Vec<Oid> = revwalk_all_refs(&t.repo)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(2, walked.len());
+        let tags: Vec<Oid> = revwalk_refs(&t.repo, "refs/tags/*")
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(vec![first], tags);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        assert_eq!(Some((1_700_000_000, 1_700_000_000)), time_span(&commits));
+        assert_eq!(None, time_span(&[]));
+    }
+
+    #[test]
+    fn test_time_span() {
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", 1_700_000_500, "middle", &[]);
+        t.commit_as(
+            "A",
+            "a@x.com",
+            1_700_000_000,
+            "oldest, committed later",
+            &[],
+        );
+        t.commit_as("A", "a@x.com", 1_700_090_000, "newest", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        assert_eq!(Some((1_700_000_000, 1_700_090_000)), time_span(&commits));
+    }
+
+    #[test]
+    fn test_merges() {
+        let t = TestRepo::new();
+        let base = t.commit("base", &[("a.txt", "1")]);
+        let branch = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("one", Some(base));
+        let one = t.commit("one", &[("b.txt", "1")]);
+        t.checkout("two", Some(base));
+        let two = t.commit("two", &[("c.txt", "1")]);
+        t.checkout("three", Some(base));
+        let three = t.commit("three", &[("d.txt", "1")]);
+        t.checkout(&branch, None);
+        let merge = t.merge(one, 1_700_000_000, "merge one");
+        // git2 merges two commits at a time, so the octopus merge of the
+        // other two branches is written by hand.
+        let head = t.repo.find_commit(merge).unwrap();
+        let (two, three) = (
+            t.repo.find_commit(two).unwrap(),
+            t.repo.find_commit(three).unwrap(),
+        );
+        let sig = git2::Signature::now("test", "test@example.com").unwrap();
+        let octopus = t
+            .repo
+            .commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "octopus",
+                &head.tree().unwrap(),
+                &[&head, &two, &three],
+            )
+            .unwrap();
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let kinds = |oid| {
+            let commit = commits.iter().find(|c| c.oid == oid).unwrap();
+            (commit.is_merge(), commit.is_octopus())
+        };
+        assert_eq!(
+            vec![(false, false), (true, false), (true, true)],
+            vec![kinds(base), kinds(merge), kinds(octopus)]
+        );
+        assert_eq!(2, commits.iter().filter(|c| c.is_merge()).count());
+        assert_eq!(1, commits.iter().filter(|c| c.is_octopus()).count());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let t = TestRepo::new();
+        let first = t.commit("one", &[("a.txt", "1")]);
+        let snapshot = RefSnapshot::take(&t.repo).unwrap();
+        let second = t.commit("two", &[("a.txt", "2")]);
+        // A branch whose commit was removed by gc, as a concurrent writer
+        // might leave it.
+        std::fs::write(
+            t.repo.path().join("refs/heads/gone"),
+            "1111111111111111111111111111111111111111\n",
+        )
+        .unwrap();
+
+        let commits = collect_commits(&t.repo, Some(&snapshot), &mut Progress::default()).unwrap();
+        assert_eq!(
+            vec![first],
+            commits.iter().map(|c| c.oid).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(first), snapshot.head);
+        let current = RefSnapshot::take(&t.repo).unwrap();
+        assert_eq!(2, current.refs.len());
+        assert!(current.find("refs/heads/gone").is_some());
+        assert_eq!(None, snapshot.find("refs/heads/gone"));
+        assert_ne!(snapshot.id(), current.id());
+        assert_eq!(
+            2,
+            collect_commits(&t.repo, Some(&current), &mut Progress::default())
+                .unwrap()
+                .len()
+        );
+
+        // Pull request refs are walked but not named.
+        t.repo
+            .reference("refs/pull/1/head", second, true, "")
+            .unwrap();
+        let with_pull = RefSnapshot::take(&t.repo).unwrap();
+        assert_eq!(None, with_pull.find("refs/pull/1/head"));
+        assert!(with_pull.targets.contains(&second));
+        assert_eq!(with_pull.refs.len() + 1, with_pull.count);
+    }
+
+    #[test]
+    fn test_empty_commits() {
+        let t = TestRepo::new();
+        t.commit("init", &[]);
+        t.commit("one", &[("a.txt", "1")]);
+        t.commit("again", &[("a.txt", "1")]);
+        t.commit("nothing", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        assert_eq!(
+            EmptyCommits {
+                empty: 3,
+                noop_merges: 0
+            },
+            count_empty_commits(&commits)
+        );
+    }
+
+    #[test]
+    fn test_noop_merges() {
+        let t = TestRepo::new();
+        let base = t.commit("base", &[("a.txt", "1")]);
+        let branch = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("picked", Some(base));
+        let picked = t.commit("fix", &[("a.txt", "2")]);
+        t.checkout("feature", Some(base));
+        let feature = t.commit("feature", &[("b.txt", "1")]);
+        t.checkout(&branch, None);
+        // The fix was cherry-picked already, so merging it changes nothing;
+        // merging the feature does.
+        t.commit("fix again", &[("a.txt", "2")]);
+        t.merge(picked, 1_700_000_000, "merge picked");
+        t.merge(feature, 1_700_000_000, "merge feature");
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        assert_eq!(
+            EmptyCommits {
+                empty: 0,
+                noop_merges: 1
+            },
+            count_empty_commits(&commits)
+        );
+    }
+}
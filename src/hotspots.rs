@@ -0,0 +1,178 @@
+//! Files that change most often.
+//!
+//! How often a file changes, and how many people change it, predict defects
+//! well, the more so the larger the file. Only files still tracked at HEAD
+//! are hotspots; merge commits are skipped, as their first-parent diff
+//! repeats the merged work. A hotspot changed often, and mostly by one
+//! person, is a knowledge silo: few others know it well enough to change it.
+//! A file changed only a few times says too little to judge.
+
+use std::collections::HashMap;
+
+use git2::Repository;
+
+use crate::{
+    churn::get_changed_paths, history::CommitInfo, identity::contributor_key, progress::Progress,
+    report::percent, tree::TreeFile,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub path: String,
+    pub commits: usize,
+    /// Distinct contributors among the commits.
+    pub authors: usize,
+    /// Contributor with the most commits, and those commits.
+    pub top_author: (String, usize),
+    /// Size at HEAD in bytes.
+    pub size: u64,
+}
+
+/// Share of a file's commits by its top author at or above which the file
+/// is a knowledge silo.
+pub const SILO_SHARE: f64 = 80.0;
+
+/// Commits a file needs before its top author's share means anything.
+pub const SILO_COMMITS: usize = 5;
+
+impl Hotspot {
+    /// Percentage of the commits made by the top author.
+    pub fn top_share(&self) -> f64 {
+        percent(self.top_author.1, self.commits)
+    }
+
+    pub fn is_silo(&self) -> bool {
+        self.commits >= SILO_COMMITS && self.top_share() >= SILO_SHARE
+    }
+}
+
+/// Counts the commits and contributors changing each file of `files` among
+/// the `commits` made at or after `since`, most commits first. Each commit
+/// diffed is reported to `progress`.
+pub fn get_hotspots(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    since: i64,
+    files: &[TreeFile],
+    progress: &mut Progress,
+) -> Result<Vec<Hotspot>, git2::Error> {
+    let sizes: HashMap<&str, u64> = files.iter().map(|f| (f.path.as_str(), f.size)).collect();
+    let mut changes: HashMap<String, (usize, HashMap<String, usize>)> = HashMap::new();
+    let recent: Vec<&CommitInfo> = commits
+        .iter()
+        .filter(|c| c.time >= since && !c.is_merge())
+        .collect();
+    progress.begin("hotspots", Some(recent.len()));
+    for commit in recent {
+        progress.tick();
+        for path in get_changed_paths(repo, commit.oid)? {
+            if sizes.contains_key(path.as_str()) {
+                let entry = changes.entry(path).or_default();
+                entry.0 += 1;
+                *entry.1.entry(contributor_key(commit)).or_default() += 1;
+            }
+        }
+    }
+    progress.end();
+    let mut hotspots: Vec<Hotspot> = changes
+        .into_iter()
+        .map(|(path, (commits, authors))| Hotspot {
+            size: sizes[path.as_str()],
+            path,
+            commits,
+            authors: authors.len(),
+            top_author: authors
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                .unwrap_or_default(),
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then(b.authors.cmp(&a.authors))
+            .then(a.path.cmp(&b.path))
+    });
+    Ok(hotspots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_hotspots() {
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            10,
+            "old",
+            &[("a.rs", "0"), ("gone.rs", "0")],
+        );
+        t.commit_as("A", "a@x.com", 100, "one", &[("a.rs", "1"), ("b.rs", "1")]);
+        t.commit_as("B", "b@x.com", 200, "two", &[("a.rs", "22")]);
+        let mut index = t.repo.index().unwrap();
+        index.remove_path(std::path::Path::new("gone.rs")).unwrap();
+        index.write().unwrap();
+        t.commit_as("B", "b@x.com", 300, "remove", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let hotspots =
+            get_hotspots(&t.repo, &commits, 100, &files, &mut Progress::default()).unwrap();
+        assert_eq!(
+            vec![
+                Hotspot {
+                    path: "a.rs".to_string(),
+                    commits: 2,
+                    authors: 2,
+                    top_author: ("A <a@x.com>".to_string(), 1),
+                    size: 2
+                },
+                Hotspot {
+                    path: "b.rs".to_string(),
+                    commits: 1,
+                    authors: 1,
+                    top_author: ("A <a@x.com>".to_string(), 1),
+                    size: 1
+                },
+            ],
+            hotspots
+        );
+        assert!(!hotspots[0].is_silo());
+        // One commit by one author is not yet a silo.
+        assert!(!hotspots[1].is_silo());
+    }
+
+    #[test]
+    fn test_silos() {
+        let t = TestRepo::new();
+        for i in 0..4 {
+            let content = i.to_string();
+            t.commit_as(
+                "A",
+                "a@x.com",
+                100 + i,
+                "a",
+                &[("a.rs", content.as_str()), ("b.rs", content.as_str())],
+            );
+        }
+        t.commit_as("B", "b@x.com", 200, "b", &[("a.rs", "b")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let hotspots =
+            get_hotspots(&t.repo, &commits, 0, &files, &mut Progress::default()).unwrap();
+        assert_eq!(
+            vec![("a.rs", 5, true), ("b.rs", 4, false)],
+            hotspots
+                .iter()
+                .map(|h| (h.path.as_str(), h.commits, h.is_silo()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Commits on weekends and outside business hours.
+//!
+//! Times are the author's local time, when the change was written, rather
+//! than when a rebase or someone applying it committed it. Weekend commits
+//! are counted once, as weekend; after-hours commits are those made on a
+//! weekday outside business hours.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    config::Config,
+    date::{SECONDS_PER_DAY, weekday},
+    history::CommitInfo,
+    identity::contributor_key,
+};
+
+/// Local hours `[start, end)` counted as business hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusinessHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Default for BusinessHours {
+    fn default() -> Self {
+        BusinessHours { start: 9, end: 18 }
+    }
+}
+
+impl BusinessHours {
+    fn new(start: i64, end: i64) -> Result<Self, String> {
+        if !(0..end).contains(&start) || end > 24 {
+            return Err(format!(
+                "invalid business hours {}-{}, expected 0 <= start < end <= 24",
+                start, end
+            ));
+        }
+        Ok(BusinessHours {
+            start: start as u32,
+            end: end as u32,
+        })
+    }
+
+    /// Reads `start` and `end` hours from the `[hours]` section.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        let start = config.get_int("hours", "start")?;
+        let end = config.get_int("hours", "end")?;
+        if start.is_none() && end.is_none() {
+            return Ok(None);
+        }
+        let default = BusinessHours::default();
+        BusinessHours::new(
+            start.unwrap_or(default.start as i64),
+            end.unwrap_or(default.end as i64),
+        )
+        .map(Some)
+    }
+}
+
+impl FromStr for BusinessHours {
+    type Err = String;
+
+    /// Parses `START-END` in whole hours, e.g. `9-17`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid business hours '{}', expected START-END like 9-17",
+                s
+            )
+        };
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let start = start.trim().parse().map_err(|_| invalid())?;
+        let end = end.trim().parse().map_err(|_| invalid())?;
+        BusinessHours::new(start, end)
+    }
+}
+
+impl fmt::Display for BusinessHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:00–{:02}:00", self.start, self.end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OffHours {
+    pub commits: usize,
+    pub weekend: usize,
+    /// Weekday commits outside business hours.
+    pub after_hours: usize,
+}
+
+impl std::ops::AddAssign for OffHours {
+    fn add_assign(&mut self, other: Self) {
+        self.commits += other.commits;
+        self.weekend += other.weekend;
+        self.after_hours += other.after_hours;
+    }
+}
+
+/// Classifies one commit.
+fn classify(commit: &CommitInfo, hours: BusinessHours) -> OffHours {
+    let local = commit.author_time + commit.author_offset_minutes as i64 * 60;
+    let hour = (local.rem_euclid(86_400) / 3_600) as u32;
+    let weekend = weekday(local.div_euclid(SECONDS_PER_DAY)) >= 5;
+    OffHours {
+        commits: 1,
+        weekend: usize::from(weekend),
+        after_hours: usize::from(!weekend && !(hours.start..hours.end).contains(&hour)),
+    }
+}
+
+/// Counts weekend and after-hours commits per contributor, most commits
+/// first.
+pub fn get_off_hours(commits: &[CommitInfo], hours: BusinessHours) -> Vec<(String, OffHours)> {
+    let mut counts: HashMap<String, OffHours> = HashMap::new();
+    for commit in commits {
+        *counts.entry(contributor_key(commit)).or_default() += classify(commit, hours);
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::days_from_civil;
+
+    #[test]
+    fn test_off_hours() {
+        // 2024-01-06 was a Saturday.
+        let saturday = days_from_civil(2024, 1, 6) * SECONDS_PER_DAY;
+        let monday = saturday + 2 * SECONDS_PER_DAY;
+        let commit = |name: &str, time: i64, offset_minutes: i32| CommitInfo {
+            author_name: name.to_string(),
+            author_time: time,
+            author_offset_minutes: offset_minutes,
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("a", saturday + 12 * 3_600, 0),
+            commit("a", monday + 10 * 3_600, 0),
+            // 17:30 UTC is 19:30 at +02:00.
+            commit("a", monday + 17 * 3_600 + 1_800, 120),
+            // Written before hours, committed during them by someone else.
+            CommitInfo {
+                time: monday + 10 * 3_600,
+                ..commit("b", monday + 8 * 3_600, 0)
+            },
+        ];
+        let hours: BusinessHours = "9-18".parse().unwrap();
+        assert_eq!(
+            vec![
+                (
+                    "a <>".to_string(),
+                    OffHours {
+                        commits: 3,
+                        weekend: 1,
+                        after_hours: 1
+                    }
+                ),
+                (
+                    "b <>".to_string(),
+                    OffHours {
+                        commits: 1,
+                        weekend: 0,
+                        after_hours: 1
+                    }
+                ),
+            ],
+            get_off_hours(&commits, hours)
+        );
+        assert!("18-9".parse::<BusinessHours>().is_err());
+        assert_eq!(
+            Ok(Some(BusinessHours { start: 8, end: 18 })),
+            BusinessHours::from_config(&Config::parse("[hours]\nstart = 8\n").unwrap())
+        );
+    }
+}
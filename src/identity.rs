@@ -0,0 +1,601 @@
+//! Contributor identity resolution.
+//!
+//! Author identities from `.mailmap` can still split one person across
+//! several name/email pairs. The optional fuzzy pass merges identities that
+//! share an email, share a normalized name, or have names a typo apart and
+//! addresses sharing their local part or organization domain, and rewrites
+//! commits to the identity of each group with the most commits.
+//!
+//! Identities can also be replaced by pseudonyms, keyed hashes that keep names
+//! and email addresses out of a report. Commit counts, dates and the kept
+//! organization domains can still point to a person, so a pseudonymized
+//! report is not safe to publish by itself.
+//!
+//! Which part of an identity tells contributors apart is configurable with
+//! [`IdentityKey`]: commits whose identities share the chosen key are
+//! rewritten to that key's most common identity.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{BuildHasher, Hasher, RandomState},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use crate::{
+    domains::{email_domain, is_personal_domain},
+    history::CommitInfo,
+};
+
+/// Host of pseudonymous emails whose original host names a single machine
+/// rather than an organization, such as `jane-macbook.local`.
+const ANONYMOUS_HOST: &str = "anonymous.invalid";
+
+/// A set of author identities resolved to one contributor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityGroup {
+    /// `(name, email)` the group is reported under.
+    pub canonical: (String, String),
+    /// Every `(name, email)` in the group with its commit count, most
+    /// commits first.
+    pub members: Vec<((String, String), usize)>,
+}
+
+/// Returns the `name <email>` key commits are counted under.
+pub fn contributor_key(commit: &CommitInfo) -> String {
+    format!("{} <{}>", commit.author_name, commit.author_email)
+}
+
+/// Returns the `name <email>` key of a commit's committer.
+pub fn committer_key(commit: &CommitInfo) -> String {
+    format!("{} <{}>", commit.committer_name, commit.committer_email)
+}
+
+/// Counts distinct contributors among the commits.
+pub fn count_contributors(commits: &[CommitInfo]) -> usize {
+    commits
+        .iter()
+        .map(contributor_key)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Lowercases a name and drops punctuation and extra whitespace, so
+/// "J. Doe" and "j doe" compare equal.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Whether two normalized names are similar enough to be the same person:
+/// equal, or one edit apart when both are long enough for a typo to be
+/// unambiguous.
+fn similar_names(a: &str, b: &str) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    a == b
+        || (a.chars().count() >= 6
+            && b.chars().count() >= 6
+            && a.chars().count().abs_diff(b.chars().count()) <= 1
+            && levenshtein(a, b) <= 1)
+}
+
+/// Parts of `email` that corroborate a typo between two names: its local
+/// part and, unless a personal provider's, its domain.
+fn corroborating_parts(email: &str) -> Vec<String> {
+    let email = email.trim().to_lowercase();
+    let mut parts = Vec::new();
+    if let Some((local, _)) = email.split_once('@')
+        && !local.is_empty()
+    {
+        parts.push(local.to_string());
+    }
+    if let Some(domain) = email_domain(&email)
+        && !is_personal_domain(&domain)
+    {
+        parts.push(format!("@{}", domain));
+    }
+    parts
+}
+
+fn find(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut i = i;
+    while parents[i] != root {
+        let next = parents[i];
+        parents[i] = root;
+        i = next;
+    }
+    root
+}
+
+/// Groups the author identities of the commits into contributors. Groups
+/// are ordered by total commits.
+pub fn resolve_identities(commits: &[CommitInfo]) -> Vec<IdentityGroup> {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for commit in commits {
+        *counts
+            .entry((commit.author_name.clone(), commit.author_email.clone()))
+            .or_default() += 1;
+    }
+    let mut identities: Vec<((String, String), usize)> = counts.into_iter().collect();
+    identities.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let names: Vec<String> = identities
+        .iter()
+        .map(|((n, _), _)| normalize_name(n))
+        .collect();
+    let mut parents: Vec<usize> = (0..identities.len()).collect();
+    let mut by_email: HashMap<String, usize> = HashMap::new();
+    for (i, ((_, email), _)) in identities.iter().enumerate() {
+        if email.is_empty() {
+            continue;
+        }
+        if let Some(&j) = by_email.get(&email.to_lowercase()) {
+            let (ri, rj) = (find(&mut parents, i), find(&mut parents, j));
+            parents[ri] = rj;
+        } else {
+            by_email.insert(email.to_lowercase(), i);
+        }
+    }
+    let mut by_name: HashMap<&str, usize> = HashMap::new();
+    for (i, name) in names.iter().enumerate() {
+        if name.is_empty() {
+            continue;
+        }
+        if let Some(&j) = by_name.get(name.as_str()) {
+            let (ri, rj) = (find(&mut parents, i), find(&mut parents, j));
+            parents[ri] = rj;
+        } else {
+            by_name.insert(name, i);
+        }
+    }
+    // Names a typo apart could be two people, so only those whose addresses
+    // share a part are compared, kept by that part and the name length a
+    // typo changes by at most one.
+    let mut candidates: HashMap<(String, usize), Vec<usize>> = HashMap::new();
+    for (i, ((_, email), _)) in identities.iter().enumerate() {
+        let length = names[i].chars().count();
+        if length < 6 {
+            continue;
+        }
+        for part in corroborating_parts(email) {
+            for other in length - 1..=length + 1 {
+                for &j in candidates.get(&(part.clone(), other)).into_iter().flatten() {
+                    if similar_names(&names[i], &names[j]) {
+                        let (ri, rj) = (find(&mut parents, i), find(&mut parents, j));
+                        parents[ri] = rj;
+                    }
+                }
+            }
+            candidates.entry((part, length)).or_default().push(i);
+        }
+    }
+
+    let mut groups: Vec<IdentityGroup> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    // Identities are sorted by commits, so the first member of every group
+    // is its most active identity.
+    for (i, identity) in identities.iter().enumerate() {
+        let root = find(&mut parents, i);
+        match group_of_root.get(&root) {
+            Some(&g) => groups[g].members.push(identity.clone()),
+            None => {
+                group_of_root.insert(root, groups.len());
+                groups.push(IdentityGroup {
+                    canonical: identity.0.clone(),
+                    members: vec![identity.clone()],
+                });
+            }
+        }
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g.members.iter().map(|(_, n)| n).sum::<usize>()));
+    groups
+}
+
+/// Rewrites commit authors and committers to the canonical identity of their
+/// group.
+pub fn apply_identities(commits: &mut [CommitInfo], groups: &[IdentityGroup]) {
+    let mut canonical: HashMap<&(String, String), &(String, String)> = HashMap::new();
+    for group in groups {
+        for (member, _) in &group.members {
+            canonical.insert(member, &group.canonical);
+        }
+    }
+    for commit in commits.iter_mut() {
+        let key = (commit.author_name.clone(), commit.author_email.clone());
+        if let Some((name, email)) = canonical.get(&key) {
+            commit.author_name = name.clone();
+            commit.author_email = email.clone();
+        }
+        let key = (
+            commit.committer_name.clone(),
+            commit.committer_email.clone(),
+        );
+        if let Some((name, email)) = canonical.get(&key) {
+            commit.committer_name = name.clone();
+            commit.committer_email = email.clone();
+        }
+    }
+}
+
+/// What tells two contributors apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdentityKey {
+    /// Name and email address, as recorded.
+    #[default]
+    NameEmail,
+    /// Email address, ignoring case, so renamed people stay one contributor.
+    Email,
+    /// Display name, so people committing from several addresses stay one
+    /// contributor.
+    Name,
+    /// Email domain, counting organizations rather than people.
+    EmailDomain,
+}
+
+impl IdentityKey {
+    fn key(&self, name: &str, email: &str) -> String {
+        match self {
+            IdentityKey::NameEmail => format!("{} <{}>", name, email),
+            IdentityKey::Email => email.to_lowercase(),
+            IdentityKey::Name => name.to_string(),
+            // Addresses without a domain only match themselves.
+            IdentityKey::EmailDomain => match email_domain(email) {
+                Some(domain) => format!("@{}", domain),
+                None => format!("{} <{}>", name, email),
+            },
+        }
+    }
+}
+
+impl FromStr for IdentityKey {
+    type Err = String;
+
+    /// Parses `name+email`, `email`, `name` or `email-domain`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name+email" => Ok(IdentityKey::NameEmail),
+            "email" => Ok(IdentityKey::Email),
+            "name" => Ok(IdentityKey::Name),
+            "email-domain" => Ok(IdentityKey::EmailDomain),
+            _ => Err(format!(
+                "invalid identity key '{}', expected name+email, email, name or email-domain",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for IdentityKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityKey::NameEmail => write!(f, "name+email"),
+            IdentityKey::Email => write!(f, "email"),
+            IdentityKey::Name => write!(f, "name"),
+            IdentityKey::EmailDomain => write!(f, "email-domain"),
+        }
+    }
+}
+
+/// Rewrites authors and committers so that identities sharing `key` become
+/// one. Each key is reported under its most common identity, or, for
+/// [`IdentityKey::EmailDomain`], under the domain itself.
+pub fn apply_identity_key(commits: &mut [CommitInfo], key: IdentityKey) {
+    if key == IdentityKey::NameEmail {
+        return;
+    }
+    let mut counts: HashMap<String, HashMap<(String, String), usize>> = HashMap::new();
+    for commit in commits.iter() {
+        for (name, email) in [
+            (&commit.author_name, &commit.author_email),
+            (&commit.committer_name, &commit.committer_email),
+        ] {
+            *counts
+                .entry(key.key(name, email))
+                .or_default()
+                .entry((name.clone(), email.clone()))
+                .or_default() += 1;
+        }
+    }
+    let canonical: HashMap<String, (String, String)> = counts
+        .into_iter()
+        .map(|(group, identities)| {
+            let domain = match key {
+                IdentityKey::EmailDomain => group.strip_prefix('@'),
+                _ => None,
+            };
+            let identity = match domain {
+                Some(domain) => (domain.to_string(), format!("*@{}", domain)),
+                None => identities
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                    .map(|(identity, _)| identity)
+                    .unwrap_or_default(),
+            };
+            (group, identity)
+        })
+        .collect();
+    for commit in commits.iter_mut() {
+        let (name, email) = &canonical[&key.key(&commit.author_name, &commit.author_email)];
+        commit.author_name = name.clone();
+        commit.author_email = email.clone();
+        let (name, email) = &canonical[&key.key(&commit.committer_name, &commit.committer_email)];
+        commit.committer_name = name.clone();
+        commit.committer_email = email.clone();
+    }
+}
+
+/// 64-bit FNV-1a, used because its output is stable across Rust releases.
+pub fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// SipHash-2-4 of `data` under the 128-bit `key`. Unlike [`fnv1a`], its
+/// output cannot be recomputed from a guessed input without the key.
+fn siphash(key: (u64, u64), data: &[u8]) -> u64 {
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        round(v);
+        round(v);
+        v[0] ^= m;
+    }
+
+    let mut v = [
+        key.0 ^ 0x736f_6d65_7073_6575,
+        key.1 ^ 0x646f_7261_6e64_6f6d,
+        key.0 ^ 0x6c79_6765_6e65_7261,
+        key.1 ^ 0x7465_6462_7974_6573,
+    ];
+    let chunks = data.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        compress(&mut v, u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let mut last = [0u8; 8];
+    last[..tail.len()].copy_from_slice(tail);
+    compress(&mut v, u64::from_le_bytes(last) | (data.len() as u64) << 56);
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// Key of the pseudonym hash, fixed for the rest of the run once used.
+static PSEUDONYM_KEY: OnceLock<(u64, u64)> = OnceLock::new();
+
+/// Derives the pseudonym key from `secret`, so runs sharing it give the same
+/// pseudonyms. Without one, each run draws a random key. Has no effect once
+/// a pseudonym was made.
+pub fn set_pseudonym_secret(secret: &str) {
+    let secret = secret.as_bytes();
+    let _ = PSEUDONYM_KEY.set((siphash((0, 0), secret), siphash((0, 1), secret)));
+}
+
+fn pseudonym_key() -> (u64, u64) {
+    *PSEUDONYM_KEY.get_or_init(|| {
+        // RandomState is seeded from the operating system's random source.
+        let random = || RandomState::new().build_hasher().finish();
+        (random(), random())
+    })
+}
+
+/// Returns the 64-bit pseudonym of `s`, ignoring case.
+pub fn pseudonym_id(s: &str) -> String {
+    format!(
+        "{:016x}",
+        siphash(pseudonym_key(), s.to_lowercase().as_bytes())
+    )
+}
+
+/// Returns the pseudonymous form of an email address. The local part is
+/// replaced by its [`pseudonym_id`]; organization domains are kept so domain
+/// and team reports stay meaningful.
+pub fn anonymize_email(email: &str) -> String {
+    let host = match email_domain(email) {
+        Some(domain)
+            if domain.contains('.')
+                && ![".local", ".lan", ".localdomain"]
+                    .iter()
+                    .any(|suffix| domain.ends_with(suffix)) =>
+        {
+            domain
+        }
+        _ => ANONYMOUS_HOST.to_string(),
+    };
+    format!("{}@{}", pseudonym_id(email.trim()), host)
+}
+
+/// Replaces author and committer identities with pseudonyms keyed from their
+/// email address, or from their name when there is none. Identities sharing
+/// an email address share a pseudonym.
+pub fn anonymize_identities(commits: &mut [CommitInfo]) {
+    for commit in commits {
+        anonymize_identity(&mut commit.author_name, &mut commit.author_email);
+        anonymize_identity(&mut commit.committer_name, &mut commit.committer_email);
+    }
+}
+
+/// Replaces one identity with its pseudonym, as [`anonymize_identities`]
+/// does, for identities read from outside the history walk.
+pub fn anonymize_identity(name: &mut String, email: &mut String) {
+    if email.is_empty() {
+        *name = format!("Contributor {}", pseudonym_id(name));
+    } else {
+        *name = format!("Contributor {}", pseudonym_id(email.trim()));
+        *email = anonymize_email(email);
+    }
+}
+
+/// Returns the `name <email>` key of `signature`, pseudonymized as
+/// [`anonymize_identity`] does when `anonymize` is set, for commits blame
+/// credits that are missing from the history.
+pub fn signature_key(signature: &git2::Signature<'_>, anonymize: bool) -> String {
+    let mut name = signature.name().unwrap_or("").to_string();
+    let mut email = signature.email().unwrap_or("").to_string();
+    if anonymize {
+        anonymize_identity(&mut name, &mut email);
+    }
+    format!("{} <{}>", name, email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(name: &str, email: &str) -> CommitInfo {
+        CommitInfo {
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_similar_names() {
+        assert!(similar_names(
+            &normalize_name("J. Doe"),
+            &normalize_name("j  doe")
+        ));
+        assert!(!similar_names("jane doe", "jane deo"));
+        assert!(similar_names("jane doe", "jane do"));
+        assert!(!similar_names("bob", "rob"));
+        assert_eq!(3, levenshtein("kitten", "sitting"));
+    }
+
+    #[test]
+    fn test_resolve_and_apply_identities() {
+        let mut commits = vec![
+            commit("Jane Doe", "jane@corp.com"),
+            commit("Jane Doe", "jane@corp.com"),
+            commit("jane doe", "jane@gmail.com"),
+            commit("JD", "JANE@corp.com"),
+            commit("Bob", "bob@corp.com"),
+        ];
+        let groups = resolve_identities(&commits);
+
+        assert_eq!(2, groups.len());
+        assert_eq!(
+            ("Jane Doe".to_string(), "jane@corp.com".to_string()),
+            groups[0].canonical
+        );
+        assert_eq!(3, groups[0].members.len());
+
+        apply_identities(&mut commits, &groups);
+        assert_eq!(2, count_contributors(&commits));
+        assert_eq!("jane@corp.com", commits[3].author_email);
+
+        // A typo is only trusted when the addresses back it up.
+        let commits = vec![
+            commit("John Smith", "john@corp.com"),
+            commit("Joan Smith", "joan@other.com"),
+            commit("Alice Smith", "alice@gmail.com"),
+            commit("Alice Smyth", "asmyth@gmail.com"),
+            commit("Pat Miller", "pat@corp.com"),
+            commit("Pat Millar", "pmillar@corp.com"),
+            commit("Chris Doe", "chris@home.example"),
+            commit("Chris Dole", "chris@work.example"),
+        ];
+        let mut groups: Vec<usize> = resolve_identities(&commits)
+            .iter()
+            .map(|g| g.members.len())
+            .collect();
+        groups.sort();
+        assert_eq!(vec![1, 1, 1, 1, 2, 2], groups);
+    }
+
+    #[test]
+    fn test_anonymize_identities() {
+        let mut commits = vec![
+            commit("Jane Doe", "jane@corp.com"),
+            commit("jane", "JANE@corp.com"),
+            commit("Bob", "bob@Bobs-MacBook.local"),
+            commit("Nobody", ""),
+        ];
+        anonymize_identities(&mut commits);
+
+        assert_eq!(commits[0].author_name, commits[1].author_name);
+        assert_eq!(anonymize_email("jane@corp.com"), commits[0].author_email);
+        assert!(commits[0].author_email.ends_with("@corp.com"));
+        assert!(!commits[0].author_email.contains("jane"));
+        assert!(commits[2].author_email.ends_with("@anonymous.invalid"));
+        assert!(commits[3].author_name.starts_with("Contributor "));
+        assert_eq!(3, count_contributors(&commits));
+    }
+
+    #[test]
+    fn test_siphash() {
+        // Reference vectors of SipHash-2-4 under the key 00 01 .. 0f.
+        let key = (0x0706_0504_0302_0100, 0x0f0e_0d0c_0b0a_0908);
+        assert_eq!(0x726f_db47_dd0e_0e31, siphash(key, b""));
+        let message: Vec<u8> = (0..15).collect();
+        assert_eq!(0xa129_ca61_49be_45e5, siphash(key, &message));
+        assert_ne!(siphash(key, b"jane"), siphash((key.0, key.1 + 1), b"jane"));
+        assert_eq!(16, pseudonym_id("jane@corp.com").len());
+    }
+
+    #[test]
+    fn test_identity_key() {
+        let commits = vec![
+            commit("Jane Doe", "jane@corp.com"),
+            commit("Jane Doe", "jane@corp.com"),
+            commit("Jane D", "Jane@corp.com"),
+            commit("Bob", "bob@corp.com"),
+        ];
+
+        let mut by_email = commits.clone();
+        apply_identity_key(&mut by_email, IdentityKey::Email);
+        assert_eq!(2, count_contributors(&by_email));
+        assert_eq!("Jane Doe <jane@corp.com>", contributor_key(&by_email[2]));
+
+        let mut by_domain = commits.clone();
+        apply_identity_key(&mut by_domain, IdentityKey::EmailDomain);
+        assert_eq!("corp.com <*@corp.com>", contributor_key(&by_domain[3]));
+        assert_eq!(1, count_contributors(&by_domain));
+        assert_eq!(Ok(IdentityKey::Name), "name".parse());
+    }
+}
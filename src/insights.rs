@@ -0,0 +1,217 @@
+//! Notable findings with the subcommand to dig deeper, for casual users.
+//!
+//! Each rule checks one metric against a threshold. Findings are scored by
+//! how far past their threshold the metric is, so the most pressing ones come
+//! first.
+
+use std::collections::HashMap;
+
+use git2::Oid;
+
+use crate::{
+    ai::AiEvidence,
+    contributors::get_active_contributors,
+    date::SECONDS_PER_DAY,
+    history::CommitInfo,
+    identity::{contributor_key, resolve_identities},
+    message::trailers,
+    report::percent,
+    stats::format_size,
+    tree::TreeFile,
+};
+
+/// Share of last year's commits by one contributor worth pointing out.
+const CONCENTRATION_PERCENT: f64 = 60.0;
+/// Drop in commits between the last two 90-day windows worth pointing out.
+const ACTIVITY_DROP_PERCENT: f64 = 50.0;
+const LARGE_FILE_BYTES: u64 = 10 << 20;
+const AI_SHARE_PERCENT: f64 = 20.0;
+/// Share of contributors from the last year still active in the last 30 days
+/// below which retention is worth a look.
+const ACTIVE_SHARE_PERCENT: f64 = 25.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insight {
+    /// How far past its threshold the metric is; 1.0 is at the threshold.
+    pub score: f64,
+    pub finding: String,
+    /// Command to dig deeper, e.g. `gno filesizes`.
+    pub command: &'static str,
+}
+
+impl Insight {
+    /// Returns the subcommand the insight suggests.
+    pub fn subcommand(&self) -> &str {
+        self.command.split_whitespace().nth(1).unwrap_or("")
+    }
+}
+
+/// Derives insights from the history, the AI tools its commits are
+/// attributed to, and the files at HEAD, most pressing first.
+pub fn get_insights(
+    commits: &[CommitInfo],
+    ai_tools: &HashMap<Oid, (&'static str, AiEvidence)>,
+    head_files: &[TreeFile],
+    now: i64,
+) -> Vec<Insight> {
+    let mut insights = Vec::new();
+    let days_ago = |days: i64| now - days * SECONDS_PER_DAY;
+
+    let last_year: Vec<&CommitInfo> = commits.iter().filter(|c| c.time >= days_ago(365)).collect();
+    let mut authors: HashMap<String, usize> = HashMap::new();
+    for commit in &last_year {
+        *authors.entry(contributor_key(commit)).or_default() += 1;
+    }
+    if let Some((author, count)) = authors
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        && last_year.len() >= 20
+    {
+        let share = percent(*count, last_year.len());
+        if share >= CONCENTRATION_PERCENT {
+            insights.push(Insight {
+                score: share / CONCENTRATION_PERCENT,
+                finding: format!("{} made {:.0}% of last year's commits", author, share),
+                command: "gno owners",
+            });
+        }
+    }
+
+    let recent = commits.iter().filter(|c| c.time >= days_ago(90)).count();
+    let previous = commits
+        .iter()
+        .filter(|c| c.time >= days_ago(180) && c.time < days_ago(90))
+        .count();
+    if previous >= 10 {
+        let drop = 100.0 - percent(recent, previous);
+        if drop >= ACTIVITY_DROP_PERCENT {
+            insights.push(Insight {
+                score: drop / ACTIVITY_DROP_PERCENT,
+                finding: format!("Commits fell {:.0}% over the last 90 days", drop),
+                command: "gno commits --bucket month",
+            });
+        }
+    }
+
+    let large = head_files
+        .iter()
+        .filter(|f| f.size >= LARGE_FILE_BYTES)
+        .count();
+    if large > 0 {
+        insights.push(Insight {
+            score: 1.0 + large as f64 / 10.0,
+            finding: match large {
+                1 => format!(
+                    "1 file at HEAD is {} or larger",
+                    format_size(LARGE_FILE_BYTES)
+                ),
+                n => format!(
+                    "{} files at HEAD are {} or larger",
+                    n,
+                    format_size(LARGE_FILE_BYTES)
+                ),
+            },
+            command: "gno filesizes",
+        });
+    }
+
+    let ai = commits
+        .iter()
+        .filter(|c| ai_tools.contains_key(&c.oid))
+        .count();
+    let ai_share = percent(ai, commits.len());
+    if ai_share >= AI_SHARE_PERCENT {
+        insights.push(Insight {
+            score: ai_share / AI_SHARE_PERCENT,
+            finding: format!("{:.0}% of commits are attributed to AI tools", ai_share),
+            command: "gno ai",
+        });
+    }
+
+    let split = resolve_identities(commits)
+        .iter()
+        .filter(|g| g.members.len() > 1)
+        .count();
+    if split > 0 {
+        insights.push(Insight {
+            score: 1.0 + split as f64 / 10.0,
+            finding: match split {
+                1 => "1 contributor commits under several identities".to_string(),
+                n => format!("{} contributors commit under several identities", n),
+            },
+            command: "gno contributors --show-identities",
+        });
+    }
+
+    let signed = commits
+        .iter()
+        .filter(|c| {
+            trailers(&c.message)
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case("Signed-off-by"))
+        })
+        .count();
+    let signed_share = percent(signed, commits.len());
+    // Only projects that mostly sign off care about the commits that don't.
+    if (50.0..100.0).contains(&signed_share) {
+        insights.push(Insight {
+            score: 1.0 + (100.0 - signed_share) / 10.0,
+            finding: format!(
+                "{:.0}% of commits lack a Signed-off-by trailer",
+                100.0 - signed_share
+            ),
+            command: "gno signoffs",
+        });
+    }
+
+    if let [month, year] = get_active_contributors(commits, now, &[30, 365])[..]
+        && year >= 8
+    {
+        let share = percent(month, year);
+        if share < ACTIVE_SHARE_PERCENT {
+            insights.push(Insight {
+                score: ACTIVE_SHARE_PERCENT / share.max(1.0),
+                finding: format!(
+                    "Only {} of last year's {} contributors were active this month",
+                    month, year
+                ),
+                command: "gno retention",
+            });
+        }
+    }
+
+    insights.sort_by(|a, b| b.score.total_cmp(&a.score));
+    insights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insights() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let commit = |name: &str, days_ago: i64| CommitInfo {
+            author_name: name.to_string(),
+            author_email: format!("{}@x.com", name),
+            time: now - days_ago * SECONDS_PER_DAY,
+            ..Default::default()
+        };
+        let mut commits: Vec<CommitInfo> = (0..20).map(|i| commit("a", 100 + i)).collect();
+        commits.push(commit("b", 1));
+        let files = vec![TreeFile {
+            path: "big.iso".to_string(),
+            oid: Oid::zero(),
+            size: 20 << 20,
+        }];
+
+        let insights = get_insights(&commits, &HashMap::new(), &files, now);
+        let commands: Vec<&str> = insights.iter().map(|i| i.command).collect();
+        assert_eq!(
+            vec!["gno commits --bucket month", "gno owners", "gno filesizes"],
+            commands
+        );
+        assert_eq!("commits", insights[0].subcommand());
+        assert!(get_insights(&[], &HashMap::new(), &[], now).is_empty());
+    }
+}
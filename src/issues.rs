@@ -0,0 +1,145 @@
+//! Issue tracker references in commit messages.
+//!
+//! A pattern is the prefix written before an issue number, such as `#` or
+//! `GH-`. A `*` in the prefix stands for an uppercase project key, so `*-`
+//! matches JIRA-style references like `PROJ-456`, and also names such as
+//! `UTF-8`. References must stand on their own: `abc#1` and `#12b` are not
+//! references.
+
+use std::collections::HashMap;
+
+use crate::{config::Config, history::CommitInfo};
+
+/// Patterns used when neither `--pattern` nor `issues.patterns` is given.
+pub const DEFAULT_PATTERNS: [&str; 3] = ["#", "GH-", "*-"];
+
+/// Reads the `patterns` list of the `[issues]` section.
+pub fn patterns_from_config(config: &Config) -> Result<Option<Vec<String>>, String> {
+    let patterns = config.get_str_array("issues", "patterns")?;
+    if patterns
+        .as_ref()
+        .is_some_and(|p| p.iter().any(|p| p.is_empty()))
+    {
+        return Err("issues.patterns must not contain empty patterns".to_string());
+    }
+    Ok(patterns)
+}
+
+/// Returns the length of a project key such as `PROJ` at the start of
+/// `text`: an uppercase letter followed by uppercase letters, digits or
+/// underscores.
+fn project_key_len(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return 0;
+    }
+    text.find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+        .unwrap_or(text.len())
+}
+
+/// Returns the length of the reference matching `pattern` at the start of
+/// `text`, if any.
+fn match_reference(pattern: &str, text: &str) -> Option<usize> {
+    let (before, after) = pattern.split_once('*').unwrap_or((pattern, ""));
+    let mut rest = text.strip_prefix(before)?;
+    if pattern.contains('*') {
+        let key = project_key_len(rest);
+        if key < 2 {
+            return None;
+        }
+        rest = rest[key..].strip_prefix(after)?;
+    }
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    if digits == 0 || rest[digits..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(text.len() - rest.len() + digits)
+}
+
+/// Returns the distinct references in `message`, in order of appearance.
+pub fn find_references(message: &str, patterns: &[String]) -> Vec<String> {
+    let mut references: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for (i, ch) in message.char_indices() {
+        let standalone = !previous.is_some_and(|p| p.is_alphanumeric() || p == '_');
+        previous = Some(ch);
+        if !standalone {
+            continue;
+        }
+        let text = &message[i..];
+        if let Some(len) = patterns.iter().find_map(|p| match_reference(p, text))
+            && !references.iter().any(|r| r == &text[..len])
+        {
+            references.push(text[..len].to_string());
+        }
+    }
+    references
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueStats {
+    pub commits: usize,
+    /// Commits referencing at least one issue.
+    pub referencing: usize,
+    /// Commits per referenced issue, most first.
+    pub issues: Vec<(String, usize)>,
+}
+
+/// Counts the commits referencing issues and the commits per issue.
+pub fn get_issue_stats(commits: &[CommitInfo], patterns: &[String]) -> IssueStats {
+    let mut stats = IssueStats {
+        commits: commits.len(),
+        ..Default::default()
+    };
+    let mut issues: HashMap<String, usize> = HashMap::new();
+    for commit in commits {
+        let references = find_references(&commit.message, patterns);
+        stats.referencing += usize::from(!references.is_empty());
+        for reference in references {
+            *issues.entry(reference).or_default() += 1;
+        }
+    }
+    stats.issues = issues.into_iter().collect();
+    stats
+        .issues
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_references() {
+        let patterns: Vec<String> = DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            vec!["#12", "GH-3", "PROJ-456"],
+            find_references("Fix #12 and GH-3 (PROJ-456, see #12)", &patterns)
+        );
+        assert!(find_references("abc#1, #12b, A-1 and issue #", &patterns).is_empty());
+        assert_eq!(
+            vec!["bug 7"],
+            find_references("Closes bug 7", &["bug ".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_issue_stats() {
+        let patterns = vec!["#".to_string()];
+        let commits: Vec<CommitInfo> = ["Fix #1", "Fix #1 again, #1", "Refs #2", "Tidy"]
+            .iter()
+            .map(|m| CommitInfo {
+                message: m.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let stats = get_issue_stats(&commits, &patterns);
+        assert_eq!((4, 3), (stats.commits, stats.referencing));
+        assert_eq!(
+            vec![("#1".to_string(), 2), ("#2".to_string(), 1)],
+            stats.issues
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! How commits landed on protected branches.
+//!
+//! A branch's first-parent chain lists the commits that were added to it
+//! directly, one per update. Work that went through review shows up in that
+//! chain as a merge commit, or as a squash or rebase made by the forge, which
+//! leaves a `(#123)` suffix, a review trailer or its own committer identity.
+//! Any other commit on the chain was most likely pushed straight to the
+//! branch. Root commits count as direct pushes too.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{Oid, Repository};
+
+use crate::{
+    history::{CommitInfo, is_missing},
+    identity::contributor_key,
+    message::{subject, trailers},
+};
+
+/// Trailers left by review tools such as Gerrit, Phabricator and ghstack.
+const REVIEW_TRAILERS: [&str; 5] = [
+    "Reviewed-on",
+    "Reviewed-by",
+    "Approved-by",
+    "Differential Revision",
+    "Pull Request resolved",
+];
+
+/// Committer names forges use when they squash or rebase a pull request.
+const FORGE_COMMITTERS: [&str; 3] = ["GitHub", "GitLab", "Gitea"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Landing {
+    Merge,
+    /// A squash or rebase merge made through a forge or review tool.
+    Squash,
+    Direct,
+}
+
+/// Returns whether a subject ends in a pull request number such as `(#123)`.
+fn has_pull_request_suffix(subject: &str) -> bool {
+    subject
+        .strip_suffix(')')
+        .and_then(|s| s.rsplit_once("(#"))
+        .is_some_and(|(_, number)| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Classifies how a commit on a first-parent chain got there.
+pub fn classify_landing(commit: &CommitInfo) -> Landing {
+    if commit.is_merge() {
+        return Landing::Merge;
+    }
+    let reviewed = has_pull_request_suffix(subject(&commit.message))
+        || trailers(&commit.message)
+            .iter()
+            .any(|(key, _)| REVIEW_TRAILERS.iter().any(|r| key.eq_ignore_ascii_case(r)))
+        || FORGE_COMMITTERS.contains(&commit.committer_name.as_str())
+        || commit.committer_email.ends_with("noreply@github.com");
+    if reviewed {
+        Landing::Squash
+    } else {
+        Landing::Direct
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LandingStats {
+    /// Commits on the first-parent chains of the branches.
+    pub commits: usize,
+    pub merges: usize,
+    pub squashes: usize,
+    pub direct: usize,
+    /// Direct pushes and first-parent commits per contributor, most direct
+    /// pushes first.
+    pub authors: Vec<(String, usize, usize)>,
+}
+
+/// Returns the first-parent chain from `tip` down to the root. The chain
+/// ends early at a commit that has gone missing.
+pub fn first_parent_chain(repo: &Repository, tip: Oid) -> Result<Vec<Oid>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    match revwalk.push(tip) {
+        Ok(()) => {}
+        Err(e) if is_missing(&e) => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    }
+    revwalk.simplify_first_parent()?;
+    let mut chain = Vec::new();
+    for oid in revwalk {
+        match oid {
+            Ok(oid) => chain.push(oid),
+            Err(e) if is_missing(&e) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(chain)
+}
+
+/// Classifies the commits on the first-parent chains of `tips`, each commit
+/// once. Commits missing from `commits`, such as those filtered out, are
+/// skipped. Landings are classified on the identities in `recorded`, as
+/// `--anonymize` rewrites the forges' committer identities in `commits`.
+pub fn get_landing_stats(
+    repo: &Repository,
+    tips: &[Oid],
+    commits: &[CommitInfo],
+    recorded: &[CommitInfo],
+) -> Result<LandingStats, git2::Error> {
+    let by_oid: HashMap<Oid, &CommitInfo> = commits.iter().map(|c| (c.oid, c)).collect();
+    let recorded: HashMap<Oid, &CommitInfo> = recorded.iter().map(|c| (c.oid, c)).collect();
+    let mut seen = HashSet::new();
+    let mut stats = LandingStats::default();
+    let mut authors: HashMap<String, (usize, usize)> = HashMap::new();
+    for tip in tips {
+        for oid in first_parent_chain(repo, *tip)? {
+            let Some(commit) = by_oid.get(&oid) else {
+                continue;
+            };
+            if !seen.insert(oid) {
+                continue;
+            }
+            let landing = classify_landing(recorded.get(&oid).unwrap_or(commit));
+            stats.commits += 1;
+            let author = authors.entry(contributor_key(commit)).or_default();
+            author.1 += 1;
+            match landing {
+                Landing::Merge => stats.merges += 1,
+                Landing::Squash => stats.squashes += 1,
+                Landing::Direct => {
+                    stats.direct += 1;
+                    author.0 += 1;
+                }
+            }
+        }
+    }
+    stats.authors = authors
+        .into_iter()
+        .map(|(author, (direct, total))| (author, direct, total))
+        .collect();
+    stats
+        .authors
+        .sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, identity::anonymize_identities, progress::Progress,
+        testutil::TestRepo,
+    };
+
+    #[test]
+    fn test_landing_stats() {
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", 1, "init", &[("a.txt", "1")]);
+        t.commit_as("B", "b@x.com", 2, "Add parser (#12)", &[("a.txt", "2")]);
+        t.commit_as(
+            "B",
+            "b@x.com",
+            3,
+            "Fix parser\n\nReviewed-by: A <a@x.com>\n",
+            &[("a.txt", "3")],
+        );
+        let tip = t.commit_as("A", "a@x.com", 4, "hotfix", &[("a.txt", "4")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let stats = get_landing_stats(&t.repo, &[tip, tip], &commits, &commits).unwrap();
+        assert_eq!(
+            (4, 0, 2, 2),
+            (stats.commits, stats.merges, stats.squashes, stats.direct)
+        );
+        assert_eq!(
+            vec![
+                ("A <a@x.com>".to_string(), 2, 2),
+                ("B <b@x.com>".to_string(), 0, 2)
+            ],
+            stats.authors
+        );
+        assert!(!has_pull_request_suffix("Bump to (#)"));
+    }
+
+    #[test]
+    fn test_landing_stats_anonymized() {
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", 1, "init", &[("a.txt", "1")]);
+        let tip = t.commit_as(
+            "GitHub",
+            "noreply@github.com",
+            2,
+            "Add parser",
+            &[("a.txt", "2")],
+        );
+
+        let recorded = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let mut commits = recorded.clone();
+        anonymize_identities(&mut commits);
+        let stats = get_landing_stats(&t.repo, &[tip], &commits, &recorded).unwrap();
+        assert_eq!((1, 1), (stats.squashes, stats.direct));
+        assert!(
+            stats
+                .authors
+                .iter()
+                .all(|(a, _, _)| a.starts_with("Contributor "))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Languages by file extension, and filtering of files by language.
+//!
+//! The built-in languages can be extended and overridden by a `[languages]`
+//! config section mapping language names to extensions:
+//!
+//! ```toml
+//! [languages]
+//! starlark = ["bzl", "star"]
+//! c = ["c"]
+//! ```
+//!
+//! A configured language claims its extensions before the built-in ones, so
+//! the example counts `.h` files as neither C nor anything else.
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{config::Config, tree::TreeFile};
+
+/// Language name for files no language claims.
+pub const OTHER: &str = "(other)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,
+    /// Lowercase extensions without the leading dot.
+    pub extensions: &'static [&'static str],
+}
+
+const fn language(name: &'static str, extensions: &'static [&'static str]) -> Language {
+    Language { name, extensions }
+}
+
+pub const LANGUAGES: &[Language] = &[
+    language("c", &["c", "h"]),
+    language("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    language("csharp", &["cs"]),
+    language("css", &["css", "scss", "sass", "less"]),
+    language("go", &["go"]),
+    language("html", &["html", "htm"]),
+    language("java", &["java"]),
+    language("javascript", &["js", "mjs", "cjs", "jsx"]),
+    language("json", &["json"]),
+    language("kotlin", &["kt", "kts"]),
+    language("markdown", &["md", "markdown"]),
+    language("php", &["php"]),
+    language("python", &["py", "pyi"]),
+    language("ruby", &["rb"]),
+    language("rust", &["rs"]),
+    language("shell", &["sh", "bash", "zsh"]),
+    language("swift", &["swift"]),
+    language("toml", &["toml"]),
+    language("typescript", &["ts", "tsx"]),
+    language("yaml", &["yml", "yaml"]),
+];
+
+/// Returns the lowercase extension of `path`, without the dot.
+pub fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+}
+
+/// Parses a language name such as `rust`, ignoring case.
+pub fn parse_language(s: &str) -> Result<Language, String> {
+    LANGUAGES
+        .iter()
+        .find(|l| l.name.eq_ignore_ascii_case(s.trim()))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = LANGUAGES.iter().map(|l| l.name).collect();
+            format!(
+                "unknown language '{}', expected one of {}",
+                s,
+                names.join(", ")
+            )
+        })
+}
+
+/// Parses an extension given as `.rs` or `rs`.
+pub fn parse_extension(s: &str) -> Result<String, String> {
+    let extension = s.trim().trim_start_matches('.').to_lowercase();
+    if extension.is_empty() {
+        return Err(format!("invalid extension '{}'", s));
+    }
+    Ok(extension)
+}
+
+/// Extensions per language, configured languages first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageMap {
+    languages: Vec<(String, Vec<String>)>,
+}
+
+impl LanguageMap {
+    /// Reads the `[languages]` section: a configured language replaces the
+    /// built-in language of the same name.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut languages = Vec::new();
+        for (name, extensions) in config.section("languages") {
+            let extensions = extensions.as_str_array().ok_or_else(|| {
+                format!("config key languages.{} must be an array of strings", name)
+            })?;
+            let extensions = extensions
+                .iter()
+                .map(|e| parse_extension(e))
+                .collect::<Result<_, _>>()?;
+            languages.push((name.clone(), extensions));
+        }
+        for language in LANGUAGES {
+            if !languages.iter().any(|(name, _)| name == language.name) {
+                languages.push((
+                    language.name.to_string(),
+                    language.extensions.iter().map(|e| e.to_string()).collect(),
+                ));
+            }
+        }
+        Ok(LanguageMap { languages })
+    }
+
+    /// Returns the language claiming the extension of `path`.
+    pub fn language_of(&self, path: &str) -> Option<&str> {
+        let extension = extension(path)?;
+        self.languages
+            .iter()
+            .find(|(_, extensions)| extensions.contains(&extension))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageShare {
+    pub language: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Sums the files and bytes of `files` per language, most bytes first.
+/// Files no language claims are summed as [`OTHER`].
+pub fn get_language_breakdown(files: &[TreeFile], map: &LanguageMap) -> Vec<LanguageShare> {
+    let mut shares: HashMap<&str, (usize, u64)> = HashMap::new();
+    for file in files {
+        let share = shares
+            .entry(map.language_of(&file.path).unwrap_or(OTHER))
+            .or_default();
+        share.0 += 1;
+        share.1 += file.size;
+    }
+    let mut shares: Vec<LanguageShare> = shares
+        .into_iter()
+        .map(|(language, (files, bytes))| LanguageShare {
+            language: language.to_string(),
+            files,
+            bytes,
+        })
+        .collect();
+    shares.sort_by(|a, b| {
+        b.bytes
+            .cmp(&a.bytes)
+            .then(b.files.cmp(&a.files))
+            .then(a.language.cmp(&b.language))
+    });
+    shares
+}
+
+/// The files a report is restricted to. An empty filter matches every file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathFilter {
+    extensions: Vec<String>,
+}
+
+impl PathFilter {
+    /// Matches files with any of the lowercase `extensions`.
+    pub fn new(mut extensions: Vec<String>) -> Self {
+        extensions.sort();
+        extensions.dedup();
+        PathFilter { extensions }
+    }
+
+    /// Builds the filter from `--lang` and `--ext`.
+    pub fn from_matches(matches: &clap::ArgMatches) -> Self {
+        PathFilter::new(
+            matches
+                .get_many::<Language>("lang")
+                .into_iter()
+                .flatten()
+                .flat_map(|l| l.extensions.iter().map(|e| e.to_string()))
+                .chain(
+                    matches
+                        .get_many::<String>("ext")
+                        .into_iter()
+                        .flatten()
+                        .cloned(),
+                )
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.is_empty() || extension(path).is_some_and(|e| self.extensions.contains(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_filter() {
+        let filter = PathFilter::new(vec!["toml".to_string(), "rs".to_string()]);
+        assert!(filter.matches("src/main.RS"));
+        assert!(filter.matches("Cargo.toml"));
+        assert!(!filter.matches("README.md"));
+        assert!(!filter.matches("Makefile"));
+        assert!(PathFilter::default().matches("Makefile"));
+
+        assert_eq!(Ok("rs".to_string()), parse_extension(".RS"));
+        assert_eq!("rust", parse_language("Rust").unwrap().name);
+        assert!(parse_language("klingon").is_err());
+    }
+
+    #[test]
+    fn test_language_breakdown() {
+        let config = Config::parse("[languages]\nstarlark = [\"bzl\"]\nc = [\".c\"]\n").unwrap();
+        let map = LanguageMap::from_config(&config).unwrap();
+        let file = |path: &str, size: u64| TreeFile {
+            path: path.to_string(),
+            oid: git2::Oid::zero(),
+            size,
+        };
+        let files = vec![
+            file("main.c", 10),
+            file("main.h", 5),
+            file("BUILD.bzl", 20),
+            file("lib.rs", 30),
+            file("src/util.rs", 30),
+        ];
+        assert_eq!(
+            vec![
+                ("rust", 2, 60),
+                ("starlark", 1, 20),
+                ("c", 1, 10),
+                (OTHER, 1, 5)
+            ],
+            get_language_breakdown(&files, &map)
+                .iter()
+                .map(|s| (s.language.as_str(), s.files, s.bytes))
+                .collect::<Vec<_>>()
+        );
+    }
+}
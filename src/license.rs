@@ -0,0 +1,202 @@
+//! License and notice files at the root of HEAD.
+//!
+//! License files are named like `LICENSE`, `LICENCE`, `COPYING` or
+//! `UNLICENSE`, optionally with a suffix such as `LICENSE-MIT` and a text
+//! extension such as `LICENSE.md`, so source files like `license.go` are not
+//! license files. Their license is identified by an
+//! `SPDX-License-Identifier` line, or else by phrases only the common
+//! licenses' texts contain. Notice files, named like `NOTICE`, are listed
+//! without a license.
+
+use git2::Repository;
+
+use crate::tree::TreeFile;
+
+/// Base names of license files, uppercase.
+const LICENSE_NAMES: [&str; 4] = ["LICENSE", "LICENCE", "COPYING", "UNLICENSE"];
+
+/// Base name of notice files, uppercase.
+const NOTICE_NAME: &str = "NOTICE";
+
+/// Extensions of license and notice files, uppercase.
+const TEXT_EXTENSIONS: [&str; 3] = ["MD", "TXT", "RST"];
+
+/// Characters at the start of a license text its first phrase must lie
+/// within. License texts mention other licenses further down, as the GPL
+/// does the LGPL and AGPL.
+const HEAD_CHARS: usize = 500;
+
+/// `(SPDX identifier, phrases all found in the text)`, most specific first.
+/// Phrases are uppercase with single spaces; the first must lie within
+/// [`HEAD_CHARS`].
+const LICENSE_PHRASES: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["GNU AFFERO GENERAL PUBLIC LICENSE VERSION 3"]),
+    ("LGPL-3.0", &["GNU LESSER GENERAL PUBLIC LICENSE VERSION 3"]),
+    (
+        "LGPL-2.1",
+        &["GNU LESSER GENERAL PUBLIC LICENSE VERSION 2.1"],
+    ),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE VERSION 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE VERSION 2,"]),
+    ("Apache-2.0", &["APACHE LICENSE VERSION 2.0"]),
+    ("MPL-2.0", &["MOZILLA PUBLIC LICENSE VERSION 2.0"]),
+    ("EPL-2.0", &["ECLIPSE PUBLIC LICENSE - V 2.0"]),
+    ("BSL-1.0", &["BOOST SOFTWARE LICENSE - VERSION 1.0"]),
+    ("CC0-1.0", &["CC0 1.0 UNIVERSAL"]),
+    (
+        "Unlicense",
+        &["THIS IS FREE AND UNENCUMBERED SOFTWARE RELEASED INTO THE PUBLIC DOMAIN"],
+    ),
+    (
+        "BSD-3-Clause",
+        &[
+            "REDISTRIBUTION AND USE IN SOURCE AND BINARY FORMS",
+            "NEITHER THE NAME OF",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["REDISTRIBUTION AND USE IN SOURCE AND BINARY FORMS"],
+    ),
+    (
+        "ISC",
+        &["PERMISSION TO USE, COPY, MODIFY, AND/OR DISTRIBUTE THIS SOFTWARE FOR ANY PURPOSE"],
+    ),
+    (
+        "MIT",
+        &["PERMISSION IS HEREBY GRANTED, FREE OF CHARGE, TO ANY PERSON"],
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseFile {
+    pub path: String,
+    pub notice: bool,
+    /// SPDX identifier of the license, if recognized.
+    pub spdx: Option<String>,
+}
+
+/// Identifies the license of `text`.
+pub fn identify_license(text: &str) -> Option<String> {
+    for line in text.lines().take(20) {
+        if let Some((_, id)) = line.split_once("SPDX-License-Identifier:") {
+            let id = id.trim().trim_end_matches("*/").trim();
+            if !id.is_empty() {
+                return Some(id.to_string());
+            }
+        }
+    }
+    let normalized = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase();
+    let head: String = normalized.chars().take(HEAD_CHARS).collect();
+    LICENSE_PHRASES
+        .iter()
+        .find(|(_, phrases)| {
+            head.contains(phrases[0]) && phrases[1..].iter().all(|p| normalized.contains(p))
+        })
+        .map(|(id, _)| id.to_string())
+}
+
+/// Returns whether a file named `name` is a license or notice file, as
+/// `Some(notice)`.
+fn classify(name: &str) -> Option<bool> {
+    let upper = name.to_uppercase();
+    let stem = match upper.rsplit_once('.') {
+        Some((stem, extension)) if TEXT_EXTENSIONS.contains(&extension) => stem,
+        Some(_) => return None,
+        None => upper.as_str(),
+    };
+    let base = stem.split(['.', '-', '_']).next().unwrap_or(stem);
+    if LICENSE_NAMES.contains(&base) {
+        Some(false)
+    } else if base == NOTICE_NAME {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Finds the license and notice files among the root-level `files` and
+/// identifies their licenses.
+pub fn get_license_files(
+    repo: &Repository,
+    files: &[TreeFile],
+) -> Result<Vec<LicenseFile>, git2::Error> {
+    let mut licenses = Vec::new();
+    for file in files.iter().filter(|f| !f.path.contains('/')) {
+        let Some(notice) = classify(&file.path) else {
+            continue;
+        };
+        let spdx = if notice {
+            None
+        } else {
+            identify_license(&String::from_utf8_lossy(
+                repo.find_blob(file.oid)?.content(),
+            ))
+        };
+        licenses.push(LicenseFile {
+            path: file.path.clone(),
+            notice,
+            spdx,
+        });
+    }
+    Ok(licenses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testutil::TestRepo, tree::get_head_files};
+
+    #[test]
+    fn test_license_files() {
+        let t = TestRepo::new();
+        t.commit(
+            "add",
+            &[
+                (
+                    "LICENSE-MIT",
+                    "MIT License\n\nPermission is hereby granted, free of charge,\nto any person",
+                ),
+                (
+                    "LICENSE-APACHE",
+                    "                Apache License\n          Version 2.0, January 2004",
+                ),
+                ("COPYING.txt", "All rights reserved."),
+                ("NOTICE", "Copyright"),
+                ("docs/LICENSE", "MIT"),
+                ("licenses.rs", ""),
+                ("license.go", ""),
+                ("license_test.go", ""),
+                ("NOTICE.md", "Copyright"),
+            ],
+        );
+        let files = get_head_files(&t.repo, None).unwrap();
+        let licenses: Vec<(String, bool, Option<String>)> = get_license_files(&t.repo, &files)
+            .unwrap()
+            .into_iter()
+            .map(|l| (l.path, l.notice, l.spdx))
+            .collect();
+        assert_eq!(
+            vec![
+                ("COPYING.txt".to_string(), false, None),
+                (
+                    "LICENSE-APACHE".to_string(),
+                    false,
+                    Some("Apache-2.0".to_string())
+                ),
+                ("LICENSE-MIT".to_string(), false, Some("MIT".to_string())),
+                ("NOTICE".to_string(), true, None),
+                ("NOTICE.md".to_string(), true, None),
+            ],
+            licenses
+        );
+        assert_eq!(
+            Some("MIT OR Apache-2.0".to_string()),
+            identify_license("// SPDX-License-Identifier: MIT OR Apache-2.0\n")
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! How long files live before they are deleted.
+//!
+//! A file is born when a non-merge commit adds it and dies when one deletes
+//! it, following renames detected between each commit and its first parent,
+//! so a moved file lives on. Paths are tracked per line of history, replayed
+//! parents first, so what one branch does to a path leaves the others'
+//! files alone, and merges carry in the files they merge. A path added again
+//! after its deletion is a new file. Lifetimes are those of deleted files,
+//! each counted at its first deletion; files still alive have not finished
+//! theirs.
+
+use std::collections::{BTreeMap, HashMap};
+
+use git2::{Delta, DiffFindOptions, Repository};
+
+use crate::{
+    bucket::Bucketing,
+    churn::diff_commit,
+    history::{CommitInfo, replay_history},
+    progress::Progress,
+    stats::percentile,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeriodLifetimes {
+    pub created: usize,
+    pub deleted: usize,
+    /// Files created by the end of the period and not yet deleted.
+    pub alive: usize,
+}
+
+impl PeriodLifetimes {
+    /// Share of the files alive during the period that were deleted in it.
+    pub fn deletion_rate(&self) -> f64 {
+        let existed = self.alive + self.deleted;
+        if existed == 0 {
+            0.0
+        } else {
+            self.deleted as f64 * 100.0 / existed as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileLifetimes {
+    /// Files that ever existed, on any branch.
+    pub files: usize,
+    /// `(bucket start, counts)`, oldest first.
+    pub periods: Vec<(i64, PeriodLifetimes)>,
+    /// Seconds deleted files lived, sorted ascending.
+    lifetimes: Vec<i64>,
+}
+
+impl FileLifetimes {
+    pub fn deleted(&self) -> usize {
+        self.lifetimes.len()
+    }
+
+    pub fn percentile(&self, p: f64) -> Option<i64> {
+        percentile(&self.lifetimes, p)
+    }
+}
+
+/// Follows every file the non-merge `commits` created through to its
+/// deletion, counting births and deaths per period of `bucketing`. Each
+/// commit is reported to `progress`.
+pub fn get_file_lifetimes(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    bucketing: Bucketing,
+    progress: &mut Progress,
+) -> Result<FileLifetimes, git2::Error> {
+    let mut lifetimes = FileLifetimes::default();
+    // Birth time of each file, and whether it has died.
+    let mut files: Vec<(i64, bool)> = Vec::new();
+    let mut periods: BTreeMap<i64, PeriodLifetimes> = BTreeMap::new();
+    progress.begin("lifetime", Some(commits.len()));
+    // The file under each path, per commit.
+    replay_history(
+        commits,
+        None,
+        |commit, current: &mut HashMap<String, usize>, merged| {
+            progress.tick();
+            let Some(mut diff) = diff_commit(repo, commit.oid)? else {
+                return Ok(());
+            };
+            diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+            let period = periods.entry(bucketing.start_of(commit.day())).or_default();
+            let merged_file = |path: &str| merged.iter().find_map(|m| m.get(path).copied());
+            for delta in diff.deltas() {
+                let path =
+                    |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().into_owned());
+                let (Some(old), Some(new)) = (path(delta.old_file()), path(delta.new_file()))
+                else {
+                    continue;
+                };
+                match delta.status() {
+                    Delta::Added if !current.contains_key(&new) => {
+                        let file = match merged_file(&new) {
+                            Some(file) => file,
+                            // Merges are not counted, so what they add
+                            // without a merged file is left untracked.
+                            None if commit.is_merge() => continue,
+                            None => {
+                                files.push((commit.time, false));
+                                period.created += 1;
+                                files.len() - 1
+                            }
+                        };
+                        current.insert(new, file);
+                    }
+                    Delta::Renamed => {
+                        if let Some(file) = merged_file(&new).or_else(|| current.remove(&old)) {
+                            current.remove(&old);
+                            current.insert(new, file);
+                        }
+                    }
+                    Delta::Deleted => {
+                        let Some(file) = current.remove(&old) else {
+                            continue;
+                        };
+                        let (born, dead) = &mut files[file];
+                        if !commit.is_merge() && !*dead {
+                            *dead = true;
+                            lifetimes.lifetimes.push((commit.time - *born).max(0));
+                            period.deleted += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok::<_, git2::Error>(())
+        },
+    )?;
+    progress.end();
+    lifetimes.files = files.len();
+    let mut alive = 0;
+    for period in periods.values_mut() {
+        // Clock skew can date a deletion before the addition.
+        alive = (alive + period.created).saturating_sub(period.deleted);
+        period.alive = alive;
+    }
+    lifetimes.lifetimes.sort_unstable();
+    lifetimes.periods = periods.into_iter().collect();
+    Ok(lifetimes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_file_lifetimes() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        let remove = |path: &str| {
+            let mut index = t.repo.index().unwrap();
+            index.remove_path(std::path::Path::new(path)).unwrap();
+            index.write().unwrap();
+        };
+        t.commit_as(
+            "A",
+            "a@x.com",
+            day,
+            "add",
+            &[("a.txt", "a\nb\nc\nd\n"), ("tmp.txt", "1"), ("b.txt", "b")],
+        );
+        remove("a.txt");
+        t.commit_as(
+            "A",
+            "a@x.com",
+            2 * day,
+            "move",
+            &[("moved.txt", "a\nb\nc\nd\n")],
+        );
+        remove("tmp.txt");
+        t.commit_as("A", "a@x.com", 11 * day, "clean", &[]);
+        remove("moved.txt");
+        t.commit_as("A", "a@x.com", 400 * day, "drop", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let lifetimes =
+            get_file_lifetimes(&t.repo, &commits, Bucketing::Year, &mut Progress::default())
+                .unwrap();
+        assert_eq!((3, 2), (lifetimes.files, lifetimes.deleted()));
+        assert_eq!(Some(10 * day), lifetimes.percentile(50.0));
+        assert_eq!(Some(399 * day), lifetimes.percentile(100.0));
+        assert_eq!(
+            vec![
+                PeriodLifetimes {
+                    created: 3,
+                    deleted: 1,
+                    alive: 2
+                },
+                PeriodLifetimes {
+                    created: 0,
+                    deleted: 1,
+                    alive: 1
+                },
+            ],
+            lifetimes
+                .periods
+                .iter()
+                .map(|(_, p)| *p)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(50.0, lifetimes.periods[1].1.deletion_rate());
+    }
+
+    #[test]
+    fn test_file_lifetimes_per_branch() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        let base = t.commit_as("A", "a@x.com", day, "add", &[("a.txt", "a")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("side", Some(base));
+        let mut index = t.repo.index().unwrap();
+        index.remove_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        t.commit_as("B", "b@x.com", 2 * day, "drop a", &[("c.txt", "side")]);
+        t.checkout(&main, None);
+        t.commit_as("A", "a@x.com", 3 * day, "edit", &[("a.txt", "b")]);
+        t.commit_as("A", "a@x.com", 4 * day, "add c", &[("c.txt", "main")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let lifetimes =
+            get_file_lifetimes(&t.repo, &commits, Bucketing::Year, &mut Progress::default())
+                .unwrap();
+        // Both branches' c.txt count, and a.txt dies on the side branch only.
+        assert_eq!((3, 1), (lifetimes.files, lifetimes.deleted()));
+        assert_eq!(Some(day), lifetimes.percentile(50.0));
+        assert_eq!(2, lifetimes.periods[0].1.alive);
+    }
+}
@@ -0,0 +1,154 @@
+//! Lines of code in the blobs at HEAD.
+//!
+//! Blob contents are read from the object database, so no checkout is needed
+//! and bare repositories work too. Comments are recognized by heuristics: a
+//! line is a comment line when it starts with a line-comment marker of its
+//! language or lies inside a block comment. Code followed by a comment on the
+//! same line counts as code. Binary blobs have no lines.
+
+use git2::Repository;
+
+use crate::{
+    lang::{LanguageMap, OTHER},
+    tree::TreeFile,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    pub lines: usize,
+    pub blank: usize,
+    pub comment: usize,
+}
+
+impl LineCounts {
+    /// Lines that are neither blank nor comments.
+    pub fn code(&self) -> usize {
+        self.lines - self.blank - self.comment
+    }
+}
+
+impl std::ops::AddAssign for LineCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.lines += other.lines;
+        self.blank += other.blank;
+        self.comment += other.comment;
+    }
+}
+
+/// How a language marks comments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommentSyntax {
+    pub line: &'static [&'static str],
+    pub block: Option<(&'static str, &'static str)>,
+}
+
+/// Returns the comment syntax of a built-in language. Other languages have
+/// none, so all their non-blank lines count as code.
+pub fn comment_syntax(language: &str) -> CommentSyntax {
+    const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+    match language {
+        "c" | "cpp" | "csharp" | "go" | "java" | "javascript" | "kotlin" | "rust" | "swift"
+        | "typescript" => CommentSyntax {
+            line: &["//"],
+            block: C_BLOCK,
+        },
+        "php" => CommentSyntax {
+            line: &["//", "#"],
+            block: C_BLOCK,
+        },
+        "css" => CommentSyntax {
+            line: &[],
+            block: C_BLOCK,
+        },
+        "html" | "markdown" => CommentSyntax {
+            line: &[],
+            block: Some(("<!--", "-->")),
+        },
+        "python" | "ruby" | "shell" | "toml" | "yaml" => CommentSyntax {
+            line: &["#"],
+            block: None,
+        },
+        _ => CommentSyntax::default(),
+    }
+}
+
+/// Counts the lines of `content`. A final line without a newline counts.
+pub fn count_lines(content: &[u8], syntax: CommentSyntax) -> LineCounts {
+    let text = String::from_utf8_lossy(content);
+    let mut counts = LineCounts::default();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = line.trim();
+        counts.lines += 1;
+        if in_block {
+            counts.comment += 1;
+            if let Some((_, end)) = syntax.block {
+                in_block = !line.contains(end);
+            }
+        } else if line.is_empty() {
+            counts.blank += 1;
+        } else if syntax.line.iter().any(|marker| line.starts_with(marker)) {
+            counts.comment += 1;
+        } else if let Some((start, end)) = syntax.block
+            && let Some(rest) = line.strip_prefix(start)
+        {
+            counts.comment += 1;
+            in_block = !rest.contains(end);
+        }
+    }
+    counts
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLines {
+    pub path: String,
+    pub language: String,
+    pub counts: LineCounts,
+}
+
+/// Counts the lines of every file in `files`, attributed to the languages of
+/// `map`.
+pub fn get_file_lines(
+    repo: &Repository,
+    files: &[TreeFile],
+    map: &LanguageMap,
+) -> Result<Vec<FileLines>, git2::Error> {
+    let mut lines = Vec::with_capacity(files.len());
+    for file in files {
+        let language = map.language_of(&file.path).unwrap_or(OTHER);
+        let blob = repo.find_blob(file.oid)?;
+        let counts = if blob.is_binary() {
+            LineCounts::default()
+        } else {
+            count_lines(blob.content(), comment_syntax(language))
+        };
+        lines.push(FileLines {
+            path: file.path.clone(),
+            language: language.to_string(),
+            counts,
+        });
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_lines() {
+        let rust =
+            "// header\n\nfn main() {\n    /* block\n       comment */\n    run(); // trailing\n}";
+        assert_eq!(
+            LineCounts {
+                lines: 7,
+                blank: 1,
+                comment: 3
+            },
+            count_lines(rust.as_bytes(), comment_syntax("rust"))
+        );
+        let counts = count_lines(b"# comment\nkey = 1\n", comment_syntax("toml"));
+        assert_eq!((2, 1), (counts.lines, counts.code()));
+        assert_eq!(2, count_lines(b"# a\n# b\n", comment_syntax("json")).code());
+    }
+}
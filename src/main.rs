@@ -1,11 +1,115 @@
+mod age;
+mod ai;
+mod alerts;
+mod anomalies;
+#[cfg(feature = "bench")]
+mod bench;
+mod bigfiles;
+mod binary;
+mod branches;
+mod bucket;
+mod cherrypicks;
+mod churn;
+mod clone;
+mod codeowners;
+mod collaboration;
+mod commands;
+mod config;
+mod context;
+mod contributors;
+mod conventional;
+mod dashboard;
+mod date;
+mod dirs;
+mod doctor;
+mod domains;
+mod duplicates;
+mod export;
+mod extensions;
+mod fingerprint;
+mod generated;
+mod glob;
+mod graph;
+mod handoff;
+mod health;
+mod heatmap;
+mod history;
+mod hotspots;
+mod hours;
+mod identity;
+mod insights;
+mod issues;
+mod landing;
+mod lang;
+mod license;
+mod lifetime;
+mod loc;
+mod merges;
+mod message;
+mod milestones;
+mod owners;
+mod platform;
+mod progress;
+mod protection;
+mod punchcard;
+mod push;
+mod quality;
+mod range;
+mod reflog;
+mod releases;
+mod report;
+mod retained;
+mod roots;
+mod share;
+mod signoff;
+mod size;
+mod sparse;
+mod sprints;
 mod stats;
+mod streaks;
+mod support;
+mod teams;
+mod testratio;
+#[cfg(test)]
+mod testutil;
+mod timezones;
+mod tree;
+mod unreachable;
+mod velocity;
+mod vendor;
+mod worktrees;
+mod xlsx;
 
-use clap::{Arg, Command, crate_authors, crate_description, crate_version};
-use git2::Repository;
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+};
 
-use crate::stats::{
-    format_number, get_branch_count, get_contributor_count, get_repository_size, get_total_commits,
+use clap::{
+    Arg, ArgAction, Command, crate_authors, crate_description, crate_version, parser::ValueSource,
+    value_parser,
 };
+use git2::Oid;
+
+use crate::bucket::Bucketing;
+use crate::clone::parse_bandwidth;
+use crate::commands::run_subcommand;
+use crate::config::Config;
+use crate::context::Context;
+use crate::date::{DateFormat, now};
+use crate::export::Field;
+use crate::fingerprint::get_repo_identity;
+use crate::hours::BusinessHours;
+use crate::identity::{IdentityKey, pseudonym_id, set_pseudonym_secret};
+use crate::insights::get_insights;
+use crate::lang::{parse_extension, parse_language};
+use crate::report::{Format, Report, Section, Table, render_bytes, render_terminal};
+use crate::size::SizeMode;
+use crate::stats::{parse_sigma, parse_size};
+use crate::worktrees::open_repository;
 
 fn main() {
     match run() {
@@ -17,11 +121,15 @@ fn main() {
     }
 }
 
-fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let cmd = Command::new("gno")
+fn cli() -> Command {
+    let cli = Command::new("gno")
         .version(crate_version!())
         .author(crate_authors!())
         .about(crate_description!())
+        .after_help(
+            "Several subcommands can run in one invocation, separated by then, sharing the \
+             history walk: gno summary then contributors --show-identities",
+        )
         .subcommand_required(true)
         .arg(
             Arg::new("config")
@@ -36,59 +144,1572 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
                 .long("path")
                 .short('p')
                 .help("Path to the git repository")
-                .long_help("Path to the git repository")
-                .default_value("."),
+                .long_help(
+                    "Path to the git repository or a directory inside it. Without it, the \
+                     repository is found like git does, from GIT_DIR and GIT_WORK_TREE or \
+                     the current directory",
+                )
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("identity-key")
+                .long("identity-key")
+                .global(true)
+                .value_parser(value_parser!(IdentityKey))
+                .help("Tell contributors apart by name+email (default), email, name or email-domain")
+                .long_help(
+                    "Choose what identifies a contributor: the recorded name and email \
+                     (name+email, the default), the email address so renamed people are not \
+                     split (email), the display name (name), or the email domain to count \
+                     organizations (email-domain). Applied after .mailmap and before \
+                     --merge-identities.",
+                ),
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .global(true)
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(parse_language)
+                .help("Only count commits touching files of these languages, e.g. rust,toml")
+                .long_help(
+                    "Restrict commit, contributor, activity and churn metrics to commits \
+                     touching files of these languages, recognized by extension, and churn \
+                     to the lines of those files. Combines with --ext.",
+                ),
+        )
+        .arg(
+            Arg::new("ext")
+                .long("ext")
+                .global(true)
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(parse_extension)
+                .help("Only count commits touching files with these extensions, e.g. .rs"),
+        )
+        .arg(
+            Arg::new("first-parent")
+                .long("first-parent")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Only count commits on the first-parent chain of HEAD")
+                .long_help(
+                    "Only count the commits on the first-parent chain of HEAD, as git log \
+                     --first-parent lists them: the commits that landed on the current \
+                     branch, with each merged branch as its merge commit and without the \
+                     commits made on it",
+                ),
+        )
+        .arg(
+            Arg::new("merge-identities")
+                .long("merge-identities")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Merge authors with the same email or near-identical names")
+                .long_help(
+                    "After applying .mailmap, merge author identities that share an email \
+                     address, have the same name, or have names one typo apart and addresses \
+                     sharing their local part or organization domain, and report each merged \
+                     group under its most active identity",
+                ),
+        )
+        .arg(
+            Arg::new("anonymize")
+                .long("anonymize")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Replace contributor names and emails with pseudonyms")
+                .long_help(
+                    "Replace author and committer names and emails with pseudonyms keyed \
+                     from the email address. The key is random for each run, so pseudonyms \
+                     match across the reports of one run; set secret in the [anonymize] \
+                     section of the config to keep them across runs. Organization email \
+                     domains are kept, and commit counts and dates still say a lot about \
+                     a person, so review a report before publishing it.",
+                ),
+        )
+        .arg(
+            Arg::new("repo-identity")
+                .long("repo-identity")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Start reports with the repository's fingerprint, HEAD and origin")
+                .long_help(
+                    "Start reports with a block identifying the repository: its root \
+                     commits, the default branch, HEAD, the origin URL without credentials \
+                     and a fingerprint hashed from the root commits of the default branch, \
+                     or of HEAD without one, which is the same in every clone. With \
+                     --anonymize the URL is replaced by a pseudonym.",
+                ),
+        )
+        .arg(
+            Arg::new("insights")
+                .long("insights")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("End each report with notable findings and the subcommands to dig deeper"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .global(true)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+                .help("Write the report to a file, or '-' for stdout (repeatable)")
+                .long_help(
+                    "Write the report to a file, or '-' for stdout. Repeat to write the same \
+                     report to several destinations; the format of each file is inferred from \
+                     its extension (.json, .md, .txt, .csv, .html, .xlsx) and falls back to \
+                     --format.",
+                ),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .global(true)
+                .value_parser(value_parser!(Format))
+                .help("Report format for stdout and files without a known extension: text, json, markdown, csv, html or xlsx")
+                .long_help(
+                    "Report format for stdout and files without a known extension: text, \
+                     json, markdown, csv, html or xlsx. JSON is an array with one object per \
+                     subcommand, also when only one ran.",
+                ),
+        )
+        .arg(
+            Arg::new("no-write")
+                .long("no-write")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Never write caches to the repository, keep them in memory")
+                .long_help(
+                    "Never write to the repository: caches such as commit generations are \
+                     kept in memory for this run only. For read-only checkouts, snapshots, \
+                     backups and NFS-mounted mirrors; a cache that cannot be written turns \
+                     this on by itself, with a warning.",
+                ),
+        )
+        .arg(
+            Arg::new("progress-events")
+                .long("progress-events")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("With --format json, write progress events to stderr as JSON lines")
+                .long_help(
+                    "With --format json, report the progress of long walks on stderr as \
+                     JSON lines of {\"event\", \"phase\", \"done\", \"total\", \"percent\", \
+                     \"eta_secs\"}, and a \"done\" event with \"elapsed_secs\" when a phase \
+                     ends. Unknown totals are null.",
+                ),
+        )
+        .arg(
+            Arg::new("date-format")
+                .long("date-format")
+                .global(true)
+                .value_parser(value_parser!(DateFormat))
+                .help("Show dates as iso (default), relative, unix or custom:<strftime>")
+                .long_help(
+                    "Show dates and times in every report and format as ISO 8601 (iso, the \
+                     default), relative to now (relative, e.g. \"3 days ago\"), seconds since the \
+                     epoch (unix, numbers in JSON) or a strftime pattern in UTC \
+                     (custom:%d.%m.%Y)",
+                ),
+        )
+        .arg(
+            Arg::new("bucket")
+                .long("bucket")
+                .short('b')
+                .global(true)
+                .value_parser(value_parser!(Bucketing))
+                .help(
+                    "Group time-bucketed reports by week, month, quarter, year or sprint:<days>[:<date>]",
+                )
+                .long_help(
+                    "Group time-bucketed reports into ISO weeks (week), calendar months (month), \
+                     calendar quarters (quarter), calendar years (year) or sprints of N days \
+                     anchored on a date (sprint:14:2024-01-08)",
+                ),
+        )
+        .subcommand(
+            Command::new("activity")
+                .about("show a histogram of commits per week, month or year")
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .short('i')
+                        .value_parser(value_parser!(Bucketing))
+                        .help("Period per bar: week, month or year, or any --bucket value (default: --bucket or month)"),
+                )
+                .arg(
+                    Arg::new("anomalies")
+                        .long("anomalies")
+                        .action(ArgAction::SetTrue)
+                        .help("List weeks whose commits deviate from the trailing 12 weeks"),
+                )
+                .arg(
+                    Arg::new("sigma")
+                        .long("sigma")
+                        .value_parser(parse_sigma)
+                        .default_value("2")
+                        .help("Standard deviations from the trailing mean that count as an anomaly"),
+                ),
+        )
+        .subcommand(
+            Command::new("age")
+                .about("show when the files at HEAD were created and last modified")
+                .long_about(
+                    "Date every file at HEAD by the oldest and newest commits touching it, \
+                     and list the files untouched the longest, which are dead or stable \
+                     code. Renames are not followed, so a renamed file counts as created \
+                     by the rename; merge commits are not counted.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of files shown"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .help("Show every file instead of --top"),
+                ),
+        )
+        .subcommand(
+            Command::new("ai")
+                .about("show commits attributed to AI tools")
+                .long_about(
+                    "Show commits likely generated or co-authored by AI tools, detected from \
+                     tool author identities, Co-authored-by trailers and tool signatures in \
+                     commit messages, and their share per period (--bucket, default month)",
+                ),
+        )
+        .subcommand(
+            Command::new("alerts")
+                .about("alert on metrics whose last week is unusual for the repository")
+                .long_about(
+                    "Judge the commits, contributors and lines changed of the last full week \
+                     against their mean and standard deviation over the weeks before it, \
+                     flagging those more than --sigma deviations away. Metrics that did not \
+                     vary are not judged. With --fail, any alert makes gno exit with an \
+                     error once the report is written, for scheduled runs to act on.",
+                )
+                .arg(
+                    Arg::new("weeks")
+                        .long("weeks")
+                        .value_parser(value_parser!(u16).range(2..))
+                        .default_value("12")
+                        .help("Weeks before the last full week the normal range is learned from"),
+                )
+                .arg(
+                    Arg::new("sigma")
+                        .long("sigma")
+                        .value_parser(parse_sigma)
+                        .default_value("2")
+                        .help("Standard deviations from the mean that raise an alert"),
+                )
+                .arg(
+                    Arg::new("fail")
+                        .long("fail")
+                        .action(ArgAction::SetTrue)
+                        .help("Exit with an error when any metric raises an alert"),
+                ),
+        )
+        .subcommand(
+            Command::new("bigfiles")
+                .about("list the largest blobs ever committed")
+                .long_about(
+                    "List the largest blobs in the object database, including those deleted \
+                     long ago, with the path and commit that introduced them. Every clone \
+                     carries them until history is rewritten, so check here before \
+                     slimming a repository. Blobs no commit in the history introduces are \
+                     shown without a path.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of blobs shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("binary")
+                .about("compare binary and text files")
+                .long_about(
+                    "Count the binary and text files at HEAD and their sizes, and the binary \
+                     and text blobs introduced per period (--bucket, default month), to \
+                     spot binary assets creeping into the repository. A blob is binary when \
+                     its first 8000 bytes contain a NUL byte, as git decides.",
+                ),
+        )
+        .subcommand(
+            Command::new("branches")
+                .about("count local, remote and tracking branches and list the remotes")
+                .arg(
+                    Arg::new("divergence")
+                        .long("divergence")
+                        .action(ArgAction::SetTrue)
+                        .help("Show how far each branch is ahead of and behind the default branch"),
+                )
+                .arg(
+                    Arg::new("branch")
+                        .long("branch")
+                        .help("Branch to compare against instead of the default branch"),
+                ),
+        )
+        .subcommand(
+            Command::new("calendar")
+                .about("show the commits per day of a year, month by month")
+                .long_about(
+                    "Show the commits per local day of a year as shaded cells, one row per \
+                     month, with the total per month. With --format json, each month's row \
+                     holds its daily counts for plotting elsewhere.",
+                )
+                .arg(
+                    Arg::new("year")
+                        .required(true)
+                        .value_parser(value_parser!(i64).range(1..=9999))
+                        .help("Year to show, e.g. 2024"),
+                ),
+        )
+        .subcommand(
+            Command::new("cherry-picks")
+                .about("find changes cherry-picked onto several branches")
+                .long_about(
+                    "Group the commits reachable from any reference by patch id, the hash of \
+                     their changes, and list the changes that were committed more than once, \
+                     as cherry-picks onto release branches are, with the branches holding a \
+                     copy. Merge commits and commits changing nothing are left out.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of cherry-picked changes shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("churn")
+                .about("show the lines changed per file over its life, following renames")
+                .long_about(
+                    "Sum the lines inserted and deleted per file over the whole history, \
+                     following renames detected between each commit and its first parent, \
+                     so a file's churn includes its life under earlier names. Files are \
+                     listed under their latest name; merge commits are not counted.",
+                )
+                .arg(Arg::new("path").help(
+                    "Only include files below this path, under their latest or an earlier name",
+                ))
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("clone-cost")
+                .about("estimate full, shallow and blobless clone sizes and download times")
+                .long_about(
+                    "Estimate the download size of a full clone, a shallow clone (--depth 1) \
+                     and a blobless partial clone (--filter=blob:none) from the local object \
+                     store, and how long each takes at the given bandwidths. Sizes are scaled \
+                     by the store's compression ratio, so they are approximate.",
+                )
+                .arg(
+                    Arg::new("bandwidth")
+                        .long("bandwidth")
+                        .action(ArgAction::Append)
+                        .value_parser(parse_bandwidth)
+                        .help("Estimate download time at this bandwidth, e.g. 50Mbps (repeatable)"),
+                ),
+        )
+        .subcommand(
+            Command::new("codeowners")
+                .about("show how much of the tree CODEOWNERS covers")
+                .long_about(
+                    "Read CODEOWNERS from HEAD, in .github/, the root or docs/, and show the \
+                     share of files at HEAD with an owner, the files per owner, and the \
+                     unowned files changed within the last days, which are the ones reviews \
+                     miss. The last matching rule wins, and rules without owners leave \
+                     files unowned.",
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("90")
+                        .help("Only count changes of the last this many days"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of owners and unowned files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("collaboration")
+                .alias("collab")
+                .about("show how often pairs of contributors touch the same files")
+                .long_about(
+                    "Count, for every pair of contributors, the files both changed within a \
+                     time window of each other, as a ranked list of pairs and an adjacency \
+                     matrix of the most active contributors (use --format json for a \
+                     machine-readable matrix).",
+                )
+                .arg(
+                    Arg::new("window")
+                        .long("window")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("30")
+                        .help("Days within which two changes to a file count as collaboration"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .value_parser(value_parser!(usize))
+                        .default_value("10")
+                        .help("Number of contributors in the matrix"),
+                ),
         )
         .subcommand(
             Command::new("commits")
                 .alias("c")
-                .about("commit statistics"),
+                .about("commit statistics")
+                .arg(
+                    Arg::new("sizes")
+                        .long("sizes")
+                        .action(ArgAction::SetTrue)
+                        .help("Show percentiles and a histogram of commit diff sizes"),
+                )
+                .arg(
+                    Arg::new("largest")
+                        .long("largest")
+                        .value_parser(value_parser!(u16).range(1..))
+                        .value_name("N")
+                        .help("List the N biggest non-merge commits"),
+                )
+                .arg(
+                    Arg::new("by")
+                        .long("by")
+                        .value_parser(["lines", "files"])
+                        .default_value("lines")
+                        .help("Rank --largest by lines changed or files touched"),
+                ),
+        )
+        .subcommand(
+            Command::new("committers")
+                .about("show top committers versus top authors")
+                .long_about(
+                    "Show who commits work versus who authors it. Committers who mostly commit \
+                     others' work apply patches, rebase or merge on their behalf, as in \
+                     mailing-list or rebase-heavy workflows.",
+                ),
         )
         .subcommand(
             Command::new("contributors")
                 .alias("cont")
-                .about("show contributor statistics"),
+                .about("show contributor statistics")
+                .arg(
+                    Arg::new("min-commits")
+                        .long("min-commits")
+                        .value_parser(value_parser!(usize))
+                        .default_value("1")
+                        .help("Fold contributors with fewer commits into an \"others\" row"),
+                )
+                .arg(
+                    Arg::new("show-identities")
+                        .long("show-identities")
+                        .action(ArgAction::SetTrue)
+                        .help("List the author identities merged into each contributor"),
+                ),
+        )
+        .subcommand(
+            Command::new("conventional")
+                .about("classify commit subjects by Conventional Commit type")
+                .long_about(
+                    "Parse commit subjects as Conventional Commits (type(scope)!: description) \
+                     and break them down by type, with the share of non-merge commits that \
+                     conform. --strict only counts subjects without violations, such as an \
+                     uppercase or unknown type, an empty scope or a missing space, as \
+                     conforming and lists the violations.",
+                )
+                .arg(
+                    Arg::new("strict")
+                        .long("strict")
+                        .action(ArgAction::SetTrue)
+                        .help("Count subjects with format violations separately"),
+                ),
+        )
+        .subcommand(
+            Command::new("dashboard")
+                .about("write an HTML dashboard of several repositories")
+                .long_about(
+                    "Write index.html to --out-dir with one card per --repo: its \
+                     commits and contributors in the last 90 days, its top contributor, a \
+                     sparkline of weekly commits over the last year and the number of \
+                     findings --insights would list. Each card links to a page of details \
+                     written next to it. The pages are standalone HTML, ready to publish.",
+                )
+                .arg(
+                    Arg::new("repo")
+                        .long("repo")
+                        .required(true)
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Repository to include, or a directory inside it, repeatable"),
+                )
+                .arg(
+                    Arg::new("out-dir")
+                        .long("out-dir")
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("dashboard")
+                        .help("Directory the pages are written to"),
+                )
+                .arg(
+                    Arg::new("title")
+                        .long("title")
+                        .default_value("Repositories")
+                        .help("Title of the dashboard"),
+                ),
+        )
+        .subcommand(
+            Command::new("direct-pushes")
+                .about("flag commits pushed to protected branches without review")
+                .long_about(
+                    "Walk the first-parent chain of each protected branch (default: the \
+                     default branch) and flag the commits that neither are merges nor look \
+                     like a forge squash or rebase, by a (#123) subject suffix, a review \
+                     trailer or a forge committer. These were most likely pushed directly.",
+                )
+                .arg(
+                    Arg::new("branch")
+                        .long("branch")
+                        .action(ArgAction::Append)
+                        .help("Protected branch to check, repeatable"),
+                ),
+        )
+        .subcommand(
+            Command::new("dirs")
+                .about("roll up commits, churn, contributors and size per directory")
+                .long_about(
+                    "Show per directory, truncated to a depth, the commits and contributors \
+                     that changed its files, the lines inserted and deleted, and the files \
+                     and bytes it holds at HEAD. Files at the root count towards '.'; \
+                     merge commits are not counted.",
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("1")
+                        .help("Number of leading path components grouping a directory"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of directories shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("show diagnostics to attach to bug reports")
+                .long_about(
+                    "Show how this gno was built and the libgit2 it links, the git settings \
+                     that influence results, the files gno reads and caches with their \
+                     sizes, and permission problems or conditions such as shallow clones \
+                     that make results incomplete.",
+                ),
+        )
+        .subcommand(
+            Command::new("domains")
+                .alias("d")
+                .about("show contributors and commits by email domain"),
+        )
+        .subcommand(
+            Command::new("duplicates")
+                .about("list identical files stored under several paths")
+                .long_about(
+                    "Group the files at HEAD with identical content, the same blob under \
+                     different paths, with the size of one copy and the bytes the extra \
+                     copies take in a checkout, to find copy-pasted assets and duplicated \
+                     vendored code. Empty files are left out.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of duplicate sets shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("list every commit with the chosen fields, for CSV or JSON exports")
+                .after_help(
+                    "Fields: oid, short-oid, author, author-email, committer, committer-email, \
+                     date, parents, files, insertions, deletions, subject",
+                )
+                .arg(
+                    Arg::new("fields")
+                        .long("fields")
+                        .value_delimiter(',')
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser!(Field))
+                        .help("Columns to export, e.g. oid,author,date,insertions")
+                        .long_help(
+                            "Columns to export, in order. Defaults to \
+                             oid,author,date,files,insertions,deletions,subject. Diff sizes \
+                             (files, insertions, deletions) are against the first parent and \
+                             only computed when asked for.",
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("extensions")
+                .about("show how the mix of file extensions changed over time")
+                .long_about(
+                    "Count the files changed per extension in each period (--bucket, default \
+                     year), from the changes of non-merge commits, to see a project drifting \
+                     from one language to another, or with --lines the lines changed, to see \
+                     whether effort goes into code, docs or CI config. The most changed \
+                     extensions get a column each, the rest are summed as (other).",
+                )
+                .arg(
+                    Arg::new("lines")
+                        .long("lines")
+                        .action(ArgAction::SetTrue)
+                        .help("Count lines inserted and deleted instead of files changed"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("6")
+                        .help("Number of extension columns"),
+                ),
+        )
+        .subcommand(
+            Command::new("filesizes")
+                .about("show the size distribution of files tracked at HEAD")
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .short('t')
+                        .action(ArgAction::Append)
+                        .value_parser(parse_size)
+                        .help("Count files at or above this size, e.g. 5MB (repeatable)"),
+                ),
+        )
+        .subcommand(
+            Command::new("generated")
+                .about("split statistics by vendored and generated paths")
+                .long_about(
+                    "Show files, size, commits and line churn for all paths, for vendored \
+                     and generated paths such as lockfiles and codegen output, and for the \
+                     hand-written rest, and list the generated paths with the most churn. \
+                     Set the globs with --pattern or as patterns in the [generated] section \
+                     of the config. Merge commits are not counted.",
+                )
+                .arg(
+                    Arg::new("pattern")
+                        .long("pattern")
+                        .short('p')
+                        .action(ArgAction::Append)
+                        .help("Generated path glob such as 'vendor/' or '*.pb.rs', repeatable"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of generated paths shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("handoff")
+                .about("show how ownership of a file shifted between authors per year")
+                .long_about(
+                    "Blame a file as it stood after the last commit of each year that changed \
+                     it and show each author's share of its lines, one row per year. Use \
+                     --format csv to chart the shares as stacked percentages.",
+                )
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .help("Path of the file, relative to the repository root"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("5")
+                        .help("Number of authors shown, the rest are summed as others"),
+                ),
+        )
+        .subcommand(
+            Command::new("health")
+                .about("recommend git gc or git repack from the state of the object store")
+                .long_about(
+                    "Count loose objects and packs against git's auto-gc thresholds, gc.auto \
+                     and gc.autoPackLimit, find the objects no reference, reflog, worktree \
+                     HEAD or index reaches, and recommend git gc or git repack where they \
+                     would help, with an estimate of the space they would free. Repacked \
+                     objects are assumed to compress as well as the existing packs, and git gc \
+                     keeps unreachable objects younger than gc.pruneExpire.",
+                ),
+        )
+        .subcommand(
+            Command::new("heatmap")
+                .about("show daily commits of the last year as a contribution calendar")
+                .long_about(
+                    "Show commits per day over the last weeks as a calendar of shaded cells, \
+                     one column per week and one row per weekday, in color on terminals \
+                     unless NO_COLOR is set.",
+                )
+                .arg(
+                    Arg::new("author")
+                        .long("author")
+                        .short('a')
+                        .help("Only count commits by this author name or email"),
+                )
+                .arg(
+                    Arg::new("weeks")
+                        .long("weeks")
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("52")
+                        .help("Number of weeks to show"),
+                ),
+        )
+        .subcommand(
+            Command::new("hotspots")
+                .about("list the files changed by the most commits and authors")
+                .long_about(
+                    "List the files at HEAD changed by the most commits within the last \
+                     days, and those changed by the most distinct authors, with their size. \
+                     Files that change often predict defects, the more so the larger they \
+                     are. Files with five or more commits whose top author made 80% or more \
+                     of them are listed as knowledge silos. Merge commits are not counted.",
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("365")
+                        .help("Only count commits of the last this many days"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("hours")
+                .about("show commits on weekends and outside business hours")
+                .long_about(
+                    "Show the share of commits made on weekends and on weekdays outside \
+                     business hours, overall and per contributor, in the author's local time, \
+                     and the commits per weekday in the committer's, as in the punch card. \
+                     Business hours default to 9-18 and can be set in the [hours] section of \
+                     the config as start and end.",
+                )
+                .arg(
+                    Arg::new("business-hours")
+                        .long("business-hours")
+                        .value_parser(value_parser!(BusinessHours))
+                        .value_name("START-END")
+                        .help("Local business hours, e.g. 9-17"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of contributors listed"),
+                ),
+        )
+        .subcommand(
+            Command::new("issues")
+                .about("show how many commits reference tracker issues")
+                .long_about(
+                    "Find issue references such as #123, GH-123 or PROJ-456 in commit \
+                     messages and show the share of commits referencing an issue and the \
+                     most-referenced issues. A pattern is the prefix before the issue number, \
+                     with * standing for an uppercase project key; set them with --pattern or \
+                     as patterns in the [issues] section of the config.",
+                )
+                .arg(
+                    Arg::new("pattern")
+                        .long("pattern")
+                        .short('p')
+                        .action(ArgAction::Append)
+                        .help("Issue prefix such as '#' or '*-', repeatable (default: #, GH-, *-)"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of issues to list"),
+                ),
+        )
+        .subcommand(
+            Command::new("languages")
+                .alias("langs")
+                .about("show the files and bytes per language at HEAD")
+                .long_about(
+                    "Map the files tracked at HEAD to languages by extension and show the \
+                     files, bytes and share of bytes per language. Languages can be added \
+                     or redefined in the [languages] section of the config, as name = \
+                     [\"ext\", ...]; files of no known language are counted as (other).",
+                )
+                .arg(
+                    Arg::new("loc")
+                        .long("loc")
+                        .action(ArgAction::SetTrue)
+                        .help("Count lines of code, comments and blank lines per language and file")
+                        .long_help(
+                            "Read every blob at HEAD from the object database, so bare \
+                             repositories work too, and count its lines, blank lines and \
+                             comment lines per language, with the longest files. Comments are \
+                             recognized by the line and block markers of the built-in \
+                             languages; binary files have no lines.",
+                        ),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of longest files shown with --loc"),
+                ),
+        )
+        .subcommand(
+            Command::new("lifetime")
+                .about("show how long files live and how often they are deleted")
+                .long_about(
+                    "Follow every file from the commit adding it to the one deleting it, \
+                     across renames and per branch, and show how many files ever existed \
+                     against how many exist at HEAD, how long deleted files lived, and the \
+                     files created and deleted per period (--bucket, default year) with the \
+                     share of files deleted. Merge commits are not counted.",
+                ),
+        )
+        .subcommand(
+            Command::new("messages")
+                .about("show commit message hygiene statistics")
+                .long_about(
+                    "Show the distribution of commit subject lengths, the share of subjects \
+                     longer than 72 characters, of messages with a body beyond the subject \
+                     and trailers, and of one-word messages. Merge commits are left out.",
+                ),
+        )
+        .subcommand(
+            Command::new("milestones")
+                .about("list contributor milestones reached in the last days")
+                .long_about(
+                    "List the milestones contributors reached in the last --days, today \
+                     included: their first commit, their 10th, 50th, 100th, 500th and \
+                     1000th commits, each yearly anniversary of their first commit, and \
+                     becoming the top contributor of a month someone else topped the month \
+                     before, dated the day after that month ends. A scheduled run with \
+                     --days set to its interval announces each milestone once.",
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("7")
+                        .help("Days up to today the milestones are listed for"),
+                ),
+        )
+        .subcommand(
+            Command::new("owners")
+                .about("show which contributor owns the most current lines per directory")
+                .long_about(
+                    "Blame every text file at HEAD below a path (relative to the repository \
+                     root) and show, per directory, the contributor who last changed the \
+                     largest share of its lines. --fast credits whole files to the author of \
+                     their last change instead of blaming.",
+                )
+                .arg(Arg::new("path").help("Only include files below this path"))
+                .arg(
+                    Arg::new("fast")
+                        .long("fast")
+                        .action(ArgAction::SetTrue)
+                        .help("Use the last author of each file instead of blame"),
+                ),
+        )
+        .subcommand(
+            Command::new("platforms")
+                .about("estimate the operating systems contributors commit from")
+                .long_about(
+                    "Estimate each contributor's platform from line endings, OS metadata files, \
+                     case-only renames, executable bits and machine host names in author \
+                     emails. Estimates are heuristic and reported with a confidence level.",
+                ),
+        )
+        .subcommand(
+            Command::new("protection")
+                .about("compare branch history with the configured branch protection")
+                .long_about(
+                    "Check the branches named in the [protection] section of the config \
+                     (default: the default branch) against its rules: require_signed flags \
+                     unsigned commits on the first-parent chain, require_review direct \
+                     pushes as found by direct-pushes, and force pushes, unless \
+                     allow_force_push is set, are read from the reflogs of the branch and \
+                     its remote-tracking branches. Only this clone's reflogs are read.",
+                ),
+        )
+        .subcommand(
+            Command::new("punchcard")
+                .about("show commits by weekday and hour of day")
+                .long_about(
+                    "Show the weekday-by-hour commit matrix in each committer's local time, \
+                     darker cells meaning more commits. --format json exports the counts.",
+                ),
+        )
+        .subcommand(
+            Command::new("push-latency")
+                .about("show how long commits sit locally before being pushed")
+                .long_about(
+                    "Estimate from the local reflogs how long commits sit in this clone \
+                     before a push makes them reachable from a remote-tracking branch, and \
+                     list the work not pushed yet. Only pushes made from this clone are seen.",
+                ),
+        )
+        .subcommand(
+            Command::new("quality")
+                .about("count reverts, fixups and squashes as a code-stability signal")
+                .long_about(
+                    "Count commits whose subject starts with Revert \"...\", fixup! or \
+                     squash!, and their share of all commits. Many of them mean work landed \
+                     before it was ready or autosquash was skipped before merging.",
+                ),
+        )
+        .subcommand(
+            Command::new("range")
+                .about("show commits, contributors and changes between two refs")
+                .long_about(
+                    "Show the commits in a range as git log A..B lists them, the commits \
+                     reachable from B but not from A, with their contributors, the lines and \
+                     files they changed, and the most active contributors and most changed \
+                     files. A single ref A stands for A..HEAD.",
+                )
+                .arg(
+                    Arg::new("range")
+                        .required(true)
+                        .help("Range of commits, e.g. v1.0..v2.0"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of contributors and files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("reflog")
+                .about("show reflog entries per ref, resets, rebases and amends, and the oldest recoverable state")
+                .long_about(
+                    "Count the reflog entries of HEAD and every reference, classify those of \
+                     HEAD by action from their messages, such as commits, amends, resets and \
+                     rebases, with their rate per week, and show the oldest entry, the \
+                     furthest back local history can be recovered. Reflogs are local: a \
+                     fresh clone has next to none.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of references shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("releases")
+                .about("show tag counts, commits per release and release cadence")
+                .long_about(
+                    "List the tags as releases, oldest first, with the commits each added \
+                     since the release before it. Shows annotated versus lightweight tags, \
+                     the average days between releases and the commits on HEAD since the \
+                     newest tag it contains. Tags that parse as semantic versions, with an \
+                     optional v prefix, are also counted as major, minor, patch and \
+                     pre-releases, with a warning for versions released out of order.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of most recent releases listed"),
+                ),
+        )
+        .subcommand(
+            Command::new("retained")
+                .about("measure blob data only history still holds")
+                .long_about(
+                    "Measure the blobs the history added that HEAD's tree no longer has, \
+                     which every clone still carries, and list the deleted files among them \
+                     by size with the commit that deleted them. These are the candidates \
+                     for a history rewrite with git filter-repo.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("20")
+                        .help("Number of deleted files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("retention")
+                .about("show contributor retention per cohort of first-time contributors")
+                .long_about(
+                    "Group contributors into cohorts by the quarter of their first commit \
+                     (or --bucket) and show what share of each cohort was still committing 3, \
+                     6 and 12 months later. Periods not yet reached are shown as '-'.",
+                ),
+        )
+        .subcommand(
+            Command::new("roots")
+                .about("show root commits and disconnected histories")
+                .long_about(
+                    "Count the root commits and split the history into disconnected parts. \
+                     Several roots in one history mean unrelated projects were merged in; \
+                     separate histories are orphan branches such as gh-pages. Ignores --lang \
+                     and --ext, which would cut the history apart.",
+                ),
+        )
+        .subcommand(
+            Command::new("share")
+                .about("compare contributor shares by commits, lines changed and ownership")
+                .long_about(
+                    "Show each contributor's share of the work computed three ways side by \
+                     side: of all commits, of all lines inserted or deleted outside merges, \
+                     and of the lines at HEAD they last changed. --fast credits whole files \
+                     to the author of their last change instead of blaming.",
+                )
+                .arg(
+                    Arg::new("fast")
+                        .long("fast")
+                        .action(ArgAction::SetTrue)
+                        .help("Use the last author of each file instead of blame"),
+                ),
+        )
+        .subcommand(
+            Command::new("signoffs")
+                .alias("dco")
+                .about("show how many commits carry Signed-off-by trailers"),
+        )
+        .subcommand(
+            Command::new("sparse")
+                .about("recommend a sparse-checkout cone set from a contributor's recent work")
+                .long_about(
+                    "Recommend sparse-checkout cone directories covering most of the files a \
+                     contributor touched recently, with the git sparse-checkout command to \
+                     apply them. Defaults to the user.email of the repository.",
+                )
+                .arg(
+                    Arg::new("author")
+                        .long("author")
+                        .short('a')
+                        .help("Author name or email (default: git config user.email)"),
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("90")
+                        .help("Only consider commits from the last N days"),
+                )
+                .arg(
+                    Arg::new("coverage")
+                        .long("coverage")
+                        .value_parser(value_parser!(u8).range(1..=100))
+                        .default_value("95")
+                        .help("Percentage of touched files the cones must cover"),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("2")
+                        .help("Maximum depth of cone directories"),
+                ),
+        )
+        .subcommand(
+            Command::new("sprints")
+                .about("show commits, contributors and churn per sprint")
+                .long_about(
+                    "Show commits, contributors and churn per sprint. Sprints are read from \
+                     the [sprints] section of the config file (start + length, or a list of \
+                     start dates) unless --bucket sprint:<days>:<date> is given.",
+                )
+                .arg(
+                    Arg::new("current")
+                        .long("current")
+                        .action(ArgAction::SetTrue)
+                        .help("Only show the sprint in progress"),
+                ),
+        )
+        .subcommand(
+            Command::new("streaks")
+                .about("show longest and current streaks of days and weeks with commits"),
         )
         .subcommand(
             Command::new("summary")
                 .alias("s")
-                .about("show summary of the repository statistics"),
-        )
-        .get_matches();
-
-    // Discover git repository in current directory or parent directories
-    let repo = Repository::discover(".")?;
-
-    match cmd.subcommand() {
-        Some(("commits", _)) => {
-            let total_commits = get_total_commits(&repo)?;
-            println!("Git Repository Statistics");
-            println!("{}", "=".repeat(25));
-            println!(
-                "{:<20} {:>12}",
-                "Total Commits:",
-                format_number(total_commits)
-            );
+                .about("show summary of the repository statistics")
+                .arg(
+                    Arg::new("size-mode")
+                        .long("size-mode")
+                        .value_parser(value_parser!(SizeMode))
+                        .help("Measure repository size as apparent (default), allocated or reachable")
+                        .long_help(
+                            "Measure repository size as the summed length of the files in the \
+                             git directory (apparent, like du --apparent-size), the disk \
+                             blocks they occupy (allocated, like du), or the uncompressed size \
+                             of the objects reachable from any reference (reachable), which \
+                             ignores packing and unreachable objects. Apparent and allocated \
+                             sizes are broken down into packfiles, loose objects, refs, hooks, \
+                             the index and the LFS cache, next to the size of the tracked \
+                             files in the working tree.",
+                        ),
+                )
+                .arg(
+                    Arg::new("extended")
+                        .long("extended")
+                        .action(ArgAction::SetTrue)
+                        .help("Add the history depth and width")
+                        .long_help(
+                            "Add the history depth, the commits on the longest path from HEAD \
+                             to a root commit, the longest path in the whole history, and the \
+                             DAG width, the most commits at one generation, which shows how \
+                             much work ran in parallel. Ignores --lang and --ext.",
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("support")
+                .about("show the support matrix of maintained release branches")
+                .long_about(
+                    "List the release branches (local or remote-tracking, matching \
+                     release/*, release-*, stable/* or *.x unless --pattern is given) with \
+                     their latest tag, commits since that tag, last activity and the fixes \
+                     on the default branch not backported to them. Use --format markdown to \
+                     paste the table into SECURITY.md or SUPPORT.md.",
+                )
+                .arg(
+                    Arg::new("pattern")
+                        .long("pattern")
+                        .short('p')
+                        .action(ArgAction::Append)
+                        .help("Release branch name pattern, repeatable"),
+                )
+                .arg(
+                    Arg::new("branch")
+                        .long("branch")
+                        .help("Branch fixes are made on (default: the default branch)"),
+                ),
+        )
+        .subcommand(
+            Command::new("teams")
+                .about("roll contributor statistics up to teams")
+                .long_about(
+                    "Roll commits, contributors, churn and file ownership up to teams. Teams \
+                     are read from the [teams] section of a mapping file, each team listing \
+                     member emails and email domains. A file is owned by the team with the \
+                     most commits touching it.",
+                )
+                .arg(
+                    Arg::new("teams-file")
+                        .long("teams-file")
+                        .default_value("teams.toml")
+                        .help("Team mapping file"),
+                ),
+        )
+        .subcommand(
+            Command::new("test-ratio")
+                .about("compare the test code with the source code")
+                .long_about(
+                    "Count the test and source files at HEAD and their code lines, and the \
+                     ratio of test to source per period (--bucket, default quarter), \
+                     measured at the last commit of each period. Files in a known language \
+                     matching a test glob are tests, the others source; set the globs with \
+                     --pattern or as patterns in the [tests] section of the config.",
+                )
+                .arg(
+                    Arg::new("pattern")
+                        .long("pattern")
+                        .short('p')
+                        .action(ArgAction::Append)
+                        .help("Test file glob such as 'tests/**' or '*_test.go', repeatable"),
+                ),
+        )
+        .subcommand(
+            Command::new("time-to-merge")
+                .about("show how long branches live before they are merged")
+                .long_about(
+                    "For every merge commit, measure the time from the earliest authored \
+                     commit it brought in to the merge, as a proxy for pull request cycle \
+                     time, and show the median, mean and slowest merges. Squash and rebase \
+                     merges leave no branch behind and are not counted.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of slowest merges shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("timezones")
+                .alias("tz")
+                .about("show committer UTC offsets overall and author offsets per contributor"),
+        )
+        .subcommand(
+            Command::new("tree")
+                .about("count files and directories at HEAD and list the largest files")
+                .long_about(
+                    "Walk the tree at HEAD and show the tracked files and directories, the \
+                     deepest path, the total and average file size, the symlinks, \
+                     executable files and submodules, and the largest files. This is what a \
+                     checkout holds now, unlike the repository size, which includes all \
+                     history.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of largest files shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("unreachable")
+                .about("show objects no ref or reflog reaches and the dangling commits among them")
+                .long_about(
+                    "Count and size the commits, trees, blobs and tags no reference, reflog \
+                     entry, worktree HEAD or index reaches, which git gc prunes once they \
+                     expire, and list the largest dangling commits, the tips of lost work, \
+                     with the unreachable commits and bytes behind each. Restore one with git \
+                     branch <name> <oid>.",
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .short('n')
+                        .value_parser(value_parser!(u16).range(1..))
+                        .default_value("10")
+                        .help("Number of dangling commits shown"),
+                ),
+        )
+        .subcommand(
+            Command::new("vendor")
+                .about("show vendored dependencies, when they were last updated and version drift"),
+        )
+        .subcommand(
+            Command::new("worktrees")
+                .alias("wt")
+                .about("show branch, local changes, last commit and disk usage per worktree"),
+        );
+    #[cfg(feature = "bench")]
+    let cli = cli.subcommand(bench::command());
+    cli
+}
+
+/// Word separating chained subcommands.
+const THEN: &str = "then";
+
+/// Splits `args` at each [`THEN`] into one invocation per subcommand, each
+/// with the top-level arguments of the first.
+fn chained_invocations(cli: &Command, args: &[OsString]) -> Vec<Vec<OsString>> {
+    let prefix = top_level_args(cli, args);
+    let mut invocations = vec![prefix.clone()];
+    for arg in &args[prefix.len()..] {
+        if arg == THEN {
+            invocations.push(prefix.clone());
+        } else {
+            invocations
+                .last_mut()
+                .expect("one invocation")
+                .push(arg.clone());
+        }
+    }
+    invocations
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = cli();
+    let args: Vec<OsString> = std::env::args_os().collect();
+    // The whole chain is parsed before anything runs, so a mistake in a
+    // later subcommand is reported up front.
+    let invocations: Vec<clap::ArgMatches> = chained_invocations(&cli, &args)
+        .into_iter()
+        .map(|args| cli.clone().get_matches_from(args))
+        .collect();
+    let cmd = &invocations[0];
+
+    let config_path = cmd
+        .get_one::<String>("config")
+        .expect("config has a default");
+    let config = Config::load(
+        Path::new(config_path),
+        cmd.value_source("config") == Some(ValueSource::CommandLine),
+    )?;
+
+    if let Some(secret) = config.get_str("anonymize", "secret")? {
+        set_pseudonym_secret(secret);
+    }
+
+    let repo = open_repository(cmd.get_one::<PathBuf>("path").map(PathBuf::as_path))?;
+    let mut ctx = Context::new(repo, config);
+
+    let mut reports = Vec::new();
+    let mut outputs: Vec<PathBuf> = Vec::new();
+    let mut format = None;
+    for matches in &invocations {
+        let (name, sub) = matches.subcommand().expect("a subcommand is required");
+        let mut report = Report::new("Git Repository Statistics");
+        report.command = name.to_string();
+        if sub.get_flag("progress-events") {
+            if sub.get_one::<Format>("format") != Some(&Format::Json) {
+                return Err("--progress-events requires --format json".into());
+            }
+            ctx.progress.set_enabled(true);
+        }
+        if sub.get_flag("no-write") {
+            ctx.no_write = true;
+        }
+        if let Some(dates) = sub.get_one::<DateFormat>("date-format") {
+            report.date_format = dates.clone();
         }
-        Some(("branches", _)) => {
-            let branch_count = get_branch_count(&repo)?;
-            println!("Git Repository Statistics");
-            println!("{}", "=".repeat(25));
-            println!("{:<20} {:>12}", "Branches:", branch_count);
+        run_subcommand(&mut ctx, name, sub, &mut report)?;
+        if sub.get_flag("insights") {
+            add_insights(&mut ctx, name, sub, &mut report)?;
         }
-        Some(("contributors", _)) => {
-            let contributor_count = get_contributor_count(&repo)?;
-            println!("Git Repository Statistics");
-            println!("{}", "=".repeat(25));
-            println!("{:<20} {:>12}", "Contributors:", contributor_count);
+        if sub.get_flag("repo-identity") {
+            add_repo_identity(&mut ctx, sub, &mut report)?;
         }
-        Some(("summary", _)) => {
-            let repo_size = get_repository_size(&repo)?;
-            println!("Git Repository Statistics");
-            println!("{}", "=".repeat(25));
-            println!("{:<20} {:>12}", "Repository Size:", repo_size);
+        report.snapshot = ctx.taken_snapshot().map(|s| s.id());
+        reports.push(report);
+        for output in sub.get_many::<PathBuf>("output").into_iter().flatten() {
+            if !outputs.contains(output) {
+                outputs.push(output.clone());
+            }
         }
-        _ => {}
-    };
+        format = sub.get_one::<Format>("format").copied().or(format);
+    }
+    write_reports(&reports, &outputs, format)?;
+    match ctx.failure {
+        Some(reason) => Err(reason.into()),
+        None => Ok(()),
+    }
+}
+
+/// Appends the three most notable findings, leaving out those suggesting the
+/// subcommand that built the report.
+fn add_insights(
+    ctx: &mut Context,
+    name: &str,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), git2::Error> {
+    let ai_tools = ctx.ai_tools()?;
+    let commits = ctx.commits(sub)?;
+    let insights = get_insights(&commits, &ai_tools, ctx.head_files()?, now());
+    let mut table = Table::new("Insights", &["Finding", "Next Step"]);
+    for insight in insights.iter().filter(|i| i.subcommand() != name).take(3) {
+        table.push(vec![
+            insight.finding.as_str().into(),
+            insight.command.into(),
+        ]);
+    }
+    if !table.rows.is_empty() {
+        report.table(table);
+    }
+    Ok(())
+}
+
+/// Writes the reports to every output, or to stdout without any.
+fn write_reports(
+    reports: &[Report],
+    outputs: &[PathBuf],
+    format: Option<Format>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = [PathBuf::from("-")];
+    let outputs = if outputs.is_empty() { &stdout } else { outputs };
+    for output in outputs {
+        if output.as_os_str() == "-" {
+            let format = format.unwrap_or(Format::Text);
+            let terminal = std::io::stdout().is_terminal();
+            let color = terminal && env::var_os("NO_COLOR").is_none();
+            if format == Format::Text && color {
+                print!("{}", render_terminal(reports));
+            } else if format == Format::Xlsx && terminal {
+                return Err(
+                    "not writing a workbook to a terminal, pass --output or redirect".into(),
+                );
+            } else {
+                std::io::stdout().write_all(&render_bytes(reports, format))?;
+            }
+            continue;
+        }
+        let format = Format::from_path(output).or(format).unwrap_or(Format::Text);
+        fs::write(output, render_bytes(reports, format))
+            .map_err(|e| format!("cannot write {}: {}", output.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Puts the repository identity block at the top of the report.
+fn add_repo_identity(
+    ctx: &mut Context,
+    sub: &clap::ArgMatches,
+    report: &mut Report,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commits = ctx.raw_commits()?.to_vec();
+    let snapshot = ctx.snapshot()?.clone();
+    let identity = get_repo_identity(&ctx.repo, &snapshot, &commits);
+    let origin = identity.origin.as_ref().map(|url| {
+        if sub.get_flag("anonymize") {
+            format!("origin-{}", pseudonym_id(url))
+        } else {
+            url.clone()
+        }
+    });
+    let roots: Vec<String> = identity.roots.iter().map(Oid::to_string).collect();
+    let mut fields = vec![
+        (
+            "Fingerprint".to_string(),
+            identity.fingerprint().unwrap_or("-".to_string()).into(),
+        ),
+        (
+            "Root Commits".to_string(),
+            if roots.is_empty() {
+                "-".to_string()
+            } else {
+                roots.join(", ")
+            }
+            .into(),
+        ),
+        (
+            "Default Branch".to_string(),
+            identity.default_branch.as_deref().unwrap_or("-").into(),
+        ),
+        (
+            "HEAD".to_string(),
+            match &identity.head {
+                Some((branch, oid)) => {
+                    format!("{} {}", branch.as_deref().unwrap_or("(detached)"), oid)
+                }
+                None => "-".to_string(),
+            }
+            .into(),
+        ),
+        (
+            "Origin".to_string(),
+            origin.as_deref().unwrap_or("-").into(),
+        ),
+    ];
+    let snapshot = ctx.snapshot()?;
+    fields.push((
+        "Ref Snapshot".to_string(),
+        format!("{} ({} refs)", snapshot.id(), snapshot.count).into(),
+    ));
+    report.sections.insert(0, Section::Fields(fields));
     Ok(())
 }
+
+/// Returns the program name and the arguments before the first subcommand.
+fn top_level_args(cli: &Command, args: &[OsString]) -> Vec<OsString> {
+    let mut end = 1;
+    while let Some(arg) = args.get(end).and_then(|a| a.to_str()) {
+        if !arg.starts_with('-') {
+            break;
+        }
+        // Options given as `--name value` consume the next argument too.
+        let takes_value = cli.get_arguments().any(|a| {
+            let named = a.get_long().is_some_and(|l| arg == format!("--{}", l))
+                || a.get_short().is_some_and(|s| arg == format!("-{}", s));
+            named && a.get_action().takes_values()
+        });
+        end += if takes_value { 2 } else { 1 };
+    }
+    args[..end.min(args.len())].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chained_invocations() {
+        let cli = cli();
+        let parse = |line: &str| -> Vec<(String, Option<String>)> {
+            let args: Vec<OsString> = line.split(' ').map(OsString::from).collect();
+            chained_invocations(&cli, &args)
+                .into_iter()
+                .map(|args| {
+                    let matches = cli.clone().get_matches_from(args);
+                    let (name, sub) = matches.subcommand().unwrap();
+                    (
+                        name.to_string(),
+                        sub.try_get_one::<String>("path").ok().flatten().cloned(),
+                    )
+                })
+                .collect()
+        };
+        let chained = |name: &str, path: Option<&str>| (name.to_string(), path.map(String::from));
+
+        // A subcommand's positional argument is never taken for the next one.
+        assert_eq!(
+            vec![chained("churn", None), chained("summary", None)],
+            parse("gno churn then summary")
+        );
+        assert_eq!(
+            vec![chained("owners", Some("src")), chained("summary", None)],
+            parse("gno owners src then summary")
+        );
+        assert_eq!(
+            vec![chained("churn", Some("summary"))],
+            parse("gno churn summary")
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! How long branches live before they are merged.
+//!
+//! A merge commit joins a branch whose unique commits are those reachable
+//! from the merged parent but not from the first parent. The branch's
+//! lifetime runs from the earliest author date among them to the merge, which
+//! approximates a pull request's cycle time when every pull request is merged
+//! with a merge commit. Squash and rebase merges leave no branch behind and
+//! are not seen.
+
+use git2::{Oid, Repository};
+
+use crate::{history::CommitInfo, message::subject, stats::percentile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedBranch {
+    pub merge: Oid,
+    /// Subject of the merge commit.
+    pub subject: String,
+    /// Commits the merge brought in.
+    pub commits: usize,
+    /// Seconds from the earliest authored commit to the merge.
+    pub lifetime: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeTimes {
+    /// Merged branches, longest-lived first.
+    pub branches: Vec<MergedBranch>,
+    /// Lifetimes in seconds, sorted ascending.
+    lifetimes: Vec<i64>,
+}
+
+impl MergeTimes {
+    pub fn percentile(&self, p: f64) -> Option<i64> {
+        percentile(&self.lifetimes, p)
+    }
+
+    pub fn mean(&self) -> Option<i64> {
+        (!self.lifetimes.is_empty())
+            .then(|| self.lifetimes.iter().sum::<i64>() / self.lifetimes.len() as i64)
+    }
+}
+
+/// Measures the lifetime of the branch each merge commit among `commits`
+/// joined. Octopus merges count the earliest commit of all merged parents.
+/// Merges that brought in nothing new, and merges whose parents are missing
+/// as in shallow clones, are left out.
+pub fn get_merge_times(
+    repo: &Repository,
+    commits: &[CommitInfo],
+) -> Result<MergeTimes, git2::Error> {
+    let mut times = MergeTimes::default();
+    for commit in commits.iter().filter(|c| c.is_merge()) {
+        let mut revwalk = repo.revwalk()?;
+        let mut complete = true;
+        for parent in &commit.parents[1..] {
+            complete &= revwalk.push(*parent).is_ok();
+        }
+        complete &= revwalk.hide(commit.parents[0]).is_ok();
+        if !complete {
+            continue;
+        }
+        let (mut count, mut first) = (0, None::<i64>);
+        for oid in revwalk {
+            let authored = repo.find_commit(oid?)?.author().when().seconds();
+            count += 1;
+            first = Some(first.map_or(authored, |f| f.min(authored)));
+        }
+        if let Some(first) = first {
+            times.branches.push(MergedBranch {
+                merge: commit.oid,
+                subject: subject(&commit.message).to_string(),
+                commits: count,
+                lifetime: (commit.time - first).max(0),
+            });
+        }
+    }
+    times.lifetimes = times.branches.iter().map(|b| b.lifetime).collect();
+    times.lifetimes.sort_unstable();
+    times
+        .branches
+        .sort_by(|a, b| b.lifetime.cmp(&a.lifetime).then(a.merge.cmp(&b.merge)));
+    Ok(times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_merge_times() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        let base = t.commit_as("A", "a@x.com", day, "init", &[("a.txt", "1")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", Some(base));
+        t.commit_as("A", "a@x.com", 2 * day, "start", &[("f.txt", "1")]);
+        let feature = t.commit_as("A", "a@x.com", 3 * day, "finish", &[("f.txt", "2")]);
+        t.checkout(&main, None);
+        t.commit_as("A", "a@x.com", 4 * day, "two", &[("a.txt", "2")]);
+        let merge = t.merge(feature, 6 * day, "Merge feature");
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let times = get_merge_times(&t.repo, &commits).unwrap();
+        assert_eq!(
+            vec![MergedBranch {
+                merge,
+                subject: "Merge feature".to_string(),
+                commits: 2,
+                lifetime: 4 * day,
+            }],
+            times.branches
+        );
+        assert_eq!(Some(4 * day), times.percentile(50.0));
+        assert_eq!(Some(4 * day), times.mean());
+    }
+}
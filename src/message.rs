@@ -0,0 +1,157 @@
+//! Parsing of commit messages.
+
+use crate::{history::CommitInfo, stats::percentile};
+
+/// Subject length beyond which `git log --oneline` and most tools truncate.
+pub const SUBJECT_LIMIT: usize = 72;
+
+/// Upper bounds of the subject length buckets, in characters.
+pub const SUBJECT_BUCKETS: [usize; 4] = [24, 50, SUBJECT_LIMIT, 100];
+
+/// Returns the first line of a message, trimmed.
+pub fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("").trim()
+}
+
+/// Returns whether a message has text beyond its subject, ignoring trailers.
+pub fn has_body(message: &str) -> bool {
+    let trailer_count = trailers(message).len();
+    let paragraphs: Vec<&str> = message
+        .trim_end()
+        .split("\n\n")
+        .skip(1)
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    paragraphs.len() > usize::from(trailer_count > 0)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageStats {
+    /// Commits considered, which excludes merges.
+    pub commits: usize,
+    /// Subject lengths in characters, sorted ascending.
+    pub subject_lengths: Vec<usize>,
+    /// Subjects longer than [`SUBJECT_LIMIT`].
+    pub long_subjects: usize,
+    pub with_body: usize,
+    /// Messages of a single word, such as `fix` or `wip`.
+    pub one_word: usize,
+}
+
+impl MessageStats {
+    pub fn percentile(&self, p: f64) -> Option<usize> {
+        percentile(&self.subject_lengths, p)
+    }
+
+    /// Counts subjects per [`SUBJECT_BUCKETS`] bound, with a last count for
+    /// longer subjects.
+    pub fn buckets(&self) -> [usize; SUBJECT_BUCKETS.len() + 1] {
+        let mut counts = [0; SUBJECT_BUCKETS.len() + 1];
+        for length in &self.subject_lengths {
+            let index = SUBJECT_BUCKETS
+                .iter()
+                .position(|bound| length <= bound)
+                .unwrap_or(SUBJECT_BUCKETS.len());
+            counts[index] += 1;
+        }
+        counts
+    }
+}
+
+/// Measures the messages of all non-merge commits. Merge messages are
+/// written by git, not by the committer.
+pub fn get_message_stats(commits: &[CommitInfo]) -> MessageStats {
+    let mut stats = MessageStats::default();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        let subject = subject(&commit.message);
+        let length = subject.chars().count();
+        stats.commits += 1;
+        stats.subject_lengths.push(length);
+        stats.long_subjects += usize::from(length > SUBJECT_LIMIT);
+        stats.with_body += usize::from(has_body(&commit.message));
+        stats.one_word += usize::from(commit.message.split_whitespace().count() == 1);
+    }
+    stats.subject_lengths.sort_unstable();
+    stats
+}
+
+/// Returns the `Key: value` trailers from the last paragraph of a message,
+/// in order. A paragraph only counts as a trailer block if every non-empty
+/// line is a trailer or the continuation of one.
+pub fn trailers(message: &str) -> Vec<(&str, &str)> {
+    let paragraphs: Vec<&str> = message
+        .trim_end()
+        .split("\n\n")
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    // The subject is never a trailer block.
+    if paragraphs.len() < 2 {
+        return Vec::new();
+    }
+    let Some(last) = paragraphs.last() else {
+        return Vec::new();
+    };
+
+    let mut trailers = Vec::new();
+    for line in last.lines() {
+        if line.starts_with(char::is_whitespace) && !trailers.is_empty() {
+            continue;
+        }
+        match parse_trailer(line) {
+            Some(trailer) => trailers.push(trailer),
+            None => return Vec::new(),
+        }
+    }
+    trailers
+}
+
+fn parse_trailer(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid_key.then(|| (key, value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailers() {
+        let message = "Add feature\n\nSome body: not a trailer block\nbecause of this line\n\n\
+                       Signed-off-by: A <a@x.com>\nCo-authored-by: B <b@x.com>\n";
+        assert_eq!(
+            vec![
+                ("Signed-off-by", "A <a@x.com>"),
+                ("Co-authored-by", "B <b@x.com>")
+            ],
+            trailers(message)
+        );
+        assert!(trailers("Signed-off-by: only a subject").is_empty());
+        assert!(trailers("Subject\n\nJust prose here.\n").is_empty());
+    }
+
+    #[test]
+    fn test_message_stats() {
+        assert!(has_body(
+            "Fix\n\nWhy it broke.\n\nSigned-off-by: A <a@x.com>\n"
+        ));
+        assert!(!has_body("Fix\n\nSigned-off-by: A <a@x.com>\n"));
+        assert!(!has_body("Fix\n"));
+
+        let commits: Vec<CommitInfo> = ["wip", "Fix the parser\n\nIt broke.", &"x".repeat(80)]
+            .iter()
+            .map(|m| CommitInfo {
+                message: m.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let stats = get_message_stats(&commits);
+        assert_eq!(vec![3, 14, 80], stats.subject_lengths);
+        assert_eq!(
+            (1, 1, 2),
+            (stats.long_subjects, stats.with_body, stats.one_word)
+        );
+        assert_eq!(Some(14), stats.percentile(50.0));
+        assert_eq!([2, 0, 0, 1, 0], stats.buckets());
+    }
+}
@@ -0,0 +1,154 @@
+//! Contributor milestones reached recently, for recognition posts.
+//!
+//! A contributor reaches a milestone with their first commit, with each of
+//! their [`COMMIT_MILESTONES`]th, on each yearly anniversary of their first
+//! commit, and by becoming the top contributor of a month that someone else
+//! topped the month before. A month's top contributor is known once the
+//! month is over, so that milestone falls on the first day of the next.
+//! Only milestones on the last days asked for are listed: a scheduled run
+//! covering the days since the previous one announces each exactly once.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{bucket::Bucketing, date::add_months, history::CommitInfo, identity::contributor_key};
+
+/// Commit counts worth celebrating.
+pub const COMMIT_MILESTONES: [usize; 5] = [10, 50, 100, 500, 1_000];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Milestone {
+    pub day: i64,
+    pub contributor: String,
+    pub milestone: String,
+}
+
+/// Returns the milestones of `commits` falling from `since` to `today`,
+/// both days included, oldest first.
+pub fn get_milestones(commits: &[CommitInfo], since: i64, today: i64) -> Vec<Milestone> {
+    let mut ordered: Vec<&CommitInfo> = commits.iter().collect();
+    ordered.sort_by_key(|c| (c.time, c.oid));
+    let mut milestones = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut first_days: BTreeMap<String, i64> = BTreeMap::new();
+    let mut months: BTreeMap<i64, HashMap<String, usize>> = BTreeMap::new();
+    for commit in ordered {
+        let contributor = contributor_key(commit);
+        let count = counts.entry(contributor.clone()).or_default();
+        *count += 1;
+        let milestone = match *count {
+            1 => Some("first commit".to_string()),
+            n if COMMIT_MILESTONES.contains(&n) => Some(format!("{}th commit", n)),
+            _ => None,
+        };
+        if let Some(milestone) = milestone {
+            milestones.push(Milestone {
+                day: commit.day(),
+                contributor: contributor.clone(),
+                milestone,
+            });
+        }
+        first_days
+            .entry(contributor.clone())
+            .or_insert(commit.day());
+        *months
+            .entry(Bucketing::Month.start_of(commit.day()))
+            .or_default()
+            .entry(contributor)
+            .or_default() += 1;
+    }
+
+    for (contributor, first) in &first_days {
+        for years in 1.. {
+            let day = add_months(*first, 12 * years);
+            if day > today {
+                break;
+            }
+            let milestone = match years {
+                1 => "1 year since the first commit".to_string(),
+                n => format!("{} years since the first commit", n),
+            };
+            milestones.push(Milestone {
+                day,
+                contributor: contributor.clone(),
+                milestone,
+            });
+        }
+    }
+
+    let mut previous: Option<String> = None;
+    for (month, authors) in &months {
+        let top = authors
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(author, _)| author.clone());
+        let end = Bucketing::Month.next(*month);
+        if let Some(top) = &top
+            && previous.as_ref().is_some_and(|p| p != top)
+        {
+            milestones.push(Milestone {
+                day: end,
+                contributor: top.clone(),
+                milestone: format!("top contributor of {}", Bucketing::Month.label(*month)),
+            });
+        }
+        previous = top;
+    }
+
+    milestones.retain(|m| m.day >= since && m.day <= today);
+    // Stable, so milestones of one day stay in the order they were reached.
+    milestones.sort_by_key(|m| m.day);
+    milestones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::{SECONDS_PER_DAY, days_from_civil},
+        history::collect_commits,
+        progress::Progress,
+        testutil::TestRepo,
+    };
+
+    #[test]
+    fn test_milestones() {
+        let day = |y, m, d| days_from_civil(y, m, d);
+        let at = |days: i64| days * SECONDS_PER_DAY + 3600;
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", at(day(2023, 3, 5)), "first", &[]);
+        for i in 0..9 {
+            t.commit_as("B", "b@x.com", at(day(2024, 2, 1) + i), "b", &[]);
+        }
+        for i in 0..2 {
+            t.commit_as("A", "a@x.com", at(day(2024, 3, 1) + i), "a", &[]);
+        }
+        t.commit_as("B", "b@x.com", at(day(2024, 3, 5)), "tenth", &[]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let milestones: Vec<(i64, String, String)> =
+            get_milestones(&commits, day(2024, 2, 1), day(2024, 4, 1))
+                .into_iter()
+                .map(|m| (m.day, m.contributor, m.milestone))
+                .collect();
+        let a = "A <a@x.com>".to_string();
+        let b = "B <b@x.com>".to_string();
+        assert_eq!(
+            vec![
+                (day(2024, 2, 1), b.clone(), "first commit".to_string()),
+                (
+                    day(2024, 3, 1),
+                    b.clone(),
+                    "top contributor of 2024-02".to_string()
+                ),
+                (day(2024, 3, 5), b.clone(), "10th commit".to_string()),
+                (
+                    day(2024, 3, 5),
+                    a.clone(),
+                    "1 year since the first commit".to_string()
+                ),
+                (day(2024, 4, 1), a, "top contributor of 2024-03".to_string()),
+            ],
+            milestones
+        );
+    }
+}
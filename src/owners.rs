@@ -0,0 +1,234 @@
+//! Ownership of the lines at HEAD, per directory.
+//!
+//! Lines are attributed either by blaming every file, which is exact but
+//! slow, or by crediting all of a file's lines to the author of the last
+//! commit that touched it.
+
+use std::collections::HashMap;
+
+use git2::{BlameOptions, Oid, Repository};
+
+use crate::{
+    churn::get_changed_paths,
+    history::{CommitInfo, is_missing},
+    identity::{contributor_key, signature_key},
+    progress::Progress,
+    tree::TreeFile,
+};
+
+/// Lines per contributor in one file, most lines first.
+pub type FileLines = (String, Vec<(String, usize)>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryOwnership {
+    /// Directory path, or `.` for files at the root.
+    pub directory: String,
+    pub lines: usize,
+    /// Lines per contributor, most lines first.
+    pub owners: Vec<(String, usize)>,
+}
+
+/// Returns whether `path` is `prefix` or lies below it. An empty prefix
+/// matches every path.
+pub fn in_prefix(path: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn line_count(content: &[u8]) -> usize {
+    let newlines = content.iter().filter(|b| **b == b'\n').count();
+    newlines + usize::from(content.last().is_some_and(|b| *b != b'\n'))
+}
+
+fn sorted(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+/// Blames every text file as of `head`, crediting lines to the contributor
+/// of the commit that last changed them, or to its recorded author,
+/// pseudonymized when `anonymize` is set, for commits missing from
+/// `commits`. Files whose blob or history has gone missing are left out.
+/// Each file is reported to `progress`.
+pub fn blame_lines(
+    repo: &Repository,
+    head: Option<Oid>,
+    files: &[&TreeFile],
+    commits: &[CommitInfo],
+    anonymize: bool,
+    progress: &mut Progress,
+) -> Result<Vec<FileLines>, git2::Error> {
+    let authors: HashMap<Oid, String> = commits
+        .iter()
+        .map(|c| (c.oid, contributor_key(c)))
+        .collect();
+    let mut result = Vec::new();
+    progress.begin("blame", Some(files.len()));
+    for file in files {
+        progress.tick();
+        let mut options = BlameOptions::new();
+        options.use_mailmap(true);
+        if let Some(head) = head {
+            options.newest_commit(head);
+        }
+        let blame = match repo.find_blob(file.oid) {
+            Ok(blob) if blob.is_binary() => continue,
+            Ok(_) => repo.blame_file(std::path::Path::new(&file.path), Some(&mut options)),
+            Err(e) => Err(e),
+        };
+        let blame = match blame {
+            Ok(blame) => blame,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for hunk in blame.iter() {
+            let author = match authors.get(&hunk.final_commit_id()) {
+                Some(author) => author.clone(),
+                None => signature_key(&hunk.final_signature(), anonymize),
+            };
+            *counts.entry(author).or_default() += hunk.lines_in_hunk();
+        }
+        result.push((file.path.clone(), sorted(counts)));
+    }
+    progress.end();
+    Ok(result)
+}
+
+/// Credits every line of each text file to the author of the last commit
+/// that touched it.
+pub fn last_touched_lines(
+    repo: &Repository,
+    files: &[&TreeFile],
+    commits: &[CommitInfo],
+) -> Result<Vec<FileLines>, git2::Error> {
+    let mut pending: HashMap<&str, &TreeFile> =
+        files.iter().map(|f| (f.path.as_str(), *f)).collect();
+    let mut newest: Vec<&CommitInfo> = commits.iter().collect();
+    newest.sort_by_key(|c| std::cmp::Reverse(c.time));
+
+    let mut result = Vec::new();
+    for commit in newest {
+        if pending.is_empty() {
+            break;
+        }
+        for path in get_changed_paths(repo, commit.oid)? {
+            let Some(file) = pending.remove(path.as_str()) else {
+                continue;
+            };
+            let blob = repo.find_blob(file.oid)?;
+            if !blob.is_binary() {
+                let lines = line_count(blob.content());
+                result.push((path, vec![(contributor_key(commit), lines)]));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Totals per-file line ownership per contributor, most lines first.
+pub fn get_owned_lines(files: &[FileLines]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, owners) in files {
+        for (owner, lines) in owners {
+            *counts.entry(owner.clone()).or_default() += lines;
+        }
+    }
+    sorted(counts)
+}
+
+/// Aggregates per-file line ownership by parent directory, ordered by path.
+pub fn get_directory_ownership(files: &[FileLines]) -> Vec<DirectoryOwnership> {
+    let mut directories: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for (path, owners) in files {
+        let directory = path.rsplit_once('/').map_or(".", |(dir, _)| dir);
+        let counts = directories.entry(directory.to_string()).or_default();
+        for (owner, lines) in owners {
+            *counts.entry(owner.clone()).or_default() += lines;
+        }
+    }
+    let mut directories: Vec<DirectoryOwnership> = directories
+        .into_iter()
+        .map(|(directory, counts)| DirectoryOwnership {
+            directory,
+            lines: counts.values().sum(),
+            owners: sorted(counts),
+        })
+        .collect();
+    directories.sort_by(|a, b| a.directory.cmp(&b.directory));
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_ownership() {
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            1,
+            "add",
+            &[("src/a.rs", "1\n2\n3\n"), ("README", "r\n")],
+        );
+        t.commit_as("B", "b@x.com", 2, "edit", &[("src/a.rs", "1\n2\nthree\n")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let head = get_head_files(&t.repo, None).unwrap();
+        let files: Vec<&TreeFile> = head.iter().filter(|f| in_prefix(&f.path, "src")).collect();
+        assert_eq!(1, files.len());
+
+        let blamed = get_directory_ownership(
+            &blame_lines(
+                &t.repo,
+                None,
+                &files,
+                &commits,
+                false,
+                &mut Progress::default(),
+            )
+            .unwrap(),
+        );
+        assert_eq!("src", blamed[0].directory);
+        assert_eq!(
+            vec![
+                ("A <a@x.com>".to_string(), 2),
+                ("B <b@x.com>".to_string(), 1)
+            ],
+            blamed[0].owners
+        );
+
+        let fast = last_touched_lines(&t.repo, &files, &commits).unwrap();
+        assert_eq!(
+            vec![("src/a.rs".to_string(), vec![("B <b@x.com>".to_string(), 3)])],
+            fast
+        );
+        assert_eq!(vec![("B <b@x.com>".to_string(), 3)], get_owned_lines(&fast));
+        assert!(!in_prefix("srcx/a.rs", "src"));
+    }
+
+    #[test]
+    fn test_blame_anonymizes_missing_commits() {
+        let t = TestRepo::new();
+        t.commit_as("A", "a@x.com", 1, "add", &[("a.rs", "1\n")]);
+
+        let head = get_head_files(&t.repo, None).unwrap();
+        let files: Vec<&TreeFile> = head.iter().collect();
+        // Commits filtered out of the history are credited to their blamed
+        // author.
+        let lines =
+            blame_lines(&t.repo, None, &files, &[], true, &mut Progress::default()).unwrap();
+        let (author, count) = &lines[0].1[0];
+        assert_eq!(1, *count);
+        assert!(author.starts_with("Contributor ") && !author.contains("<a@"));
+    }
+}
@@ -0,0 +1,271 @@
+//! Heuristic estimate of the operating systems contributors commit from.
+//!
+//! Each commit is scanned for platform hints, each counted at most once per
+//! commit:
+//!
+//! - added lines ending in CRLF (Windows),
+//! - OS metadata files such as `.DS_Store` (macOS) or `Thumbs.db` (Windows),
+//! - case-only renames, typical on case-insensitive filesystems (Windows, macOS),
+//! - newly added executable files, which Windows checkouts rarely produce
+//!   (macOS, Linux),
+//! - machine host names in unconfigured author emails, such as
+//!   `user@DESKTOP-1A2B3C` (Windows) or `user@Jane-MacBook-Pro.local` (macOS).
+
+use std::collections::HashMap;
+
+use git2::{Delta, DiffDelta, FileMode, Repository};
+
+use crate::{history::CommitInfo, identity::contributor_key};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl Platform {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Platform::Windows => "Windows",
+            Platform::MacOs => "macOS",
+            Platform::Linux => "Linux",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+/// Number of commits carrying a hint for each platform.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlatformSignals {
+    pub windows: usize,
+    pub macos: usize,
+    pub linux: usize,
+}
+
+impl PlatformSignals {
+    fn add(&mut self, platforms: &[Platform]) {
+        for platform in platforms {
+            match platform {
+                Platform::Windows => self.windows += 1,
+                Platform::MacOs => self.macos += 1,
+                Platform::Linux => self.linux += 1,
+            }
+        }
+    }
+
+    /// Returns the most likely platform and how confident the guess is, or
+    /// `None` without any hints or on a tie.
+    pub fn estimate(&self) -> Option<(Platform, Confidence)> {
+        let mut scores = [
+            (Platform::Windows, self.windows),
+            (Platform::MacOs, self.macos),
+            (Platform::Linux, self.linux),
+        ];
+        scores.sort_by_key(|s| std::cmp::Reverse(s.1));
+        let (platform, best) = scores[0];
+        if best == 0 || best == scores[1].1 {
+            return None;
+        }
+        let total = self.windows + self.macos + self.linux;
+        let dominance = best as f64 / total as f64;
+        let confidence = if best >= 5 && dominance >= 0.8 {
+            Confidence::High
+        } else if best >= 2 && dominance >= 0.6 {
+            Confidence::Medium
+        } else {
+            Confidence::Low
+        };
+        Some((platform, confidence))
+    }
+}
+
+/// Platforms hinted at by the host part of an author email.
+fn email_hints(email: &str) -> &'static [Platform] {
+    let Some((_, host)) = email.rsplit_once('@') else {
+        return &[];
+    };
+    let host = host.to_lowercase();
+    if host.starts_with("desktop-") || host.starts_with("laptop-") || host.ends_with(".lan.windows")
+    {
+        &[Platform::Windows]
+    } else if host.contains("macbook")
+        || host.contains("imac")
+        || host.contains("mac-mini")
+        || (host.ends_with(".local") && host.contains("mac"))
+    {
+        &[Platform::MacOs]
+    } else if host.ends_with(".localdomain") || host == "ubuntu" || host.contains("-thinkpad") {
+        &[Platform::Linux]
+    } else {
+        &[]
+    }
+}
+
+fn file_name(delta: &DiffDelta<'_>) -> Option<String> {
+    let path = delta.new_file().path()?;
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Returns the platform hints found in a single commit.
+fn commit_hints(repo: &Repository, commit: &CommitInfo) -> Result<Vec<Platform>, git2::Error> {
+    let mut hints: Vec<Platform> = email_hints(&commit.author_email).to_vec();
+
+    let git_commit = repo.find_commit(commit.oid)?;
+    let tree = git_commit.tree()?;
+    let parent_tree = match git_commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+    let mut added = Vec::new();
+    let mut deleted = Vec::new();
+    let mut executable = false;
+    for delta in diff.deltas() {
+        match file_name(&delta).as_deref() {
+            Some(".DS_Store") | Some("._.DS_Store") => hints.push(Platform::MacOs),
+            Some("Thumbs.db") | Some("desktop.ini") => hints.push(Platform::Windows),
+            _ => {}
+        }
+        match delta.status() {
+            Delta::Added => {
+                executable |= delta.new_file().mode() == FileMode::BlobExecutable;
+                added.extend(
+                    delta
+                        .new_file()
+                        .path()
+                        .map(|p| p.to_string_lossy().into_owned()),
+                );
+            }
+            Delta::Deleted => {
+                deleted.extend(
+                    delta
+                        .old_file()
+                        .path()
+                        .map(|p| p.to_string_lossy().into_owned()),
+                );
+            }
+            _ => {}
+        }
+    }
+    let case_only_rename = added.iter().any(|a| {
+        deleted
+            .iter()
+            .any(|d| d != a && d.to_lowercase() == a.to_lowercase())
+    });
+    if case_only_rename {
+        hints.extend([Platform::Windows, Platform::MacOs]);
+    }
+    if executable {
+        hints.extend([Platform::MacOs, Platform::Linux]);
+    }
+
+    let mut crlf = false;
+    let scanned = diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |_, _, line| {
+            if line.origin() == '+' && line.content().ends_with(b"\r\n") {
+                crlf = true;
+                // Stop scanning this commit once one CRLF line is found.
+                return false;
+            }
+            true
+        }),
+    );
+    // Returning false from the callback aborts the diff with an error.
+    if !crlf {
+        scanned?;
+    }
+    if crlf {
+        hints.push(Platform::Windows);
+    }
+
+    hints.sort_by_key(|p| p.label());
+    hints.dedup();
+    Ok(hints)
+}
+
+/// Collects platform hints per contributor, ordered by number of hints.
+pub fn get_platform_signals(
+    repo: &Repository,
+    commits: &[CommitInfo],
+) -> Result<Vec<(String, PlatformSignals)>, git2::Error> {
+    let mut authors: HashMap<String, PlatformSignals> = HashMap::new();
+    for commit in commits {
+        let hints = commit_hints(repo, commit)?;
+        authors
+            .entry(contributor_key(commit))
+            .or_default()
+            .add(&hints);
+    }
+    let mut authors: Vec<_> = authors.into_iter().collect();
+    let total = |s: &PlatformSignals| s.windows + s.macos + s.linux;
+    authors.sort_by(|a, b| total(&b.1).cmp(&total(&a.1)).then(a.0.cmp(&b.0)));
+    Ok(authors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_estimate() {
+        let signals = PlatformSignals {
+            windows: 6,
+            macos: 1,
+            linux: 0,
+        };
+        assert_eq!(
+            Some((Platform::Windows, Confidence::High)),
+            signals.estimate()
+        );
+        let tie = PlatformSignals {
+            windows: 1,
+            macos: 1,
+            linux: 0,
+        };
+        assert_eq!(None, tie.estimate());
+        assert_eq!(None, PlatformSignals::default().estimate());
+    }
+
+    #[test]
+    fn test_platform_signals() {
+        let t = TestRepo::new();
+        t.commit_as("w", "w@corp.com", 1, "crlf", &[("a.txt", "one\r\ntwo\r\n")]);
+        t.commit_as("m", "m@corp.com", 2, "mac", &[(".DS_Store", "junk")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let signals: HashMap<_, _> = get_platform_signals(&t.repo, &commits)
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        assert_eq!(1, signals["w <w@corp.com>"].windows);
+        assert_eq!(
+            Some(Platform::Windows),
+            signals["w <w@corp.com>"].estimate().map(|e| e.0)
+        );
+        assert_eq!(&[Platform::Windows], email_hints("w@DESKTOP-1A2B3C"));
+        assert_eq!(1, signals["m <m@corp.com>"].macos);
+    }
+}
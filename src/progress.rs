@@ -0,0 +1,138 @@
+//! Progress events for wrappers that draw their own progress bars.
+//!
+//! With `--format json --progress-events`, each long walk reports on stderr
+//! as JSON lines, one object per event:
+//!
+//! ```text
+//! {"event":"progress","phase":"filter","done":120,"total":480,"percent":25.0,"eta_secs":3.1}
+//! {"event":"done","phase":"filter","done":480,"elapsed_secs":4.2}
+//! ```
+//!
+//! `total`, `percent` and `eta_secs` are `null` while the amount of work is
+//! not known up front. Events are throttled so that a fast walk does not
+//! flood the reader.
+
+use std::time::{Duration, Instant};
+
+use crate::report::json_string;
+
+/// Minimum time between two progress events of one phase.
+const INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+struct Phase {
+    name: String,
+    total: Option<usize>,
+    done: usize,
+    started: Instant,
+    last_event: Option<Instant>,
+}
+
+/// Emits progress events when enabled and does nothing otherwise.
+#[derive(Debug, Default)]
+pub struct Progress {
+    enabled: bool,
+    phase: Option<Phase>,
+}
+
+/// Formats a progress event. `elapsed` is the time since the phase began.
+pub fn progress_event(phase: &str, done: usize, total: Option<usize>, elapsed: Duration) -> String {
+    let null = || "null".to_string();
+    let (total_json, percent, eta) = match total {
+        Some(total) if total > 0 => {
+            let fraction = (done as f64 / total as f64).min(1.0);
+            let eta = (done > 0)
+                .then(|| elapsed.as_secs_f64() * (1.0 - fraction) / fraction)
+                .map_or_else(null, |eta| format!("{:.1}", eta));
+            (total.to_string(), format!("{:.1}", fraction * 100.0), eta)
+        }
+        Some(total) => (total.to_string(), "100.0".to_string(), "0.0".to_string()),
+        None => (null(), null(), null()),
+    };
+    format!(
+        "{{\"event\":\"progress\",\"phase\":{},\"done\":{},\"total\":{},\"percent\":{},\"eta_secs\":{}}}",
+        json_string(phase),
+        done,
+        total_json,
+        percent,
+        eta
+    )
+}
+
+/// Formats the event ending a phase.
+pub fn done_event(phase: &str, done: usize, elapsed: Duration) -> String {
+    format!(
+        "{{\"event\":\"done\",\"phase\":{},\"done\":{},\"elapsed_secs\":{:.1}}}",
+        json_string(phase),
+        done,
+        elapsed.as_secs_f64()
+    )
+}
+
+impl Progress {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Starts a phase of `total` steps, if known, ending any phase in
+    /// progress.
+    pub fn begin(&mut self, name: &str, total: Option<usize>) {
+        self.end();
+        if self.enabled {
+            self.phase = Some(Phase {
+                name: name.to_string(),
+                total,
+                done: 0,
+                started: Instant::now(),
+                last_event: None,
+            });
+        }
+    }
+
+    /// Records one finished step.
+    pub fn tick(&mut self) {
+        let Some(phase) = &mut self.phase else {
+            return;
+        };
+        phase.done += 1;
+        let now = Instant::now();
+        if phase.last_event.is_none_or(|last| now - last >= INTERVAL) {
+            phase.last_event = Some(now);
+            eprintln!(
+                "{}",
+                progress_event(&phase.name, phase.done, phase.total, now - phase.started)
+            );
+        }
+    }
+
+    /// Ends the phase in progress.
+    pub fn end(&mut self) {
+        if let Some(phase) = self.phase.take() {
+            eprintln!(
+                "{}",
+                done_event(&phase.name, phase.done, phase.started.elapsed())
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_events() {
+        assert_eq!(
+            "{\"event\":\"progress\",\"phase\":\"filter\",\"done\":25,\"total\":100,\"percent\":25.0,\"eta_secs\":6.0}",
+            progress_event("filter", 25, Some(100), Duration::from_secs(2))
+        );
+        assert_eq!(
+            "{\"event\":\"progress\",\"phase\":\"history\",\"done\":7,\"total\":null,\"percent\":null,\"eta_secs\":null}",
+            progress_event("history", 7, None, Duration::from_secs(2))
+        );
+        assert_eq!(
+            "{\"event\":\"done\",\"phase\":\"history\",\"done\":7,\"elapsed_secs\":1.5}",
+            done_event("history", 7, Duration::from_millis(1_500))
+        );
+    }
+}
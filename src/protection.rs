@@ -0,0 +1,224 @@
+//! Drift between the protection configured for branches and their history.
+//!
+//! The rules are read from the `[protection]` section of the config, set the
+//! way a forge's branch protection is:
+//!
+//! ```toml
+//! [protection]
+//! branches = ["main"]
+//! require_signed = true
+//! require_review = true
+//! allow_force_push = false
+//! ```
+//!
+//! Each rule is checked against the branch's first-parent chain and reflogs.
+//! An unsigned commit breaks `require_signed`, a direct push as
+//! [`classify_landing`] tells them apart breaks `require_review`, and a
+//! reflog entry moving the branch or one of its remote-tracking branches to
+//! a commit that does not descend from the previous tip is a force push.
+//! Only the reflogs of this clone are read, so a force push made elsewhere
+//! shows once it has been fetched here.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository};
+
+use crate::{
+    config::Config,
+    graph::Generations,
+    history::{CommitInfo, is_missing},
+    landing::{Landing, classify_landing, first_parent_chain},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtectionRules {
+    /// Branches the rules apply to; the default branch when empty.
+    pub branches: Vec<String>,
+    pub require_signed: bool,
+    pub require_review: bool,
+    pub allow_force_push: bool,
+}
+
+impl ProtectionRules {
+    /// Reads the rules from the `[protection]` section, if there is one.
+    /// Force pushes are forbidden unless allowed, as on the forges.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        if config.section("protection").is_empty() {
+            return Ok(None);
+        }
+        let flag = |key| config.get_bool("protection", key);
+        Ok(Some(ProtectionRules {
+            branches: config
+                .get_str_array("protection", "branches")?
+                .unwrap_or_default(),
+            require_signed: flag("require_signed")?.unwrap_or(false),
+            require_review: flag("require_review")?.unwrap_or(false),
+            allow_force_push: flag("allow_force_push")?.unwrap_or(false),
+        }))
+    }
+
+    /// Returns the rules in force and how often `history` breaks each.
+    pub fn drift(&self, history: &BranchHistory) -> Vec<(&'static str, usize)> {
+        let mut drift = Vec::new();
+        if self.require_signed {
+            drift.push(("signed commits", history.unsigned));
+        }
+        if self.require_review {
+            drift.push(("review", history.direct));
+        }
+        if !self.allow_force_push {
+            drift.push(("no force pushes", history.force_pushes));
+        }
+        drift
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchHistory {
+    pub branch: String,
+    /// Commits on the first-parent chain.
+    pub commits: usize,
+    pub unsigned: usize,
+    pub direct: usize,
+    /// Reflog entries rewriting the branch or its remote-tracking branches.
+    pub force_pushes: usize,
+}
+
+/// Counts the reflog entries of `name` that moved it to a commit not
+/// descending from the one before. Entries whose commits have gone missing
+/// are not judged.
+fn count_rewrites(
+    repo: &Repository,
+    generations: &mut Generations,
+    name: &str,
+) -> Result<usize, git2::Error> {
+    let reflog = repo.reflog(name)?;
+    let mut rewrites = 0;
+    for entry in reflog.iter() {
+        let (old, new) = (entry.id_old(), entry.id_new());
+        if old.is_zero() || new.is_zero() || old == new {
+            continue;
+        }
+        match generations.contains(repo, new, old) {
+            Ok(true) => {}
+            Ok(false) => rewrites += 1,
+            Err(e) if is_missing(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(rewrites)
+}
+
+/// Reads what the history of `branch`, at `tip`, says about its protection.
+/// Commits missing from `commits` are skipped, and landings are classified
+/// on the identities in `recorded`, as for [`get_landing_stats`].
+///
+/// [`get_landing_stats`]: crate::landing::get_landing_stats
+pub fn get_branch_history(
+    repo: &Repository,
+    generations: &mut Generations,
+    branch: &str,
+    tip: Oid,
+    commits: &[CommitInfo],
+    recorded: &[CommitInfo],
+) -> Result<BranchHistory, git2::Error> {
+    let by_oid: HashMap<Oid, &CommitInfo> = commits.iter().map(|c| (c.oid, c)).collect();
+    let recorded: HashMap<Oid, &CommitInfo> = recorded.iter().map(|c| (c.oid, c)).collect();
+    let mut history = BranchHistory {
+        branch: branch.to_string(),
+        ..Default::default()
+    };
+    for oid in first_parent_chain(repo, tip)? {
+        let Some(commit) = by_oid.get(&oid) else {
+            continue;
+        };
+        history.commits += 1;
+        if classify_landing(recorded.get(&oid).unwrap_or(commit)) == Landing::Direct {
+            history.direct += 1;
+        }
+        // The chain holds only commits that exist, so not found means
+        // not signed.
+        match repo.extract_signature(&oid, None) {
+            Ok(_) => {}
+            Err(e) if is_missing(&e) => history.unsigned += 1,
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut names = vec![format!("refs/heads/{}", branch)];
+    for remote in repo.remotes()?.iter().flatten() {
+        names.push(format!("refs/remotes/{}/{}", remote, branch));
+    }
+    for name in names {
+        history.force_pushes += count_rewrites(repo, generations, &name)?;
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_branch_history() {
+        let t = TestRepo::new();
+        let base = t.commit("base", &[("a.txt", "1")]);
+        let tip = t.commit("direct", &[("a.txt", "2")]);
+        let branch = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        // A signed commit on top of the tip.
+        let sig = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = t.repo.find_commit(tip).unwrap();
+        let tree = parent.tree().unwrap();
+        let buffer = t
+            .repo
+            .commit_create_buffer(&sig, &sig, "signed (#12)", &tree, &[&parent])
+            .unwrap();
+        let signed = t
+            .repo
+            .commit_signed(
+                buffer.as_str().unwrap(),
+                "-----BEGIN PGP SIGNATURE-----",
+                None,
+            )
+            .unwrap();
+        let name = format!("refs/heads/{}", branch);
+        t.repo.reference(&name, signed, true, "commit").unwrap();
+        // Rewinding the branch rewrites it; moving it forward again does not.
+        t.repo.reference(&name, base, true, "reset").unwrap();
+        t.repo.reference(&name, signed, true, "pull").unwrap();
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let mut generations = Generations::default();
+        let history = get_branch_history(
+            &t.repo,
+            &mut generations,
+            &branch,
+            signed,
+            &commits,
+            &commits,
+        )
+        .unwrap();
+        assert_eq!(
+            BranchHistory {
+                branch: branch.clone(),
+                commits: 3,
+                unsigned: 2,
+                direct: 2,
+                force_pushes: 1,
+            },
+            history
+        );
+
+        let config = Config::parse("[protection]\nrequire_signed = true\n").unwrap();
+        let rules = ProtectionRules::from_config(&config).unwrap().unwrap();
+        assert_eq!(
+            vec![("signed commits", 2), ("no force pushes", 1)],
+            rules.drift(&history)
+        );
+        assert_eq!(
+            None,
+            ProtectionRules::from_config(&Config::default()).unwrap()
+        );
+    }
+}
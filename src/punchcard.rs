@@ -0,0 +1,43 @@
+//! Commits by weekday and hour of day.
+
+use crate::{date::weekday, history::CommitInfo};
+
+/// Commit counts per weekday, Monday first, and local hour.
+pub type Punchcard = [[usize; 24]; 7];
+
+/// Counts commits by the committer's local weekday and hour.
+pub fn get_punchcard(commits: &[CommitInfo]) -> Punchcard {
+    let mut punchcard = [[0; 24]; 7];
+    for commit in commits {
+        let local = commit.time + commit.offset_minutes as i64 * 60;
+        let hour = local.rem_euclid(86_400) / 3_600;
+        punchcard[weekday(commit.day()) as usize][hour as usize] += 1;
+    }
+    punchcard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{SECONDS_PER_DAY, days_from_civil};
+
+    #[test]
+    fn test_punchcard() {
+        // 2024-01-01 was a Monday.
+        let monday = days_from_civil(2024, 1, 1) * SECONDS_PER_DAY;
+        let commit = |time: i64, offset_minutes: i32| CommitInfo {
+            time,
+            offset_minutes,
+            ..Default::default()
+        };
+        let commits = vec![
+            commit(monday + 9 * 3_600, 0),
+            // 23:30 UTC on Monday is 01:30 on Tuesday at +02:00.
+            commit(monday + 23 * 3_600 + 1_800, 120),
+        ];
+        let punchcard = get_punchcard(&commits);
+        assert_eq!(1, punchcard[0][9]);
+        assert_eq!(1, punchcard[1][1]);
+        assert_eq!(2, punchcard.iter().flatten().sum::<usize>());
+    }
+}
@@ -0,0 +1,182 @@
+//! How long commits sit locally before they are pushed.
+//!
+//! Local branch reflogs record when each commit was created in this clone, and
+//! remote-tracking reflogs record every `update by push`. A commit's push
+//! latency is the time from its first local reflog entry to the first push
+//! that made it reachable from a remote-tracking branch. Only the reflogs of
+//! this clone are read, so pushes from other clones are not seen.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository};
+
+use crate::{graph::Generations, stats::percentile};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PushLatency {
+    /// Seconds between local creation and push for every pushed commit,
+    /// sorted ascending.
+    pub latencies: Vec<i64>,
+    /// Creation times of local commits not yet reachable from any
+    /// remote-tracking branch, oldest first.
+    pub unpushed: Vec<i64>,
+}
+
+impl PushLatency {
+    pub fn percentile(&self, p: f64) -> Option<i64> {
+        percentile(&self.latencies, p)
+    }
+}
+
+/// A reflog entry as `(time, old, new, message)`.
+type ReflogEntry = (i64, Oid, Oid, String);
+
+/// Reads the reflog entries of every reference under `prefix`, oldest entry
+/// first.
+fn reflogs(repo: &Repository, prefix: &str) -> Result<Vec<Vec<ReflogEntry>>, git2::Error> {
+    let mut logs = Vec::new();
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if name.ends_with("/HEAD") {
+            continue;
+        }
+        let reflog = repo.reflog(name)?;
+        let mut entries: Vec<_> = reflog
+            .iter()
+            .map(|e| {
+                (
+                    e.committer().when().seconds(),
+                    e.id_old(),
+                    e.id_new(),
+                    e.message().unwrap_or("").to_string(),
+                )
+            })
+            .collect();
+        entries.reverse();
+        logs.push(entries);
+    }
+    Ok(logs)
+}
+
+/// Returns whether `commit` is `tip` or one of its ancestors. A zero `tip`,
+/// as in the entry that created a reference, reaches nothing.
+fn reachable(
+    repo: &Repository,
+    generations: &mut Generations,
+    tip: Oid,
+    commit: Oid,
+) -> Result<bool, git2::Error> {
+    Ok(!tip.is_zero() && generations.contains(repo, tip, commit)?)
+}
+
+/// Measures push latency from the reflogs of local and remote-tracking
+/// branches, answering ancestry queries from `generations`.
+pub fn get_push_latency(
+    repo: &Repository,
+    generations: &mut Generations,
+) -> Result<PushLatency, git2::Error> {
+    let mut created: HashMap<Oid, i64> = HashMap::new();
+    for entries in reflogs(repo, "refs/heads/")? {
+        for (time, _, new, _) in entries {
+            if !new.is_zero() {
+                created.entry(new).or_insert(time);
+            }
+        }
+    }
+
+    let mut pushes: Vec<(i64, Oid, Oid)> = reflogs(repo, "refs/remotes/")?
+        .into_iter()
+        .flatten()
+        .filter(|(_, _, _, message)| message.starts_with("update by push"))
+        .map(|(time, old, new, _)| (time, old, new))
+        .collect();
+    pushes.sort_by_key(|p| p.0);
+
+    let mut remote_tips = Vec::new();
+    for reference in repo.references_glob("refs/remotes/*")? {
+        remote_tips.extend(reference?.target());
+    }
+
+    let mut latency = PushLatency::default();
+    for (&commit, &created_at) in &created {
+        let mut pushed = None;
+        for &(time, old, new) in &pushes {
+            if time >= created_at
+                && reachable(repo, generations, new, commit)?
+                && !reachable(repo, generations, old, commit)?
+            {
+                pushed = Some(time);
+                break;
+            }
+        }
+        match pushed {
+            Some(time) => latency.latencies.push(time - created_at),
+            None => {
+                let mut on_remote = false;
+                for tip in &remote_tips {
+                    if reachable(repo, generations, *tip, commit)? {
+                        on_remote = true;
+                        break;
+                    }
+                }
+                // A commit that reached a remote some other way, such as a
+                // fetch of the same work, is not at risk.
+                if !on_remote && repo.find_commit(commit).is_ok() {
+                    latency.unpushed.push(created_at);
+                }
+            }
+        }
+    }
+    latency.latencies.sort_unstable();
+    latency.unpushed.sort_unstable();
+    Ok(latency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+    use git2::{Signature, Time};
+
+    /// Replaces the reflog of `name` with `entries` of `(time, new, message)`.
+    fn write_reflog(t: &TestRepo, name: &str, entries: &[(i64, Oid, &str)]) {
+        let mut reflog = t.repo.reflog(name).unwrap();
+        while !reflog.is_empty() {
+            reflog.remove(0, false).unwrap();
+        }
+        for (time, new, message) in entries {
+            let sig = Signature::new("test", "test@example.com", &Time::new(*time, 0)).unwrap();
+            reflog.append(*new, &sig, Some(message)).unwrap();
+        }
+        reflog.write().unwrap();
+    }
+
+    #[test]
+    fn test_push_latency() {
+        let t = TestRepo::new();
+        let first = t.commit("one", &[("a.txt", "1")]);
+        let second = t.commit("two", &[("a.txt", "2")]);
+        let third = t.commit("three", &[("a.txt", "3")]);
+        let branch = t.repo.head().unwrap().name().unwrap().to_string();
+        write_reflog(
+            &t,
+            &branch,
+            &[
+                (100, first, "commit"),
+                (200, second, "commit"),
+                (300, third, "commit"),
+            ],
+        );
+        let remote = "refs/remotes/origin/main";
+        t.repo.reference(remote, second, true, "push").unwrap();
+        write_reflog(&t, remote, &[(1_000, second, "update by push")]);
+
+        let latency = get_push_latency(&t.repo, &mut Generations::default()).unwrap();
+        assert_eq!(vec![800, 900], latency.latencies);
+        assert_eq!(vec![300], latency.unpushed);
+        assert_eq!(Some(900), latency.percentile(90.0));
+    }
+}
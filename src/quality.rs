@@ -0,0 +1,96 @@
+//! Commits that undo or amend earlier work, as a code-stability signal.
+//!
+//! `git revert` titles its commits `Revert "<subject>"` and `git commit
+//! --fixup` / `--squash` prefix theirs with `fixup!` and `squash!`. Many of
+//! them in a branch's history means work landed before it was ready, or that
+//! autosquash was skipped before merging.
+
+use crate::history::CommitInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rework {
+    Revert,
+    Fixup,
+    Squash,
+}
+
+/// Returns how a commit reworks an earlier one, judging by its subject.
+pub fn classify(message: &str) -> Option<Rework> {
+    let subject = message.lines().next().unwrap_or("").trim_start();
+    if subject.starts_with("Revert \"") {
+        Some(Rework::Revert)
+    } else if subject.starts_with("fixup!") {
+        Some(Rework::Fixup)
+    } else if subject.starts_with("squash!") {
+        Some(Rework::Squash)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QualityStats {
+    pub commits: usize,
+    pub reverts: usize,
+    pub fixups: usize,
+    pub squashes: usize,
+}
+
+impl QualityStats {
+    /// Commits reworking earlier ones in any way.
+    pub fn rework(&self) -> usize {
+        self.reverts + self.fixups + self.squashes
+    }
+}
+
+/// Counts revert, fixup and squash commits.
+pub fn get_quality_stats(commits: &[CommitInfo]) -> QualityStats {
+    let mut stats = QualityStats {
+        commits: commits.len(),
+        ..Default::default()
+    };
+    for commit in commits {
+        match classify(&commit.message) {
+            Some(Rework::Revert) => stats.reverts += 1,
+            Some(Rework::Fixup) => stats.fixups += 1,
+            Some(Rework::Squash) => stats.squashes += 1,
+            None => {}
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_stats() {
+        assert_eq!(
+            Some(Rework::Revert),
+            classify("Revert \"Add cache\"\n\nThis reverts commit 1234.\n")
+        );
+        assert_eq!(Some(Rework::Fixup), classify("fixup! Add cache"));
+        assert_eq!(None, classify("Reverted the cache to fix a leak"));
+        assert_eq!(None, classify("Add cache\n\nfixup! in the body"));
+
+        let commits: Vec<CommitInfo> = ["Revert \"x\"", "squash! x", "fixup! x", "x"]
+            .iter()
+            .map(|m| CommitInfo {
+                message: m.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let stats = get_quality_stats(&commits);
+        assert_eq!(
+            QualityStats {
+                commits: 4,
+                reverts: 1,
+                fixups: 1,
+                squashes: 1
+            },
+            stats
+        );
+        assert_eq!(3, stats.rework());
+    }
+}
@@ -0,0 +1,118 @@
+//! Statistics of the history between two refs, as for release notes.
+//!
+//! A range `A..B` holds the commits reachable from `B` but not from `A`,
+//! as `git log A..B` lists them. A single ref `A` stands for `A..HEAD`.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository, RevparseMode};
+
+use crate::{
+    churn::{CommitChurn, get_changed_paths, get_commit_churn},
+    history::CommitInfo,
+    lang::PathFilter,
+};
+
+/// Resolves `spec` to the commits excluded and included by it, as
+/// `(from, to)`.
+pub fn resolve_range(repo: &Repository, spec: &str) -> Result<(Oid, Oid), String> {
+    let invalid = |e: git2::Error| format!("invalid range '{}': {}", spec, e.message());
+    let revspec = repo.revparse(spec).map_err(invalid)?;
+    if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+        return Err(format!(
+            "symmetric ranges like '{}' are not supported, use A..B",
+            spec
+        ));
+    }
+    let commit = |object: Option<&git2::Object>| -> Result<Oid, String> {
+        let object = object.ok_or_else(|| format!("invalid range '{}'", spec))?;
+        Ok(object.peel_to_commit().map_err(invalid)?.id())
+    };
+    if revspec.mode().contains(RevparseMode::RANGE) {
+        Ok((commit(revspec.from())?, commit(revspec.to())?))
+    } else {
+        let head = repo
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .map_err(invalid)?;
+        Ok((commit(revspec.from())?, head.id()))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeChanges {
+    /// Lines and files changed by the non-merge commits.
+    pub churn: CommitChurn,
+    /// Commits per changed file, most first.
+    pub files: Vec<(String, usize)>,
+}
+
+/// Sums the changes `commits` made to the files matching `filter`. Merge
+/// commits are skipped, as their first-parent diff repeats the merged work.
+pub fn get_range_changes(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    filter: &PathFilter,
+) -> Result<RangeChanges, git2::Error> {
+    let mut changes = RangeChanges::default();
+    let mut files: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|c| !c.is_merge()) {
+        changes.churn += get_commit_churn(repo, commit.oid, filter)?;
+        for path in get_changed_paths(repo, commit.oid)? {
+            if filter.matches(&path) {
+                *files.entry(path).or_default() += 1;
+            }
+        }
+    }
+    changes.files = files.into_iter().collect();
+    changes
+        .files
+        .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, support::commits_between, testutil::TestRepo,
+    };
+
+    #[test]
+    fn test_range() {
+        let t = TestRepo::new();
+        let v1 = t.commit("one", &[("a.txt", "1\n")]);
+        t.repo
+            .tag_lightweight("v1.0", &t.repo.find_object(v1, None).unwrap(), false)
+            .unwrap();
+        t.commit("two", &[("a.txt", "2\n"), ("b.txt", "b\n")]);
+        let v2 = t.commit("three", &[("a.txt", "3\n")]);
+        t.repo
+            .tag_lightweight("v2.0", &t.repo.find_object(v2, None).unwrap(), false)
+            .unwrap();
+
+        assert_eq!(Ok((v1, v2)), resolve_range(&t.repo, "v1.0..v2.0"));
+        assert_eq!(Ok((v1, v2)), resolve_range(&t.repo, "v1.0"));
+        assert!(resolve_range(&t.repo, "v1.0...v2.0").is_err());
+
+        let range = commits_between(&t.repo, v2, Some(v1)).unwrap();
+        let commits: Vec<CommitInfo> = collect_commits(&t.repo, None, &mut Progress::default())
+            .unwrap()
+            .into_iter()
+            .filter(|c| range.contains(&c.oid))
+            .collect();
+        let changes = get_range_changes(&t.repo, &commits, &PathFilter::default()).unwrap();
+        assert_eq!(
+            CommitChurn {
+                files_changed: 3,
+                insertions: 3,
+                deletions: 2
+            },
+            changes.churn
+        );
+        assert_eq!(
+            vec![("a.txt".to_string(), 2), ("b.txt".to_string(), 1)],
+            changes.files
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Reflog entries, the local record of where each reference has pointed.
+//!
+//! Reflogs are never pushed or cloned, so they show how a repository is
+//! worked in locally. Actions are read from the entry messages git writes,
+//! such as `commit (amend): ...` or `reset: moving to HEAD~1`. They are
+//! counted on HEAD's reflog, which records every move of any checked-out
+//! branch, and a rebase counts once, at its start. The oldest entry bounds
+//! how far back `git reset HEAD@{n}` can recover.
+
+use git2::{Oid, Repository};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefLog {
+    pub name: String,
+    pub entries: usize,
+    /// Times of the oldest and newest entries.
+    pub oldest: i64,
+    pub newest: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflogEntry {
+    pub name: String,
+    pub oid: Oid,
+    pub time: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReflogStats {
+    /// References with a non-empty reflog, most entries first.
+    pub refs: Vec<RefLog>,
+    /// Entries of HEAD's reflog per action, most first.
+    pub actions: Vec<(String, usize)>,
+    /// Seconds HEAD's reflog spans.
+    pub span: i64,
+    /// The oldest entry of any reflog.
+    pub oldest: Option<ReflogEntry>,
+}
+
+/// Returns the action an entry with `message` records, as `amend` for
+/// `commit (amend): ...` and the command name otherwise. Rebase steps after
+/// the start are `rebase step`.
+pub fn reflog_action(message: &str) -> String {
+    let prefix = message.split(':').next().unwrap_or("").trim();
+    let command = prefix.split_whitespace().next().unwrap_or("");
+    match command {
+        "" => "unknown".to_string(),
+        "commit" if prefix.contains("(amend)") => "amend".to_string(),
+        "rebase" if !prefix.contains("(start)") => "rebase step".to_string(),
+        _ => command.to_string(),
+    }
+}
+
+/// Reads the reflogs of HEAD and every reference.
+pub fn get_reflog_stats(repo: &Repository) -> Result<ReflogStats, git2::Error> {
+    let mut names = vec!["HEAD".to_string()];
+    for reference in repo.references()? {
+        if let Some(name) = reference?.name() {
+            names.push(name.to_string());
+        }
+    }
+    let mut stats = ReflogStats::default();
+    let mut actions: Vec<(String, usize)> = Vec::new();
+    for name in names {
+        let reflog = repo.reflog(&name)?;
+        if reflog.is_empty() {
+            continue;
+        }
+        let time = |e: &git2::ReflogEntry| e.committer().when().seconds();
+        let first = reflog.iter().min_by_key(time).expect("reflog is not empty");
+        let (oldest, newest) = (
+            time(&first),
+            reflog.iter().map(|e| time(&e)).max().unwrap_or(0),
+        );
+        if name == "HEAD" {
+            stats.span = newest - oldest;
+            for entry in reflog.iter() {
+                let action = reflog_action(entry.message().unwrap_or(""));
+                match actions.iter_mut().find(|(a, _)| *a == action) {
+                    Some((_, n)) => *n += 1,
+                    None => actions.push((action, 1)),
+                }
+            }
+        }
+        if stats.oldest.as_ref().is_none_or(|o| oldest < o.time) {
+            stats.oldest = Some(ReflogEntry {
+                name: name.clone(),
+                oid: first.id_new(),
+                time: oldest,
+                message: first.message().unwrap_or("").to_string(),
+            });
+        }
+        stats.refs.push(RefLog {
+            name,
+            entries: reflog.len(),
+            oldest,
+            newest,
+        });
+    }
+    stats
+        .refs
+        .sort_by(|a, b| b.entries.cmp(&a.entries).then(a.name.cmp(&b.name)));
+    actions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    stats.actions = actions;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+    use git2::{Signature, Time};
+
+    #[test]
+    fn test_reflog_stats() {
+        let t = TestRepo::new();
+        let oid = t.commit("one", &[("a.txt", "a")]);
+        t.repo.reflog_delete("HEAD").unwrap();
+        let mut head = t.repo.reflog("HEAD").unwrap();
+        let messages = [
+            "commit (initial): one",
+            "commit (amend): one",
+            "rebase (start): checkout main",
+            "rebase (pick): two",
+            "rebase (finish): returning to refs/heads/main",
+            "reset: moving to HEAD~1",
+            "commit: three",
+        ];
+        for (i, message) in messages.iter().enumerate() {
+            let sig = Signature::new("t", "t@x.com", &Time::new(1000 + i as i64 * 60, 0)).unwrap();
+            head.append(oid, &sig, Some(message)).unwrap();
+        }
+        head.write().unwrap();
+        let sig = Signature::new("t", "t@x.com", &Time::new(500, 0)).unwrap();
+        t.repo
+            .branch("old", &t.repo.find_commit(oid).unwrap(), false)
+            .unwrap();
+        let mut old = t.repo.reflog("refs/heads/old").unwrap();
+        old.append(oid, &sig, Some("branch: Created from HEAD"))
+            .unwrap();
+        old.write().unwrap();
+
+        let stats = get_reflog_stats(&t.repo).unwrap();
+        assert_eq!(
+            vec![
+                ("commit".to_string(), 2),
+                ("rebase step".to_string(), 2),
+                ("amend".to_string(), 1),
+                ("rebase".to_string(), 1),
+                ("reset".to_string(), 1),
+            ],
+            stats.actions
+        );
+        assert_eq!(
+            ("HEAD", 7),
+            (stats.refs[0].name.as_str(), stats.refs[0].entries)
+        );
+        assert_eq!(360, stats.span);
+        let oldest = stats.oldest.unwrap();
+        assert_eq!(("refs/heads/old", 500), (oldest.name.as_str(), oldest.time));
+    }
+}
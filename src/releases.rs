@@ -0,0 +1,310 @@
+//! Tags and the release history they mark.
+//!
+//! Every tag pointing at a commit counts as a release. A tag's date is its
+//! tagger date for annotated tags and the tagged commit's date otherwise, and
+//! releases are ordered by it.
+
+use git2::{Oid, Repository};
+
+use crate::{
+    history::RefSnapshot,
+    support::{commits_between, latest_tag, tagged_commits},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub tag: String,
+    pub commit: Oid,
+    pub annotated: bool,
+    pub time: i64,
+    /// Commits reachable from this release but not the one before it.
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseStats {
+    /// Releases, oldest first.
+    pub releases: Vec<Release>,
+    /// Newest tag reachable from HEAD and the commits made since.
+    pub since_latest: Option<(String, usize)>,
+}
+
+impl ReleaseStats {
+    pub fn annotated(&self) -> usize {
+        self.releases.iter().filter(|r| r.annotated).count()
+    }
+
+    /// Average days between consecutive releases, with two or more.
+    pub fn cadence_days(&self) -> Option<f64> {
+        let (first, last) = (self.releases.first()?, self.releases.last()?);
+        (self.releases.len() > 1)
+            .then(|| (last.time - first.time) as f64 / 86_400.0 / (self.releases.len() - 1) as f64)
+    }
+}
+
+/// A semantic version, as in `v1.2.3-rc.1+build`. Build metadata is
+/// dropped since it does not take part in ordering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+/// Parses a tag as a semantic version, with an optional `v` prefix.
+pub fn parse_version(tag: &str) -> Option<Version> {
+    let version = tag.strip_prefix('v').unwrap_or(tag);
+    let version = version.split_once('+').map_or(version, |(v, _)| v);
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+        Some(_) => return None,
+        None => (version, None),
+    };
+    let mut numbers = core.split('.').map(|n| {
+        // Leading zeros are not allowed, as in the specification.
+        (!n.is_empty() && (n == "0" || !n.starts_with('0')))
+            .then(|| n.parse::<u64>().ok())
+            .flatten()
+    });
+    let version = Version {
+        major: numbers.next()??,
+        minor: numbers.next()??,
+        patch: numbers.next()??,
+        pre,
+    };
+    numbers.next().is_none().then_some(version)
+}
+
+impl Ord for Version {
+    /// Orders by precedence: pre-releases come before their release and
+    /// compare identifier by identifier, numbers below words.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        let core =
+            (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch));
+        let pre = match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => {
+                let identifier = |i: &str| match i.parse::<u64>() {
+                    Ok(n) => (0, n, String::new()),
+                    Err(_) => (1, 0, i.to_string()),
+                };
+                a.split('.')
+                    .map(identifier)
+                    .cmp(b.split('.').map(identifier))
+            }
+        };
+        core.then(pre)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SemverStats {
+    /// Releases whose tag parses as a semantic version.
+    pub versioned: usize,
+    pub majors: usize,
+    pub minors: usize,
+    pub patches: usize,
+    pub prereleases: usize,
+    /// Times of the major releases, oldest first.
+    pub major_times: Vec<i64>,
+    /// Consecutive releases whose versions go backwards, as (earlier, later)
+    /// tags.
+    pub out_of_order: Vec<(String, String)>,
+}
+
+impl SemverStats {
+    /// Average days between major releases, with two or more.
+    pub fn days_between_majors(&self) -> Option<f64> {
+        let (first, last) = (self.major_times.first()?, self.major_times.last()?);
+        (self.major_times.len() > 1)
+            .then(|| (last - first) as f64 / 86_400.0 / (self.major_times.len() - 1) as f64)
+    }
+}
+
+/// Classifies the semver releases among `releases`, which are oldest
+/// first. Stable releases count by their version alone: `x.0.0` is a major
+/// release, `x.y.0` a minor one and anything else a patch, so backported
+/// patch releases count as patches.
+pub fn get_semver_stats(releases: &[Release]) -> SemverStats {
+    let mut stats = SemverStats::default();
+    let mut previous: Option<(&str, Version)> = None;
+    for release in releases {
+        let Some(version) = parse_version(&release.tag) else {
+            continue;
+        };
+        stats.versioned += 1;
+        if let Some((tag, before)) = &previous
+            && version < *before
+        {
+            stats
+                .out_of_order
+                .push((tag.to_string(), release.tag.clone()));
+        }
+        if version.pre.is_some() {
+            stats.prereleases += 1;
+        } else if version.patch > 0 {
+            stats.patches += 1;
+        } else if version.minor > 0 {
+            stats.minors += 1;
+        } else {
+            stats.majors += 1;
+            stats.major_times.push(release.time);
+        }
+        previous = Some((&release.tag, version));
+    }
+    stats
+}
+
+/// Lists the tags of `snapshot` pointing at commits, oldest first.
+fn get_tags(repo: &Repository, snapshot: &RefSnapshot) -> Vec<(String, Oid, bool, i64)> {
+    let mut tags = Vec::new();
+    for (name, oid) in snapshot.under("refs/tags/") {
+        let Ok(object) = repo.find_object(oid, None) else {
+            continue;
+        };
+        let Ok(commit) = object.peel_to_commit() else {
+            continue;
+        };
+        let tag = object.peel_to_tag().ok();
+        let annotated = tag.is_some();
+        let time = tag
+            .and_then(|tag| tag.tagger().map(|t| t.when().seconds()))
+            .unwrap_or_else(|| commit.time().seconds());
+        tags.push((name.to_string(), commit.id(), annotated, time));
+    }
+    tags.sort_by(|a, b| a.3.cmp(&b.3).then(a.0.cmp(&b.0)));
+    tags
+}
+
+/// Collects the releases in `snapshot` and the commits since the newest one
+/// reachable from its HEAD.
+pub fn get_release_stats(
+    repo: &Repository,
+    snapshot: &RefSnapshot,
+) -> Result<ReleaseStats, git2::Error> {
+    let mut stats = ReleaseStats::default();
+    let mut previous = None;
+    for (tag, commit, annotated, time) in get_tags(repo, snapshot) {
+        stats.releases.push(Release {
+            tag,
+            commit,
+            annotated,
+            time,
+            commits: commits_between(repo, commit, previous)?.len(),
+        });
+        previous = Some(commit);
+    }
+    if let Some(head) = snapshot.head {
+        stats.since_latest = latest_tag(repo, head, &tagged_commits(repo, snapshot))?;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_release_stats() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        let v1 = t.commit_as("A", "a@x.com", day, "one", &[("a.txt", "1")]);
+        t.commit_as("A", "a@x.com", 2 * day, "two", &[("a.txt", "2")]);
+        let v2 = t.commit_as("A", "a@x.com", 3 * day, "three", &[("a.txt", "3")]);
+        let head = t.commit_as("A", "a@x.com", 4 * day, "four", &[("a.txt", "4")]);
+        t.repo
+            .tag_lightweight("v1", &t.repo.find_object(v1, None).unwrap(), false)
+            .unwrap();
+        let sig = git2::Signature::new("A", "a@x.com", &git2::Time::new(5 * day, 0)).unwrap();
+        t.repo
+            .tag(
+                "v2",
+                &t.repo.find_object(v2, None).unwrap(),
+                &sig,
+                "v2",
+                false,
+            )
+            .unwrap();
+
+        let snapshot = RefSnapshot::take(&t.repo).unwrap();
+        assert_eq!(Some(head), snapshot.head);
+        let stats = get_release_stats(&t.repo, &snapshot).unwrap();
+        let releases: Vec<_> = stats
+            .releases
+            .iter()
+            .map(|r| (r.tag.as_str(), r.annotated, r.time, r.commits))
+            .collect();
+        assert_eq!(
+            vec![("v1", false, day, 1), ("v2", true, 5 * day, 2)],
+            releases
+        );
+        assert_eq!(1, stats.annotated());
+        assert_eq!(Some(4.0), stats.cadence_days());
+        assert_eq!(Some(("v2".to_string(), 1)), stats.since_latest);
+    }
+
+    #[test]
+    fn test_semver_stats() {
+        assert_eq!(
+            Some(Version {
+                major: 1,
+                minor: 2,
+                patch: 3,
+                pre: Some("rc.1".to_string())
+            }),
+            parse_version("v1.2.3-rc.1+build.5")
+        );
+        assert_eq!(None, parse_version("1.02.3"));
+        assert_eq!(None, parse_version("release-1"));
+        assert!(parse_version("1.0.0-rc.2") < parse_version("1.0.0-rc.10"));
+        assert!(parse_version("1.0.0-rc.1") < parse_version("1.0.0"));
+
+        let releases: Vec<Release> = [
+            "v0.1.0",
+            "v0.1.1",
+            "v1.0.0-rc.1",
+            "v1.0.0",
+            "v1.1.0",
+            "v1.0.2",
+            "nightly",
+            "v2.0.0",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| Release {
+            tag: tag.to_string(),
+            commit: Oid::zero(),
+            annotated: false,
+            time: i as i64 * 86_400,
+            commits: 1,
+        })
+        .collect();
+        let stats = get_semver_stats(&releases);
+        assert_eq!(
+            (7, 2, 2, 2, 1),
+            (
+                stats.versioned,
+                stats.majors,
+                stats.minors,
+                stats.patches,
+                stats.prereleases
+            )
+        );
+        assert_eq!(Some(4.0), stats.days_between_majors());
+        assert_eq!(
+            vec![("v1.1.0".to_string(), "v1.0.2".to_string())],
+            stats.out_of_order
+        );
+    }
+}
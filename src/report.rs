@@ -0,0 +1,780 @@
+//! Report model shared by all subcommands.
+//!
+//! Subcommands build a [`Report`] out of labelled fields and tables instead of
+//! printing directly, so every report is laid out the same way and can be
+//! rendered as text, JSON, Markdown, CSV, HTML or a workbook.
+
+use std::{fmt, path::Path, str::FromStr};
+
+use crate::{
+    date::{DateFormat, now},
+    stats::{format_number, format_size},
+};
+
+/// A single value in a report.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Count(usize),
+    Percent(f64),
+    /// A size in bytes.
+    Size(u64),
+    /// A day, in days since the epoch.
+    Date(i64),
+    /// A point in time, in seconds since the epoch.
+    Time(i64),
+    Text(String),
+    /// A row of counts drawn as intensity characters scaled against `max`,
+    /// two characters per count, as in a heatmap.
+    Intensities {
+        counts: Vec<usize>,
+        max: usize,
+    },
+}
+
+/// Intensity characters from no activity to the most.
+const INTENSITY_LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// 256-color ANSI shades of green for each intensity level.
+const INTENSITY_COLORS: [u8; 5] = [240, 22, 28, 34, 40];
+
+fn intensity(count: usize, max: usize) -> char {
+    if count == 0 || max == 0 {
+        return INTENSITY_LEVELS[0];
+    }
+    let levels = INTENSITY_LEVELS.len() - 1;
+    INTENSITY_LEVELS[(count * levels).div_ceil(max).min(levels)]
+}
+
+/// Wraps every intensity character of `cell` in its ANSI color.
+fn colorize(cell: &str) -> String {
+    let mut out = String::new();
+    for c in cell.chars() {
+        match INTENSITY_LEVELS.iter().position(|l| *l == c) {
+            Some(level) => out.push_str(&format!(
+                "\x1b[38;5;{}m{}\x1b[0m",
+                INTENSITY_COLORS[level], c
+            )),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+impl Value {
+    /// Formats the value, showing dates and times in `dates`.
+    pub fn render(&self, dates: &DateFormat) -> String {
+        match self {
+            Value::Date(days) => dates.format_day(*days, now()),
+            Value::Time(seconds) => dates.format_time(*seconds, now()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// Dates and times are shown in ISO 8601; see [`Value::render`] for others.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Count(n) => f.pad(&format_number(*n)),
+            Value::Percent(p) => f.pad(&format!("{:.1}%", p)),
+            Value::Size(n) => f.pad(&format_size(*n)),
+            Value::Date(days) => f.pad(&DateFormat::Iso.format_day(*days, 0)),
+            Value::Time(seconds) => f.pad(&DateFormat::Iso.format_time(*seconds, 0)),
+            Value::Text(s) => f.pad(s),
+            Value::Intensities { counts, max } => f.pad(
+                &counts
+                    .iter()
+                    .flat_map(|n| [intensity(*n, *max); 2])
+                    .collect::<String>(),
+            ),
+        }
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Count(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+/// A titled table with one header per column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    pub fn new(title: impl Into<String>, columns: &[&str]) -> Self {
+        Table {
+            title: title.into(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Section {
+    Fields(Vec<(String, Value)>),
+    Table(Table),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub title: String,
+    /// Subcommand the report was built by, empty if unknown.
+    pub command: String,
+    /// How dates and times are shown, in every format.
+    pub date_format: DateFormat,
+    /// Id of the references snapshot the history was read at, if the
+    /// report walked it. Written to JSON only.
+    pub snapshot: Option<String>,
+    pub sections: Vec<Section>,
+}
+
+impl Report {
+    pub fn new(title: impl Into<String>) -> Self {
+        Report {
+            title: title.into(),
+            command: String::new(),
+            date_format: DateFormat::default(),
+            snapshot: None,
+            sections: Vec::new(),
+        }
+    }
+
+    /// Adds a labelled value, grouping consecutive fields into one section.
+    pub fn field(&mut self, label: impl Into<String>, value: impl Into<Value>) {
+        let field = (label.into(), value.into());
+        match self.sections.last_mut() {
+            Some(Section::Fields(fields)) => fields.push(field),
+            _ => self.sections.push(Section::Fields(vec![field])),
+        }
+    }
+
+    pub fn table(&mut self, table: Table) {
+        self.sections.push(Section::Table(table));
+    }
+}
+
+/// The alternate form, `{:#}`, colors intensities for terminals.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.title)?;
+        writeln!(f, "{}", "=".repeat(25))?;
+        for (i, section) in self.sections.iter().enumerate() {
+            match section {
+                Section::Fields(fields) => {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    for (label, value) in fields {
+                        let value = value.render(&self.date_format);
+                        writeln!(f, "{:<20} {:>12}", format!("{}:", label), value)?;
+                    }
+                }
+                Section::Table(table) => {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write_table(f, table, &self.date_format, f.alternate())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_table(
+    f: &mut fmt::Formatter<'_>,
+    table: &Table,
+    dates: &DateFormat,
+    color: bool,
+) -> fmt::Result {
+    let cells: Vec<Vec<String>> = table
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|v| v.render(dates)).collect())
+        .collect();
+
+    // The first column and columns holding only text or intensities are
+    // left-aligned; the rest hold values and are right-aligned.
+    let left: Vec<bool> = (0..table.columns.len())
+        .map(|i| {
+            i == 0
+                || (!table.rows.is_empty()
+                    && table.rows.iter().all(|row| {
+                        matches!(row.get(i), Some(Value::Text(_) | Value::Intensities { .. }))
+                    }))
+        })
+        .collect();
+    let mut widths: Vec<usize> = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c.chars().count().max(if i == 0 { 20 } else { 12 }))
+        .collect();
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    writeln!(f, "{}", table.title)?;
+    writeln!(f, "{}", "-".repeat(25))?;
+    write_row(f, &widths, &left, &table.columns, &[])?;
+    for (row, values) in cells.iter().zip(&table.rows) {
+        let painted: Vec<bool> = values
+            .iter()
+            .map(|v| color && matches!(v, Value::Intensities { .. }))
+            .collect();
+        write_row(f, &widths, &left, row, &painted)?;
+    }
+    Ok(())
+}
+
+/// Writes one padded row, coloring the cells marked in `painted` after
+/// padding so escape codes do not count towards the width.
+fn write_row(
+    f: &mut fmt::Formatter<'_>,
+    widths: &[usize],
+    left: &[bool],
+    cells: &[String],
+    painted: &[bool],
+) -> fmt::Result {
+    let mut line = String::new();
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if i > 0 {
+            line.push(' ');
+        }
+        let cell = if left[i] {
+            format!("{:<width$}", cell, width = width)
+        } else {
+            format!("{:>width$}", cell, width = width)
+        };
+        if painted.get(i).copied().unwrap_or(false) {
+            line.push_str(&colorize(&cell));
+        } else {
+            line.push_str(&cell);
+        }
+    }
+    writeln!(f, "{}", line.trim_end())
+}
+
+/// Output format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    /// An array with one object per report, also when a single subcommand
+    /// ran, so consumers need not tell the two cases apart.
+    Json,
+    Markdown,
+    Csv,
+    /// A standalone page with its styles inline.
+    Html,
+    /// An Excel workbook, the only binary format.
+    Xlsx,
+}
+
+impl Format {
+    /// Infers the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "txt" | "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "md" | "markdown" => Some(Format::Markdown),
+            "csv" => Some(Format::Csv),
+            "html" | "htm" => Some(Format::Html),
+            "xlsx" => Some(Format::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "html" => Ok(Format::Html),
+            "xlsx" => Ok(Format::Xlsx),
+            _ => Err(format!(
+                "invalid format '{}': expected text, json, markdown, csv, html or xlsx",
+                s
+            )),
+        }
+    }
+}
+
+/// Renders reports as text for a terminal, with colors.
+pub fn render_terminal(reports: &[Report]) -> String {
+    reports
+        .iter()
+        .map(|r| format!("{:#}", r))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders reports in `format`, as bytes since workbooks are binary.
+pub fn render_bytes(reports: &[Report], format: Format) -> Vec<u8> {
+    match format {
+        Format::Xlsx => crate::xlsx::workbook(reports),
+        _ => render(reports, format).into_bytes(),
+    }
+}
+
+/// Renders reports in a text `format`. Several JSON reports become an
+/// array. Workbooks are rendered by [`render_bytes`].
+pub fn render(reports: &[Report], format: Format) -> String {
+    match format {
+        Format::Text => reports
+            .iter()
+            .map(Report::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Json => {
+            let rendered: Vec<String> = reports.iter().map(json_report).collect();
+            format!("[{}]\n", rendered.join(","))
+        }
+        Format::Markdown => reports
+            .iter()
+            .map(markdown_report)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Csv => reports
+            .iter()
+            .map(csv_report)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Html => {
+            let title = reports.first().map_or("gno", |r| r.title.as_str());
+            let body: Vec<String> = reports.iter().map(html_report).collect();
+            html_page(title, &body.join("\n"))
+        }
+        Format::Xlsx => unreachable!("workbooks are rendered by render_bytes"),
+    }
+}
+
+/// Quotes `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Values are written as raw numbers: counts, percentages and sizes in bytes.
+/// Dates and times are strings in the report's date format, or numbers for
+/// `unix`. Intensities become arrays of their counts.
+fn json_value(value: &Value, dates: &DateFormat) -> String {
+    match value {
+        Value::Date(_) | Value::Time(_) if *dates == DateFormat::Unix => value.render(dates),
+        Value::Date(_) | Value::Time(_) => json_string(&value.render(dates)),
+        Value::Count(n) => n.to_string(),
+        Value::Percent(p) => format!("{:.1}", p),
+        Value::Size(n) => n.to_string(),
+        Value::Text(s) => json_string(s),
+        Value::Intensities { counts, .. } => {
+            let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+            format!("[{}]", counts.join(","))
+        }
+    }
+}
+
+fn json_report(report: &Report) -> String {
+    let sections: Vec<String> = report
+        .sections
+        .iter()
+        .map(|section| match section {
+            Section::Fields(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(label, value)| {
+                        format!(
+                            "{}:{}",
+                            json_string(label),
+                            json_value(value, &report.date_format)
+                        )
+                    })
+                    .collect();
+                format!("{{\"fields\":{{{}}}}}", fields.join(","))
+            }
+            Section::Table(table) => {
+                let columns: Vec<String> = table.columns.iter().map(|c| json_string(c)).collect();
+                let rows: Vec<String> = table
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let cells: Vec<String> = row
+                            .iter()
+                            .map(|v| json_value(v, &report.date_format))
+                            .collect();
+                        format!("[{}]", cells.join(","))
+                    })
+                    .collect();
+                format!(
+                    "{{\"table\":{{\"title\":{},\"columns\":[{}],\"rows\":[{}]}}}}",
+                    json_string(&table.title),
+                    columns.join(","),
+                    rows.join(",")
+                )
+            }
+        })
+        .collect();
+    let snapshot = match &report.snapshot {
+        Some(id) => format!(",\"snapshot\":{}", json_string(id)),
+        None => String::new(),
+    };
+    format!(
+        "{{\"title\":{},\"command\":{}{},\"sections\":[{}]}}",
+        json_string(&report.title),
+        json_string(&report.command),
+        snapshot,
+        sections.join(",")
+    )
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+fn markdown_report(report: &Report) -> String {
+    let mut out = format!("# {}\n", report.title);
+    for section in &report.sections {
+        out.push('\n');
+        match section {
+            Section::Fields(fields) => {
+                out.push_str("| Field | Value |\n|---|---:|\n");
+                for (label, value) in fields {
+                    out.push_str(&format!(
+                        "| {} | {} |\n",
+                        markdown_cell(label),
+                        markdown_cell(&value.render(&report.date_format))
+                    ));
+                }
+            }
+            Section::Table(table) => {
+                out.push_str(&format!("## {}\n\n", table.title));
+                let header: Vec<String> = table.columns.iter().map(|c| markdown_cell(c)).collect();
+                out.push_str(&format!("| {} |\n", header.join(" | ")));
+                let align: Vec<&str> = (0..table.columns.len())
+                    .map(|i| {
+                        let text = table
+                            .rows
+                            .iter()
+                            .all(|row| matches!(row.get(i), Some(Value::Text(_))));
+                        if i == 0 || text { "---" } else { "---:" }
+                    })
+                    .collect();
+                out.push_str(&format!("|{}|\n", align.join("|")));
+                for row in &table.rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|v| markdown_cell(&v.render(&report.date_format)))
+                        .collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Escapes `s` for HTML text and attribute values.
+pub fn html_escape(s: &str) -> String {
+    let mut out = String::new();
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Styles of every HTML page gno writes.
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin:1em 0}\
+th,td{padding:.25em .75em;border-bottom:1px solid #ddd;text-align:left}\
+td.value{text-align:right;font-variant-numeric:tabular-nums}\
+.cards{display:flex;flex-wrap:wrap;gap:1em}\
+.card{border:1px solid #ddd;border-radius:6px;padding:1em;width:16em}\
+.card h2{margin:0 0 .5em;font-size:1.1em}\
+.card dl{display:grid;grid-template-columns:auto auto;gap:.2em 1em;margin:.5em 0}\
+.card dd{margin:0;text-align:right}";
+
+/// Wraps `body` in a standalone page titled `title`.
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        html_escape(title),
+        HTML_STYLE,
+        body
+    )
+}
+
+/// Renders the report as a fragment of a page: fields as a two-column
+/// table, and values other than text right-aligned.
+fn html_report(report: &Report) -> String {
+    let cell = |value: &Value| {
+        let class = match value {
+            Value::Text(_) | Value::Intensities { .. } => "",
+            _ => " class=\"value\"",
+        };
+        format!(
+            "<td{}>{}</td>",
+            class,
+            html_escape(&value.render(&report.date_format))
+        )
+    };
+    let mut out = format!("<h1>{}</h1>\n", html_escape(&report.title));
+    for section in &report.sections {
+        match section {
+            Section::Fields(fields) => {
+                out.push_str("<table>\n");
+                for (label, value) in fields {
+                    out.push_str(&format!(
+                        "<tr><th>{}</th>{}</tr>\n",
+                        html_escape(label),
+                        cell(value)
+                    ));
+                }
+                out.push_str("</table>\n");
+            }
+            Section::Table(table) => {
+                out.push_str(&format!(
+                    "<h2>{}</h2>\n<table>\n<tr>",
+                    html_escape(&table.title)
+                ));
+                for column in &table.columns {
+                    out.push_str(&format!("<th>{}</th>", html_escape(column)));
+                }
+                out.push_str("</tr>\n");
+                for row in &table.rows {
+                    let cells: Vec<String> = row.iter().map(cell).collect();
+                    out.push_str(&format!("<tr>{}</tr>\n", cells.join("")));
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+    out
+}
+
+/// Quotes a CSV cell when it contains a separator, quote or line break.
+/// A cell a spreadsheet would read as a formula, such as a commit subject
+/// starting with `=`, gets a leading `'` so it stays text.
+fn csv_cell(cell: &str) -> String {
+    let cell = if cell.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", cell)
+    } else {
+        cell.to_string()
+    };
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+/// Values are written raw as in JSON, without quotes unless needed, and
+/// intensities as their counts separated by spaces.
+fn csv_value(value: &Value, dates: &DateFormat) -> String {
+    match value {
+        Value::Text(s) => csv_cell(s),
+        Value::Date(_) | Value::Time(_) => csv_cell(&value.render(dates)),
+        Value::Intensities { counts, .. } => {
+            let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+            counts.join(" ")
+        }
+        _ => json_value(value, dates),
+    }
+}
+
+/// Renders each section as its own CSV block with a header row, separated
+/// by blank lines. Fields become a two-column block.
+fn csv_report(report: &Report) -> String {
+    let blocks: Vec<String> = report
+        .sections
+        .iter()
+        .map(|section| {
+            let mut out = String::new();
+            match section {
+                Section::Fields(fields) => {
+                    out.push_str("Field,Value\n");
+                    for (label, value) in fields {
+                        out.push_str(&format!(
+                            "{},{}\n",
+                            csv_cell(label),
+                            csv_value(value, &report.date_format)
+                        ));
+                    }
+                }
+                Section::Table(table) => {
+                    let header: Vec<String> = table.columns.iter().map(|c| csv_cell(c)).collect();
+                    out.push_str(&format!("{}\n", header.join(",")));
+                    for row in &table.rows {
+                        let cells: Vec<String> = row
+                            .iter()
+                            .map(|v| csv_value(v, &report.date_format))
+                            .collect();
+                        out.push_str(&format!("{}\n", cells.join(",")));
+                    }
+                }
+            }
+            out
+        })
+        .collect();
+    blocks.join("\n")
+}
+
+/// Renders `value` as a bar of up to `width` blocks, scaled against `max`.
+pub fn bar(value: usize, max: usize, width: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    let blocks = (value * width).div_ceil(max);
+    "█".repeat(blocks)
+}
+
+/// Returns `part` as a percentage of `total`, or zero for an empty total.
+pub fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fields_and_table() {
+        let mut report = Report::new("Git Repository Statistics");
+        report.field("Total Commits", 1234usize);
+        let mut table = Table::new("Commits per month", &["Month", "Commits"]);
+        table.push(vec!["2024-01".into(), 7usize.into()]);
+        report.table(table);
+
+        let expected = "\
+Git Repository Statistics
+=========================
+Total Commits:              1,234
+
+Commits per month
+-------------------------
+Month                     Commits
+2024-01                         7
+";
+        assert_eq!(expected, report.to_string());
+    }
+
+    #[test]
+    fn test_render_json_and_markdown() {
+        let mut report = Report::new("Stats");
+        report.command = "commits".to_string();
+        report.field("Size", Value::Size(2048));
+        let mut table = Table::new("Top", &["Name", "Share"]);
+        table.push(vec!["a \"b\" | c".into(), Value::Percent(12.34)]);
+        report.table(table);
+        let mut formulas = report.clone();
+        let mut table = Table::new("Subjects", &["Subject"]);
+        table.push(vec!["=HYPERLINK(\"x\")".into()]);
+        table.push(vec!["-1".into()]);
+        formulas.table(table);
+
+        assert_eq!(
+            "[{\"title\":\"Stats\",\"command\":\"commits\",\"sections\":[\
+             {\"fields\":{\"Size\":2048}},\
+             {\"table\":{\"title\":\"Top\",\"columns\":[\"Name\",\"Share\"],\
+             \"rows\":[[\"a \\\"b\\\" | c\",12.3]]}}]}]\n",
+            render(std::slice::from_ref(&report), Format::Json)
+        );
+        assert!(render(&[report.clone(), report.clone()], Format::Json).starts_with("[{"));
+        let mut snapshotted = report.clone();
+        snapshotted.snapshot = Some("00ff".to_string());
+        assert!(
+            render(&[snapshotted], Format::Json).starts_with(
+                "[{\"title\":\"Stats\",\"command\":\"commits\",\"snapshot\":\"00ff\","
+            )
+        );
+        assert_eq!(
+            "# Stats\n\n| Field | Value |\n|---|---:|\n| Size | 2.0 KB |\n\n## Top\n\n\
+             | Name | Share |\n|---|---:|\n| a \"b\" \\| c | 12.3% |\n",
+            render(std::slice::from_ref(&report), Format::Markdown)
+        );
+        let html = render(std::slice::from_ref(&report), Format::Html);
+        assert!(html.starts_with(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Stats</title>"
+        ));
+        assert!(html.contains(
+            "<tr><th>Size</th><td class=\"value\">2.0 KB</td></tr>\n</table>\n\
+             <h2>Top</h2>\n<table>\n<tr><th>Name</th><th>Share</th></tr>\n\
+             <tr><td>a &quot;b&quot; | c</td><td class=\"value\">12.3%</td></tr>\n"
+        ));
+        assert_eq!(
+            "Field,Value\nSize,2048\n\nName,Share\n\"a \"\"b\"\" | c\",12.3\n",
+            render(std::slice::from_ref(&report), Format::Csv)
+        );
+        assert!(
+            render(&[formulas], Format::Csv)
+                .ends_with("\n\nSubject\n\"'=HYPERLINK(\"\"x\"\")\"\n'-1\n")
+        );
+        assert_eq!(
+            Some(Format::Json),
+            Format::from_path(Path::new("out/r.JSON"))
+        );
+        assert_eq!(None, Format::from_path(Path::new("report")));
+
+        let levels = Value::Intensities {
+            counts: vec![0, 1, 4],
+            max: 4,
+        };
+        assert_eq!("··░░██", levels.to_string());
+        assert_eq!("[0,1,4]", json_value(&levels, &DateFormat::Iso));
+        assert_eq!(
+            "\"1970-01-02\"",
+            json_value(&Value::Date(1), &DateFormat::Iso)
+        );
+        assert_eq!("86400", json_value(&Value::Date(1), &DateFormat::Unix));
+    }
+}
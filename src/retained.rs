@@ -0,0 +1,149 @@
+//! Blob data that only history still holds.
+//!
+//! Every blob some commit once added but HEAD's tree no longer has is
+//! carried by every clone: old versions of files still tracked, and whole
+//! files deleted since. Deleted files are the usual targets of a history
+//! rewrite with `git filter-repo`, so they are listed by path. Blobs are
+//! attributed to the path they were first added at.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{Delta, ObjectType, Oid, Repository};
+
+use crate::{churn::diff_commit, history::CommitInfo, tree::TreeFile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedFile {
+    pub path: String,
+    /// Distinct blobs committed at the path.
+    pub versions: usize,
+    /// Their total size in bytes.
+    pub size: u64,
+    /// Latest commit deleting the path.
+    pub deleted_in: Option<Oid>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetainedData {
+    /// Distinct blobs the history ever added, and their size.
+    pub history: (usize, u64),
+    /// Those not in HEAD's tree.
+    pub retained: (usize, u64),
+    /// Those among them added at paths deleted since.
+    pub deleted: (usize, u64),
+    /// Deleted paths, largest first.
+    pub files: Vec<DeletedFile>,
+}
+
+/// Measures the blobs `commits` added that are not among the `files` at
+/// HEAD.
+pub fn get_retained_data(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    files: &[TreeFile],
+) -> Result<RetainedData, git2::Error> {
+    let mut oldest_first: Vec<&CommitInfo> = commits.iter().collect();
+    oldest_first.sort_by_key(|c| c.time);
+    let mut added: HashMap<Oid, String> = HashMap::new();
+    let mut deletions: HashMap<String, Oid> = HashMap::new();
+    for commit in oldest_first {
+        let Some(diff) = diff_commit(repo, commit.oid)? else {
+            continue;
+        };
+        for delta in diff.deltas() {
+            let file = match delta.status() {
+                Delta::Added | Delta::Modified => delta.new_file(),
+                Delta::Deleted => {
+                    if let Some(path) = delta.old_file().path() {
+                        deletions.insert(path.to_string_lossy().into_owned(), commit.oid);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(path) = file.path() {
+                added
+                    .entry(file.id())
+                    .or_insert_with(|| path.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    let head_blobs: HashSet<Oid> = files.iter().map(|f| f.oid).collect();
+    let head_paths: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let odb = repo.odb()?;
+    let mut data = RetainedData::default();
+    let mut deleted: HashMap<String, DeletedFile> = HashMap::new();
+    for (oid, path) in added {
+        // Submodule entries point at commits that are not in this repository.
+        let Ok((size, ObjectType::Blob)) = odb.read_header(oid) else {
+            continue;
+        };
+        let size = size as u64;
+        data.history.0 += 1;
+        data.history.1 += size;
+        if head_blobs.contains(&oid) {
+            continue;
+        }
+        data.retained.0 += 1;
+        data.retained.1 += size;
+        if head_paths.contains(path.as_str()) {
+            continue;
+        }
+        data.deleted.0 += 1;
+        data.deleted.1 += size;
+        let file = deleted.entry(path.clone()).or_insert_with(|| DeletedFile {
+            deleted_in: deletions.get(&path).copied(),
+            path,
+            versions: 0,
+            size: 0,
+        });
+        file.versions += 1;
+        file.size += size;
+    }
+    data.files = deleted.into_values().collect();
+    data.files
+        .sort_by(|a, b| b.size.cmp(&a.size).then(a.path.cmp(&b.path)));
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_retained_data() {
+        let t = TestRepo::new();
+        t.commit_as(
+            "A",
+            "a@x.com",
+            100,
+            "add",
+            &[("a.txt", "1"), ("dump.sql", "12345")],
+        );
+        t.commit_as("A", "a@x.com", 200, "grow", &[("dump.sql", "1234567")]);
+        let mut index = t.repo.index().unwrap();
+        index.remove_path(std::path::Path::new("dump.sql")).unwrap();
+        index.write().unwrap();
+        let removal = t.commit_as("A", "a@x.com", 300, "remove", &[("a.txt", "22")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let data = get_retained_data(&t.repo, &commits, &files).unwrap();
+        assert_eq!((4, 15), data.history);
+        assert_eq!((3, 13), data.retained);
+        assert_eq!((2, 12), data.deleted);
+        assert_eq!(
+            vec![DeletedFile {
+                path: "dump.sql".to_string(),
+                versions: 2,
+                size: 12,
+                deleted_in: Some(removal),
+            }],
+            data.files
+        );
+    }
+}
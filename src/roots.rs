@@ -0,0 +1,133 @@
+//! Root commits and disconnected histories.
+//!
+//! Most repositories have one root commit. More roots mean that unrelated
+//! histories were joined, as when a project is merged in with
+//! `--allow-unrelated-histories`, or that orphan branches such as `gh-pages`
+//! keep their own history. A history is a set of commits connected through
+//! parent links; merged-in projects share one with the main line, orphan
+//! branches do not.
+
+use std::collections::HashMap;
+
+use git2::Oid;
+
+use crate::history::{CommitInfo, RefSnapshot, shorthand};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    /// Root commits of the history, oldest first.
+    pub roots: Vec<Oid>,
+    pub commits: usize,
+    /// Committer time of the newest commit.
+    pub last_activity: i64,
+    /// Branches and other references pointing into the history, sorted.
+    pub refs: Vec<String>,
+}
+
+/// Returns the representative of `index`'s set, compressing the path.
+fn find(sets: &mut [usize], mut index: usize) -> usize {
+    while sets[index] != index {
+        sets[index] = sets[sets[index]];
+        index = sets[index];
+    }
+    index
+}
+
+/// Splits `commits` into disconnected histories, largest first, with the
+/// branches and tags of `snapshot` pointing into each. Parents missing from
+/// `commits`, as in shallow clones, are ignored.
+pub fn get_histories(snapshot: &RefSnapshot, commits: &[CommitInfo]) -> Vec<History> {
+    let indices: HashMap<Oid, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.oid, i))
+        .collect();
+    let mut sets: Vec<usize> = (0..commits.len()).collect();
+    for (i, commit) in commits.iter().enumerate() {
+        for parent in commit.parents.iter().filter_map(|p| indices.get(p)) {
+            let (a, b) = (find(&mut sets, i), find(&mut sets, *parent));
+            sets[a] = b;
+        }
+    }
+
+    let mut histories: HashMap<usize, History> = HashMap::new();
+    let mut roots: HashMap<usize, Vec<(i64, Oid)>> = HashMap::new();
+    for (i, commit) in commits.iter().enumerate() {
+        let set = find(&mut sets, i);
+        let history = histories.entry(set).or_insert_with(|| History {
+            roots: Vec::new(),
+            commits: 0,
+            last_activity: commit.time,
+            refs: Vec::new(),
+        });
+        history.commits += 1;
+        history.last_activity = history.last_activity.max(commit.time);
+        if commit.parents.is_empty() {
+            roots
+                .entry(set)
+                .or_default()
+                .push((commit.time, commit.oid));
+        }
+    }
+    for (set, mut oids) in roots {
+        oids.sort();
+        histories
+            .get_mut(&set)
+            .expect("roots belong to a history")
+            .roots = oids.into_iter().map(|(_, oid)| oid).collect();
+    }
+    for (name, oid) in &snapshot.refs {
+        if let Some(i) = indices.get(oid) {
+            let set = find(&mut sets, *i);
+            histories
+                .get_mut(&set)
+                .expect("every commit belongs to a history")
+                .refs
+                .push(shorthand(name).to_string());
+        }
+    }
+
+    let mut histories: Vec<History> = histories.into_values().collect();
+    for history in &mut histories {
+        history.refs.sort();
+    }
+    histories.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.roots.cmp(&b.roots)));
+    histories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    #[test]
+    fn test_histories() {
+        let t = TestRepo::new();
+        let main = t.commit_as("A", "a@x.com", 1, "init", &[("a.txt", "1")]);
+        t.commit_as("A", "a@x.com", 2, "two", &[("a.txt", "2")]);
+        let branch = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.repo.set_head("refs/heads/gh-pages").unwrap();
+        let pages = t.commit_as("A", "a@x.com", 3, "pages", &[("index.html", "")]);
+
+        let snapshot = RefSnapshot::take(&t.repo).unwrap();
+        let commits = collect_commits(&t.repo, Some(&snapshot), &mut Progress::default()).unwrap();
+        let histories = get_histories(&snapshot, &commits);
+        assert_eq!(
+            vec![
+                History {
+                    roots: vec![main],
+                    commits: 2,
+                    last_activity: 2,
+                    refs: vec![branch],
+                },
+                History {
+                    roots: vec![pages],
+                    commits: 1,
+                    last_activity: 3,
+                    refs: vec!["gh-pages".to_string()],
+                },
+            ],
+            histories
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Each contributor's share of the work, measured three ways.
+//!
+//! Commit counts favour those who commit often in small steps, lines changed
+//! favour bulk edits and generated files, and ownership of the lines at HEAD
+//! only credits work that survived. Side by side they tell more than any one
+//! of them alone.
+
+use std::collections::HashMap;
+
+use crate::report::percent;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContributionShare {
+    pub contributor: String,
+    /// Percent of all commits.
+    pub commits: f64,
+    /// Percent of all lines inserted or deleted.
+    pub lines_changed: f64,
+    /// Percent of the lines at HEAD last changed by the contributor.
+    pub lines_owned: f64,
+}
+
+/// Combines per-contributor commit counts, changed lines and owned lines
+/// into percentages of their totals, ordered by commit share.
+pub fn get_shares(
+    commits: &[(String, usize)],
+    lines_changed: &[(String, usize)],
+    lines_owned: &[(String, usize)],
+) -> Vec<ContributionShare> {
+    let measures = [commits, lines_changed, lines_owned];
+    let mut counts: HashMap<&str, [usize; 3]> = HashMap::new();
+    for (index, measure) in measures.iter().enumerate() {
+        for (contributor, count) in *measure {
+            counts.entry(contributor).or_default()[index] += count;
+        }
+    }
+    let totals = measures.map(|m| m.iter().map(|(_, n)| n).sum::<usize>());
+
+    let mut shares: Vec<ContributionShare> = counts
+        .into_iter()
+        .map(|(contributor, counts)| ContributionShare {
+            contributor: contributor.to_string(),
+            commits: percent(counts[0], totals[0]),
+            lines_changed: percent(counts[1], totals[1]),
+            lines_owned: percent(counts[2], totals[2]),
+        })
+        .collect();
+    shares.sort_by(|a, b| {
+        b.commits
+            .total_cmp(&a.commits)
+            .then(b.lines_owned.total_cmp(&a.lines_owned))
+            .then(a.contributor.cmp(&b.contributor))
+    });
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shares() {
+        let counts = |pairs: &[(&str, usize)]| -> Vec<(String, usize)> {
+            pairs.iter().map(|(c, n)| (c.to_string(), *n)).collect()
+        };
+        let shares = get_shares(
+            &counts(&[("a", 3), ("b", 1)]),
+            &counts(&[("a", 10), ("b", 30)]),
+            &counts(&[("b", 5), ("c", 5)]),
+        );
+        assert_eq!(
+            vec![
+                ContributionShare {
+                    contributor: "a".to_string(),
+                    commits: 75.0,
+                    lines_changed: 25.0,
+                    lines_owned: 0.0,
+                },
+                ContributionShare {
+                    contributor: "b".to_string(),
+                    commits: 25.0,
+                    lines_changed: 75.0,
+                    lines_owned: 50.0,
+                },
+                ContributionShare {
+                    contributor: "c".to_string(),
+                    commits: 0.0,
+                    lines_changed: 0.0,
+                    lines_owned: 50.0,
+                },
+            ],
+            shares
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! `Signed-off-by` trailers, as required by projects enforcing the Developer
+//! Certificate of Origin (DCO).
+
+use std::collections::HashMap;
+
+use git2::Oid;
+
+use crate::{history::CommitInfo, identity::contributor_key, message::trailers};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignoffStats {
+    pub commits: usize,
+    /// Commits with at least one `Signed-off-by` trailer.
+    pub signed: usize,
+    /// Commits signed off by their own author, as the DCO expects.
+    pub signed_by_author: usize,
+}
+
+impl SignoffStats {
+    /// Counts `commit`, comparing its sign-offs with the author `email` it
+    /// was recorded with.
+    fn add(&mut self, commit: &CommitInfo, email: &str) {
+        let signers: Vec<&str> = trailers(&commit.message)
+            .into_iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("Signed-off-by"))
+            .map(|(_, value)| value)
+            .collect();
+        self.commits += 1;
+        self.signed += usize::from(!signers.is_empty());
+        let by_author = signers.iter().any(|signer| {
+            let signer_email = signer
+                .rsplit_once('<')
+                .map_or(*signer, |(_, email)| email.trim_end_matches('>'));
+            signer_email.trim().eq_ignore_ascii_case(email)
+        });
+        self.signed_by_author += usize::from(by_author);
+    }
+}
+
+/// Counts signed-off commits overall and per contributor, with contributors
+/// ordered by commit count. Sign-offs are compared with the author emails
+/// in `recorded`, as `--anonymize` and `--merge-identities` rewrite those in
+/// `commits` but not the trailers.
+pub fn get_signoff_stats(
+    commits: &[CommitInfo],
+    recorded: &[CommitInfo],
+) -> (SignoffStats, Vec<(String, SignoffStats)>) {
+    let recorded: HashMap<Oid, &str> = recorded
+        .iter()
+        .map(|c| (c.oid, c.author_email.as_str()))
+        .collect();
+    let mut overall = SignoffStats::default();
+    let mut authors: HashMap<String, SignoffStats> = HashMap::new();
+    for commit in commits {
+        let email = recorded
+            .get(&commit.oid)
+            .copied()
+            .unwrap_or(&commit.author_email);
+        overall.add(commit, email);
+        authors
+            .entry(contributor_key(commit))
+            .or_default()
+            .add(commit, email);
+    }
+    let mut authors: Vec<_> = authors.into_iter().collect();
+    authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(&b.0)));
+    (overall, authors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::anonymize_identities;
+
+    #[test]
+    fn test_signoff_stats() {
+        let commit = |email: &str, message: &str| CommitInfo {
+            author_name: "a".to_string(),
+            author_email: email.to_string(),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("a@x.com", "Fix\n\nSigned-off-by: A <A@x.com>\n"),
+            commit("a@x.com", "Fix\n\nsigned-off-by: Maintainer <m@x.com>\n"),
+            commit("a@x.com", "Unsigned\n"),
+        ];
+        let (overall, authors) = get_signoff_stats(&commits, &commits);
+
+        assert_eq!(
+            SignoffStats {
+                commits: 3,
+                signed: 2,
+                signed_by_author: 1
+            },
+            overall
+        );
+        assert_eq!(1, authors.len());
+    }
+
+    #[test]
+    fn test_signoff_stats_anonymized() {
+        let recorded = vec![CommitInfo {
+            oid: Oid::from_str("1111111111111111111111111111111111111111").unwrap(),
+            author_name: "A".to_string(),
+            author_email: "a@x.com".to_string(),
+            message: "Fix\n\nSigned-off-by: A <a@x.com>\n".to_string(),
+            ..Default::default()
+        }];
+        let mut commits = recorded.clone();
+        anonymize_identities(&mut commits);
+        assert_ne!("a@x.com", commits[0].author_email);
+
+        let (overall, _) = get_signoff_stats(&commits, &recorded);
+        assert_eq!(1, overall.signed_by_author);
+    }
+}
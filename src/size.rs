@@ -0,0 +1,294 @@
+//! What "repository size" means.
+//!
+//! Users ask different questions with it: how many bytes the files add up to,
+//! how much disk the repository takes, or how much history there is
+//! regardless of packing and garbage. Each answer is a [`SizeProvider`].
+
+use std::{collections::HashSet, error::Error, fmt, fs, str::FromStr};
+
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+
+use crate::{
+    history::{is_missing, revwalk_all_refs},
+    stats::directory_size,
+    tree::TreeFile,
+    worktrees::common_dir,
+};
+
+pub trait SizeProvider {
+    /// Measures the repository in bytes.
+    fn size(&self, repo: &Repository) -> Result<u64, Box<dyn Error>>;
+}
+
+/// Sum of the lengths of the files in the git directory shared by all
+/// worktrees, as `du --apparent-size` reports.
+pub struct ApparentSize;
+
+impl SizeProvider for ApparentSize {
+    fn size(&self, repo: &Repository) -> Result<u64, Box<dyn Error>> {
+        directory_size(&common_dir(repo), &|metadata| metadata.len())
+    }
+}
+
+/// Disk blocks allocated to the files in the shared git directory, as `du`
+/// reports. Falls back to apparent sizes on platforms without block counts.
+pub struct AllocatedSize;
+
+#[cfg(unix)]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks is always in 512-byte units, whatever the filesystem's block
+    // size.
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+impl SizeProvider for AllocatedSize {
+    fn size(&self, repo: &Repository) -> Result<u64, Box<dyn Error>> {
+        directory_size(&common_dir(repo), &allocated)
+    }
+}
+
+/// Uncompressed size of the objects reachable from any reference, leaving
+/// out unreachable objects and every effect of packing.
+pub struct ReachableSize;
+
+impl SizeProvider for ReachableSize {
+    fn size(&self, repo: &Repository) -> Result<u64, Box<dyn Error>> {
+        let odb = repo.odb()?;
+        let mut seen: HashSet<Oid> = HashSet::new();
+        let mut total = 0u64;
+        let mut add = |oid: Oid, total: &mut u64| -> Result<bool, git2::Error> {
+            if !seen.insert(oid) {
+                return Ok(false);
+            }
+            *total += odb.read_header(oid)?.0 as u64;
+            Ok(true)
+        };
+
+        // Annotated tags are objects of their own.
+        for reference in repo.references_glob("refs/tags/*")? {
+            if let Some(oid) = reference?.target()
+                && odb.read_header(oid)?.1 == ObjectType::Tag
+            {
+                add(oid, &mut total)?;
+            }
+        }
+        for oid in revwalk_all_refs(repo)? {
+            let commit = match oid.and_then(|oid| repo.find_commit(oid)) {
+                Ok(commit) => commit,
+                Err(e) if is_missing(&e) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if !add(commit.id(), &mut total)? {
+                continue;
+            }
+            let tree = commit.tree()?;
+            if !add(tree.id(), &mut total)? {
+                continue;
+            }
+            let mut error = None;
+            tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+                if !matches!(entry.kind(), Some(ObjectType::Tree | ObjectType::Blob)) {
+                    return TreeWalkResult::Ok;
+                }
+                match add(entry.id(), &mut total) {
+                    Ok(true) => TreeWalkResult::Ok,
+                    // A subtree seen before was walked before.
+                    Ok(false) => TreeWalkResult::Skip,
+                    Err(e) => {
+                        error = Some(e);
+                        TreeWalkResult::Abort
+                    }
+                }
+            })?;
+            if let Some(e) = error {
+                return Err(e.into());
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// Selects a [`SizeProvider`] on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeMode {
+    #[default]
+    Apparent,
+    Allocated,
+    Reachable,
+}
+
+impl SizeMode {
+    pub fn provider(&self) -> Box<dyn SizeProvider> {
+        match self {
+            SizeMode::Apparent => Box::new(ApparentSize),
+            SizeMode::Allocated => Box::new(AllocatedSize),
+            SizeMode::Reachable => Box::new(ReachableSize),
+        }
+    }
+}
+
+impl FromStr for SizeMode {
+    type Err = String;
+
+    /// Parses `apparent`, `allocated` or `reachable`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "apparent" => Ok(SizeMode::Apparent),
+            "allocated" => Ok(SizeMode::Allocated),
+            "reachable" => Ok(SizeMode::Reachable),
+            _ => Err(format!(
+                "invalid size mode '{}', expected apparent, allocated or reachable",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SizeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeMode::Apparent => write!(f, "apparent"),
+            SizeMode::Allocated => write!(f, "allocated"),
+            SizeMode::Reachable => write!(f, "reachable"),
+        }
+    }
+}
+
+/// Parts of the shared git directory, as `(label, paths relative to it)`.
+/// Whatever they leave out, such as reflogs and config, is reported as
+/// other.
+const COMPONENTS: [(&str, &[&str]); 6] = [
+    ("Packfiles", &["objects/pack"]),
+    ("Loose Objects", &[]),
+    ("Refs", &["refs", "packed-refs"]),
+    ("Hooks", &["hooks"]),
+    ("Index", &["index"]),
+    ("LFS Cache", &["lfs"]),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    /// Bytes per part of the git directory, ending with the rest as other.
+    pub components: Vec<(&'static str, u64)>,
+    /// Bytes the files tracked at HEAD take in the working tree, so build
+    /// output and other ignored files are left out. Bare repositories have
+    /// none.
+    pub working_tree: Option<u64>,
+}
+
+impl SizeBreakdown {
+    pub fn total(&self) -> u64 {
+        self.components.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Splits the size of the shared git directory into its parts, and measures
+/// the checked-out copies of `files`, by their allocated blocks rather than
+/// their length if `allocated` is set. Loose objects are the objects not in
+/// packs or `objects/info`.
+pub fn get_size_breakdown(
+    repo: &Repository,
+    files: &[TreeFile],
+    allocated: bool,
+) -> Result<SizeBreakdown, Box<dyn Error>> {
+    let measure: &dyn Fn(&fs::Metadata) -> u64 = if allocated {
+        &self::allocated
+    } else {
+        &|metadata| metadata.len()
+    };
+    let dir = common_dir(repo);
+    let size = |paths: &[&str]| -> Result<u64, Box<dyn Error>> {
+        paths
+            .iter()
+            .map(|p| directory_size(&dir.join(p), measure))
+            .sum()
+    };
+    let total = directory_size(&dir, measure)?;
+    let mut components = Vec::with_capacity(COMPONENTS.len() + 1);
+    for (label, paths) in COMPONENTS {
+        let bytes = if paths.is_empty() {
+            directory_size(&dir.join("objects"), measure)?
+                .saturating_sub(size(&["objects/pack", "objects/info"])?)
+        } else {
+            size(paths)?
+        };
+        components.push((label, bytes));
+    }
+    let known: u64 = components.iter().map(|(_, size)| size).sum();
+    components.push(("Other", total.saturating_sub(known)));
+
+    // Deleted or unreadable files take no space.
+    let working_tree = repo.workdir().map(|workdir| {
+        files
+            .iter()
+            .filter_map(|f| fs::symlink_metadata(workdir.join(&f.path)).ok())
+            .map(|metadata| measure(&metadata))
+            .sum()
+    });
+    Ok(SizeBreakdown {
+        components,
+        working_tree,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testutil::TestRepo, tree::get_head_files};
+
+    #[test]
+    fn test_size_providers() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "hello"), ("b.txt", "hello")]);
+        t.commit("two", &[("a.txt", "hello, world")]);
+        // Not reachable from any reference.
+        t.repo.blob(&[0; 4096]).unwrap();
+
+        let reachable = ReachableSize.size(&t.repo).unwrap();
+        let apparent = ApparentSize.size(&t.repo).unwrap();
+        // Two commits, two trees and two distinct blobs of 5 and 12 bytes.
+        assert!(reachable > 17 && reachable < 1_000, "{}", reachable);
+        assert!(apparent > reachable);
+        assert!(AllocatedSize.size(&t.repo).unwrap() > 0);
+        assert_eq!(Ok(SizeMode::Reachable), "reachable".parse());
+
+        let files = get_head_files(&t.repo, None).unwrap();
+        let breakdown = get_size_breakdown(&t.repo, &files, false).unwrap();
+        assert_eq!(apparent, breakdown.total());
+        let component = |label| {
+            breakdown
+                .components
+                .iter()
+                .find(|(l, _)| *l == label)
+                .unwrap()
+                .1
+        };
+        // Objects written by libgit2 are loose until repacked.
+        assert!(component("Loose Objects") > 0);
+        assert_eq!(0, component("Packfiles"));
+        assert!(component("Index") > 0);
+        assert_eq!(Some(17), breakdown.working_tree);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_size_breakdown_symlink_loop() {
+        let t = TestRepo::new();
+        t.commit("one", &[("a.txt", "hi")]);
+        let workdir = t.repo.workdir().unwrap();
+        std::os::unix::fs::symlink("..", workdir.join("loop")).unwrap();
+        std::os::unix::fs::symlink(".", workdir.join(".git/self")).unwrap();
+        std::fs::write(workdir.join("ignored.bin"), [0; 4096]).unwrap();
+
+        let files = get_head_files(&t.repo, None).unwrap();
+        let breakdown = get_size_breakdown(&t.repo, &files, false).unwrap();
+        assert_eq!(Some(2), breakdown.working_tree);
+        assert!(breakdown.total() < 1_000_000, "{}", breakdown.total());
+    }
+}
@@ -0,0 +1,154 @@
+//! Sparse-checkout cone recommendations from a contributor's recent work.
+//!
+//! Files touched by the contributor are grouped into directories no deeper
+//! than a given depth, and the most-touched directories are picked until they
+//! cover the requested share of touches. Files at the repository root are
+//! always part of a cone-mode checkout and count as covered.
+
+use std::collections::HashMap;
+
+use git2::Repository;
+
+use crate::{churn::get_changed_paths, history::CommitInfo, tree::TreeFile};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseRecommendation {
+    /// File touches considered, counting only files still present at HEAD.
+    pub touches: usize,
+    pub files: usize,
+    /// Root-level touches, which every cone checkout includes.
+    pub root_touches: usize,
+    /// Chosen directories with their touches, most touched first.
+    pub directories: Vec<(String, usize)>,
+    /// Share of touches covered by the chosen directories, in percent.
+    pub coverage: f64,
+    /// Size of the files at HEAD a checkout of the chosen cones contains.
+    pub checkout_size: u64,
+}
+
+/// Returns the directory of `path` truncated to `depth` components, or
+/// `None` for files at the root.
+pub fn cone_of(path: &str, depth: usize) -> Option<String> {
+    let (dir, _) = path.rsplit_once('/')?;
+    Some(dir.split('/').take(depth).collect::<Vec<_>>().join("/"))
+}
+
+/// Recommends cone directories covering at least `coverage` percent of the
+/// touches in `commits` on files that still exist in `head_files`.
+pub fn recommend_cones(
+    repo: &Repository,
+    commits: &[&CommitInfo],
+    head_files: &[TreeFile],
+    depth: usize,
+    coverage: f64,
+) -> Result<SparseRecommendation, git2::Error> {
+    let sizes: HashMap<&str, u64> = head_files
+        .iter()
+        .map(|f| (f.path.as_str(), f.size))
+        .collect();
+    let mut files: HashMap<String, usize> = HashMap::new();
+    for commit in commits {
+        for path in get_changed_paths(repo, commit.oid)? {
+            if sizes.contains_key(path.as_str()) {
+                *files.entry(path).or_default() += 1;
+            }
+        }
+    }
+
+    let touches: usize = files.values().sum();
+    let mut root_touches = 0;
+    let mut cones: HashMap<String, usize> = HashMap::new();
+    for (path, count) in &files {
+        match cone_of(path, depth) {
+            Some(cone) => *cones.entry(cone).or_default() += count,
+            None => root_touches += count,
+        }
+    }
+    let mut cones: Vec<(String, usize)> = cones.into_iter().collect();
+    cones.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut covered = root_touches;
+    let mut directories = Vec::new();
+    for (cone, count) in cones {
+        if touches == 0 || covered as f64 * 100.0 / touches as f64 >= coverage {
+            break;
+        }
+        covered += count;
+        directories.push((cone, count));
+    }
+
+    let checkout_size = head_files
+        .iter()
+        .filter(|f| match cone_of(&f.path, usize::MAX) {
+            None => true,
+            Some(dir) => directories
+                .iter()
+                .any(|(cone, _)| dir == *cone || dir.starts_with(&format!("{}/", cone))),
+        })
+        .map(|f| f.size)
+        .sum();
+
+    Ok(SparseRecommendation {
+        touches,
+        files: files.len(),
+        root_touches,
+        coverage: if touches == 0 {
+            100.0
+        } else {
+            covered as f64 * 100.0 / touches as f64
+        },
+        directories,
+        checkout_size,
+    })
+}
+
+/// Returns the `git sparse-checkout set` command selecting `directories`.
+pub fn sparse_checkout_command(directories: &[(String, usize)]) -> String {
+    let mut command = "git sparse-checkout set --cone".to_string();
+    for (dir, _) in directories {
+        command.push(' ');
+        if dir
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c))
+        {
+            command.push_str(dir);
+        } else {
+            command.push_str(&format!("'{}'", dir.replace('\'', r"'\''")));
+        }
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        history::collect_commits, progress::Progress, testutil::TestRepo, tree::get_head_files,
+    };
+
+    #[test]
+    fn test_recommend_cones() {
+        let t = TestRepo::new();
+        t.commit("a", &[("README", "r"), ("src/core/a.rs", "a")]);
+        t.commit("b", &[("src/core/a.rs", "b"), ("src/core/b.rs", "b")]);
+        t.commit("c", &[("src/core/a.rs", "c"), ("docs/intro.md", "d")]);
+        t.commit("d", &[("web/my app/x.js", "x")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let commits: Vec<&CommitInfo> = commits.iter().collect();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let recommendation = recommend_cones(&t.repo, &commits, &files, 2, 70.0).unwrap();
+
+        assert_eq!(7, recommendation.touches);
+        assert_eq!(1, recommendation.root_touches);
+        assert_eq!(
+            vec![("src/core".to_string(), 4)],
+            recommendation.directories
+        );
+        assert_eq!(3, recommendation.checkout_size);
+        assert_eq!(
+            "git sparse-checkout set --cone src/core 'web/my app'",
+            sparse_checkout_command(&[("src/core".to_string(), 4), ("web/my app".to_string(), 1)])
+        );
+    }
+}
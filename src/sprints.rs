@@ -0,0 +1,217 @@
+//! Commit statistics mapped onto sprint iterations.
+
+use std::collections::HashSet;
+
+use git2::Repository;
+
+use crate::{
+    bucket::Bucketing,
+    churn::{CommitChurn, get_commit_churn},
+    config::Config,
+    date::parse_date,
+    history::CommitInfo,
+    identity::contributor_key,
+    lang::PathFilter,
+};
+
+/// Default sprint length in days when the config does not set one.
+const DEFAULT_SPRINT_LENGTH: u32 = 14;
+
+/// Sprint boundaries, either a fixed cadence or an explicit list of start
+/// dates where each sprint runs until the next one starts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sprints {
+    Fixed { length: u32, anchor: i64 },
+    Explicit { starts: Vec<i64>, last_length: u32 },
+}
+
+impl Sprints {
+    /// Reads the `[sprints]` section: either `start` and `length`, or a
+    /// `dates` list of sprint start dates (with `length` for the final sprint).
+    pub fn from_config(config: &Config) -> Result<Option<Sprints>, String> {
+        let length = match config.get_int("sprints", "length")? {
+            Some(n) if n > 0 => n as u32,
+            Some(n) => return Err(format!("invalid sprint length {}", n)),
+            None => DEFAULT_SPRINT_LENGTH,
+        };
+        if let Some(dates) = config.get_str_array("sprints", "dates")? {
+            let mut starts = dates
+                .iter()
+                .map(|d| parse_date(d))
+                .collect::<Result<Vec<_>, _>>()?;
+            if starts.is_empty() {
+                return Err("sprints.dates must not be empty".to_string());
+            }
+            starts.sort_unstable();
+            starts.dedup();
+            return Ok(Some(Sprints::Explicit {
+                starts,
+                last_length: length,
+            }));
+        }
+        match config.get_str("sprints", "start")? {
+            Some(start) => Ok(Some(Sprints::Fixed {
+                length,
+                anchor: parse_date(start)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns fixed sprints for a `sprint:` bucketing, if it is one.
+    pub fn from_bucketing(bucketing: Bucketing) -> Option<Sprints> {
+        match bucketing {
+            Bucketing::Sprint { days, anchor } => Some(Sprints::Fixed {
+                length: days,
+                anchor,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the `[start, end)` days of the sprint containing `day`.
+    pub fn sprint_of(&self, day: i64) -> Option<(i64, i64)> {
+        match self {
+            Sprints::Fixed { length, anchor } => {
+                let bucketing = Bucketing::Sprint {
+                    days: *length,
+                    anchor: *anchor,
+                };
+                let start = bucketing.start_of(day);
+                Some((start, bucketing.next(start)))
+            }
+            Sprints::Explicit {
+                starts,
+                last_length,
+            } => {
+                let index = starts.partition_point(|s| *s <= day).checked_sub(1)?;
+                let start = starts[index];
+                let end = starts
+                    .get(index + 1)
+                    .copied()
+                    .unwrap_or(start + *last_length as i64);
+                (day < end).then_some((start, end))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SprintStats {
+    pub start: i64,
+    pub end: i64,
+    pub commits: usize,
+    pub contributors: usize,
+    pub churn: CommitChurn,
+}
+
+/// Aggregates commits, distinct authors and churn per sprint, in
+/// chronological order. Commits outside every sprint are ignored.
+pub fn get_sprint_stats(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    sprints: &Sprints,
+    filter: &PathFilter,
+) -> Result<Vec<SprintStats>, git2::Error> {
+    let mut stats: Vec<(SprintStats, HashSet<String>)> = Vec::new();
+
+    for commit in commits {
+        let Some((start, end)) = sprints.sprint_of(commit.day()) else {
+            continue;
+        };
+        let index = match stats.iter().position(|(s, _)| s.start == start) {
+            Some(index) => index,
+            None => {
+                stats.push((
+                    SprintStats {
+                        start,
+                        end,
+                        commits: 0,
+                        contributors: 0,
+                        churn: CommitChurn::default(),
+                    },
+                    HashSet::new(),
+                ));
+                stats.len() - 1
+            }
+        };
+        let (sprint, authors) = &mut stats[index];
+        sprint.commits += 1;
+        sprint.churn += get_commit_churn(repo, commit.oid, filter)?;
+        authors.insert(contributor_key(commit));
+    }
+
+    let mut stats: Vec<SprintStats> = stats
+        .into_iter()
+        .map(|(mut sprint, authors)| {
+            sprint.contributors = authors.len();
+            sprint
+        })
+        .collect();
+    stats.sort_by_key(|s| s.start);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::days_from_civil, history::collect_commits, progress::Progress, testutil::TestRepo,
+    };
+
+    #[test]
+    fn test_sprints_from_config() {
+        let config = Config::parse("[sprints]\nstart = \"2024-01-08\"\nlength = 7").unwrap();
+        let sprints = Sprints::from_config(&config).unwrap().unwrap();
+        let day = days_from_civil(2024, 1, 17);
+        assert_eq!(
+            Some((days_from_civil(2024, 1, 15), days_from_civil(2024, 1, 22))),
+            sprints.sprint_of(day)
+        );
+
+        assert_eq!(None, Sprints::from_config(&Config::default()).unwrap());
+    }
+
+    #[test]
+    fn test_explicit_sprints() {
+        let config =
+            Config::parse("[sprints]\ndates = [\"2024-02-01\", \"2024-01-01\"]\nlength = 10")
+                .unwrap();
+        let sprints = Sprints::from_config(&config).unwrap().unwrap();
+
+        assert_eq!(None, sprints.sprint_of(days_from_civil(2023, 12, 31)));
+        assert_eq!(
+            Some((days_from_civil(2024, 1, 1), days_from_civil(2024, 2, 1))),
+            sprints.sprint_of(days_from_civil(2024, 1, 20))
+        );
+        assert_eq!(
+            Some((days_from_civil(2024, 2, 1), days_from_civil(2024, 2, 11))),
+            sprints.sprint_of(days_from_civil(2024, 2, 10))
+        );
+        assert_eq!(None, sprints.sprint_of(days_from_civil(2024, 2, 11)));
+    }
+
+    #[test]
+    fn test_sprint_stats() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        let base = days_from_civil(2024, 1, 8) * day;
+        t.commit_as("a", "a@x.com", base, "one", &[("a.txt", "1\n2\n")]);
+        t.commit_as("b", "b@x.com", base + day, "two", &[("a.txt", "1\n")]);
+        t.commit_as("a", "a@x.com", base + 8 * day, "three", &[("b.txt", "x\n")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let sprints = Sprints::Fixed {
+            length: 7,
+            anchor: days_from_civil(2024, 1, 8),
+        };
+        let stats = get_sprint_stats(&t.repo, &commits, &sprints, &PathFilter::default()).unwrap();
+
+        assert_eq!(2, stats.len());
+        assert_eq!(2, stats[0].commits);
+        assert_eq!(2, stats[0].contributors);
+        assert_eq!(2, stats[0].churn.insertions);
+        assert_eq!(1, stats[0].churn.deletions);
+        assert_eq!(1, stats[1].commits);
+    }
+}
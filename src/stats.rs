@@ -1,124 +1,94 @@
-use std::{collections::HashSet, fs, path::Path};
+use std::{fs, path::Path};
 
-use git2::{BranchType, Repository};
-
-pub fn get_total_commits(repo: &Repository) -> Result<usize, git2::Error> {
-    let mut count = 0;
-    let mut visited = HashSet::new();
-
-    // Collect all head OIDs from references
-    let mut heads = Vec::new();
-    let refs = repo.references()?;
-
-    for reference in refs {
-        let reference = reference?;
-        if let Some(oid) = reference.target() {
-            heads.push(oid);
-        }
-    }
-
-    // Walk from all heads in a single revwalk
-    let mut revwalk = repo.revwalk()?;
-    for head in heads {
-        revwalk.push(head)?;
-    }
-    revwalk.set_sorting(git2::Sort::NONE)?;
-
-    for oid in revwalk {
-        let oid = oid?;
-        if visited.insert(oid) {
-            count += 1;
-        }
+/// Formats a byte count in human-readable form, e.g. `41.5 KB`.
+pub fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else if size < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
     }
-
-    Ok(count)
 }
 
-pub fn get_branch_count(repo: &Repository) -> Result<usize, git2::Error> {
-    let branches = repo.branches(Some(BranchType::Local))?;
-    let mut count = 0;
+/// Parses a size such as `512`, `100KB`, `1.5 MB` or `2G` into bytes, using
+/// binary multiples like [`format_size`].
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", s))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1u64,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("invalid size unit in '{}'", s)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
 
-    for branch in branches {
-        let _ = branch?;
-        count += 1;
+/// Returns the `p`th percentile (0-100) of sorted values, using the
+/// nearest-rank method.
+pub fn percentile<T: Copy>(sorted: &[T], p: f64) -> Option<T> {
+    if sorted.is_empty() {
+        return None;
     }
-
-    Ok(count)
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
 }
 
-pub fn get_contributor_count(repo: &Repository) -> Result<usize, git2::Error> {
-    let mut contributors = HashSet::new();
-    let mut visited = HashSet::new();
-
-    // Collect all head OIDs from references
-    let mut heads = Vec::new();
-    let refs = repo.references()?;
-
-    for reference in refs {
-        let reference = reference?;
-        if let Some(oid) = reference.target() {
-            heads.push(oid);
-        }
+/// Parses a positive number of standard deviations.
+pub fn parse_sigma(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(sigma) if sigma > 0.0 && sigma.is_finite() => Ok(sigma),
+        _ => Err(format!(
+            "invalid deviation '{}', expected a positive number",
+            s
+        )),
     }
+}
 
-    // Walk from all heads in a single revwalk
-    let mut revwalk = repo.revwalk()?;
-    for head in heads {
-        revwalk.push(head)?;
+/// Returns the mean of `values` and their standard deviation.
+pub fn mean_deviation(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
     }
-    revwalk.set_sorting(git2::Sort::NONE)?;
-
-    for oid in revwalk {
-        let oid = oid?;
-        if visited.insert(oid) {
-            if let Ok(commit) = repo.find_commit(oid) {
-                let author = commit.author();
-                let email = author.email().unwrap_or("");
-                let name = author.name().unwrap_or("");
-                contributors.insert(format!("{} <{}>", name, email));
-            }
-        }
-    }
-
-    Ok(contributors.len())
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt())
 }
 
-pub fn get_repository_size(repo: &Repository) -> Result<String, Box<dyn std::error::Error>> {
-    let git_dir = repo.path();
-    let size = calculate_directory_size(git_dir)?;
-
-    // Format size in human-readable format
-    let size_str = if size < 1024 {
-        format!("{} B", size)
-    } else if size < 1024 * 1024 {
-        format!("{:.1} KB", size as f64 / 1024.0)
-    } else if size < 1024 * 1024 * 1024 {
-        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
-    } else {
-        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
-    };
-
-    Ok(size_str)
+pub fn calculate_directory_size(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    directory_size(path, &|metadata| metadata.len())
 }
 
-pub fn calculate_directory_size(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+/// Sums `measure` over every file below `path`, or of `path` itself if it is
+/// a file. Symlinks are measured themselves, never followed, and entries
+/// that cannot be read are skipped.
+pub fn directory_size(
+    path: &Path,
+    measure: &dyn Fn(&fs::Metadata) -> u64,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(0);
+    };
+    if !metadata.is_dir() {
+        return Ok(measure(&metadata));
+    }
     let mut total_size = 0u64;
-
-    if path.is_dir() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let entry_path = entry.path();
-
-            if entry_path.is_dir() {
-                total_size += calculate_directory_size(&entry_path)?;
-            } else if let Ok(metadata) = entry.metadata() {
-                total_size += metadata.len();
-            }
-        }
-    } else if let Ok(metadata) = fs::metadata(path) {
-        total_size += metadata.len();
+    let Ok(entries) = fs::read_dir(path) else {
+        return Ok(0);
+    };
+    for entry in entries.flatten() {
+        total_size += directory_size(&entry.path(), measure)?;
     }
-
     Ok(total_size)
 }
 
@@ -128,7 +98,7 @@ pub fn format_number(n: usize) -> String {
     let chars: Vec<char> = s.chars().collect();
 
     for (i, ch) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
+        if i > 0 && (chars.len() - i).is_multiple_of(3) {
             result.push(',');
         }
         result.push(*ch);
@@ -140,15 +110,32 @@ pub fn format_number(n: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use git2::Signature;
+    use crate::{history::collect_commits, progress::Progress};
+    use git2::{Repository, Signature};
     use tempfile::TempDir;
 
+    #[test]
+    fn test_sizes_and_percentiles() {
+        assert_eq!("512 B", format_size(512));
+        assert_eq!("1.5 MB", format_size(1536 * 1024));
+        assert_eq!(Ok(1536 * 1024), parse_size("1.5MB"));
+        assert_eq!(Ok(100 * 1024), parse_size("100 kb"));
+        assert!(parse_size("10 parsecs").is_err());
+
+        let values = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(Some(5), percentile(&values, 50.0));
+        assert_eq!(Some(10), percentile(&values, 95.0));
+        assert_eq!(None, percentile::<u64>(&[], 50.0));
+    }
+
     #[test]
     fn test_repository_with_no_commits() {
         let td = TempDir::new().unwrap();
         let path = td.path();
         let repo = Repository::init(path).unwrap();
-        let commit_count = get_total_commits(&repo).unwrap();
+        let commit_count = collect_commits(&repo, None, &mut Progress::default())
+            .unwrap()
+            .len();
         assert_eq!(0, commit_count);
     }
 
@@ -176,7 +163,9 @@ mod tests {
         )
         .unwrap();
 
-        let commit_count = get_total_commits(&repo).unwrap();
+        let commit_count = collect_commits(&repo, None, &mut Progress::default())
+            .unwrap()
+            .len();
         assert_eq!(1, commit_count);
     }
 
@@ -221,7 +210,9 @@ mod tests {
         )
         .unwrap();
 
-        let commit_count = get_total_commits(&repo).unwrap();
+        let commit_count = collect_commits(&repo, None, &mut Progress::default())
+            .unwrap()
+            .len();
         assert_eq!(2, commit_count);
     }
 }
@@ -0,0 +1,130 @@
+//! Streaks of consecutive days or weeks with commits.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{bucket::Bucketing, history::CommitInfo, identity::contributor_key};
+
+/// A run of consecutive active periods, identified by the first day of its
+/// first and last period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Streak {
+    pub length: usize,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Longest and current streaks over days and ISO weeks.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StreakStats {
+    pub longest_days: Streak,
+    pub current_days: Streak,
+    pub longest_weeks: Streak,
+    pub current_weeks: Streak,
+}
+
+/// Returns the longest streak of periods `step` days apart, and the streak
+/// still running at `current` (the period containing today). A streak that
+/// ended in the previous period is still current, since today may not have
+/// seen a commit yet.
+fn find_streaks(periods: &BTreeSet<i64>, step: i64, current: i64) -> (Streak, Streak) {
+    let mut longest = Streak::default();
+    let mut run = Streak::default();
+    for &period in periods {
+        if run.length > 0 && period == run.end + step {
+            run.length += 1;
+            run.end = period;
+        } else {
+            run = Streak {
+                length: 1,
+                start: period,
+                end: period,
+            };
+        }
+        if run.length > longest.length {
+            longest = run;
+        }
+    }
+    let running = run.length > 0 && (run.end == current || run.end == current - step);
+    (longest, if running { run } else { Streak::default() })
+}
+
+fn streak_stats(days: &BTreeSet<i64>, today: i64) -> StreakStats {
+    let weeks: BTreeSet<i64> = days.iter().map(|d| Bucketing::Week.start_of(*d)).collect();
+    let (longest_days, current_days) = find_streaks(days, 1, today);
+    let (longest_weeks, current_weeks) = find_streaks(&weeks, 7, Bucketing::Week.start_of(today));
+    StreakStats {
+        longest_days,
+        current_days,
+        longest_weeks,
+        current_weeks,
+    }
+}
+
+/// Returns repository-wide streaks and per-author streaks, with authors
+/// ordered by their longest daily streak.
+pub fn get_streaks(
+    commits: &[CommitInfo],
+    today: i64,
+) -> (StreakStats, Vec<(String, StreakStats)>) {
+    let mut all_days = BTreeSet::new();
+    let mut author_days: HashMap<String, BTreeSet<i64>> = HashMap::new();
+    for commit in commits {
+        all_days.insert(commit.day());
+        author_days
+            .entry(contributor_key(commit))
+            .or_default()
+            .insert(commit.day());
+    }
+
+    let mut authors: Vec<(String, StreakStats)> = author_days
+        .into_iter()
+        .map(|(author, days)| (author, streak_stats(&days, today)))
+        .collect();
+    authors.sort_by(|a, b| {
+        b.1.longest_days
+            .length
+            .cmp(&a.1.longest_days.length)
+            .then(a.0.cmp(&b.0))
+    });
+    (streak_stats(&all_days, today), authors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::days_from_civil;
+
+    #[test]
+    fn test_find_streaks() {
+        let days: BTreeSet<i64> = [1, 2, 3, 7, 8, 10, 11].into_iter().collect();
+        let (longest, current) = find_streaks(&days, 1, 12);
+        assert_eq!(
+            Streak {
+                length: 3,
+                start: 1,
+                end: 3
+            },
+            longest
+        );
+        assert_eq!(2, current.length);
+        assert_eq!(0, find_streaks(&days, 1, 13).1.length);
+    }
+
+    #[test]
+    fn test_get_streaks() {
+        let day = |d| CommitInfo {
+            author_name: "a".to_string(),
+            time: days_from_civil(2024, 3, d) * 86_400 + 3600,
+            ..Default::default()
+        };
+        // Monday 2024-03-04 to Wednesday, then Monday of the next week.
+        let commits = vec![day(4), day(5), day(6), day(11)];
+        let (repo, authors) = get_streaks(&commits, days_from_civil(2024, 3, 12));
+
+        assert_eq!(3, repo.longest_days.length);
+        assert_eq!(1, repo.current_days.length);
+        assert_eq!(2, repo.longest_weeks.length);
+        assert_eq!(2, repo.current_weeks.length);
+        assert_eq!(1, authors.len());
+    }
+}
@@ -0,0 +1,236 @@
+//! Support matrix of maintained release branches.
+//!
+//! A fix on the default branch counts as backported to a release branch when
+//! a commit on the release branch has the same patch id, or records it in a
+//! `(cherry picked from commit ...)` line as `git cherry-pick -x` writes.
+//! Fixes are recognized by a `fix:` Conventional Commit type or a subject
+//! starting with "fix".
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{Oid, Repository};
+
+use crate::{
+    churn::get_patch_id,
+    conventional::parse_conventional,
+    glob::{Glob, matches_any},
+    history::{RefSnapshot, is_missing},
+    message::subject,
+};
+
+/// Branch name patterns taken as release branches when none are given.
+pub const RELEASE_PATTERNS: [&str; 4] = ["release/*", "release-*", "stable/*", "*.x"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseBranch {
+    pub name: String,
+    /// Newest tag reachable from the branch and the commits made since.
+    pub latest_tag: Option<(String, usize)>,
+    /// Committer time of the branch tip.
+    pub last_activity: i64,
+    /// Fixes on the default branch since the release branch forked that
+    /// have not been applied to it.
+    pub unbackported: usize,
+}
+
+/// Returns whether a commit message describes a fix.
+pub fn is_fix(message: &str) -> bool {
+    match parse_conventional(message) {
+        Some(subject) => subject.kind == "fix",
+        None => subject(message).to_ascii_lowercase().starts_with("fix"),
+    }
+}
+
+/// Returns the local and remote-tracking branches of `snapshot` matching
+/// any of `patterns`, without the remote name, sorted. Local branches win
+/// over remote-tracking ones of the same name.
+pub fn find_release_branches(snapshot: &RefSnapshot, patterns: &[String]) -> Vec<(String, Oid)> {
+    let globs: Vec<Glob> = patterns.iter().filter_map(|p| Glob::new(p).ok()).collect();
+    let mut branches: HashMap<String, Oid> = HashMap::new();
+    let remote = snapshot
+        .under("refs/remotes/")
+        .filter_map(|(name, oid)| Some((name.split_once('/')?.1, oid)));
+    for (name, oid) in snapshot.under("refs/heads/").chain(remote) {
+        if name != "HEAD" && matches_any(&globs, name) {
+            branches.entry(name.to_string()).or_insert(oid);
+        }
+    }
+    let mut branches: Vec<_> = branches.into_iter().collect();
+    branches.sort();
+    branches
+}
+
+/// Returns the commits reachable from `tip` but not from `hide`.
+/// Commits that have gone missing are skipped.
+pub fn commits_between(
+    repo: &Repository,
+    tip: Oid,
+    hide: Option<Oid>,
+) -> Result<Vec<Oid>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    let pushed = revwalk.push(tip);
+    let hidden = hide.map_or(Ok(()), |hide| revwalk.hide(hide));
+    for result in [pushed, hidden] {
+        if let Err(e) = result
+            && !is_missing(&e)
+        {
+            return Err(e);
+        }
+    }
+    let mut oids = Vec::new();
+    for oid in revwalk {
+        match oid {
+            Ok(oid) => oids.push(oid),
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(oids)
+}
+
+/// Returns the tag names in `snapshot` per tagged commit, sorted.
+pub fn tagged_commits(repo: &Repository, snapshot: &RefSnapshot) -> HashMap<Oid, Vec<String>> {
+    let mut tags: HashMap<Oid, Vec<String>> = HashMap::new();
+    for (name, oid) in snapshot.under("refs/tags/") {
+        if let Ok(commit) = repo.find_object(oid, None).and_then(|o| o.peel_to_commit()) {
+            tags.entry(commit.id()).or_default().push(name.to_string());
+        }
+    }
+    for names in tags.values_mut() {
+        names.sort();
+    }
+    tags
+}
+
+/// Returns the newest tag reachable from `tip` and the commits made since.
+pub fn latest_tag(
+    repo: &Repository,
+    tip: Oid,
+    tags: &HashMap<Oid, Vec<String>>,
+) -> Result<Option<(String, usize)>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    match revwalk.push(tip) {
+        Ok(()) => {}
+        Err(e) if is_missing(&e) => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    for oid in revwalk {
+        let oid = match oid {
+            Ok(oid) => oid,
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        if let Some(name) = tags.get(&oid).and_then(|names| names.last()) {
+            let since = commits_between(repo, tip, Some(oid))?.len();
+            return Ok(Some((name.clone(), since)));
+        }
+    }
+    Ok(None)
+}
+
+/// Counts the fixes made on `default` since it forked from `branch` and not
+/// applied to `branch`.
+fn count_unbackported(repo: &Repository, default: Oid, branch: Oid) -> Result<usize, git2::Error> {
+    let mut applied: HashSet<Oid> = HashSet::new();
+    let mut picked: HashSet<Oid> = HashSet::new();
+    for oid in commits_between(repo, branch, Some(default))? {
+        let commit = repo.find_commit(oid)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        applied.extend(get_patch_id(repo, oid)?);
+        let message = String::from_utf8_lossy(commit.message_bytes()).into_owned();
+        for line in message.lines() {
+            if let Some(original) = line
+                .trim()
+                .strip_prefix("(cherry picked from commit ")
+                .and_then(|l| l.strip_suffix(')'))
+                .and_then(|l| Oid::from_str(l).ok())
+            {
+                picked.insert(original);
+            }
+        }
+    }
+
+    let mut unbackported = 0;
+    for oid in commits_between(repo, default, Some(branch))? {
+        let commit = repo.find_commit(oid)?;
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        if commit.parent_count() > 1 || !is_fix(&message) || picked.contains(&oid) {
+            continue;
+        }
+        if let Some(patch_id) = get_patch_id(repo, oid)?
+            && !applied.contains(&patch_id)
+        {
+            unbackported += 1;
+        }
+    }
+    Ok(unbackported)
+}
+
+/// Builds the support matrix of the release branches in `snapshot` matching
+/// `patterns`, comparing each with the `default` branch tip. Branches whose
+/// tip has gone missing are left out.
+pub fn get_support_matrix(
+    repo: &Repository,
+    snapshot: &RefSnapshot,
+    default: Oid,
+    patterns: &[String],
+) -> Result<Vec<ReleaseBranch>, git2::Error> {
+    let tags = tagged_commits(repo, snapshot);
+    let mut matrix = Vec::new();
+    for (name, tip) in find_release_branches(snapshot, patterns) {
+        let last_activity = match repo.find_commit(tip) {
+            Ok(commit) => commit.time().seconds(),
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        matrix.push(ReleaseBranch {
+            name,
+            latest_tag: latest_tag(repo, tip, &tags)?,
+            last_activity,
+            unbackported: count_unbackported(repo, default, tip)?,
+        });
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_support_matrix() {
+        let t = TestRepo::new();
+        let base = t.commit_as("A", "a@x.com", 1, "init", &[("a.txt", "1")]);
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.commit_as("A", "a@x.com", 2, "fix: crash", &[("b.txt", "1")]);
+        t.commit_as("A", "a@x.com", 3, "Fix leak", &[("c.txt", "1")]);
+        let default = t.commit_as("A", "a@x.com", 4, "feat: new", &[("d.txt", "1")]);
+
+        t.checkout("release/1.x", Some(base));
+        t.repo
+            .tag_lightweight("v1.0", &t.repo.find_object(base, None).unwrap(), false)
+            .unwrap();
+        t.commit_as("A", "a@x.com", 5, "fix: crash", &[("b.txt", "1")]);
+        t.commit_as("A", "a@x.com", 6, "Bump version", &[("v.txt", "1.0.1")]);
+        t.checkout(&main, None);
+
+        let patterns: Vec<String> = RELEASE_PATTERNS.iter().map(|p| p.to_string()).collect();
+        let snapshot = RefSnapshot::take(&t.repo).unwrap();
+        let matrix = get_support_matrix(&t.repo, &snapshot, default, &patterns).unwrap();
+        assert_eq!(
+            vec![ReleaseBranch {
+                name: "release/1.x".to_string(),
+                latest_tag: Some(("v1.0".to_string(), 2)),
+                last_activity: 6,
+                unbackported: 1,
+            }],
+            matrix
+        );
+        assert!(is_fix("Fixes the build"));
+        assert!(!is_fix("feat: fix-ups"));
+    }
+}
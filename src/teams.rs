@@ -0,0 +1,224 @@
+//! Contributor statistics rolled up to teams.
+//!
+//! Teams are read from a mapping file with one `[teams]` entry per team,
+//! listing member email addresses and email domains:
+//!
+//! ```toml
+//! [teams]
+//! payments = ["alice@corp.com", "@payments.corp.com"]
+//! web = ["bob@corp.com", "web.corp.com"]
+//! ```
+//!
+//! An entry with a local part matches that address exactly; any other entry
+//! is a domain that also matches its subdomains. The first matching team wins.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+
+use crate::{
+    churn::{CommitChurn, diff_churn, diff_commit, diff_paths},
+    config::Config,
+    domains::email_domain,
+    history::CommitInfo,
+    identity::{anonymize_email, contributor_key},
+    lang::PathFilter,
+};
+
+/// Team name for contributors no team entry matches.
+pub const UNASSIGNED: &str = "(unassigned)";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Teams {
+    /// `(team, lowercased members)` in file order.
+    teams: Vec<(String, Vec<String>)>,
+}
+
+impl Teams {
+    pub fn from_config(config: &Config) -> Result<Teams, String> {
+        let teams = config
+            .section("teams")
+            .iter()
+            .map(|(team, members)| {
+                let members = members
+                    .as_str_array()
+                    .ok_or_else(|| format!("team {} must be an array of strings", team))?;
+                Ok((
+                    team.clone(),
+                    members.iter().map(|m| m.to_lowercase()).collect(),
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Teams { teams })
+    }
+
+    /// Replaces member email addresses by their pseudonyms, for matching
+    /// anonymized commits. Domain entries are kept as they are.
+    pub fn anonymize(&mut self) {
+        for (_, members) in &mut self.teams {
+            for member in members.iter_mut() {
+                if member
+                    .split_once('@')
+                    .is_some_and(|(local, _)| !local.is_empty())
+                {
+                    *member = anonymize_email(member);
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.teams.is_empty()
+    }
+
+    /// Returns the team an email address belongs to.
+    pub fn team_of(&self, email: &str) -> Option<&str> {
+        let email = email.to_lowercase();
+        let domain = email_domain(&email).unwrap_or_default();
+        self.teams
+            .iter()
+            .find(|(_, members)| {
+                members.iter().any(|member| match member.split_once('@') {
+                    Some((local, _)) if !local.is_empty() => *member == email,
+                    _ => {
+                        let member = member.trim_start_matches('@');
+                        domain == member
+                            || domain
+                                .strip_suffix(member)
+                                .is_some_and(|rest| rest.ends_with('.'))
+                    }
+                })
+            })
+            .map(|(team, _)| team.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TeamStats {
+    pub team: String,
+    pub contributors: usize,
+    pub commits: usize,
+    pub churn: CommitChurn,
+    /// Files in `owned_paths` on which the team has the most commits.
+    pub files_owned: usize,
+}
+
+/// Aggregates commits, churn and file ownership per team, ordered by
+/// commits. Ownership is only counted for `owned_paths`, typically the files
+/// at HEAD; ties go to the alphabetically first team.
+pub fn get_team_stats(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    teams: &Teams,
+    owned_paths: &[String],
+    filter: &PathFilter,
+) -> Result<Vec<TeamStats>, git2::Error> {
+    let owned: HashSet<&str> = owned_paths.iter().map(String::as_str).collect();
+    let mut stats: HashMap<&str, TeamStats> = HashMap::new();
+    let mut contributors: HashMap<&str, HashSet<String>> = HashMap::new();
+    let mut file_commits: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+
+    for commit in commits {
+        let team = teams.team_of(&commit.author_email).unwrap_or(UNASSIGNED);
+        let entry = stats.entry(team).or_insert_with(|| TeamStats {
+            team: team.to_string(),
+            ..Default::default()
+        });
+        entry.commits += 1;
+        contributors
+            .entry(team)
+            .or_default()
+            .insert(contributor_key(commit));
+        let Some(diff) = diff_commit(repo, commit.oid)? else {
+            continue;
+        };
+        entry.churn += diff_churn(&diff, filter)?;
+        for path in diff_paths(&diff) {
+            if owned.contains(path.as_str()) {
+                *file_commits
+                    .entry(path)
+                    .or_default()
+                    .entry(team)
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    for counts in file_commits.values() {
+        let owner = counts
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(team, _)| *team);
+        if let Some(owner) = owner.and_then(|t| stats.get_mut(t)) {
+            owner.files_owned += 1;
+        }
+    }
+    for (team, members) in contributors {
+        if let Some(entry) = stats.get_mut(team) {
+            entry.contributors = members.len();
+        }
+    }
+
+    let mut stats: Vec<TeamStats> = stats.into_values().collect();
+    stats.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.team.cmp(&b.team)));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{history::collect_commits, progress::Progress, testutil::TestRepo};
+
+    fn teams() -> Teams {
+        let config = Config::parse(
+            r#"
+            [teams]
+            payments = ["alice@corp.com", "@payments.corp.com"]
+            web = ["corp.com"]
+            "#,
+        )
+        .unwrap();
+        Teams::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_team_of() {
+        let teams = teams();
+        assert_eq!(Some("payments"), teams.team_of("Alice@Corp.com"));
+        assert_eq!(Some("payments"), teams.team_of("carol@payments.corp.com"));
+        assert_eq!(Some("web"), teams.team_of("bob@corp.com"));
+        assert_eq!(Some("web"), teams.team_of("dan@eu.corp.com"));
+        assert_eq!(None, teams.team_of("eve@notcorp.com"));
+        assert!(Teams::default().is_empty());
+
+        let mut anonymized = teams.clone();
+        anonymized.anonymize();
+        assert_eq!(
+            Some("payments"),
+            anonymized.team_of(&anonymize_email("alice@corp.com"))
+        );
+    }
+
+    #[test]
+    fn test_team_stats() {
+        let t = TestRepo::new();
+        t.commit_as("Alice", "alice@corp.com", 1, "add", &[("a.txt", "1\n")]);
+        t.commit_as("Alice", "alice@corp.com", 2, "edit", &[("a.txt", "2\n")]);
+        t.commit_as("Bob", "bob@corp.com", 3, "add", &[("b.txt", "1\n")]);
+        t.commit_as("Eve", "eve@example.org", 4, "edit", &[("a.txt", "3\n")]);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let paths = vec!["a.txt".to_string(), "b.txt".to_string()];
+        let stats =
+            get_team_stats(&t.repo, &commits, &teams(), &paths, &PathFilter::default()).unwrap();
+
+        assert_eq!(
+            vec![("payments", 2, 1), ("(unassigned)", 1, 0), ("web", 1, 1)],
+            stats
+                .iter()
+                .map(|s| (s.team.as_str(), s.commits, s.files_owned))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, stats[0].churn.insertions);
+    }
+}
@@ -0,0 +1,213 @@
+//! How much test code there is for the source code.
+//!
+//! Files are told apart by path globs: a file in a known language matching
+//! any test glob is a test file, any other file in a known language a source
+//! file. Lines are code lines, without blanks and comments. The trend
+//! measures the tree of the last commit in each period on HEAD's
+//! first-parent chain, so work on unmerged branches does not show.
+
+use std::collections::{HashMap, HashSet};
+
+use git2::{Oid, Repository};
+
+use crate::{
+    bucket::Bucketing,
+    config::Config,
+    glob::{Glob, matches_any},
+    history::CommitInfo,
+    landing::first_parent_chain,
+    lang::LanguageMap,
+    loc::{comment_syntax, count_lines},
+    tree::{TreeFile, get_tree_files},
+};
+
+/// Globs used when neither `--pattern` nor `tests.patterns` is given.
+pub const DEFAULT_TEST_PATTERNS: [&str; 12] = [
+    "**/test/**",
+    "**/tests/**",
+    "**/spec/**",
+    "**/__tests__/**",
+    "*_test.go",
+    "*_test.py",
+    "test_*.py",
+    "*_spec.rb",
+    "*.test.js",
+    "*.test.ts",
+    "*.spec.ts",
+    "*Test.java",
+];
+
+/// Reads the `patterns` list of the `[tests]` section.
+pub fn test_patterns_from_config(config: &Config) -> Result<Option<Vec<String>>, String> {
+    config.get_str_array("tests", "patterns")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TestRatio {
+    pub test_files: usize,
+    pub test_lines: usize,
+    pub source_files: usize,
+    pub source_lines: usize,
+}
+
+impl TestRatio {
+    /// Test files per source file.
+    pub fn file_ratio(&self) -> Option<f64> {
+        (self.source_files > 0).then(|| self.test_files as f64 / self.source_files as f64)
+    }
+
+    /// Test lines per source line.
+    pub fn line_ratio(&self) -> Option<f64> {
+        (self.source_lines > 0).then(|| self.test_lines as f64 / self.source_lines as f64)
+    }
+}
+
+/// Code lines per blob, as blobs recur across the measured trees.
+type LineCache = HashMap<Oid, usize>;
+
+/// Measures the test and source code among `files`.
+pub fn get_test_ratio(
+    repo: &Repository,
+    files: &[TreeFile],
+    tests: &[Glob],
+    map: &LanguageMap,
+) -> Result<TestRatio, git2::Error> {
+    measure(repo, files, tests, map, &mut LineCache::new())
+}
+
+/// Measures the tree of the newest of `commits` on the first-parent chain of
+/// `head` in each period of `bucketing`. Returns `(bucket start, ratio)`
+/// pairs, oldest first, and none when HEAD is unborn.
+pub fn get_test_ratio_trend(
+    repo: &Repository,
+    head: Option<Oid>,
+    commits: &[CommitInfo],
+    tests: &[Glob],
+    map: &LanguageMap,
+    bucketing: Bucketing,
+) -> Result<Vec<(i64, TestRatio)>, git2::Error> {
+    let mainline: HashSet<Oid> = match head {
+        Some(head) => first_parent_chain(repo, head)?.into_iter().collect(),
+        None => HashSet::new(),
+    };
+    let mut last: HashMap<i64, &CommitInfo> = HashMap::new();
+    for commit in commits.iter().filter(|c| mainline.contains(&c.oid)) {
+        let entry = last
+            .entry(bucketing.start_of(commit.day()))
+            .or_insert(commit);
+        if commit.time > entry.time {
+            *entry = commit;
+        }
+    }
+    let mut periods: Vec<(i64, &CommitInfo)> = last.into_iter().collect();
+    periods.sort_by_key(|(start, _)| *start);
+
+    let mut cache = LineCache::new();
+    let mut trend = Vec::with_capacity(periods.len());
+    for (start, commit) in periods {
+        let tree = repo.find_commit(commit.oid)?.tree()?;
+        let files = get_tree_files(repo, &tree)?;
+        trend.push((start, measure(repo, &files, tests, map, &mut cache)?));
+    }
+    Ok(trend)
+}
+
+fn measure(
+    repo: &Repository,
+    files: &[TreeFile],
+    tests: &[Glob],
+    map: &LanguageMap,
+    cache: &mut LineCache,
+) -> Result<TestRatio, git2::Error> {
+    let mut ratio = TestRatio::default();
+    for file in files {
+        let Some(language) = map.language_of(&file.path) else {
+            continue;
+        };
+        let lines = match cache.get(&file.oid) {
+            Some(lines) => *lines,
+            None => {
+                let blob = repo.find_blob(file.oid)?;
+                let lines = if blob.is_binary() {
+                    0
+                } else {
+                    count_lines(blob.content(), comment_syntax(language)).code()
+                };
+                cache.insert(file.oid, lines);
+                lines
+            }
+        };
+        if matches_any(tests, &file.path) {
+            ratio.test_files += 1;
+            ratio.test_lines += lines;
+        } else {
+            ratio.source_files += 1;
+            ratio.source_lines += lines;
+        }
+    }
+    Ok(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        glob::parse_globs, history::collect_commits, progress::Progress, testutil::TestRepo,
+        tree::get_head_files,
+    };
+
+    #[test]
+    fn test_test_ratio() {
+        let t = TestRepo::new();
+        let day = 86_400;
+        t.commit_as(
+            "A",
+            "a@x.com",
+            day,
+            "add",
+            &[
+                ("src/lib.rs", "fn a() {}\nfn b() {}\n"),
+                ("README.md", "# x\n"),
+            ],
+        );
+        t.commit_as(
+            "A",
+            "a@x.com",
+            100 * day,
+            "test",
+            &[("tests/lib.rs", "// test\n#[test]\nfn a() {}\n")],
+        );
+
+        let map = LanguageMap::from_config(&Config::default()).unwrap();
+        let tests = parse_globs(&["tests/**".to_string()]).unwrap();
+        let files = get_head_files(&t.repo, None).unwrap();
+        let ratio = get_test_ratio(&t.repo, &files, &tests, &map).unwrap();
+        assert_eq!((1, 2), (ratio.test_files, ratio.test_lines));
+        assert_eq!((2, 3), (ratio.source_files, ratio.source_lines));
+        assert_eq!(Some(0.5), ratio.file_ratio());
+
+        // A newer commit on an unmerged branch is not what HEAD measures.
+        let head = t.repo.head().unwrap().target();
+        let main = t.repo.head().unwrap().shorthand().unwrap().to_string();
+        t.checkout("feature", head);
+        t.commit_as(
+            "A",
+            "a@x.com",
+            101 * day,
+            "more tests",
+            &[("tests/more.rs", "fn b() {}\nfn c() {}\n")],
+        );
+        t.checkout(&main, None);
+
+        let commits = collect_commits(&t.repo, None, &mut Progress::default()).unwrap();
+        let trend = get_test_ratio_trend(&t.repo, head, &commits, &tests, &map, Bucketing::Quarter)
+            .unwrap();
+        assert_eq!(
+            vec![Some(0.0), Some(2.0 / 3.0)],
+            trend
+                .iter()
+                .map(|(_, r)| r.line_ratio())
+                .collect::<Vec<_>>()
+        );
+    }
+}
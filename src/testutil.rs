@@ -0,0 +1,104 @@
+//! Helpers for building throwaway repositories in tests.
+
+use std::{fs, path::Path};
+
+use git2::{Oid, Repository, Signature, Time};
+use tempfile::TempDir;
+
+pub struct TestRepo {
+    // Kept alive so the repository is removed when the test ends.
+    _dir: TempDir,
+    pub repo: Repository,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        TestRepo { _dir: dir, repo }
+    }
+
+    /// Commits `files` (path, contents) on top of HEAD as the default author.
+    pub fn commit(&self, message: &str, files: &[(&str, &str)]) -> Oid {
+        self.commit_as("test", "test@example.com", 1_700_000_000, message, files)
+    }
+
+    /// Commits `files` (path, contents) on top of HEAD with the given author
+    /// and timestamp. An empty file list creates an empty commit.
+    pub fn commit_as(
+        &self,
+        name: &str,
+        email: &str,
+        time: i64,
+        message: &str,
+        files: &[(&str, &str)],
+    ) -> Oid {
+        let workdir = self.repo.workdir().unwrap();
+        let mut index = self.repo.index().unwrap();
+        for (path, contents) in files {
+            let full = workdir.join(path);
+            if let Some(parent) = full.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(&full, contents).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        self.write_commit(&mut index, name, email, time, message)
+    }
+
+    /// Merges `other` into HEAD with a merge commit at `time`. The two sides
+    /// must not conflict.
+    pub fn merge(&self, other: Oid, time: i64, message: &str) -> Oid {
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        let other = self.repo.find_commit(other).unwrap();
+        let mut index = self.repo.merge_commits(&head, &other, None).unwrap();
+        let tree = self
+            .repo
+            .find_tree(index.write_tree_to(&self.repo).unwrap())
+            .unwrap();
+        let sig = Signature::new("test", "test@example.com", &Time::new(time, 0)).unwrap();
+        let merge = self
+            .repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &[&head, &other])
+            .unwrap();
+        self.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        merge
+    }
+
+    /// Points HEAD at branch `name`, creating it at `start` first if given,
+    /// and checks it out.
+    pub fn checkout(&self, name: &str, start: Option<Oid>) {
+        if let Some(start) = start {
+            let commit = self.repo.find_commit(start).unwrap();
+            self.repo.branch(name, &commit, true).unwrap();
+        }
+        self.repo.set_head(&format!("refs/heads/{}", name)).unwrap();
+        self.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+
+    fn write_commit(
+        &self,
+        index: &mut git2::Index,
+        name: &str,
+        email: &str,
+        time: i64,
+        message: &str,
+    ) -> Oid {
+        index.write().unwrap();
+        let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::new(name, email, &Time::new(time, 0)).unwrap();
+        let parent = self
+            .repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+}
@@ -0,0 +1,90 @@
+//! Distribution of commit UTC offsets.
+//!
+//! The overall distribution uses the committer offsets, where the commits
+//! were made. Contributors are placed by their author offsets, since a rebase
+//! or a patch applied by someone else rewrites the committer's.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{history::CommitInfo, identity::contributor_key};
+
+/// Commit counts per UTC offset in minutes, ordered from west to east.
+pub type OffsetCounts = BTreeMap<i32, usize>;
+
+/// Formats a UTC offset in minutes as `+HH:MM`.
+pub fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.unsigned_abs();
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// Returns the overall offset distribution and one per contributor, with
+/// contributors ordered by commit count.
+pub fn get_timezone_distribution(
+    commits: &[CommitInfo],
+) -> (OffsetCounts, Vec<(String, OffsetCounts)>) {
+    let mut overall = OffsetCounts::new();
+    let mut authors: HashMap<String, OffsetCounts> = HashMap::new();
+    for commit in commits {
+        *overall.entry(commit.offset_minutes).or_default() += 1;
+        *authors
+            .entry(contributor_key(commit))
+            .or_default()
+            .entry(commit.author_offset_minutes)
+            .or_default() += 1;
+    }
+
+    let mut authors: Vec<(String, OffsetCounts)> = authors.into_iter().collect();
+    let total = |counts: &OffsetCounts| counts.values().sum::<usize>();
+    authors.sort_by(|a, b| total(&b.1).cmp(&total(&a.1)).then(a.0.cmp(&b.0)));
+    (overall, authors)
+}
+
+/// Returns the offset with the most commits, preferring the westernmost on ties.
+pub fn primary_offset(counts: &OffsetCounts) -> Option<i32> {
+    counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(offset, _)| *offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!("+00:00", format_offset(0));
+        assert_eq!("+05:30", format_offset(330));
+        assert_eq!("-08:00", format_offset(-480));
+    }
+
+    #[test]
+    fn test_timezone_distribution() {
+        let commit = |name: &str, offset| CommitInfo {
+            author_name: name.to_string(),
+            offset_minutes: offset,
+            author_offset_minutes: offset,
+            ..Default::default()
+        };
+        // b's commit was rebased by someone at +00:00.
+        let commits = vec![
+            commit("a", 60),
+            commit("a", 60),
+            commit("a", 120),
+            CommitInfo {
+                offset_minutes: 0,
+                ..commit("b", -300)
+            },
+        ];
+        let (overall, authors) = get_timezone_distribution(&commits);
+
+        assert_eq!(
+            vec![(0, 1), (60, 2), (120, 1)],
+            overall.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!("a <>", authors[0].0);
+        assert_eq!(Some(60), primary_offset(&authors[0].1));
+        assert_eq!(Some(-300), primary_offset(&authors[1].1));
+    }
+}
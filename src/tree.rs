@@ -0,0 +1,280 @@
+//! Statistics about the tree checked out at HEAD.
+
+use std::collections::HashSet;
+
+use git2::{FileMode, ObjectType, Oid, Repository, Tree, TreeWalkMode, TreeWalkResult};
+
+use crate::{
+    history::RefSnapshot,
+    stats::{format_size, percentile},
+};
+
+/// A blob tracked at HEAD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeFile {
+    pub path: String,
+    pub oid: Oid,
+    pub size: u64,
+}
+
+/// Lists every blob in the HEAD tree with its size, taking HEAD from
+/// `snapshot` when there is one. Sizes come from object headers, so blob
+/// contents are not read. An unborn HEAD has no files.
+pub fn get_head_files(
+    repo: &Repository,
+    snapshot: Option<&RefSnapshot>,
+) -> Result<Vec<TreeFile>, git2::Error> {
+    let tree = match snapshot {
+        Some(snapshot) => match snapshot.head {
+            Some(head) => repo.find_commit(head)?.tree()?,
+            None => return Ok(Vec::new()),
+        },
+        None => match repo.head() {
+            Ok(head) => head.peel_to_tree()?,
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        },
+    };
+    get_tree_files(repo, &tree)
+}
+
+/// Lists every blob in `tree` with its size.
+pub fn get_tree_files(repo: &Repository, tree: &Tree<'_>) -> Result<Vec<TreeFile>, git2::Error> {
+    let odb = repo.odb()?;
+    let mut files = Vec::new();
+    let mut error = None;
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        match odb.read_header(entry.id()) {
+            Ok((size, _)) => {
+                files.push(TreeFile {
+                    path: format!("{}{}", root, entry.name().unwrap_or("")),
+                    oid: entry.id(),
+                    size: size as u64,
+                });
+                TreeWalkResult::Ok
+            }
+            Err(e) => {
+                error = Some(e);
+                TreeWalkResult::Abort
+            }
+        }
+    })?;
+    match error {
+        Some(e) => Err(e),
+        None => Ok(files),
+    }
+}
+
+/// Upper bounds of the file-size histogram buckets, in bytes.
+const HISTOGRAM_BOUNDS: [u64; 6] = [1 << 10, 10 << 10, 100 << 10, 1 << 20, 10 << 20, 100 << 20];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeDistribution {
+    pub files: usize,
+    pub total: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub max: u64,
+    /// `(label, files)` per size range, from smallest to largest.
+    pub histogram: Vec<(String, usize)>,
+    /// `(threshold, files at or above it)`.
+    pub over: Vec<(u64, usize)>,
+}
+
+/// Summarizes file sizes with percentiles, a histogram and counts of files
+/// at or above each threshold.
+pub fn get_size_distribution(files: &[TreeFile], thresholds: &[u64]) -> SizeDistribution {
+    let mut sizes: Vec<u64> = files.iter().map(|f| f.size).collect();
+    sizes.sort_unstable();
+
+    let mut histogram = Vec::new();
+    let mut lower = 0;
+    for bound in HISTOGRAM_BOUNDS {
+        let count = sizes.iter().filter(|s| **s >= lower && **s < bound).count();
+        let label = if lower == 0 {
+            format!("< {}", format_size(bound))
+        } else {
+            format!("{} – {}", format_size(lower), format_size(bound))
+        };
+        histogram.push((label, count));
+        lower = bound;
+    }
+    histogram.push((
+        format!(">= {}", format_size(lower)),
+        sizes.iter().filter(|s| **s >= lower).count(),
+    ));
+
+    SizeDistribution {
+        files: sizes.len(),
+        total: sizes.iter().sum(),
+        p50: percentile(&sizes, 50.0).unwrap_or(0),
+        p95: percentile(&sizes, 95.0).unwrap_or(0),
+        p99: percentile(&sizes, 99.0).unwrap_or(0),
+        max: sizes.last().copied().unwrap_or(0),
+        histogram,
+        over: thresholds
+            .iter()
+            .map(|t| (*t, sizes.iter().filter(|s| *s >= t).count()))
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeShape {
+    pub files: usize,
+    /// Directories holding tracked files, not counting the root.
+    pub directories: usize,
+    pub total: u64,
+    /// The file nested deepest with its depth, the root's files being at 1.
+    /// Ties go to the path sorting first.
+    pub deepest: Option<(String, usize)>,
+}
+
+impl TreeShape {
+    pub fn average_size(&self) -> u64 {
+        self.total.checked_div(self.files as u64).unwrap_or(0)
+    }
+}
+
+/// Counts the files and directories of `files` and finds the deepest path.
+pub fn get_tree_shape(files: &[TreeFile]) -> TreeShape {
+    let mut directories = HashSet::new();
+    let mut deepest: Option<(String, usize)> = None;
+    for file in files {
+        let mut dir = file.path.as_str();
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            if !directories.insert(parent) {
+                break;
+            }
+            dir = parent;
+        }
+        let depth = file.path.matches('/').count() + 1;
+        if deepest
+            .as_ref()
+            .is_none_or(|(path, d)| depth > *d || (depth == *d && file.path < *path))
+        {
+            deepest = Some((file.path.clone(), depth));
+        }
+    }
+    TreeShape {
+        files: files.len(),
+        directories: directories.len(),
+        total: files.iter().map(|f| f.size).sum(),
+        deepest,
+    }
+}
+
+/// Tree entries that behave differently across platforms: symlinks are
+/// checked out as plain files where they are unsupported, the executable bit
+/// is lost on Windows, and submodules need their own checkout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecialEntries {
+    pub symlinks: Vec<String>,
+    pub executables: Vec<String>,
+    /// Gitlinks, the commits submodules are pinned to.
+    pub submodules: Vec<String>,
+}
+
+/// Lists the symlinks, executable files and submodules in the HEAD tree.
+pub fn get_special_entries(repo: &Repository) -> Result<SpecialEntries, git2::Error> {
+    let mut entries = SpecialEntries::default();
+    let tree = match repo.head() {
+        Ok(head) => head.peel_to_tree()?,
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(entries),
+        Err(e) => return Err(e),
+    };
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let list = match entry.filemode() {
+            mode if mode == i32::from(FileMode::Link) => &mut entries.symlinks,
+            mode if mode == i32::from(FileMode::BlobExecutable) => &mut entries.executables,
+            mode if mode == i32::from(FileMode::Commit) => &mut entries.submodules,
+            _ => return TreeWalkResult::Ok,
+        };
+        list.push(format!("{}{}", root, entry.name().unwrap_or("")));
+        TreeWalkResult::Ok
+    })?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_head_files_and_distribution() {
+        let t = TestRepo::new();
+        assert!(get_head_files(&t.repo, None).unwrap().is_empty());
+
+        let big = "x".repeat(20 * 1024);
+        t.commit("add", &[("a.txt", "hello"), ("src/big.bin", &big)]);
+
+        let mut files = get_head_files(&t.repo, None).unwrap();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            vec![("a.txt", 5), ("src/big.bin", 20 * 1024)],
+            files
+                .iter()
+                .map(|f| (f.path.as_str(), f.size))
+                .collect::<Vec<_>>()
+        );
+
+        let distribution = get_size_distribution(&files, &[1024]);
+        assert_eq!(2, distribution.files);
+        assert_eq!(5, distribution.p50);
+        assert_eq!(20 * 1024, distribution.max);
+        assert_eq!(1, distribution.histogram[0].1);
+        assert_eq!(1, distribution.histogram[2].1);
+        assert_eq!(vec![(1024, 1)], distribution.over);
+
+        t.commit("nest", &[("src/a/b/c.rs", ""), ("src/a/d.rs", "")]);
+        let shape = get_tree_shape(&get_head_files(&t.repo, None).unwrap());
+        assert_eq!((4, 3), (shape.files, shape.directories));
+        assert_eq!(Some(("src/a/b/c.rs".to_string(), 4)), shape.deepest);
+        assert_eq!(5 * 1024 + 1, shape.average_size());
+    }
+
+    #[test]
+    fn test_special_entries() {
+        let t = TestRepo::new();
+        let blob = t.repo.blob(b"target").unwrap();
+        let head = t.commit("add", &[("a.txt", "a")]);
+        let mut bin = t.repo.treebuilder(None).unwrap();
+        bin.insert("run.sh", blob, FileMode::BlobExecutable.into())
+            .unwrap();
+        let bin = bin.write().unwrap();
+        let mut root = t
+            .repo
+            .treebuilder(Some(&t.repo.find_commit(head).unwrap().tree().unwrap()))
+            .unwrap();
+        root.insert("bin", bin, FileMode::Tree.into()).unwrap();
+        root.insert("link", blob, FileMode::Link.into()).unwrap();
+        root.insert("lib", head, FileMode::Commit.into()).unwrap();
+        let tree = t.repo.find_tree(root.write().unwrap()).unwrap();
+        let parent = t.repo.find_commit(head).unwrap();
+        let signature = parent.author();
+        t.repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "special",
+                &tree,
+                &[&parent],
+            )
+            .unwrap();
+
+        assert_eq!(
+            SpecialEntries {
+                symlinks: vec!["link".to_string()],
+                executables: vec!["bin/run.sh".to_string()],
+                submodules: vec!["lib".to_string()],
+            },
+            get_special_entries(&t.repo).unwrap()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Unreachable objects, and the lost work among them.
+//!
+//! These are the objects no reference, reflog entry, worktree HEAD or index
+//! reaches, which `git gc` prunes once they are older than `gc.pruneExpire`.
+//! A dangling commit is an unreachable commit no other unreachable commit
+//! has as a parent: the tip of a deleted branch or of a rewritten history
+//! whose reflog has expired. Its lost work is the unreachable objects
+//! reachable from it, and `git branch` can restore it.
+
+use std::collections::{BTreeMap, HashSet};
+
+use git2::{ObjectType, Oid, Repository};
+
+use crate::{date::local_day, health::get_reachable};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingCommit {
+    pub oid: Oid,
+    /// Day of the commit in its committer's time zone.
+    pub day: i64,
+    pub author: String,
+    pub email: String,
+    pub summary: String,
+    /// Unreachable commits in its history, itself included.
+    pub commits: usize,
+    /// Uncompressed bytes of the unreachable objects reachable from it.
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnreachableObjects {
+    /// Count and uncompressed bytes per object kind.
+    pub kinds: BTreeMap<String, (usize, u64)>,
+    /// Dangling commits, largest first.
+    pub dangling: Vec<DanglingCommit>,
+}
+
+impl UnreachableObjects {
+    pub fn count(&self) -> usize {
+        self.kinds.values().map(|(n, _)| n).sum()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.kinds.values().map(|(_, size)| size).sum()
+    }
+}
+
+/// Sums the unreachable objects reachable from the commit `tip`, returning
+/// the commits among them and their bytes.
+fn lost_work(
+    repo: &Repository,
+    tip: Oid,
+    unreachable: &HashSet<Oid>,
+    sizes: &BTreeMap<Oid, u64>,
+) -> Result<(usize, u64), git2::Error> {
+    let mut seen = HashSet::new();
+    let mut pending = vec![tip];
+    let (mut commits, mut size) = (0, 0);
+    while let Some(oid) = pending.pop() {
+        // A reachable object holds only reachable ones.
+        if !unreachable.contains(&oid) || !seen.insert(oid) {
+            continue;
+        }
+        size += sizes[&oid];
+        let object = repo.find_object(oid, None)?;
+        match object.kind() {
+            Some(ObjectType::Commit) => {
+                let commit = object.peel_to_commit()?;
+                commits += 1;
+                pending.push(commit.tree_id());
+                pending.extend(commit.parent_ids());
+            }
+            Some(ObjectType::Tree) => {
+                let tree = object.peel_to_tree()?;
+                pending.extend(tree.iter().map(|e| e.id()));
+            }
+            _ => {}
+        }
+    }
+    Ok((commits, size))
+}
+
+/// Finds the objects of `repo` that [`get_reachable`] does not reach, and
+/// the dangling commits among them.
+pub fn get_unreachable_objects(repo: &Repository) -> Result<UnreachableObjects, git2::Error> {
+    let reachable = get_reachable(repo)?;
+    let odb = repo.odb()?;
+    let mut sizes: BTreeMap<Oid, u64> = BTreeMap::new();
+    let mut unreachable = UnreachableObjects::default();
+    let mut commits = Vec::new();
+    let mut error = None;
+    odb.foreach(|oid| {
+        if reachable.contains(oid) || sizes.contains_key(oid) {
+            return true;
+        }
+        match odb.read_header(*oid) {
+            Ok((size, kind)) => {
+                sizes.insert(*oid, size as u64);
+                let entry = unreachable.kinds.entry(kind.to_string()).or_default();
+                entry.0 += 1;
+                entry.1 += size as u64;
+                if kind == ObjectType::Commit {
+                    commits.push(*oid);
+                }
+                true
+            }
+            Err(e) => {
+                error = Some(e);
+                false
+            }
+        }
+    })
+    .or_else(|e| if error.is_some() { Ok(()) } else { Err(e) })?;
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    let ids: HashSet<Oid> = sizes.keys().copied().collect();
+    let mut parents = HashSet::new();
+    for oid in &commits {
+        parents.extend(repo.find_commit(*oid)?.parent_ids());
+    }
+    for oid in commits.into_iter().filter(|oid| !parents.contains(oid)) {
+        let commit = repo.find_commit(oid)?;
+        let (commits, size) = lost_work(repo, oid, &ids, &sizes)?;
+        unreachable.dangling.push(DanglingCommit {
+            oid,
+            day: local_day(commit.time().seconds(), commit.time().offset_minutes()),
+            author: commit.author().name().unwrap_or("").to_string(),
+            email: commit.author().email().unwrap_or("").to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            commits,
+            size,
+        });
+    }
+    unreachable
+        .dangling
+        .sort_by(|a, b| b.size.cmp(&a.size).then(b.day.cmp(&a.day)));
+    Ok(unreachable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_unreachable_objects() {
+        let t = TestRepo::new();
+        let base = t.commit("base", &[("a.txt", "a")]);
+        t.commit("lost", &[("b.txt", "lost work")]);
+        let tip = t.commit("tip", &[("b.txt", "more lost work")]);
+        t.repo.blob(b"stray").unwrap();
+
+        // Reflogs keep the rewound commits reachable until they are gone.
+        let branch = t.repo.head().unwrap().name().unwrap().to_string();
+        let base = t.repo.find_object(base, None).unwrap();
+        t.repo.reset(&base, git2::ResetType::Hard, None).unwrap();
+        assert_eq!(1, get_unreachable_objects(&t.repo).unwrap().count());
+        t.repo.reflog_delete("HEAD").unwrap();
+        t.repo.reflog_delete(&branch).unwrap();
+
+        let unreachable = get_unreachable_objects(&t.repo).unwrap();
+        // Two commits, two trees and three blobs.
+        assert_eq!(7, unreachable.count());
+        assert_eq!(
+            vec!["blob", "commit", "tree"],
+            unreachable.kinds.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(1, unreachable.dangling.len());
+        let dangling = &unreachable.dangling[0];
+        assert_eq!(
+            (tip, "tip", 2),
+            (dangling.oid, dangling.summary.as_str(), dangling.commits)
+        );
+        assert_eq!(unreachable.size() - 5, dangling.size);
+    }
+}
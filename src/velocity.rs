@@ -0,0 +1,77 @@
+//! Commit rate over recent periods and its trend.
+
+use crate::{date::SECONDS_PER_DAY, history::CommitInfo};
+
+/// Windows, in weeks, for which velocity is reported.
+pub const VELOCITY_WEEKS: [i64; 3] = [4, 12, 52];
+
+/// Change against the previous period below which the rate counts as steady.
+const STEADY_PERCENT: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Velocity {
+    /// Commits per week over the window.
+    pub current: f64,
+    /// Commits per week over the window before it.
+    pub previous: f64,
+}
+
+impl Velocity {
+    /// Returns `↑`, `↓` or `→` for rising, falling or steady activity.
+    pub fn arrow(&self) -> &'static str {
+        match self.change() {
+            Some(change) if change >= STEADY_PERCENT => "↑",
+            Some(change) if change <= -STEADY_PERCENT => "↓",
+            Some(_) => "→",
+            None if self.current > 0.0 => "↑",
+            None => "→",
+        }
+    }
+
+    /// Returns the change against the previous period in percent, or `None`
+    /// if there were no commits then.
+    pub fn change(&self) -> Option<f64> {
+        (self.previous > 0.0).then(|| (self.current - self.previous) * 100.0 / self.previous)
+    }
+}
+
+/// Measures commits per week over the last `weeks` weeks before `now` and
+/// the same span before that.
+pub fn get_velocity(commits: &[CommitInfo], now: i64, weeks: i64) -> Velocity {
+    let span = weeks * 7 * SECONDS_PER_DAY;
+    let (mut current, mut previous) = (0, 0);
+    for commit in commits {
+        let age = now - commit.time;
+        if (0..span).contains(&age) {
+            current += 1;
+        } else if (span..2 * span).contains(&age) {
+            previous += 1;
+        }
+    }
+    Velocity {
+        current: current as f64 / weeks as f64,
+        previous: previous as f64 / weeks as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_velocity() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let commit = |days_ago: i64| CommitInfo {
+            time: now - days_ago * SECONDS_PER_DAY,
+            ..Default::default()
+        };
+        let commits: Vec<CommitInfo> = [1, 2, 3, 10, 20, 30, 40].into_iter().map(commit).collect();
+
+        let velocity = get_velocity(&commits, now, 4);
+        assert_eq!(1.25, velocity.current);
+        assert_eq!(0.5, velocity.previous);
+        assert_eq!(Some(150.0), velocity.change());
+        assert_eq!("↑", velocity.arrow());
+        assert_eq!("→", get_velocity(&[], now, 4).arrow());
+    }
+}
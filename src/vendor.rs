@@ -0,0 +1,384 @@
+//! Tracking of vendored dependency directories.
+//!
+//! Vendored packages are found under well-known directories at HEAD. Each
+//! package's own version is compared with the version the project declares
+//! for it, where the ecosystem makes that possible: `cargo vendor` output
+//! against `Cargo.lock`, and Go's `vendor/modules.txt` against `go.mod`.
+
+use std::collections::HashMap;
+
+use git2::{DiffOptions, ObjectType, Oid, Repository, Sort, Tree};
+
+use crate::history::is_missing;
+
+/// Directory names that hold vendored code.
+const VENDOR_DIRS: &[&str] = &["vendor", "third_party", "third-party", "thirdparty"];
+
+/// How deep below the root vendor directories are looked for.
+const MAX_VENDOR_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VendoredPackage {
+    /// Path of the package directory, relative to the repository root.
+    pub path: String,
+    /// Committer time of the most recent commit touching the package.
+    pub last_updated: Option<i64>,
+    /// Version recorded in the vendored copy itself.
+    pub vendored_version: Option<String>,
+    /// Versions the project's manifests or lockfiles declare for it.
+    pub declared_versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionStatus {
+    Current,
+    Drifted,
+    Unknown,
+}
+
+impl VendoredPackage {
+    pub fn status(&self) -> VersionStatus {
+        match &self.vendored_version {
+            Some(v) if !self.declared_versions.is_empty() => {
+                if self.declared_versions.contains(v) {
+                    VersionStatus::Current
+                } else {
+                    VersionStatus::Drifted
+                }
+            }
+            _ => VersionStatus::Unknown,
+        }
+    }
+}
+
+impl VersionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VersionStatus::Current => "current",
+            VersionStatus::Drifted => "drifted",
+            VersionStatus::Unknown => "unknown",
+        }
+    }
+}
+
+fn blob_text(repo: &Repository, tree: &Tree<'_>, path: &str) -> Option<String> {
+    let entry = tree.get_path(std::path::Path::new(path)).ok()?;
+    let blob = repo.find_blob(entry.id()).ok()?;
+    Some(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// Finds vendor directories in the tree, without descending into them.
+fn find_vendor_roots(
+    repo: &Repository,
+    tree: &Tree<'_>,
+    prefix: &str,
+    depth: usize,
+) -> Vec<String> {
+    let mut roots = Vec::new();
+    for entry in tree.iter() {
+        if entry.kind() != Some(ObjectType::Tree) {
+            continue;
+        }
+        let Some(name) = entry.name() else { continue };
+        let path = format!("{}{}", prefix, name);
+        if VENDOR_DIRS.contains(&name) {
+            roots.push(path);
+        } else if depth + 1 < MAX_VENDOR_DEPTH
+            && let Ok(subtree) = repo.find_tree(entry.id())
+        {
+            roots.extend(find_vendor_roots(
+                repo,
+                &subtree,
+                &format!("{}/", path),
+                depth + 1,
+            ));
+        }
+    }
+    roots
+}
+
+/// Extracts the `version = "..."` of the `[package]` section of a Cargo.toml.
+fn cargo_manifest_version(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package && let Some(value) = line.strip_prefix("version") {
+            let value = value.trim_start().strip_prefix('=')?.trim();
+            return Some(value.trim_matches('"').to_string());
+        }
+    }
+    None
+}
+
+/// Extracts the top-level `"version"` of a package.json.
+fn npm_manifest_version(manifest: &str) -> Option<String> {
+    let start = manifest.find("\"version\"")? + "\"version\"".len();
+    let rest = manifest[start..]
+        .trim_start()
+        .strip_prefix(':')?
+        .trim_start();
+    let rest = rest.strip_prefix('"')?;
+    Some(rest[..rest.find('"')?].to_string())
+}
+
+/// Returns every `name -> versions` pair in a Cargo.lock.
+fn cargo_lock_versions(lock: &str) -> HashMap<String, Vec<String>> {
+    let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+    let mut name = None;
+    for line in lock.lines().map(str::trim) {
+        if line == "[[package]]" {
+            name = None;
+        } else if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"').to_string());
+        } else if let Some(value) = line.strip_prefix("version = ")
+            && let Some(name) = name.take()
+        {
+            versions
+                .entry(name)
+                .or_default()
+                .push(value.trim_matches('"').to_string());
+        }
+    }
+    versions
+}
+
+/// Returns `module -> version` for the requirements of a go.mod.
+fn go_mod_requirements(go_mod: &str) -> HashMap<String, String> {
+    let mut requirements = HashMap::new();
+    let mut in_block = false;
+    for line in go_mod
+        .lines()
+        .map(|l| l.split("//").next().unwrap_or("").trim())
+    {
+        let spec = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(spec) = line.strip_prefix("require ") {
+            spec
+        } else {
+            continue;
+        };
+        let mut parts = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            requirements.insert(module.to_string(), version.to_string());
+        }
+    }
+    requirements
+}
+
+/// Lists the packages in one vendor directory with their versions.
+fn packages_in_root(repo: &Repository, head: &Tree<'_>, root: &str) -> Vec<VendoredPackage> {
+    let parent = root.rsplit_once('/').map_or("", |(p, _)| p);
+    let at_parent = |name: &str| {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", parent, name)
+        }
+    };
+
+    // Go modules: vendor/modules.txt lists "# module version" lines.
+    if let Some(modules) = blob_text(repo, head, &format!("{}/modules.txt", root)) {
+        let requirements = blob_text(repo, head, &at_parent("go.mod"))
+            .map(|m| go_mod_requirements(&m))
+            .unwrap_or_default();
+        return modules
+            .lines()
+            .filter_map(|l| l.strip_prefix("# "))
+            .filter_map(|l| {
+                let mut parts = l.split_whitespace();
+                Some((parts.next()?, parts.next()?))
+            })
+            .map(|(module, version)| VendoredPackage {
+                path: format!("{}/{}", root, module),
+                last_updated: None,
+                vendored_version: Some(version.to_string()),
+                declared_versions: requirements.get(module).cloned().into_iter().collect(),
+            })
+            .collect();
+    }
+
+    let lock = blob_text(repo, head, &at_parent("Cargo.lock"))
+        .map(|l| cargo_lock_versions(&l))
+        .unwrap_or_default();
+    let Ok(entry) = head.get_path(std::path::Path::new(root)) else {
+        return Vec::new();
+    };
+    let Ok(tree) = repo.find_tree(entry.id()) else {
+        return Vec::new();
+    };
+    tree.iter()
+        .filter(|e| e.kind() == Some(ObjectType::Tree))
+        .filter_map(|e| e.name().map(str::to_string))
+        .map(|name| {
+            let path = format!("{}/{}", root, name);
+            let cargo = blob_text(repo, head, &format!("{}/Cargo.toml", path));
+            let (vendored_version, declared_versions) = match cargo {
+                Some(manifest) => {
+                    // cargo vendor names directories <crate> or <crate>-<version>.
+                    let version = cargo_manifest_version(&manifest);
+                    let crate_name = match &version {
+                        Some(v) => name.strip_suffix(&format!("-{}", v)).unwrap_or(&name),
+                        None => &name,
+                    };
+                    (version, lock.get(crate_name).cloned().unwrap_or_default())
+                }
+                None => (
+                    blob_text(repo, head, &format!("{}/package.json", path))
+                        .and_then(|m| npm_manifest_version(&m)),
+                    Vec::new(),
+                ),
+            };
+            VendoredPackage {
+                path,
+                last_updated: None,
+                vendored_version,
+                declared_versions,
+            }
+        })
+        .collect()
+}
+
+/// Finds vendored packages at `head`, none when HEAD is unborn, and when
+/// each was last updated, ordered from the least recently updated.
+pub fn get_vendored_packages(
+    repo: &Repository,
+    head: Option<Oid>,
+) -> Result<Vec<VendoredPackage>, git2::Error> {
+    let Some(tip) = head else {
+        return Ok(Vec::new());
+    };
+    let head = repo.find_commit(tip)?.tree()?;
+    let roots = find_vendor_roots(repo, &head, "", 0);
+    let mut packages: Vec<VendoredPackage> = roots
+        .iter()
+        .flat_map(|root| packages_in_root(repo, &head, root))
+        .collect();
+    if packages.is_empty() {
+        return Ok(packages);
+    }
+
+    // Walk HEAD history newest first, diffing only the vendor directories,
+    // until every package has been seen changing.
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(tip)?;
+    revwalk.set_sorting(Sort::TIME)?;
+    let mut options = DiffOptions::new();
+    for root in &roots {
+        options.pathspec(root);
+    }
+    let mut remaining = packages.len();
+    for oid in revwalk {
+        let diff = oid
+            .and_then(|oid| repo.find_commit(oid))
+            .and_then(|commit| {
+                let tree = commit.tree()?;
+                let parent_tree = match commit.parents().next() {
+                    Some(parent) => Some(parent.tree()?),
+                    None => None,
+                };
+                let diff =
+                    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+                Ok((commit, diff))
+            });
+        let (commit, diff) = match diff {
+            Ok(diff) => diff,
+            // Commits removed by a concurrent gc are skipped.
+            Err(e) if is_missing(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                continue;
+            };
+            let path = path.to_string_lossy();
+            for package in packages.iter_mut().filter(|p| p.last_updated.is_none()) {
+                if path.starts_with(&format!("{}/", package.path)) {
+                    package.last_updated = Some(commit.time().seconds());
+                    remaining -= 1;
+                }
+            }
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    packages.sort_by_key(|p| (p.last_updated, p.path.clone()));
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_manifest_parsing() {
+        assert_eq!(
+            Some("1.2.3".to_string()),
+            cargo_manifest_version("[package]\nname = \"a\"\nversion = \"1.2.3\"\n")
+        );
+        assert_eq!(
+            Some("4.0.1".to_string()),
+            npm_manifest_version("{\n  \"name\": \"left-pad\",\n  \"version\": \"4.0.1\"\n}")
+        );
+        let lock = cargo_lock_versions(
+            "[[package]]\nname = \"libc\"\nversion = \"0.2.1\"\n\n[[package]]\nname = \"libc\"\nversion = \"0.2.9\"\n",
+        );
+        assert_eq!(
+            Some(&vec!["0.2.1".to_string(), "0.2.9".to_string()]),
+            lock.get("libc")
+        );
+        let go = go_mod_requirements(
+            "module x\n\nrequire (\n\tgithub.com/a/b v1.0.0 // indirect\n)\nrequire golang.org/x/y v0.3.0\n",
+        );
+        assert_eq!(Some(&"v1.0.0".to_string()), go.get("github.com/a/b"));
+        assert_eq!(Some(&"v0.3.0".to_string()), go.get("golang.org/x/y"));
+    }
+
+    #[test]
+    fn test_vendored_packages() {
+        let t = TestRepo::new();
+        t.commit_as(
+            "a",
+            "a@x.com",
+            1_000,
+            "vendor",
+            &[
+                (
+                    "Cargo.lock",
+                    "[[package]]\nname = \"foo\"\nversion = \"1.1.0\"\n",
+                ),
+                (
+                    "vendor/foo/Cargo.toml",
+                    "[package]\nname = \"foo\"\nversion = \"1.0.0\"\n",
+                ),
+                ("vendor/bar/README", "bar\n"),
+            ],
+        );
+        t.commit_as(
+            "a",
+            "a@x.com",
+            2_000,
+            "update bar",
+            &[("vendor/bar/README", "bar 2\n")],
+        );
+
+        let packages = get_vendored_packages(&t.repo, t.repo.head().unwrap().target()).unwrap();
+        assert_eq!(2, packages.len());
+        assert_eq!("vendor/foo", packages[0].path);
+        assert_eq!(Some(1_000), packages[0].last_updated);
+        assert_eq!(VersionStatus::Drifted, packages[0].status());
+        assert_eq!("vendor/bar", packages[1].path);
+        assert_eq!(Some(2_000), packages[1].last_updated);
+        assert_eq!(VersionStatus::Unknown, packages[1].status());
+    }
+}
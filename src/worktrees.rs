@@ -0,0 +1,197 @@
+//! State of the main worktree and every linked worktree, and finding the
+//! repository from any of them.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use git2::{Repository, Status, StatusOptions};
+
+use crate::stats::calculate_directory_size;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorktreeStatus {
+    /// `(main)` for the main worktree, otherwise the linked worktree's name.
+    pub name: String,
+    pub path: String,
+    /// Checked-out branch, or `None` for a detached or unborn HEAD.
+    pub branch: Option<String>,
+    /// Tracked files with staged or unstaged changes.
+    pub modified: usize,
+    pub untracked: usize,
+    /// Time and summary of the commit at HEAD.
+    pub last_commit: Option<(i64, String)>,
+    /// Bytes used by the checkout, not counting the shared `.git` directory.
+    pub disk_usage: u64,
+    pub locked: bool,
+}
+
+/// Opens the repository at or above `path`. Without a path, the repository
+/// is found the way git finds it: from `GIT_DIR` and `GIT_WORK_TREE` when
+/// set, as in hooks and CI wrappers, and otherwise from the current
+/// directory, honoring `GIT_CEILING_DIRECTORIES`. Linked worktrees are
+/// followed through their `.git` file either way.
+pub fn open_repository(path: Option<&Path>) -> Result<Repository, git2::Error> {
+    match path {
+        Some(path) => Repository::discover(path),
+        None => {
+            let repo = Repository::open_from_env()?;
+            let _ = OPENED_FROM_ENV.set(repo.path().to_path_buf());
+            Ok(repo)
+        }
+    }
+}
+
+/// Git directory of the repository [`open_repository`] found from the
+/// environment, the only one `GIT_COMMON_DIR` applies to, as in libgit2.
+static OPENED_FROM_ENV: OnceLock<PathBuf> = OnceLock::new();
+
+/// Returns the git directory shared by all worktrees: `GIT_COMMON_DIR` when
+/// set and the repository was found from the environment, otherwise the
+/// directory a linked worktree's `commondir` file points to.
+pub fn common_dir(repo: &Repository) -> PathBuf {
+    let from_env = OPENED_FROM_ENV
+        .get()
+        .filter(|dir| *dir == repo.path())
+        .and_then(|_| env::var_os("GIT_COMMON_DIR"));
+    resolve_common_dir(repo, from_env)
+}
+
+fn resolve_common_dir(repo: &Repository, from_env: Option<OsString>) -> PathBuf {
+    if let Some(dir) = from_env.filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    match fs::read_to_string(repo.path().join("commondir")) {
+        Ok(dir) if repo.is_worktree() => repo.path().join(dir.trim()),
+        _ => repo.path().to_path_buf(),
+    }
+}
+
+/// Sums the size of a checkout, skipping its top-level `.git` directory or,
+/// in linked worktrees, `.git` file.
+fn checkout_size(workdir: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut total = 0;
+    for entry in fs::read_dir(workdir)? {
+        let path = entry?.path();
+        if path.file_name().is_some_and(|n| n == ".git") {
+            continue;
+        }
+        total += calculate_directory_size(&path)?;
+    }
+    Ok(total)
+}
+
+fn worktree_status(
+    name: String,
+    repo: &Repository,
+    locked: bool,
+) -> Result<Option<WorktreeStatus>, Box<dyn std::error::Error>> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+
+    let head = repo.head().ok();
+    let branch = head
+        .as_ref()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(str::to_string));
+    let last_commit = head
+        .and_then(|h| h.peel_to_commit().ok())
+        .map(|c| (c.time().seconds(), c.summary().unwrap_or("").to_string()));
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let (mut modified, mut untracked) = (0, 0);
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        if entry.status() == Status::WT_NEW {
+            untracked += 1;
+        } else if !entry.status().is_empty() {
+            modified += 1;
+        }
+    }
+
+    Ok(Some(WorktreeStatus {
+        name,
+        path: workdir.display().to_string(),
+        branch,
+        modified,
+        untracked,
+        last_commit,
+        disk_usage: checkout_size(workdir)?,
+        locked,
+    }))
+}
+
+/// Returns the main worktree, unless the repository is bare, followed by
+/// every linked worktree that still exists on disk.
+pub fn get_worktrees(repo: &Repository) -> Result<Vec<WorktreeStatus>, Box<dyn std::error::Error>> {
+    // From inside a linked worktree, statistics still start at the main one.
+    let main = Repository::open(common_dir(repo))?;
+    let mut worktrees = Vec::new();
+    worktrees.extend(worktree_status("(main)".to_string(), &main, false)?);
+
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = main.find_worktree(name)?;
+        if worktree.validate().is_err() {
+            continue;
+        }
+        let locked = !matches!(worktree.is_locked()?, git2::WorktreeLockStatus::Unlocked);
+        let linked = Repository::open_from_worktree(&worktree)?;
+        worktrees.extend(worktree_status(name.to_string(), &linked, locked)?);
+    }
+    Ok(worktrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TestRepo;
+
+    #[test]
+    fn test_worktrees() {
+        let t = TestRepo::new();
+        t.commit("add", &[("a.txt", "one")]);
+        let workdir = t.repo.workdir().unwrap().to_path_buf();
+        fs::write(workdir.join("a.txt"), "changed").unwrap();
+        fs::write(workdir.join("new.txt"), "new").unwrap();
+
+        let linked_dir = tempfile::TempDir::new().unwrap();
+        let linked_path = linked_dir.path().join("feature");
+        t.repo.worktree("feature", &linked_path, None).unwrap();
+
+        let worktrees = get_worktrees(&t.repo).unwrap();
+        assert_eq!(2, worktrees.len());
+        assert_eq!((1, 1), (worktrees[0].modified, worktrees[0].untracked));
+        assert_eq!("feature", worktrees[1].name);
+        assert_eq!(Some("feature"), worktrees[1].branch.as_deref());
+        assert_eq!((0, 0), (worktrees[1].modified, worktrees[1].untracked));
+        assert_eq!(
+            Some("add"),
+            worktrees[1].last_commit.as_ref().map(|c| c.1.as_str())
+        );
+        assert_eq!(3, worktrees[1].disk_usage);
+    }
+
+    #[test]
+    fn test_open_linked_worktree() {
+        let t = TestRepo::new();
+        t.commit("add", &[("a.txt", "one")]);
+        let linked_dir = tempfile::TempDir::new().unwrap();
+        let linked_path = linked_dir.path().join("feature");
+        t.repo.worktree("feature", &linked_path, None).unwrap();
+        fs::create_dir(linked_path.join("sub")).unwrap();
+
+        let linked = open_repository(Some(&linked_path.join("sub"))).unwrap();
+        assert!(linked.is_worktree());
+        let main_dir = t.repo.path().canonicalize().unwrap();
+        assert_eq!(main_dir, common_dir(&linked).canonicalize().unwrap());
+        assert_eq!(
+            PathBuf::from("/elsewhere"),
+            resolve_common_dir(&linked, Some("/elsewhere".into()))
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Reports as Excel workbooks.
+//!
+//! A workbook is a zip archive of XML parts. Only what spreadsheets need to
+//! open it is written: one sheet per table, and a first sheet with the
+//! labelled fields, with bold column headers. Entries are stored without
+//! compression, which keeps the writer self-contained.
+
+use crate::report::{Report, Section, Value};
+
+/// Style indices in `styles.xml`.
+const STYLE_HEADER: usize = 1;
+const STYLE_PERCENT: usize = 2;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>{sheets}</Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="3"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/><xf numFmtId="10" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/></cellXfs></styleSheet>"#;
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::new();
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Control characters other than tab and newline are not
+            // allowed in XML 1.0.
+            c if (c as u32) < 0x20 && c != '\t' && c != '\n' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Returns the column letters of a zero-based column index: A, B, ..., AA.
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// A cell of a sheet: text, or a number with a style.
+enum Cell {
+    Text(String, usize),
+    Number(String, usize),
+}
+
+fn cell(value: &Value, report: &Report) -> Cell {
+    match value {
+        Value::Count(n) => Cell::Number(n.to_string(), 0),
+        Value::Percent(p) => Cell::Number((p / 100.0).to_string(), STYLE_PERCENT),
+        Value::Size(n) => Cell::Number(n.to_string(), 0),
+        Value::Intensities { counts, .. } => {
+            let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+            Cell::Text(counts.join(" "), 0)
+        }
+        Value::Text(s) => Cell::Text(s.clone(), 0),
+        Value::Date(_) | Value::Time(_) => Cell::Text(value.render(&report.date_format), 0),
+    }
+}
+
+fn sheet_xml(rows: &[Vec<Cell>]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>",
+    );
+    for (r, row) in rows.iter().enumerate() {
+        out.push_str(&format!("<row r=\"{}\">", r + 1));
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(text, style) => out.push_str(&format!(
+                    "<c r=\"{}\" s=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    reference,
+                    style,
+                    xml_escape(text)
+                )),
+                Cell::Number(number, style) => out.push_str(&format!(
+                    "<c r=\"{}\" s=\"{}\"><v>{}</v></c>",
+                    reference, style, number
+                )),
+            }
+        }
+        out.push_str("</row>");
+    }
+    out.push_str("</sheetData></worksheet>");
+    out
+}
+
+/// Returns a sheet name from `title` that spreadsheets accept: at most 31
+/// characters, none of `[]:*?/\`, and not used by an earlier sheet.
+fn sheet_name(title: &str, taken: &[String]) -> String {
+    let clean: String = title
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { ' ' } else { c })
+        .collect();
+    let clean = if clean.trim().is_empty() {
+        "Sheet".to_string()
+    } else {
+        clean.trim().to_string()
+    };
+    let mut name: String = clean.chars().take(31).collect();
+    let mut n = 2;
+    while taken.iter().any(|t| t.eq_ignore_ascii_case(&name)) {
+        let suffix = format!(" ({})", n);
+        name = clean.chars().take(31 - suffix.len()).collect::<String>() + &suffix;
+        n += 1;
+    }
+    name
+}
+
+/// Renders the reports as an xlsx workbook.
+pub fn workbook(reports: &[Report]) -> Vec<u8> {
+    let mut sheets: Vec<(String, String)> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let mut fields: Vec<Vec<Cell>> = vec![vec![
+        Cell::Text("Report".to_string(), STYLE_HEADER),
+        Cell::Text("Field".to_string(), STYLE_HEADER),
+        Cell::Text("Value".to_string(), STYLE_HEADER),
+    ]];
+    let mut tables = Vec::new();
+    for report in reports {
+        for section in &report.sections {
+            match section {
+                Section::Fields(values) => {
+                    for (label, value) in values {
+                        fields.push(vec![
+                            Cell::Text(report.command.clone(), 0),
+                            Cell::Text(label.clone(), 0),
+                            cell(value, report),
+                        ]);
+                    }
+                }
+                Section::Table(table) => {
+                    let mut rows = vec![
+                        table
+                            .columns
+                            .iter()
+                            .map(|c| Cell::Text(c.clone(), STYLE_HEADER))
+                            .collect::<Vec<_>>(),
+                    ];
+                    rows.extend(
+                        table
+                            .rows
+                            .iter()
+                            .map(|row| row.iter().map(|v| cell(v, report)).collect()),
+                    );
+                    tables.push((table.title.clone(), rows));
+                }
+            }
+        }
+    }
+    if fields.len() > 1 {
+        names.push("Fields".to_string());
+        sheets.push(("Fields".to_string(), sheet_xml(&fields)));
+    }
+    for (title, rows) in tables {
+        let name = sheet_name(&title, &names);
+        names.push(name.clone());
+        sheets.push((name, sheet_xml(&rows)));
+    }
+    if sheets.is_empty() {
+        sheets.push(("Sheet".to_string(), sheet_xml(&[])));
+    }
+
+    let mut content_types = String::new();
+    let mut workbook_sheets = String::new();
+    let mut workbook_rels = String::new();
+    for (i, (name, _)) in sheets.iter().enumerate() {
+        content_types.push_str(&format!(
+            "<Override PartName=\"/xl/worksheets/sheet{}.xml\" \
+             ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+            i + 1
+        ));
+        workbook_sheets.push_str(&format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            xml_escape(name),
+            i + 1,
+            i + 1
+        ));
+        workbook_rels.push_str(&format!(
+            "<Relationship Id=\"rId{}\" \
+             Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" \
+             Target=\"worksheets/sheet{}.xml\"/>",
+            i + 1,
+            i + 1
+        ));
+    }
+    workbook_rels.push_str(&format!(
+        "<Relationship Id=\"rId{}\" \
+         Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" \
+         Target=\"styles.xml\"/>",
+        sheets.len() + 1
+    ));
+
+    let mut files: Vec<(String, String)> = vec![
+        (
+            "[Content_Types].xml".to_string(),
+            CONTENT_TYPES.replace("{sheets}", &content_types),
+        ),
+        ("_rels/.rels".to_string(), ROOT_RELS.to_string()),
+        (
+            "xl/workbook.xml".to_string(),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+                 <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+                 xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+                 <sheets>{}</sheets></workbook>",
+                workbook_sheets
+            ),
+        ),
+        (
+            "xl/_rels/workbook.xml.rels".to_string(),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+                 <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+                 {}</Relationships>",
+                workbook_rels
+            ),
+        ),
+        ("xl/styles.xml".to_string(), STYLES.to_string()),
+    ];
+    for (i, (_, xml)) in sheets.into_iter().enumerate() {
+        files.push((format!("xl/worksheets/sheet{}.xml", i + 1), xml));
+    }
+    zip(&files)
+}
+
+/// CRC-32 as used by zip archives.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Writes `files` (name, contents) into a zip archive without compression,
+/// dated 1980-01-01 so the same reports give the same bytes.
+fn zip(files: &[(String, String)]) -> Vec<u8> {
+    const DOS_DATE: u16 = 0x21;
+    let mut out: Vec<u8> = Vec::new();
+    let mut central: Vec<u8> = Vec::new();
+    for (name, contents) in files {
+        let (name, data) = (name.as_bytes(), contents.as_bytes());
+        let crc = crc32(data);
+        let offset = out.len() as u32;
+        let common = |buf: &mut Vec<u8>| {
+            buf.extend(20u16.to_le_bytes()); // version needed
+            buf.extend(0u16.to_le_bytes()); // flags
+            buf.extend(0u16.to_le_bytes()); // stored
+            buf.extend(0u16.to_le_bytes()); // time
+            buf.extend(DOS_DATE.to_le_bytes());
+            buf.extend(crc.to_le_bytes());
+            buf.extend((data.len() as u32).to_le_bytes());
+            buf.extend((data.len() as u32).to_le_bytes());
+            buf.extend((name.len() as u16).to_le_bytes());
+            buf.extend(0u16.to_le_bytes()); // extra field length
+        };
+
+        out.extend(0x0403_4b50u32.to_le_bytes());
+        common(&mut out);
+        out.extend(name);
+        out.extend(data);
+
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        central.extend(20u16.to_le_bytes()); // version made by
+        common(&mut central);
+        central.extend(0u16.to_le_bytes()); // comment length
+        central.extend(0u16.to_le_bytes()); // disk
+        central.extend(0u16.to_le_bytes()); // internal attributes
+        central.extend(0u32.to_le_bytes()); // external attributes
+        central.extend(offset.to_le_bytes());
+        central.extend(name);
+    }
+    let central_offset = out.len() as u32;
+    out.extend(&central);
+    out.extend(0x0605_4b50u32.to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // disk
+    out.extend(0u16.to_le_bytes()); // disk with the directory
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((central.len() as u32).to_le_bytes());
+    out.extend(central_offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // comment length
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Table;
+
+    #[test]
+    fn test_workbook() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(
+            vec!["A", "Z", "AA", "AB"],
+            [0, 25, 26, 27].map(column_name).to_vec()
+        );
+        let taken = vec!["Commits per month".to_string()];
+        assert_eq!(
+            "Commits per month (2)",
+            sheet_name("Commits per month", &taken)
+        );
+        assert_eq!("a b", sheet_name("a/b", &[]));
+
+        let mut report = Report::new("Stats");
+        report.field("Total Commits", 3usize);
+        let mut table = Table::new("Top <contributors>", &["Name", "Share"]);
+        table.push(vec!["a & b".into(), Value::Percent(50.0)]);
+        report.table(table);
+
+        let bytes = workbook(&[report]);
+        assert!(bytes.starts_with(b"PK\x03\x04"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("<sheet name=\"Fields\" sheetId=\"1\" r:id=\"rId1\"/>"));
+        assert!(text.contains("<sheet name=\"Top &lt;contributors&gt;\""));
+        assert!(text.contains("<t xml:space=\"preserve\">a &amp; b</t>"));
+        assert!(text.contains("<c r=\"B2\" s=\"2\"><v>0.5</v></c>"));
+    }
+}